@@ -0,0 +1,54 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use sentry_types::protocol::v7::{parse_event_bytes, Event, Exception, Frame, Stacktrace};
+
+/// Creates an event with an exception of `frames` stack frames.
+fn event_with_frames(frames: u64) -> Vec<u8> {
+    let event = Event {
+        exception: vec![Exception {
+            ty: "SIGSEGV".into(),
+            value: Some("Segmentation fault".into()),
+            stacktrace: Some(Stacktrace {
+                frames: (0..frames)
+                    .map(|i| Frame {
+                        function: Some(format!("my_crate::module::function_{}", i)),
+                        filename: Some("module.rs".into()),
+                        abs_path: Some("/src/my_crate/src/module.rs".into()),
+                        lineno: Some(i + 1),
+                        colno: Some(5),
+                        instruction_addr: Some((0x1000 + i * 16).into()),
+                        in_app: Some(i % 2 == 0),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    };
+    serde_json::to_vec(&event).unwrap()
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_event_bytes");
+    for &frames in &[10, 10_000] {
+        let bytes = event_with_frames(frames);
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_function(format!("{}_frames", frames), |b| {
+            b.iter(|| parse_event_bytes(black_box(&bytes)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_serialize(c: &mut Criterion) {
+    let event = parse_event_bytes(&event_with_frames(10_000)).unwrap();
+    c.bench_function("serialize_event/10000_frames", |b| {
+        b.iter(|| serde_json::to_vec(black_box(&event)).unwrap())
+    });
+}
+
+criterion_group!(benches, bench_parse, bench_serialize);
+criterion_main!(benches);
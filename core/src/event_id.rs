@@ -0,0 +1,89 @@
+use core::fmt;
+use core::str;
+
+use uuid::Uuid;
+
+/// An error used when parsing `EventId`.
+#[derive(Debug)]
+pub struct ParseEventIdError;
+
+impl fmt::Display for ParseEventIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid event id")
+    }
+}
+
+impl_std_error!(ParseEventIdError);
+
+/// Represents an event ID.
+///
+/// This is a UUID formatted as 32 lowercase hex characters without dashes.
+/// Parsing also accepts the hyphenated form.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Default)]
+pub struct EventId(Uuid);
+
+impl EventId {
+    /// Creates a new random event ID.
+    #[cfg(feature = "rand")]
+    pub fn new() -> EventId {
+        EventId(Uuid::new_v4())
+    }
+
+    /// Creates an event ID from a UUID.
+    pub fn from_uuid(uuid: Uuid) -> EventId {
+        EventId(uuid)
+    }
+
+    /// Returns the UUID of the event ID.
+    pub fn as_uuid(&self) -> &Uuid {
+        &self.0
+    }
+
+    /// Returns true if this is the nil event ID.
+    pub fn is_nil(&self) -> bool {
+        self.0.is_nil()
+    }
+}
+
+impl From<Uuid> for EventId {
+    fn from(uuid: Uuid) -> EventId {
+        EventId(uuid)
+    }
+}
+
+impl AsRef<Uuid> for EventId {
+    fn as_ref(&self) -> &Uuid {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for EventId {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl From<EventId> for Uuid {
+    fn from(event_id: EventId) -> Uuid {
+        event_id.0
+    }
+}
+
+impl fmt::Display for EventId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.to_simple_ref())
+    }
+}
+
+impl str::FromStr for EventId {
+    type Err = ParseEventIdError;
+
+    fn from_str(string: &str) -> Result<EventId, Self::Err> {
+        Uuid::parse_str(string)
+            .map(EventId)
+            .map_err(|_| ParseEventIdError)
+    }
+}
+
+impl_try_from_str!(EventId);
+impl_str_serde!(EventId);
@@ -0,0 +1,34 @@
+//! Hex decoding for identifiers and signatures.
+
+/// Decodes a hex string into a buffer.
+///
+/// The string must contain exactly two hex characters per byte of the
+/// buffer.  Upper and lower case digits are accepted.  Returns `None` if the
+/// string does not fit the buffer or contains other characters, in which case
+/// the contents of the buffer are unspecified.
+///
+/// ```
+/// let mut bytes = [0; 2];
+/// assert!(sentry_types_core::hex::decode("c0Fe", &mut bytes).is_some());
+/// assert_eq!(bytes, [0xc0, 0xfe]);
+/// assert!(sentry_types_core::hex::decode("c0f", &mut bytes).is_none());
+/// ```
+pub fn decode(string: &str, bytes: &mut [u8]) -> Option<()> {
+    if string.len() != bytes.len() * 2 {
+        return None;
+    }
+
+    for (byte, pair) in bytes.iter_mut().zip(string.as_bytes().chunks_exact(2)) {
+        *byte = (hex_digit(pair[0])? << 4) | hex_digit(pair[1])?;
+    }
+    Some(())
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
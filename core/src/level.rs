@@ -0,0 +1,86 @@
+use core::fmt;
+use core::str;
+
+/// An error used when parsing `Level`.
+#[derive(Debug)]
+pub struct ParseLevelError;
+
+impl fmt::Display for ParseLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid level")
+    }
+}
+
+impl_std_error!(ParseLevelError);
+
+/// Represents the level of severity of an event or breadcrumb.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Level {
+    /// Indicates very spammy debug information.
+    Debug,
+    /// Informational messages.
+    #[default]
+    Info,
+    /// A warning.
+    Warning,
+    /// An error.
+    Error,
+    /// Similar to error but indicates a critical event that usually causes a shutdown.
+    Fatal,
+}
+
+impl str::FromStr for Level {
+    type Err = ParseLevelError;
+
+    fn from_str(string: &str) -> Result<Level, Self::Err> {
+        Ok(match string {
+            "debug" => Level::Debug,
+            "info" | "log" => Level::Info,
+            "warning" => Level::Warning,
+            "error" => Level::Error,
+            "fatal" => Level::Fatal,
+            _ => return Err(ParseLevelError),
+        })
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Level::Debug => write!(f, "debug"),
+            Level::Info => write!(f, "info"),
+            Level::Warning => write!(f, "warning"),
+            Level::Error => write!(f, "error"),
+            Level::Fatal => write!(f, "fatal"),
+        }
+    }
+}
+
+impl Level {
+    /// A quick way to check if the level is `debug`.
+    pub fn is_debug(&self) -> bool {
+        *self == Level::Debug
+    }
+
+    /// A quick way to check if the level is `info`.
+    pub fn is_info(&self) -> bool {
+        *self == Level::Info
+    }
+
+    /// A quick way to check if the level is `warning`.
+    pub fn is_warning(&self) -> bool {
+        *self == Level::Warning
+    }
+
+    /// A quick way to check if the level is `error`.
+    pub fn is_error(&self) -> bool {
+        *self == Level::Error
+    }
+
+    /// A quick way to check if the level is `fatal`.
+    pub fn is_fatal(&self) -> bool {
+        *self == Level::Fatal
+    }
+}
+
+impl_str_serde!(Level);
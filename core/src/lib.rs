@@ -0,0 +1,34 @@
+//! Core identifier types of the Sentry protocol.
+//!
+//! This crate contains the parts of `sentry-types` that do not need the
+//! standard library, so that they can be used on embedded and `wasm32`
+//! targets.  It only requires `alloc`.
+//!
+//! The `std` feature implements `std::error::Error` for all errors, the
+//! `rand` feature adds constructors for random identifiers and the `serde`
+//! feature implements serialization.  All are enabled by default.  Disable
+//! default features to build with `#![no_std]`.
+//!
+//! Identifiers implement `Display`, `FromStr` and `TryFrom<&str>`
+//! regardless of the enabled features.
+#![no_std]
+#![warn(missing_docs)]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[macro_use]
+mod macros;
+
+pub mod dsn;
+mod event_id;
+pub mod hex;
+mod level;
+mod project_id;
+mod trace;
+
+pub use crate::event_id::*;
+pub use crate::level::*;
+pub use crate::project_id::*;
+pub use crate::trace::*;
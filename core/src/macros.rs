@@ -0,0 +1,53 @@
+/// Helper macro to implement string based serialization and deserialization.
+///
+/// If a type implements `FromStr` and `Display` then this automatically
+/// implements a serializer/deserializer for that type that dispatches
+/// appropriately.
+macro_rules! impl_str_serde {
+    ($type:ty) => {
+        #[cfg(feature = "serde")]
+        impl ::serde::ser::Serialize for $type {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ::serde::ser::Serializer,
+            {
+                serializer.collect_str(self)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> ::serde::de::Deserialize<'de> for $type {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: ::serde::de::Deserializer<'de>,
+            {
+                <::alloc::borrow::Cow<str>>::deserialize(deserializer)?
+                    .parse()
+                    .map_err(::serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+/// Helper macro to implement `std::error::Error` when the `std` feature is
+/// enabled.
+macro_rules! impl_std_error {
+    ($type:ty) => {
+        #[cfg(feature = "std")]
+        impl ::std::error::Error for $type {}
+    };
+}
+
+/// Helper macro to implement `TryFrom<&str>` for types implementing
+/// `FromStr`.
+macro_rules! impl_try_from_str {
+    ($type:ty) => {
+        impl<'s> ::core::convert::TryFrom<&'s str> for $type {
+            type Error = <$type as ::core::str::FromStr>::Err;
+
+            fn try_from(string: &'s str) -> Result<Self, Self::Error> {
+                string.parse()
+            }
+        }
+    };
+}
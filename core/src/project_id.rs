@@ -0,0 +1,353 @@
+use alloc::string::{String, ToString};
+use core::convert::TryFrom;
+use core::fmt;
+use core::str::FromStr;
+
+/// Raised if a project ID cannot be parsed from a string.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ParseProjectIdError {
+    /// Raised if the value is not an integer in the supported range.
+    InvalidValue,
+    /// Raised if an empty value is parsed.
+    EmptyValue,
+}
+
+impl fmt::Display for ParseProjectIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseProjectIdError::InvalidValue => write!(f, "invalid value for project id"),
+            ParseProjectIdError::EmptyValue => write!(f, "empty or missing project id"),
+        }
+    }
+}
+
+impl_std_error!(ParseProjectIdError);
+
+/// Represents a project ID.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ProjectId(u64);
+
+impl ProjectId {
+    /// Creates a new project ID from its numeric value.
+    #[inline]
+    pub fn new(id: u64) -> Self {
+        Self(id)
+    }
+
+    /// Returns the numeric value of this project id.
+    #[inline]
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ProjectId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+macro_rules! impl_from {
+    ($ty:ty) => {
+        impl From<$ty> for ProjectId {
+            #[inline]
+            fn from(val: $ty) -> Self {
+                Self::new(val as u64)
+            }
+        }
+    };
+}
+
+impl_from!(u8);
+impl_from!(u16);
+impl_from!(u32);
+impl_from!(u64);
+
+macro_rules! impl_try_from {
+    ($ty:ty) => {
+        impl TryFrom<$ty> for ProjectId {
+            type Error = ParseProjectIdError;
+
+            #[inline]
+            fn try_from(val: $ty) -> Result<Self, Self::Error> {
+                match u64::try_from(val) {
+                    Ok(id) => Ok(Self::new(id)),
+                    Err(_) => Err(ParseProjectIdError::InvalidValue),
+                }
+            }
+        }
+    };
+}
+
+impl_try_from!(usize);
+impl_try_from!(i8);
+impl_try_from!(i16);
+impl_try_from!(i32);
+impl_try_from!(i64);
+
+impl FromStr for ProjectId {
+    type Err = ParseProjectIdError;
+
+    fn from_str(s: &str) -> Result<ProjectId, ParseProjectIdError> {
+        if s.is_empty() {
+            return Err(ParseProjectIdError::EmptyValue);
+        }
+
+        match s.parse::<u64>() {
+            Ok(val) => Ok(ProjectId::new(val)),
+            Err(_) => Err(ParseProjectIdError::InvalidValue),
+        }
+    }
+}
+
+impl_try_from_str!(ProjectId);
+
+/// The maximum number of characters in a project slug.
+pub const MAX_PROJECT_SLUG_LENGTH: usize = 50;
+
+/// Raised if a project slug cannot be parsed from a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ParseProjectSlugError {
+    /// Raised if an empty value is parsed.
+    EmptyValue,
+    /// Raised if the slug exceeds `MAX_PROJECT_SLUG_LENGTH` characters.
+    TooLong,
+    /// Raised if the slug contains characters other than lowercase ASCII
+    /// letters, digits, dashes and underscores.
+    InvalidCharacter,
+    /// Raised if the slug only consists of digits.
+    Numeric,
+}
+
+impl fmt::Display for ParseProjectSlugError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseProjectSlugError::EmptyValue => write!(f, "empty or missing project slug"),
+            ParseProjectSlugError::TooLong => write!(f, "project slug too long"),
+            ParseProjectSlugError::InvalidCharacter => {
+                write!(f, "invalid character in project slug")
+            }
+            ParseProjectSlugError::Numeric => write!(f, "project slug must not be numeric"),
+        }
+    }
+}
+
+impl_std_error!(ParseProjectSlugError);
+
+/// Represents a project slug.
+///
+/// Slugs consist of lowercase ASCII letters, digits, dashes and underscores,
+/// must not exceed `MAX_PROJECT_SLUG_LENGTH` characters and must not be
+/// numeric, so that they cannot be confused with a `ProjectId`.
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub struct ProjectSlug(String);
+
+impl ProjectSlug {
+    /// Returns the slug as string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Converts the slug into a string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for ProjectSlug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for ProjectSlug {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<ProjectSlug> for String {
+    fn from(slug: ProjectSlug) -> String {
+        slug.0
+    }
+}
+
+impl FromStr for ProjectSlug {
+    type Err = ParseProjectSlugError;
+
+    fn from_str(s: &str) -> Result<ProjectSlug, ParseProjectSlugError> {
+        if s.is_empty() {
+            Err(ParseProjectSlugError::EmptyValue)
+        } else if s.len() > MAX_PROJECT_SLUG_LENGTH {
+            Err(ParseProjectSlugError::TooLong)
+        } else if !s
+            .bytes()
+            .all(|c| matches!(c, b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_'))
+        {
+            Err(ParseProjectSlugError::InvalidCharacter)
+        } else if s.bytes().all(|c| c.is_ascii_digit()) {
+            Err(ParseProjectSlugError::Numeric)
+        } else {
+            Ok(ProjectSlug(s.to_string()))
+        }
+    }
+}
+
+impl TryFrom<String> for ProjectSlug {
+    type Error = ParseProjectSlugError;
+
+    fn try_from(string: String) -> Result<Self, Self::Error> {
+        string.parse::<ProjectSlug>()?;
+        Ok(ProjectSlug(string))
+    }
+}
+
+impl_try_from_str!(ProjectSlug);
+impl_str_serde!(ProjectSlug);
+
+/// Either a project ID or a project slug.
+///
+/// The web API of Sentry accepts both forms to identify a project.  Numeric
+/// strings parse as IDs and all others as slugs.
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub enum ProjectIdOrSlug {
+    /// A numeric project ID.
+    Id(ProjectId),
+    /// A project slug.
+    Slug(ProjectSlug),
+}
+
+impl ProjectIdOrSlug {
+    /// Returns the project ID, if this is an ID.
+    pub fn id(&self) -> Option<ProjectId> {
+        match *self {
+            ProjectIdOrSlug::Id(id) => Some(id),
+            ProjectIdOrSlug::Slug(_) => None,
+        }
+    }
+
+    /// Returns the project slug, if this is a slug.
+    pub fn slug(&self) -> Option<&ProjectSlug> {
+        match *self {
+            ProjectIdOrSlug::Id(_) => None,
+            ProjectIdOrSlug::Slug(ref slug) => Some(slug),
+        }
+    }
+}
+
+impl fmt::Display for ProjectIdOrSlug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProjectIdOrSlug::Id(id) => fmt::Display::fmt(&id, f),
+            ProjectIdOrSlug::Slug(ref slug) => fmt::Display::fmt(slug, f),
+        }
+    }
+}
+
+impl From<ProjectId> for ProjectIdOrSlug {
+    fn from(id: ProjectId) -> ProjectIdOrSlug {
+        ProjectIdOrSlug::Id(id)
+    }
+}
+
+impl From<ProjectSlug> for ProjectIdOrSlug {
+    fn from(slug: ProjectSlug) -> ProjectIdOrSlug {
+        ProjectIdOrSlug::Slug(slug)
+    }
+}
+
+impl FromStr for ProjectIdOrSlug {
+    type Err = ParseProjectSlugError;
+
+    fn from_str(s: &str) -> Result<ProjectIdOrSlug, ParseProjectSlugError> {
+        match s.parse::<ProjectSlug>() {
+            Ok(slug) => Ok(ProjectIdOrSlug::Slug(slug)),
+            Err(ParseProjectSlugError::Numeric) => s
+                .parse()
+                .map(ProjectIdOrSlug::Id)
+                .map_err(|_| ParseProjectSlugError::Numeric),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl_try_from_str!(ProjectIdOrSlug);
+impl_str_serde!(ProjectIdOrSlug);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_basic_api() {
+        let id: ProjectId = "42".parse().unwrap();
+        assert_eq!(id, ProjectId::new(42));
+        assert_eq!(
+            "42xxx".parse::<ProjectId>(),
+            Err(ParseProjectIdError::InvalidValue)
+        );
+        assert_eq!(
+            "".parse::<ProjectId>(),
+            Err(ParseProjectIdError::EmptyValue)
+        );
+        assert_eq!(ProjectId::new(42).to_string(), "42");
+        assert_eq!(ProjectId::try_from("42"), Ok(ProjectId::new(42)));
+    }
+
+    #[test]
+    fn test_project_slug() {
+        let slug: ProjectSlug = "my-project_2".parse().unwrap();
+        assert_eq!(slug.as_str(), "my-project_2");
+        assert_eq!(
+            "".parse::<ProjectSlug>(),
+            Err(ParseProjectSlugError::EmptyValue)
+        );
+        assert_eq!(
+            "My-Project".parse::<ProjectSlug>(),
+            Err(ParseProjectSlugError::InvalidCharacter)
+        );
+        assert_eq!(
+            "42".parse::<ProjectSlug>(),
+            Err(ParseProjectSlugError::Numeric)
+        );
+        assert_eq!(
+            "a".repeat(51).parse::<ProjectSlug>(),
+            Err(ParseProjectSlugError::TooLong)
+        );
+        assert_eq!(
+            ProjectSlug::try_from(String::from("web")).map(String::from),
+            Ok(String::from("web"))
+        );
+    }
+
+    #[test]
+    fn test_project_id_or_slug() {
+        assert_eq!("42".parse(), Ok(ProjectIdOrSlug::Id(ProjectId::new(42))));
+        let slug: ProjectIdOrSlug = "web".parse().unwrap();
+        assert_eq!(slug.slug().map(ProjectSlug::as_str), Some("web"));
+        assert_eq!(slug.id(), None);
+        assert_eq!(slug.to_string(), "web");
+        assert_eq!(ProjectIdOrSlug::from(ProjectId::new(42)).to_string(), "42");
+        assert_eq!(
+            "not a slug".parse::<ProjectIdOrSlug>(),
+            Err(ParseProjectSlugError::InvalidCharacter)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        assert_eq!(serde_json::to_string(&ProjectId::new(42)).unwrap(), "42");
+        assert_eq!(
+            serde_json::from_str::<ProjectId>("42").unwrap(),
+            ProjectId::new(42)
+        );
+        assert_eq!(
+            serde_json::from_str::<ProjectIdOrSlug>(r#""web""#).unwrap(),
+            ProjectIdOrSlug::Slug("web".parse().unwrap())
+        );
+        assert!(serde_json::from_str::<ProjectSlug>(r#""Web""#).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use core::fmt;
+use core::str;
+
+use crate::hex;
+
+/// An error used when parsing `TraceId`.
+#[derive(Debug)]
+pub struct ParseTraceIdError;
+
+impl fmt::Display for ParseTraceIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid trace id")
+    }
+}
+
+impl_std_error!(ParseTraceIdError);
+
+/// Represents a trace ID.
+///
+/// This is a 16 byte identifier formatted as 32 lowercase hex characters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Default)]
+pub struct TraceId([u8; 16]);
+
+impl TraceId {
+    /// Creates a new random trace ID.
+    #[cfg(feature = "rand")]
+    pub fn new() -> TraceId {
+        TraceId(*uuid::Uuid::new_v4().as_bytes())
+    }
+
+    /// Creates a trace ID from its raw bytes.
+    pub fn from_bytes(bytes: [u8; 16]) -> TraceId {
+        TraceId(bytes)
+    }
+
+    /// Returns the raw bytes of the trace ID.
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for TraceId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for TraceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl str::FromStr for TraceId {
+    type Err = ParseTraceIdError;
+
+    fn from_str(string: &str) -> Result<TraceId, Self::Err> {
+        let mut bytes = [0; 16];
+        hex::decode(string, &mut bytes).ok_or(ParseTraceIdError)?;
+        Ok(TraceId(bytes))
+    }
+}
+
+impl_try_from_str!(TraceId);
+impl_str_serde!(TraceId);
+
+/// An error used when parsing `SpanId`.
+#[derive(Debug)]
+pub struct ParseSpanIdError;
+
+impl fmt::Display for ParseSpanIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid span id")
+    }
+}
+
+impl_std_error!(ParseSpanIdError);
+
+/// Represents a span ID.
+///
+/// This is an 8 byte identifier formatted as 16 lowercase hex characters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Default)]
+pub struct SpanId([u8; 8]);
+
+impl SpanId {
+    /// Creates a new random span ID.
+    #[cfg(feature = "rand")]
+    pub fn new() -> SpanId {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&uuid::Uuid::new_v4().as_bytes()[..8]);
+        SpanId(bytes)
+    }
+
+    /// Creates a span ID from its raw bytes.
+    pub fn from_bytes(bytes: [u8; 8]) -> SpanId {
+        SpanId(bytes)
+    }
+
+    /// Returns the raw bytes of the span ID.
+    pub fn as_bytes(&self) -> &[u8; 8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for SpanId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for SpanId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl str::FromStr for SpanId {
+    type Err = ParseSpanIdError;
+
+    fn from_str(string: &str) -> Result<SpanId, Self::Err> {
+        let mut bytes = [0; 8];
+        hex::decode(string, &mut bytes).ok_or(ParseSpanIdError)?;
+        Ok(SpanId(bytes))
+    }
+}
+
+impl_try_from_str!(SpanId);
+impl_str_serde!(SpanId);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn test_string_conversions() {
+        let trace_id = TraceId::try_from("4c79f60c11214eb38604f4ae0781bfb2").unwrap();
+        assert_eq!(trace_id.to_string(), "4c79f60c11214eb38604f4ae0781bfb2");
+        assert_eq!(AsRef::<[u8]>::as_ref(&trace_id), &trace_id.as_bytes()[..]);
+        assert!(TraceId::try_from("4c79f60c").is_err());
+
+        let span_id = SpanId::try_from("fa90fdead5f74052").unwrap();
+        assert_eq!(span_id.to_string(), "fa90fdead5f74052");
+        assert!(SpanId::try_from("fa90fdead5f7405g").is_err());
+
+        let event_id = crate::EventId::try_from("d43e86c9-6e42-4a93-a4fb-da156dd17341").unwrap();
+        assert_eq!(event_id.to_string(), "d43e86c96e424a93a4fbda156dd17341");
+        assert_eq!(AsRef::<[u8]>::as_ref(&event_id).len(), 16);
+    }
+}
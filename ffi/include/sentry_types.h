@@ -0,0 +1,59 @@
+/*
+ * C interface of sentry-types.
+ *
+ * Link against the static or dynamic library built by the sentry-types-ffi
+ * crate.  Strings are NUL-terminated UTF-8.  Strings returned by these
+ * functions must be released with `sentry_types_string_free`.  See the
+ * documentation of sentry-types-ffi for details.
+ */
+#ifndef SENTRY_TYPES_H
+#define SENTRY_TYPES_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct sentry_types_event_s sentry_types_event_t;
+typedef struct sentry_types_breadcrumb_s sentry_types_breadcrumb_t;
+typedef struct sentry_types_dsn_s sentry_types_dsn_t;
+
+void sentry_types_string_free(char *s);
+
+sentry_types_event_t *sentry_types_event_new(void);
+sentry_types_event_t *sentry_types_event_from_json(const char *json);
+void sentry_types_event_free(sentry_types_event_t *event);
+bool sentry_types_event_set_message(sentry_types_event_t *event,
+                                    const char *message);
+bool sentry_types_event_set_level(sentry_types_event_t *event,
+                                  const char *level);
+bool sentry_types_event_set_tag(sentry_types_event_t *event, const char *key,
+                                const char *value);
+bool sentry_types_event_add_breadcrumb(sentry_types_event_t *event,
+                                       sentry_types_breadcrumb_t *breadcrumb);
+char *sentry_types_event_to_json(const sentry_types_event_t *event);
+
+sentry_types_breadcrumb_t *sentry_types_breadcrumb_new(void);
+void sentry_types_breadcrumb_free(sentry_types_breadcrumb_t *breadcrumb);
+bool sentry_types_breadcrumb_set_type(sentry_types_breadcrumb_t *breadcrumb,
+                                      const char *ty);
+bool sentry_types_breadcrumb_set_category(
+    sentry_types_breadcrumb_t *breadcrumb, const char *category);
+bool sentry_types_breadcrumb_set_message(
+    sentry_types_breadcrumb_t *breadcrumb, const char *message);
+bool sentry_types_breadcrumb_set_level(sentry_types_breadcrumb_t *breadcrumb,
+                                       const char *level);
+
+sentry_types_dsn_t *sentry_types_dsn_parse(const char *dsn);
+void sentry_types_dsn_free(sentry_types_dsn_t *dsn);
+uint64_t sentry_types_dsn_project_id(const sentry_types_dsn_t *dsn);
+char *sentry_types_dsn_public_key(const sentry_types_dsn_t *dsn);
+char *sentry_types_dsn_store_url(const sentry_types_dsn_t *dsn);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SENTRY_TYPES_H */
@@ -0,0 +1,323 @@
+//! A C-compatible interface for building events.
+//!
+//! Events, breadcrumbs and dsns are exposed as opaque pointers created and
+//! freed by the functions in this crate.  Strings are passed as
+//! NUL-terminated UTF-8, and strings returned by this crate must be
+//! released with `sentry_types_string_free`.
+//!
+//! # Safety
+//!
+//! Every pointer passed to these functions must be null or have been
+//! returned by the matching constructor of this crate and not yet freed.
+//! Pointers must not be used from multiple threads at the same time.
+//!
+//! Panics never unwind into the caller.  Functions that panic return null,
+//! false or 0 instead.
+//!
+//! The declarations for C are in `include/sentry_types.h`.  The crate builds
+//! as a static and dynamic library.
+#![allow(clippy::missing_safety_doc)]
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use sentry_types::protocol::v7::{Breadcrumb, Event, Level};
+use sentry_types::Dsn;
+
+/// Runs `f`, returning `default` instead of unwinding into the caller.
+fn catch_panic<T, F: FnOnce() -> T>(default: T, f: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(default)
+}
+
+/// Reads a string argument, returning `None` for null or invalid UTF-8.
+unsafe fn read_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+/// Moves a string to the caller, returning null if it contains NUL bytes.
+fn into_c_string(s: String) -> *mut c_char {
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Serializes a value to JSON for the caller.
+fn to_c_json<T: serde::Serialize>(value: &T) -> *mut c_char {
+    serde_json::to_string(value).map_or(ptr::null_mut(), into_c_string)
+}
+
+/// Frees a string returned by this crate.
+///
+/// Passing null is a no-op.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_string_free(s: *mut c_char) {
+    catch_panic((), || {
+        if !s.is_null() {
+            drop(CString::from_raw(s));
+        }
+    })
+}
+
+/// Creates a new event with a random id and the current timestamp.
+#[no_mangle]
+pub extern "C" fn sentry_types_event_new() -> *mut Event<'static> {
+    catch_panic(ptr::null_mut(), || Box::into_raw(Box::default()))
+}
+
+/// Parses an event from JSON, returning null if the JSON is invalid.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_event_from_json(json: *const c_char) -> *mut Event<'static> {
+    catch_panic(ptr::null_mut(), || {
+        read_str(json)
+            .and_then(|json| serde_json::from_str(json).ok())
+            .map_or(ptr::null_mut(), |event| Box::into_raw(Box::new(event)))
+    })
+}
+
+/// Frees an event.
+///
+/// Passing null is a no-op.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_event_free(event: *mut Event<'static>) {
+    catch_panic((), || {
+        if !event.is_null() {
+            drop(Box::from_raw(event));
+        }
+    })
+}
+
+/// Sets the message of an event, truncating it like `Event::set_message`.
+///
+/// Returns false if an argument is null or not valid UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_event_set_message(
+    event: *mut Event<'static>,
+    message: *const c_char,
+) -> bool {
+    catch_panic(false, || match (event.as_mut(), read_str(message)) {
+        (Some(event), Some(message)) => {
+            event.set_message(message);
+            true
+        }
+        _ => false,
+    })
+}
+
+/// Sets the level of an event from its name, such as `"warning"`.
+///
+/// Returns false if an argument is null or the level is invalid.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_event_set_level(
+    event: *mut Event<'static>,
+    level: *const c_char,
+) -> bool {
+    catch_panic(false, || {
+        match (
+            event.as_mut(),
+            read_str(level).and_then(|l| l.parse::<Level>().ok()),
+        ) {
+            (Some(event), Some(level)) => {
+                event.level = level;
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Sets a tag on an event like `Event::set_tag`.
+///
+/// Tags derived from event fields, such as `level`, are written to their
+/// fields.  Returns false if an argument is null or not valid UTF-8, or if the
+/// value is invalid for such a field.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_event_set_tag(
+    event: *mut Event<'static>,
+    key: *const c_char,
+    value: *const c_char,
+) -> bool {
+    catch_panic(false, || {
+        match (event.as_mut(), read_str(key), read_str(value)) {
+            (Some(event), Some(key), Some(value)) => event.set_tag(key, value).is_ok(),
+            _ => false,
+        }
+    })
+}
+
+/// Adds a breadcrumb to an event, taking ownership of the breadcrumb.
+///
+/// The breadcrumb must not be used or freed afterwards.  Returns false if an
+/// argument is null, in which case the breadcrumb is not consumed.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_event_add_breadcrumb(
+    event: *mut Event<'static>,
+    breadcrumb: *mut Breadcrumb,
+) -> bool {
+    catch_panic(false, || match event.as_mut() {
+        Some(event) if !breadcrumb.is_null() => {
+            event.breadcrumbs.values.push(*Box::from_raw(breadcrumb));
+            true
+        }
+        _ => false,
+    })
+}
+
+/// Serializes an event to JSON.
+///
+/// Returns null if the event is null.  The string must be freed with
+/// `sentry_types_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_event_to_json(event: *const Event<'static>) -> *mut c_char {
+    catch_panic(ptr::null_mut(), || {
+        event.as_ref().map_or(ptr::null_mut(), to_c_json)
+    })
+}
+
+/// Creates a new breadcrumb with the current timestamp.
+#[no_mangle]
+pub extern "C" fn sentry_types_breadcrumb_new() -> *mut Breadcrumb {
+    catch_panic(ptr::null_mut(), || Box::into_raw(Box::default()))
+}
+
+/// Frees a breadcrumb that was not added to an event.
+///
+/// Passing null is a no-op.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_breadcrumb_free(breadcrumb: *mut Breadcrumb) {
+    catch_panic((), || {
+        if !breadcrumb.is_null() {
+            drop(Box::from_raw(breadcrumb));
+        }
+    })
+}
+
+/// Sets the type of a breadcrumb, such as `"http"`.
+///
+/// Returns false if an argument is null or not valid UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_breadcrumb_set_type(
+    breadcrumb: *mut Breadcrumb,
+    ty: *const c_char,
+) -> bool {
+    catch_panic(false, || match (breadcrumb.as_mut(), read_str(ty)) {
+        (Some(breadcrumb), Some(ty)) => {
+            breadcrumb.ty = ty.to_string();
+            true
+        }
+        _ => false,
+    })
+}
+
+/// Sets the category of a breadcrumb.
+///
+/// Returns false if an argument is null or not valid UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_breadcrumb_set_category(
+    breadcrumb: *mut Breadcrumb,
+    category: *const c_char,
+) -> bool {
+    catch_panic(false, || match (breadcrumb.as_mut(), read_str(category)) {
+        (Some(breadcrumb), Some(category)) => {
+            breadcrumb.category = Some(category.to_string());
+            true
+        }
+        _ => false,
+    })
+}
+
+/// Sets the message of a breadcrumb, truncating it like
+/// `Breadcrumb::set_message`.
+///
+/// Returns false if an argument is null or not valid UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_breadcrumb_set_message(
+    breadcrumb: *mut Breadcrumb,
+    message: *const c_char,
+) -> bool {
+    catch_panic(false, || match (breadcrumb.as_mut(), read_str(message)) {
+        (Some(breadcrumb), Some(message)) => {
+            breadcrumb.set_message(message);
+            true
+        }
+        _ => false,
+    })
+}
+
+/// Sets the level of a breadcrumb from its name, such as `"info"`.
+///
+/// Returns false if an argument is null or the level is invalid.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_breadcrumb_set_level(
+    breadcrumb: *mut Breadcrumb,
+    level: *const c_char,
+) -> bool {
+    catch_panic(false, || {
+        match (
+            breadcrumb.as_mut(),
+            read_str(level).and_then(|l| l.parse::<Level>().ok()),
+        ) {
+            (Some(breadcrumb), Some(level)) => {
+                breadcrumb.level = level;
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Parses a dsn, returning null if it is invalid.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_dsn_parse(dsn: *const c_char) -> *mut Dsn {
+    catch_panic(ptr::null_mut(), || {
+        read_str(dsn)
+            .and_then(|dsn| dsn.parse().ok())
+            .map_or(ptr::null_mut(), |dsn| Box::into_raw(Box::new(dsn)))
+    })
+}
+
+/// Frees a dsn.
+///
+/// Passing null is a no-op.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_dsn_free(dsn: *mut Dsn) {
+    catch_panic((), || {
+        if !dsn.is_null() {
+            drop(Box::from_raw(dsn));
+        }
+    })
+}
+
+/// Returns the project id of a dsn, or 0 if the dsn is null.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_dsn_project_id(dsn: *const Dsn) -> u64 {
+    catch_panic(0, || dsn.as_ref().map_or(0, |dsn| dsn.project_id().value()))
+}
+
+/// Returns the public key of a dsn.
+///
+/// Returns null if the dsn is null.  The string must be freed with
+/// `sentry_types_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_dsn_public_key(dsn: *const Dsn) -> *mut c_char {
+    catch_panic(ptr::null_mut(), || {
+        dsn.as_ref().map_or(ptr::null_mut(), |dsn| {
+            into_c_string(dsn.public_key().to_string())
+        })
+    })
+}
+
+/// Returns the store endpoint url of a dsn.
+///
+/// Returns null if the dsn is null.  The string must be freed with
+/// `sentry_types_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn sentry_types_dsn_store_url(dsn: *const Dsn) -> *mut c_char {
+    catch_panic(ptr::null_mut(), || {
+        dsn.as_ref().map_or(ptr::null_mut(), |dsn| {
+            into_c_string(dsn.store_api_url().to_string())
+        })
+    })
+}
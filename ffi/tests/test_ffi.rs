@@ -0,0 +1,166 @@
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use sentry_types_ffi::*;
+
+fn c(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+unsafe fn take_string(s: *mut std::os::raw::c_char) -> String {
+    assert!(!s.is_null());
+    let rv = CStr::from_ptr(s).to_str().unwrap().to_string();
+    sentry_types_string_free(s);
+    rv
+}
+
+#[test]
+fn test_event_roundtrip() {
+    unsafe {
+        let event = sentry_types_event_new();
+        assert!(sentry_types_event_set_message(
+            event,
+            c("Hello World!").as_ptr()
+        ));
+        assert!(sentry_types_event_set_level(event, c("warning").as_ptr()));
+        assert!(!sentry_types_event_set_level(event, c("loud").as_ptr()));
+        assert!(sentry_types_event_set_tag(
+            event,
+            c("os").as_ptr(),
+            c("linux").as_ptr()
+        ));
+        assert!(!sentry_types_event_set_tag(
+            event,
+            ptr::null(),
+            c("x").as_ptr()
+        ));
+
+        let breadcrumb = sentry_types_breadcrumb_new();
+        assert!(sentry_types_breadcrumb_set_type(
+            breadcrumb,
+            c("http").as_ptr()
+        ));
+        assert!(sentry_types_breadcrumb_set_category(
+            breadcrumb,
+            c("xhr").as_ptr()
+        ));
+        assert!(sentry_types_breadcrumb_set_message(
+            breadcrumb,
+            c("GET /").as_ptr()
+        ));
+        assert!(sentry_types_breadcrumb_set_level(
+            breadcrumb,
+            c("error").as_ptr()
+        ));
+        assert!(sentry_types_event_add_breadcrumb(event, breadcrumb));
+
+        let json = take_string(sentry_types_event_to_json(event));
+        sentry_types_event_free(event);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["message"], "Hello World!");
+        assert_eq!(value["level"], "warning");
+        assert_eq!(value["tags"]["os"], "linux");
+        let crumb = &value["breadcrumbs"]["values"][0];
+        assert_eq!(crumb["type"], "http");
+        assert_eq!(crumb["category"], "xhr");
+        assert_eq!(crumb["message"], "GET /");
+        assert_eq!(crumb["level"], "error");
+
+        let parsed = sentry_types_event_from_json(c(&json).as_ptr());
+        assert!(!parsed.is_null());
+        let reparsed: serde_json::Value =
+            serde_json::from_str(&take_string(sentry_types_event_to_json(parsed))).unwrap();
+        assert_eq!(reparsed["event_id"], value["event_id"]);
+        assert_eq!(reparsed["breadcrumbs"]["values"][0]["message"], "GET /");
+        sentry_types_event_free(parsed);
+
+        assert!(sentry_types_event_from_json(c("{").as_ptr()).is_null());
+    }
+}
+
+#[test]
+fn test_null_pointers() {
+    unsafe {
+        assert!(sentry_types_event_to_json(ptr::null()).is_null());
+        assert!(!sentry_types_event_set_message(
+            ptr::null_mut(),
+            c("x").as_ptr()
+        ));
+        assert!(!sentry_types_event_add_breadcrumb(
+            ptr::null_mut(),
+            ptr::null_mut()
+        ));
+        sentry_types_event_free(ptr::null_mut());
+        sentry_types_breadcrumb_free(ptr::null_mut());
+        sentry_types_dsn_free(ptr::null_mut());
+        sentry_types_string_free(ptr::null_mut());
+        assert_eq!(sentry_types_dsn_project_id(ptr::null()), 0);
+    }
+}
+
+#[test]
+fn test_dsn() {
+    unsafe {
+        let dsn = sentry_types_dsn_parse(c("https://username@domain/42").as_ptr());
+        assert!(!dsn.is_null());
+        assert_eq!(sentry_types_dsn_project_id(dsn), 42);
+        assert_eq!(take_string(sentry_types_dsn_public_key(dsn)), "username");
+        assert_eq!(
+            take_string(sentry_types_dsn_store_url(dsn)),
+            "https://domain/api/42/store/"
+        );
+        sentry_types_dsn_free(dsn);
+
+        assert!(sentry_types_dsn_parse(c("not a dsn").as_ptr()).is_null());
+        assert!(sentry_types_dsn_parse(ptr::null()).is_null());
+    }
+}
+
+#[test]
+fn test_bounded_setters() {
+    unsafe {
+        let event = sentry_types_event_new();
+        let long = "x".repeat(10_000);
+        assert!(sentry_types_event_set_message(event, c(&long).as_ptr()));
+        assert!(sentry_types_event_set_tag(
+            event,
+            c("level").as_ptr(),
+            c("fatal").as_ptr()
+        ));
+        assert!(!sentry_types_event_set_tag(
+            event,
+            c("level").as_ptr(),
+            c("loud").as_ptr()
+        ));
+
+        let json = take_string(sentry_types_event_to_json(event));
+        sentry_types_event_free(event);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["level"], "fatal");
+        assert!(value.get("tags").is_none());
+        let message = value["message"].as_str().unwrap();
+        assert!(message.chars().count() <= sentry_types::protocol::v7::MAX_MESSAGE_LENGTH);
+    }
+}
+
+#[test]
+fn test_header_declares_all_functions() {
+    let header = include_str!("../include/sentry_types.h");
+    let source = include_str!("../src/lib.rs");
+    let mut count = 0;
+    for line in source.lines() {
+        let name = match line.split("extern \"C\" fn ").nth(1) {
+            Some(rest) => rest.split('(').next().unwrap(),
+            None => continue,
+        };
+        assert!(
+            header.contains(&format!(" {}(", name)) || header.contains(&format!("*{}(", name)),
+            "{} is not declared in the header",
+            name
+        );
+        count += 1;
+    }
+    assert_eq!(count, 20);
+}
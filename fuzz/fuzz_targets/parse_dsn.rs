@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(dsn) = sentry_types::parse_dsn_bytes(data) {
+        let _ = dsn.store_api_url();
+    }
+});
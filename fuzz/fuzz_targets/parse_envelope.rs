@@ -0,0 +1,20 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use sentry_types::protocol::envelope::parse_envelope_bytes;
+use sentry_types::protocol::limits::ParseLimits;
+
+fuzz_target!(|data: &[u8]| {
+    let envelope = match parse_envelope_bytes(data) {
+        Ok(envelope) => envelope,
+        Err(_) => return,
+    };
+
+    envelope.validate();
+    for item in envelope.items() {
+        let _ = item.attachment_type();
+        let _ = item.metrics();
+        let _ = item.replay_event();
+        let _ = item.replay_recording();
+        let _ = ParseLimits::default().check(item.payload());
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use sentry_types::protocol::normalize::NormalizeConfig;
+use sentry_types::protocol::v7::parse_event_bytes;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(mut event) = parse_event_bytes(data) {
+        event.normalize(&NormalizeConfig::default());
+        let _ = serde_json::to_vec(&event).unwrap();
+    }
+});
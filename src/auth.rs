@@ -1,17 +1,24 @@
 #[allow(unused_imports, deprecated)]
 use std::ascii::AsciiExt;
 use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::fmt;
-use std::str::FromStr;
+use std::str::{self, FromStr};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+#[cfg(feature = "with_signing")]
+use hmac::{Hmac, Mac};
+#[cfg(feature = "with_signing")]
+use sentry_types_core::hex;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use url::form_urlencoded;
 
 use crate::dsn::Dsn;
 use crate::protocol;
-use crate::utils::{datetime_to_timestamp, timestamp_to_datetime};
+#[cfg(feature = "clock")]
+use crate::utils::now;
+use crate::utils::{constant_time_eq, datetime_to_timestamp, timestamp_to_datetime};
 
 /// Represents an auth header parsing error.
 #[derive(Debug, Error, Copy, Clone, Eq, PartialEq)]
@@ -27,6 +34,43 @@ pub enum ParseAuthError {
     MissingPublicKey,
 }
 
+/// An error raised if the timestamp of an auth header is not acceptable.
+///
+/// See `Auth::validate_timestamp`.
+#[derive(Debug, Error, Copy, Clone, Eq, PartialEq)]
+pub enum AuthTimestampError {
+    /// Raised if the auth header has no timestamp.
+    #[error("missing timestamp in auth header")]
+    Missing,
+    /// Raised if the timestamp is older than the allowed skew.
+    #[error("auth timestamp is too old by {age}")]
+    TooOld {
+        /// How far the timestamp lies in the past.
+        age: Duration,
+    },
+    /// Raised if the timestamp lies further in the future than the allowed
+    /// skew.
+    #[error("auth timestamp is in the future by {ahead}")]
+    InFuture {
+        /// How far the timestamp lies in the future.
+        ahead: Duration,
+    },
+}
+
+/// An error raised if an auth header cannot be sent as HTTP header.
+///
+/// See `Auth::to_header_value`.
+#[derive(Debug, Error, Copy, Clone, Eq, PartialEq)]
+#[error("auth header contains characters not allowed in http headers")]
+pub struct InvalidAuthHeaderValue;
+
+/// The name of the HTTP header carrying the auth header.
+pub const AUTH_HEADER: &str = "X-Sentry-Auth";
+
+/// The name of the header carrying the signature of a signed request.
+#[cfg(feature = "with_signing")]
+pub const SIGNATURE_HEADER: &str = "X-Sentry-Signature";
+
 /// Represents an auth header.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Auth {
@@ -75,7 +119,7 @@ impl Auth {
                 }
                 "sentry_version" => {
                     rv.version = value
-                        .splitn(2, '.')
+                        .split('.')
                         .next()
                         .and_then(|v| v.parse().ok())
                         .ok_or(ParseAuthError::InvalidVersion)?;
@@ -112,6 +156,13 @@ impl Auth {
         self.version
     }
 
+    /// Returns the protocol version the client speaks as `Version`.
+    ///
+    /// Returns `None` if the version is not supported by this crate.
+    pub fn protocol_version(&self) -> Option<protocol::Version> {
+        protocol::Version::try_from(self.version).ok()
+    }
+
     /// Returns the public key
     pub fn public_key(&self) -> &str {
         &self.key
@@ -122,6 +173,70 @@ impl Auth {
         self.secret.as_deref()
     }
 
+    /// Compares the public key with the given key in constant time.
+    pub fn public_key_eq(&self, public_key: &str) -> bool {
+        constant_time_eq(&self.key, public_key)
+    }
+
+    /// Compares the client's secret with the given key in constant time.
+    ///
+    /// Returns `false` if the client did not authenticate with a secret.
+    pub fn secret_key_eq(&self, secret_key: &str) -> bool {
+        self.secret
+            .as_deref()
+            .is_some_and(|secret| constant_time_eq(secret, secret_key))
+    }
+
+    /// Checks whether the credentials of the auth header match a dsn.
+    ///
+    /// The public keys must match.  If the client authenticated with a
+    /// secret, it must match the secret key of the dsn as well.  All
+    /// comparisons run in constant time.
+    pub fn matches_dsn(&self, dsn: &Dsn) -> bool {
+        let public_key_matches = self.public_key_eq(dsn.public_key());
+        let secret_key_matches = match (self.secret.as_deref(), dsn.secret_key()) {
+            (None, _) => true,
+            (Some(secret), Some(expected)) => constant_time_eq(secret, expected),
+            (Some(_), None) => false,
+        };
+        public_key_matches & secret_key_matches
+    }
+
+    /// Checks that the timestamp lies within `max_skew` of `now`.
+    ///
+    /// Servers use this to reject replayed requests.  Since SDKs are not
+    /// required to send a timestamp, the caller decides whether a missing
+    /// timestamp is acceptable.
+    pub fn validate_timestamp(
+        &self,
+        now: DateTime<Utc>,
+        max_skew: Duration,
+    ) -> Result<(), AuthTimestampError> {
+        let timestamp = self.timestamp.ok_or(AuthTimestampError::Missing)?;
+        let age = now - timestamp;
+        if age > max_skew {
+            Err(AuthTimestampError::TooOld { age })
+        } else if -age > max_skew {
+            Err(AuthTimestampError::InFuture { ahead: -age })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the value of the `X-Sentry-Auth` HTTP header.
+    ///
+    /// This is the same as the `Display` output, validated to only contain
+    /// visible ASCII characters and spaces.  Values parsed from untrusted
+    /// input, such as the client agent, may contain other characters.
+    pub fn to_header_value(&self) -> Result<String, InvalidAuthHeaderValue> {
+        let value = self.to_string();
+        if value.bytes().all(|b| b == b' ' || b.is_ascii_graphic()) {
+            Ok(value)
+        } else {
+            Err(InvalidAuthHeaderValue)
+        }
+    }
+
     /// Returns true if the authentication implies public auth (no secret)
     pub fn is_public(&self) -> bool {
         self.secret.is_none()
@@ -131,6 +246,72 @@ impl Auth {
     pub fn client_agent(&self) -> Option<&str> {
         self.client.as_deref()
     }
+
+    /// Creates the HMAC-SHA256 of the signed message for a request body.
+    ///
+    /// The signed message is the body prefixed with the unix timestamp of
+    /// the auth header and a space, if the auth header has a timestamp.
+    #[cfg(feature = "with_signing")]
+    fn signature_mac(&self, secret_key: &str, body: &[u8]) -> Hmac<sha2::Sha256> {
+        // HMAC accepts keys of any length
+        let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret_key.as_bytes()).unwrap();
+        if let Some(timestamp) = self.timestamp {
+            mac.update(format!("{} ", timestamp.timestamp()).as_bytes());
+        }
+        mac.update(body);
+        mac
+    }
+
+    /// Computes the signature of a request body.
+    ///
+    /// The signature is the hex encoded HMAC-SHA256 of the body, keyed with
+    /// the secret key.  If the auth header has a timestamp, it is part of the
+    /// signed message, so the header must be sent along with the signature
+    /// in the `X-Sentry-Signature` header.
+    #[cfg(feature = "with_signing")]
+    pub fn sign(&self, secret_key: &str, body: &[u8]) -> String {
+        let signature = self.signature_mac(secret_key, body).finalize().into_bytes();
+        signature
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Verifies the signature of a request body.
+    ///
+    /// The comparison runs in constant time.  Signatures that are not valid
+    /// hex strings are rejected.
+    #[cfg(feature = "with_signing")]
+    pub fn verify_signature(&self, secret_key: &str, body: &[u8], signature: &str) -> bool {
+        let signature = signature.trim();
+        let mut bytes = vec![0; signature.len() / 2];
+        match hex::decode(signature, &mut bytes) {
+            Some(()) => self
+                .signature_mac(secret_key, body)
+                .verify_slice(&bytes)
+                .is_ok(),
+            None => false,
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl<'a> TryFrom<&'a Auth> for http::HeaderValue {
+    type Error = InvalidAuthHeaderValue;
+
+    fn try_from(auth: &'a Auth) -> Result<http::HeaderValue, InvalidAuthHeaderValue> {
+        let value = auth.to_header_value()?;
+        http::HeaderValue::from_str(&value).map_err(|_| InvalidAuthHeaderValue)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Auth {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        self.key.zeroize();
+        self.secret.zeroize();
+    }
 }
 
 impl fmt::Display for Auth {
@@ -179,9 +360,24 @@ impl FromStr for Auth {
     }
 }
 
+/// Parses an auth header value from raw bytes.
+///
+/// Input that is not valid UTF-8 is rejected as a non sentry auth header.
+/// This never panics, no matter the input, so it is safe to use on untrusted
+/// data.
+pub fn parse_auth_bytes(bytes: &[u8]) -> Result<Auth, ParseAuthError> {
+    str::from_utf8(bytes)
+        .map_err(|_| ParseAuthError::NonSentryAuth)?
+        .parse()
+}
+
 pub(crate) fn auth_from_dsn_and_client(dsn: &Dsn, client: Option<&str>) -> Auth {
     Auth {
-        timestamp: Some(Utc::now()),
+        // without a clock, the header is sent without a timestamp
+        #[cfg(feature = "clock")]
+        timestamp: Some(now()),
+        #[cfg(not(feature = "clock"))]
+        timestamp: None,
         client: client.map(|x| x.to_string()),
         version: protocol::LATEST,
         key: dsn.public_key().to_string(),
@@ -0,0 +1,103 @@
+use std::fmt;
+use std::time;
+
+use chrono::{DateTime, Utc};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A non-negative span of time with microsecond precision.
+///
+/// The protocol transmits durations as floating point numbers, whose unit
+/// depends on the payload.  This type makes the unit explicit at every
+/// conversion and serializes as seconds, which is what sessions expect:
+///
+/// ```rust
+/// use sentry_types::Duration;
+///
+/// let duration = Duration::from_millis_f64(1500.0).unwrap();
+/// assert_eq!(duration.as_secs_f64(), 1.5);
+/// assert_eq!(serde_json::to_string(&duration).unwrap(), "1.5");
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration(time::Duration);
+
+impl Duration {
+    /// Creates a duration from seconds.
+    ///
+    /// Returns `None` if the value is negative, not finite or out of range.
+    pub fn from_secs_f64(secs: f64) -> Option<Duration> {
+        if !secs.is_finite() || secs < 0.0 || secs >= u64::MAX as f64 {
+            return None;
+        }
+
+        let micros = (secs.fract() * 1_000_000.0).round() as u64;
+        Some(Duration(
+            time::Duration::from_secs(secs.trunc() as u64) + time::Duration::from_micros(micros),
+        ))
+    }
+
+    /// Creates a duration from milliseconds.
+    ///
+    /// Returns `None` if the value is negative, not finite or out of range.
+    pub fn from_millis_f64(millis: f64) -> Option<Duration> {
+        Duration::from_secs_f64(millis / 1000.0)
+    }
+
+    /// Returns the duration between two points in time.
+    ///
+    /// If `end` is before `start`, for instance because the clock was
+    /// adjusted, the duration is zero.
+    pub fn between(start: DateTime<Utc>, end: DateTime<Utc>) -> Duration {
+        Duration((end - start).to_std().unwrap_or_default())
+    }
+
+    /// Returns the duration in seconds.
+    pub fn as_secs_f64(&self) -> f64 {
+        self.0.as_secs() as f64 + f64::from(self.0.subsec_micros()) / 1_000_000.0
+    }
+
+    /// Returns the duration in milliseconds.
+    pub fn as_millis_f64(&self) -> f64 {
+        self.0.as_secs() as f64 * 1000.0 + f64::from(self.0.subsec_micros()) / 1000.0
+    }
+
+    /// Returns the duration as `std::time::Duration`.
+    pub fn as_std(&self) -> time::Duration {
+        self.0
+    }
+}
+
+impl From<time::Duration> for Duration {
+    fn from(duration: time::Duration) -> Duration {
+        Duration(duration)
+    }
+}
+
+impl From<Duration> for time::Duration {
+    fn from(duration: Duration) -> time::Duration {
+        duration.0
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}s", self.as_secs_f64())
+    }
+}
+
+impl Serialize for Duration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0.subsec_nanos() == 0 {
+            serializer.serialize_u64(self.0.as_secs())
+        } else {
+            serializer.serialize_f64(self.as_secs_f64())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let secs = f64::deserialize(deserializer)?;
+        Duration::from_secs_f64(secs)
+            .ok_or_else(|| de::Error::custom(format!("invalid duration: {}", secs)))
+    }
+}
@@ -0,0 +1,29 @@
+use thiserror::Error as ThisError;
+
+use crate::auth::ParseAuthError;
+use crate::dsn::ParseDsnError;
+#[cfg(feature = "with_protocol")]
+use crate::protocol::envelope::ParseEnvelopeError;
+
+/// An error of any of the subsystems of this crate.
+///
+/// Every variant wraps the error of the failing subsystem, which is also
+/// returned from `source()`.  All subsystem errors convert into this type, so
+/// it can be used as the error of functions combining several of them.
+#[derive(Debug, ThisError)]
+#[non_exhaustive]
+pub enum Error {
+    /// Raised if a dsn cannot be parsed.
+    #[error("invalid dsn")]
+    Dsn(#[from] ParseDsnError),
+    /// Raised if an auth header cannot be parsed.
+    #[error("invalid auth header")]
+    Auth(#[from] ParseAuthError),
+    /// Raised if a protocol payload cannot be deserialized.
+    #[error("invalid protocol payload")]
+    Protocol(#[from] serde_json::Error),
+    /// Raised if an envelope cannot be parsed.
+    #[cfg(feature = "with_protocol")]
+    #[error("invalid envelope")]
+    Envelope(#[from] ParseEnvelopeError),
+}
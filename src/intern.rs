@@ -0,0 +1,181 @@
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops;
+use std::sync::Arc;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+thread_local! {
+    static INTERNER: RefCell<Option<HashSet<Arc<str>>>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous interner when dropped, even on panics.
+struct InternerGuard(Option<HashSet<Arc<str>>>);
+
+impl Drop for InternerGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        INTERNER.with(|interner| *interner.borrow_mut() = previous);
+    }
+}
+
+/// Runs `f` with string interning enabled on the current thread.
+///
+/// Equal `ThinString`s created or deserialized within `f` share a single
+/// allocation.  Large events repeat module names and filenames of frames, as
+/// well as the keys of tags and data, thousands of times, so this cuts the
+/// memory of retained events:
+///
+/// ```rust
+/// use sentry_types::with_interner;
+/// use sentry_types::protocol::v7::parse_event_bytes;
+///
+/// # #[cfg(feature = "clock")] {
+/// let bytes = br#"{"stacktrace":{"frames":[{"module":"app"},{"module":"app"}]}}"#;
+/// let event = with_interner(|| parse_event_bytes(bytes)).unwrap();
+/// # }
+/// ```
+///
+/// The interned strings are released when `f` returns unless they are still
+/// referenced.
+pub fn with_interner<R, F: FnOnce() -> R>(f: F) -> R {
+    let previous = INTERNER.with(|interner| interner.borrow_mut().replace(HashSet::new()));
+    let _guard = InternerGuard(previous);
+    f()
+}
+
+/// A cheaply clonable, immutable string.
+///
+/// Clones share the same allocation.  Within `with_interner`, equal strings
+/// also share an allocation when they are created separately.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ThinString(Arc<str>);
+
+impl ThinString {
+    /// Creates a string, interning it if `with_interner` is active.
+    pub fn new(string: &str) -> ThinString {
+        INTERNER.with(|interner| match *interner.borrow_mut() {
+            Some(ref mut strings) => match strings.get(string) {
+                Some(interned) => ThinString(interned.clone()),
+                None => {
+                    let interned: Arc<str> = Arc::from(string);
+                    strings.insert(interned.clone());
+                    ThinString(interned)
+                }
+            },
+            None => ThinString(Arc::from(string)),
+        })
+    }
+
+    /// Returns the string as a slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns true if both strings share the same allocation.
+    pub fn ptr_eq(&self, other: &ThinString) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Default for ThinString {
+    fn default() -> ThinString {
+        ThinString::new("")
+    }
+}
+
+impl ops::Deref for ThinString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for ThinString {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for ThinString {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ThinString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for ThinString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl<'a> From<&'a str> for ThinString {
+    fn from(string: &'a str) -> ThinString {
+        ThinString::new(string)
+    }
+}
+
+impl From<String> for ThinString {
+    fn from(string: String) -> ThinString {
+        ThinString::new(&string)
+    }
+}
+
+impl From<ThinString> for String {
+    fn from(string: ThinString) -> String {
+        string.0.to_string()
+    }
+}
+
+impl PartialEq<str> for ThinString {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for ThinString {
+    fn eq(&self, other: &&'a str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for ThinString {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl Serialize for ThinString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ThinString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ThinString, D::Error> {
+        struct ThinStringVisitor;
+
+        impl<'de> de::Visitor<'de> for ThinStringVisitor {
+            type Value = ThinString;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a string")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<ThinString, E> {
+                Ok(ThinString::new(v))
+            }
+        }
+
+        deserializer.deserialize_str(ThinStringVisitor)
+    }
+}
@@ -15,6 +15,29 @@
 //! Right now only `v7` of the protocol is implemented but it's versioned
 //! so later versions might be added later.
 //!
+//! ## Targets Without a System Clock
+//!
+//! The default `clock` feature uses the system clock and random number
+//! generator to fill in timestamps and ids of new events.  On targets without
+//! them, such as `wasm32-unknown-unknown` without JavaScript bindings, disable
+//! default features and enable `with_protocol` only.  Types whose defaults
+//! need the current time or a random id, such as `Event` and `Breadcrumb`,
+//! then do not implement `Default`, and payloads must contain these fields.
+//! `Event::with_id` creates events from ids and timestamps obtained
+//! elsewhere, and `Timestamp::from_millis` converts such times.  The
+//! `compression` feature links C libraries and is not available on these
+//! targets.
+//!
+//! ## Untrusted Input
+//!
+//! The parsers for data received from clients never panic, no matter the
+//! input: `parse_dsn_bytes`, `parse_auth_bytes`,
+//! `protocol::v7::parse_event_bytes` and
+//! `protocol::envelope::parse_envelope_bytes`.  Each of them has a
+//! `cargo-fuzz` target in the `fuzz` directory, whose corpus also runs as a
+//! test with the `fuzz_corpus` feature.  Use
+//! `protocol::limits::ParseLimits` to bound the resources spent on payloads.
+//!
 //! ## API Concepts
 //!
 //! Most types are directly serializable or deserializable and try to implement
@@ -24,13 +47,24 @@
 //! ```rust
 //! use sentry_types::protocol::v7;
 //!
+//! # #[cfg(feature = "clock")] {
 //! let event = v7::Event {
 //!     message: Some("Hello World!".to_string()),
 //!     culprit: Some("foo in bar".to_string()),
 //!     level: v7::Level::Info,
 //!     ..Default::default()
 //! };
+//! # }
 //! ```
+//!
+//! All protocol types implement `PartialEq`, so events and their parts can be
+//! compared directly.  Floating point fields, such as `sample_rate` or the
+//! values of metric buckets, compare with IEEE 754 semantics: `0.0` equals
+//! `-0.0` and a value containing `NaN` is not equal to itself.
+//!
+//! Owned protocol types, including `Event<'static>` and `Envelope`, are
+//! `Clone + Send + Sync`, so they can be handed to other threads or async
+//! tasks.  Shared data is reference counted with `Arc`.
 #![warn(missing_docs)]
 
 #[macro_use]
@@ -38,13 +72,23 @@ mod macros;
 
 mod auth;
 mod dsn;
+mod duration;
+mod error;
+mod intern;
 mod project_id;
 pub mod protocol;
-mod utils;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+mod timestamp;
+pub mod utils;
 
 pub use crate::auth::*;
 pub use crate::dsn::*;
+pub use crate::duration::*;
+pub use crate::error::*;
+pub use crate::intern::*;
 pub use crate::project_id::*;
+pub use crate::timestamp::*;
 
 // Re-export external types and traits for convenience
 pub use chrono::{DateTime, ParseError as ChronoParseError, TimeZone, Utc};
@@ -58,8 +58,6 @@ mod str_tests {
     use std::io::Cursor;
     use std::str::FromStr;
 
-    use serde_json;
-
     struct Test;
 
     impl FromStr for Test {
@@ -127,10 +125,11 @@ macro_rules! impl_hex_de {
             type Err = ::std::num::ParseIntError;
 
             fn from_str(s: &str) -> Result<$type, ::std::num::ParseIntError> {
+                let s = s.trim();
                 if s.starts_with("0x") || s.starts_with("0X") {
                     $num::from_str_radix(&s[2..], 16).map($type)
                 } else {
-                    $num::from_str_radix(&s, 10).map($type)
+                    s.parse::<$num>().map($type)
                 }
             }
         }
@@ -157,6 +156,17 @@ macro_rules! impl_hex_de {
                         Ok($type(v as $num))
                     }
 
+                    fn visit_f64<E: ::serde::de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                        // JavaScript SDKs may send whole numbers as floats.  The
+                        // bound is `MAX + 1`, a power of two that is exact as a
+                        // float, while `u64::MAX` itself rounds up to it.
+                        if v.fract() == 0.0 && v >= 0.0 && v < $num::MAX as f64 + 1.0 {
+                            Ok($type(v as $num))
+                        } else {
+                            Err(E::invalid_value(::serde::de::Unexpected::Float(v), &self))
+                        }
+                    }
+
                     fn visit_str<E: ::serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
                         v.parse().map_err(::serde::de::Error::custom)
                     }
@@ -184,7 +194,6 @@ macro_rules! impl_hex_serde {
 
 #[cfg(test)]
 mod hex_tests {
-    use serde_json;
     use std::io::Cursor;
 
     #[derive(Debug, PartialEq)]
@@ -240,6 +249,16 @@ mod hex_tests {
         );
     }
 
+    #[test]
+    fn test_hex_deserialize_lenient() {
+        assert_eq!(Hex(42), serde_json::from_str("\" 0x2a \"").unwrap());
+        assert_eq!(Hex(42), serde_json::from_str("42.0").unwrap());
+        assert!(serde_json::from_str::<Hex>("42.5").is_err());
+        assert!(serde_json::from_str::<Hex>("-1.0").is_err());
+        assert_eq!(Hex(u32::MAX), serde_json::from_str("4294967295.0").unwrap());
+        assert!(serde_json::from_str::<Hex>("4294967296.0").is_err());
+    }
+
     #[test]
     fn test_invalid() {
         let result = serde_json::from_str::<Hex>("true").unwrap_err();
@@ -249,3 +268,154 @@ mod hex_tests {
         );
     }
 }
+
+/// Creates a `ClientSdkInfo` describing the crate this macro is invoked in.
+///
+/// The name and version of the SDK are taken from the Cargo environment
+/// variables of the calling crate, which is also recorded as a `cargo:`
+/// package.  Optionally a list of enabled integrations can be passed:
+///
+/// ```rust
+/// # #[macro_use] extern crate sentry_types;
+/// # fn main() {
+/// let sdk = client_sdk_info!["panic", "log"];
+/// assert_eq!(sdk.name, env!("CARGO_PKG_NAME"));
+/// assert_eq!(sdk.integrations, vec!["panic", "log"]);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! client_sdk_info {
+    ($($integration:expr),* $(,)?) => {
+        $crate::protocol::v7::ClientSdkInfo {
+            name: env!("CARGO_PKG_NAME").into(),
+            version: env!("CARGO_PKG_VERSION").into(),
+            integrations: vec![$($integration.into()),*],
+            packages: vec![$crate::protocol::v7::ClientSdkPackage {
+                name: concat!("cargo:", env!("CARGO_PKG_NAME")).into(),
+                version: env!("CARGO_PKG_VERSION").into(),
+            }],
+        }
+    };
+}
+
+/// Creates an `Event` from a list of fields, defaulting all other fields.
+///
+/// This requires the `clock` feature.
+///
+/// Values are converted with `Into`.  Optional string fields such as
+/// `message` take the string directly, `level` takes the name of a `Level`
+/// variant, and `tags` and `extra` take maps in `{ key => value }` syntax.
+/// `release` and `environment` are validated and panic if invalid:
+///
+/// ```rust
+/// # #[macro_use] extern crate sentry_types;
+/// # fn main() {
+/// let event = event! {
+///     level: Warning,
+///     message: "Hello World!",
+///     release: "app@1.0.0",
+///     tags: { "shard" => "7" },
+///     extra: { "attempts" => 3 },
+/// };
+/// assert_eq!(event.message.as_deref(), Some("Hello World!"));
+/// assert_eq!(event.tags["shard"], "7");
+/// # }
+/// ```
+#[cfg(feature = "clock")]
+#[macro_export]
+macro_rules! event {
+    (@field $e:ident; level: $level:ident $(, $($rest:tt)*)?) => {
+        $e.level = $crate::protocol::v7::Level::$level;
+        $crate::event!(@field $e; $($($rest)*)?);
+    };
+    (@field $e:ident; tags: { $($key:expr => $value:expr),* $(,)? } $(, $($rest:tt)*)?) => {
+        $($e.tags.insert(($key).into(), ($value).into());)*
+        $crate::event!(@field $e; $($($rest)*)?);
+    };
+    (@field $e:ident; extra: { $($key:expr => $value:expr),* $(,)? } $(, $($rest:tt)*)?) => {
+        $($e.extra.insert(($key).into(), ($value).into());)*
+        $crate::event!(@field $e; $($($rest)*)?);
+    };
+    (@field $e:ident; fingerprint: [ $($part:expr),* $(,)? ] $(, $($rest:tt)*)?) => {
+        $e.fingerprint = vec![$(($part).into()),*].into();
+        $crate::event!(@field $e; $($($rest)*)?);
+    };
+    (@field $e:ident; release: $value:expr $(, $($rest:tt)*)?) => {
+        $e.release = Some($crate::protocol::v7::Release::new($value).expect("invalid release"));
+        $crate::event!(@field $e; $($($rest)*)?);
+    };
+    (@field $e:ident; environment: $value:expr $(, $($rest:tt)*)?) => {
+        $e.environment =
+            Some($crate::protocol::v7::Environment::new($value).expect("invalid environment"));
+        $crate::event!(@field $e; $($($rest)*)?);
+    };
+    (@field $e:ident; $field:ident: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::event!(@optional $e; $field; $value);
+        $crate::event!(@field $e; $($($rest)*)?);
+    };
+    (@field $e:ident;) => {};
+    (@optional $e:ident; message; $value:expr) => { $e.message = Some(($value).into()); };
+    (@optional $e:ident; culprit; $value:expr) => { $e.culprit = Some(($value).into()); };
+    (@optional $e:ident; transaction; $value:expr) => { $e.transaction = Some(($value).into()); };
+    (@optional $e:ident; logger; $value:expr) => { $e.logger = Some(($value).into()); };
+    (@optional $e:ident; server_name; $value:expr) => { $e.server_name = Some(($value).into()); };
+    (@optional $e:ident; dist; $value:expr) => { $e.dist = Some(($value).into()); };
+    (@optional $e:ident; $field:ident; $value:expr) => { $e.$field = ($value).into(); };
+    ($($fields:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut event = $crate::protocol::v7::Event::default();
+        $crate::event!(@field event; $($fields)*);
+        event
+    }};
+}
+
+/// Creates a `Breadcrumb` from a list of fields, defaulting all other fields.
+///
+/// This requires the `clock` feature.
+///
+/// Values are converted with `Into`.  `message` and `category` take the
+/// string directly, `level` takes the name of a `Level` variant, and `data`
+/// takes a map in `{ key => value }` syntax:
+///
+/// ```rust
+/// # #[macro_use] extern crate sentry_types;
+/// # fn main() {
+/// let breadcrumb = breadcrumb! {
+///     ty: "http",
+///     category: "request",
+///     data: { "status_code" => 200 },
+/// };
+/// assert_eq!(breadcrumb.category.as_deref(), Some("request"));
+/// # }
+/// ```
+#[cfg(feature = "clock")]
+#[macro_export]
+macro_rules! breadcrumb {
+    (@field $b:ident; level: $level:ident $(, $($rest:tt)*)?) => {
+        $b.level = $crate::protocol::v7::Level::$level;
+        $crate::breadcrumb!(@field $b; $($($rest)*)?);
+    };
+    (@field $b:ident; data: { $($key:expr => $value:expr),* $(,)? } $(, $($rest:tt)*)?) => {
+        $($b.data.insert(($key).into(), ($value).into());)*
+        $crate::breadcrumb!(@field $b; $($($rest)*)?);
+    };
+    (@field $b:ident; message: $value:expr $(, $($rest:tt)*)?) => {
+        $b.message = Some(($value).into());
+        $crate::breadcrumb!(@field $b; $($($rest)*)?);
+    };
+    (@field $b:ident; category: $value:expr $(, $($rest:tt)*)?) => {
+        $b.category = Some(($value).into());
+        $crate::breadcrumb!(@field $b; $($($rest)*)?);
+    };
+    (@field $b:ident; $field:ident: $value:expr $(, $($rest:tt)*)?) => {
+        $b.$field = ($value).into();
+        $crate::breadcrumb!(@field $b; $($($rest)*)?);
+    };
+    (@field $b:ident;) => {};
+    ($($fields:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut breadcrumb = $crate::protocol::v7::Breadcrumb::default();
+        $crate::breadcrumb!(@field breadcrumb; $($fields)*);
+        breadcrumb
+    }};
+}
@@ -0,0 +1,474 @@
+//! Grouping enhancements (stack trace rules).
+//!
+//! Enhancement rules modify how frames of a stack trace are treated by the
+//! grouping algorithm.  Every rule consists of one or more matchers followed
+//! by one or more actions, for instance:
+//!
+//! ```text
+//! # mark all frames from our own crates as in-app
+//! stack.module:mycrate::* +app
+//! family:native stack.function:std::panicking::* -group
+//! stack.function:handle_panic ^-group
+//! ```
+//!
+//! Applying the rules to a stack trace allows previewing the effects of a
+//! grouping configuration before it is uploaded to Sentry.
+use std::fmt;
+use std::str;
+
+use thiserror::Error;
+
+use crate::protocol::v7::{Frame, Platform, Stacktrace};
+use crate::utils::glob_match;
+
+/// Represents an error parsing enhancement rules.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ParseEnhancementsError {
+    /// Raised if a matcher is malformed.
+    #[error("invalid matcher on line {0}")]
+    InvalidMatcher(usize),
+    /// Raised if a matcher uses an unsupported key.
+    #[error("unknown matcher `{1}` on line {0}")]
+    UnknownMatcher(usize, String),
+    /// Raised if an action is malformed or unsupported.
+    #[error("invalid action `{1}` on line {0}")]
+    InvalidAction(usize, String),
+    /// Raised if a quoted value is not terminated.
+    #[error("unterminated string on line {0}")]
+    UnterminatedString(usize),
+    /// Raised if a rule has no matchers.
+    #[error("missing matchers on line {0}")]
+    MissingMatchers(usize),
+    /// Raised if a rule has no actions.
+    #[error("missing actions on line {0}")]
+    MissingActions(usize),
+}
+
+/// The frame attribute a matcher is applied to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MatcherKey {
+    /// Matches the module of the frame (`stack.module`).
+    Module,
+    /// Matches the function of the frame (`stack.function`).
+    Function,
+    /// Matches the absolute path or filename of the frame (`stack.abs_path`).
+    Path,
+    /// Matches the package of the frame (`stack.package`).
+    Package,
+    /// Matches the platform family of the frame (`family`).
+    Family,
+    /// Matches the in-app flag of the frame (`app`).
+    App,
+}
+
+impl MatcherKey {
+    fn from_key(key: &str) -> Option<MatcherKey> {
+        Some(match key {
+            "stack.module" | "module" => MatcherKey::Module,
+            "stack.function" | "function" => MatcherKey::Function,
+            "stack.abs_path" | "stack.path" | "path" => MatcherKey::Path,
+            "stack.package" | "package" => MatcherKey::Package,
+            "family" => MatcherKey::Family,
+            "app" => MatcherKey::App,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for MatcherKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MatcherKey::Module => write!(f, "stack.module"),
+            MatcherKey::Function => write!(f, "stack.function"),
+            MatcherKey::Path => write!(f, "stack.abs_path"),
+            MatcherKey::Package => write!(f, "stack.package"),
+            MatcherKey::Family => write!(f, "family"),
+            MatcherKey::App => write!(f, "app"),
+        }
+    }
+}
+
+/// Matches a single attribute of a frame against a pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matcher {
+    /// The attribute to match.
+    pub key: MatcherKey,
+    /// The glob pattern (or value list for `family` and `app`).
+    pub pattern: String,
+    /// Inverts the result of the matcher.
+    pub negated: bool,
+}
+
+impl Matcher {
+    /// Checks whether the matcher matches the given frame.
+    pub fn matches_frame(&self, frame: &Frame, platform: &Platform) -> bool {
+        self.negated != self.matches_frame_inner(frame, platform)
+    }
+
+    fn matches_frame_inner(&self, frame: &Frame, platform: &Platform) -> bool {
+        match self.key {
+            MatcherKey::Module => frame
+                .module
+                .as_deref()
+                .is_some_and(|module| glob_match(&self.pattern, module, identity)),
+            MatcherKey::Function => frame
+                .function
+                .as_deref()
+                .is_some_and(|function| glob_match(&self.pattern, function, identity)),
+            MatcherKey::Path => [frame.abs_path.as_deref(), frame.filename.as_deref()]
+                .iter()
+                .flatten()
+                .any(|path| glob_match(&self.pattern, path, normalize_path)),
+            MatcherKey::Package => frame
+                .package
+                .as_deref()
+                .is_some_and(|package| glob_match(&self.pattern, package, normalize_path)),
+            MatcherKey::Family => {
+                let family = platform_family(platform);
+                self.pattern
+                    .split(',')
+                    .any(|value| value == "all" || value == family)
+            }
+            MatcherKey::App => {
+                let in_app = frame.in_app == Some(true);
+                match self.pattern.as_str() {
+                    "1" | "yes" | "true" => in_app,
+                    "0" | "no" | "false" => !in_app,
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negated {
+            write!(f, "!")?;
+        }
+        write!(f, "{}:", self.key)?;
+        if self.pattern.is_empty() || self.pattern.contains(char::is_whitespace) {
+            write!(f, "{:?}", self.pattern)
+        } else {
+            write!(f, "{}", self.pattern)
+        }
+    }
+}
+
+/// The frame flag modified by an action.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FrameFlag {
+    /// The in-app flag of the frame.
+    App,
+    /// Whether the frame contributes to grouping.
+    Group,
+}
+
+/// The frames an action applies to relative to the matching frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ActionRange {
+    /// All frames called by the matching frame (`^`).
+    Up,
+    /// All frames calling the matching frame (`v`).
+    Down,
+}
+
+/// An action applied to frames matched by a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Sets or clears a flag, optionally on a range of frames.
+    Flag {
+        /// The flag to modify.
+        flag: FrameFlag,
+        /// The new value of the flag.
+        value: bool,
+        /// The range of frames to modify instead of the matching frame.
+        range: Option<ActionRange>,
+    },
+    /// Limits the number of frames contributing to grouping.
+    MaxFrames(usize),
+}
+
+impl Action {
+    fn parse(token: &str, line: usize) -> Result<Action, ParseEnhancementsError> {
+        let invalid = || ParseEnhancementsError::InvalidAction(line, token.to_string());
+
+        if let Some(value) = token.strip_prefix("max-frames=") {
+            return value.parse().map(Action::MaxFrames).map_err(|_| invalid());
+        }
+
+        let (range, rest) = match token.as_bytes().first() {
+            Some(b'^') => (Some(ActionRange::Up), &token[1..]),
+            Some(b'v') => (Some(ActionRange::Down), &token[1..]),
+            _ => (None, token),
+        };
+
+        let (value, flag) = match rest.as_bytes().first() {
+            Some(b'+') => (true, &rest[1..]),
+            Some(b'-') => (false, &rest[1..]),
+            _ => return Err(invalid()),
+        };
+
+        let flag = match flag {
+            "app" => FrameFlag::App,
+            "group" => FrameFlag::Group,
+            _ => return Err(invalid()),
+        };
+
+        Ok(Action::Flag { flag, value, range })
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Action::Flag { flag, value, range } => {
+                match range {
+                    Some(ActionRange::Up) => write!(f, "^")?,
+                    Some(ActionRange::Down) => write!(f, "v")?,
+                    None => {}
+                }
+                write!(f, "{}", if value { "+" } else { "-" })?;
+                match flag {
+                    FrameFlag::App => write!(f, "app"),
+                    FrameFlag::Group => write!(f, "group"),
+                }
+            }
+            Action::MaxFrames(max) => write!(f, "max-frames={}", max),
+        }
+    }
+}
+
+/// A single enhancement rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    /// The matchers that all have to match a frame.
+    pub matchers: Vec<Matcher>,
+    /// The actions applied to matching frames.
+    pub actions: Vec<Action>,
+}
+
+impl Rule {
+    /// Checks whether all matchers of the rule match the given frame.
+    pub fn matches_frame(&self, frame: &Frame, platform: &Platform) -> bool {
+        self.matchers
+            .iter()
+            .all(|matcher| matcher.matches_frame(frame, platform))
+    }
+
+    fn parse(line: &str, lineno: usize) -> Result<Rule, ParseEnhancementsError> {
+        let mut matchers = Vec::new();
+        let mut actions = Vec::new();
+
+        for token in tokenize(line, lineno)? {
+            if is_action(&token) {
+                actions.push(Action::parse(&token, lineno)?);
+                continue;
+            }
+
+            if !actions.is_empty() {
+                return Err(ParseEnhancementsError::InvalidAction(lineno, token));
+            }
+
+            let (negated, token) = match token.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, token.as_str()),
+            };
+            let (key, pattern) = token
+                .split_once(':')
+                .ok_or(ParseEnhancementsError::InvalidMatcher(lineno))?;
+            let key = MatcherKey::from_key(key)
+                .ok_or_else(|| ParseEnhancementsError::UnknownMatcher(lineno, key.to_string()))?;
+
+            matchers.push(Matcher {
+                key,
+                pattern: pattern.to_string(),
+                negated,
+            });
+        }
+
+        if matchers.is_empty() {
+            return Err(ParseEnhancementsError::MissingMatchers(lineno));
+        }
+        if actions.is_empty() {
+            return Err(ParseEnhancementsError::MissingActions(lineno));
+        }
+
+        Ok(Rule { matchers, actions })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, matcher) in self.matchers.iter().enumerate() {
+            if idx > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", matcher)?;
+        }
+        for action in &self.actions {
+            write!(f, " {}", action)?;
+        }
+        Ok(())
+    }
+}
+
+/// A list of enhancement rules.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Enhancements {
+    /// The rules in the order they are applied.
+    pub rules: Vec<Rule>,
+}
+
+impl Enhancements {
+    /// Parses enhancement rules, one rule per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn parse(input: &str) -> Result<Enhancements, ParseEnhancementsError> {
+        let mut rules = Vec::new();
+        for (idx, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            rules.push(Rule::parse(line, idx + 1)?);
+        }
+        Ok(Enhancements { rules })
+    }
+
+    /// Applies the rules to a stack trace.
+    ///
+    /// Frames are expected in the protocol order, with the crashing frame
+    /// last.  The `in_app` flags of the frames are updated in place.  The
+    /// return value holds one entry per frame indicating whether the frame
+    /// contributes to grouping.
+    pub fn apply(&self, stacktrace: &mut Stacktrace, platform: &Platform) -> Vec<bool> {
+        let frames = &mut stacktrace.frames;
+        let mut contributes = vec![true; frames.len()];
+        let mut max_frames = None;
+
+        for rule in &self.rules {
+            let matching: Vec<usize> = (0..frames.len())
+                .filter(|&idx| rule.matches_frame(&frames[idx], platform))
+                .collect();
+
+            for &idx in &matching {
+                for action in &rule.actions {
+                    match *action {
+                        Action::Flag { flag, value, range } => {
+                            let targets = match range {
+                                None => idx..idx + 1,
+                                Some(ActionRange::Up) => idx + 1..frames.len(),
+                                Some(ActionRange::Down) => 0..idx,
+                            };
+                            for target in targets {
+                                match flag {
+                                    FrameFlag::App => frames[target].in_app = Some(value),
+                                    FrameFlag::Group => contributes[target] = value,
+                                }
+                            }
+                        }
+                        Action::MaxFrames(max) => max_frames = Some(max),
+                    }
+                }
+            }
+        }
+
+        if let Some(max) = max_frames {
+            let mut remaining = max;
+            for contributes in contributes.iter_mut().rev().filter(|c| **c) {
+                if remaining == 0 {
+                    *contributes = false;
+                } else {
+                    remaining -= 1;
+                }
+            }
+        }
+
+        contributes
+    }
+}
+
+impl str::FromStr for Enhancements {
+    type Err = ParseEnhancementsError;
+
+    fn from_str(string: &str) -> Result<Enhancements, ParseEnhancementsError> {
+        Enhancements::parse(string)
+    }
+}
+
+impl fmt::Display for Enhancements {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for rule in &self.rules {
+            writeln!(f, "{}", rule)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn identity(c: char) -> char {
+    c
+}
+
+/// Matches paths case-insensitively with backslashes normalized.
+pub(crate) fn normalize_path(c: char) -> char {
+    if c == '\\' {
+        '/'
+    } else {
+        c.to_ascii_lowercase()
+    }
+}
+
+/// Returns the platform family used by the `family` matcher.
+pub(crate) fn platform_family(platform: &Platform) -> &'static str {
+    match *platform {
+        Platform::C | Platform::Cocoa | Platform::Native | Platform::ObjC => "native",
+        Platform::JavaScript | Platform::Node => "javascript",
+        _ => "other",
+    }
+}
+
+fn is_action(token: &str) -> bool {
+    token.starts_with('+')
+        || token.starts_with('-')
+        || token.starts_with("^+")
+        || token.starts_with("^-")
+        || token.starts_with("v+")
+        || token.starts_with("v-")
+        || (token.contains('=') && !token.contains(':'))
+}
+
+/// Splits a rule into whitespace separated tokens, unquoting values.
+pub(crate) fn tokenize(line: &str, lineno: usize) -> Result<Vec<String>, ParseEnhancementsError> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            return Ok(tokens);
+        }
+
+        let mut token = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                c if c.is_whitespace() => break,
+                '"' => loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => token.push(escaped),
+                            None => return Err(ParseEnhancementsError::UnterminatedString(lineno)),
+                        },
+                        Some(c) => token.push(c),
+                        None => return Err(ParseEnhancementsError::UnterminatedString(lineno)),
+                    }
+                },
+                c => token.push(c),
+            }
+        }
+        tokens.push(token);
+    }
+}
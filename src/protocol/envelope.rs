@@ -0,0 +1,1130 @@
+//! Envelopes are the container format used to send multiple payloads (events,
+//! attachments, ...) to Sentry in a single request.
+//!
+//! An envelope consists of a line of JSON headers followed by any number of
+//! items.  Every item again consists of a line of JSON headers followed by its
+//! payload.  Items this crate does not know about are kept verbatim, so an
+//! envelope can be parsed and re-serialized without corrupting data produced
+//! by newer SDKs.
+//!
+//! With the `compression` feature, envelopes and item payloads can be
+//! compressed with gzip, deflate or zstd.
+#[cfg(feature = "compression")]
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::fmt;
+use std::io::{self, Write};
+use std::str;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::dsn::{Dsn, ParseDsnError};
+use crate::protocol::limits::{LimitError, ParseLimits};
+use crate::protocol::metrics::{MetricBucket, ParseMetricError};
+use crate::protocol::normalize::{ClockDrift, NormalizeConfig};
+use crate::protocol::quota::DataCategory;
+use crate::protocol::session::{Session, SessionAggregates};
+use crate::protocol::v7::{DynamicSamplingContext, EventType, Map, ReplayEvent, Value};
+use crate::utils::from_json_slice;
+
+/// Represents an envelope parsing error.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ParseEnvelopeError {
+    /// Raised if the envelope headers are missing or invalid.
+    #[error("invalid envelope headers")]
+    InvalidHeaders(#[source] serde_json::Error),
+    /// Raised if the headers of an item are invalid.
+    #[error("invalid item headers")]
+    InvalidItemHeaders(#[source] serde_json::Error),
+    /// Raised if an item payload is shorter than its declared length.
+    #[error("unexpected end of item payload")]
+    UnexpectedEof,
+    /// Raised if an item payload is not followed by a newline.
+    #[error("missing newline after item payload")]
+    MissingNewline,
+    /// Raised if a compressed envelope or payload cannot be decompressed.
+    #[cfg(feature = "compression")]
+    #[error("failed to decompress payload")]
+    Decompression(#[source] io::Error),
+    /// Raised if a JSON payload exceeds the limits passed to
+    /// `Envelope::from_slice_with_limits`.
+    #[error("item payload exceeds limits")]
+    LimitExceeded(#[source] LimitError),
+}
+
+/// A violation of the protocol constraints of an envelope.
+///
+/// See `Envelope::validate`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum EnvelopeViolation {
+    /// Raised if the envelope contains more than one event item.
+    #[error("envelope contains {0} event items")]
+    MultipleEvents(usize),
+    /// Raised if the event id of an event item differs from the envelope.
+    #[error("event id {item} does not match envelope event id {header:?}")]
+    EventIdMismatch {
+        /// The event id in the envelope headers.
+        header: Option<Uuid>,
+        /// The event id in the event payload.
+        item: Uuid,
+    },
+    /// Raised if the envelope has attachments but no event id.
+    #[error("attachments require an event id")]
+    MissingEventId,
+    /// Raised if the envelope has no `sent_at` header.
+    #[error("missing sent_at header")]
+    MissingSentAt,
+}
+
+/// The maximum size of decompressed envelopes and payloads.
+#[cfg(feature = "compression")]
+pub const MAX_DECOMPRESSED_SIZE: u64 = 100 * 1024 * 1024;
+
+/// An error used when parsing `Encoding`.
+#[cfg(feature = "compression")]
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("unsupported content encoding")]
+pub struct ParseEncodingError;
+
+/// A content encoding for compressed envelopes and payloads.
+///
+/// These are the values of the `Content-Encoding` HTTP header.
+#[cfg(feature = "compression")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// Gzip compression.
+    Gzip,
+    /// Deflate compression in a zlib container.
+    Deflate,
+    /// Zstandard compression.
+    Zstd,
+}
+
+#[cfg(feature = "compression")]
+impl Encoding {
+    /// Detects the encoding of compressed data from its magic bytes.
+    ///
+    /// Returns `None` for data that does not look compressed.
+    pub fn detect(slice: &[u8]) -> Option<Encoding> {
+        match *slice {
+            [0x1f, 0x8b, ..] => Some(Encoding::Gzip),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Encoding::Zstd),
+            [cmf, flg, ..]
+                if cmf & 0x0f == 8 && (u16::from(cmf) << 8 | u16::from(flg)) % 31 == 0 =>
+            {
+                Some(Encoding::Deflate)
+            }
+            _ => None,
+        }
+    }
+
+    /// Compresses data with this encoding.
+    pub fn compress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        use flate2::write::{GzEncoder, ZlibEncoder};
+        use flate2::Compression;
+
+        match self {
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Encoding::Deflate => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Encoding::Zstd => zstd::stream::encode_all(data, 0),
+        }
+    }
+
+    /// Decompresses data with this encoding.
+    ///
+    /// Fails if the decompressed data exceeds `MAX_DECOMPRESSED_SIZE`.
+    pub fn decompress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        use flate2::read::{GzDecoder, ZlibDecoder};
+        use std::io::Read;
+
+        let reader: Box<dyn Read + '_> = match self {
+            Encoding::Gzip => Box::new(GzDecoder::new(data)),
+            Encoding::Deflate => Box::new(ZlibDecoder::new(data)),
+            Encoding::Zstd => Box::new(zstd::stream::read::Decoder::new(data)?),
+        };
+
+        let mut decompressed = Vec::new();
+        reader
+            .take(MAX_DECOMPRESSED_SIZE + 1)
+            .read_to_end(&mut decompressed)?;
+        if decompressed.len() as u64 > MAX_DECOMPRESSED_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "decompressed payload too large",
+            ));
+        }
+        Ok(decompressed)
+    }
+}
+
+#[cfg(feature = "compression")]
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Encoding::Gzip => write!(f, "gzip"),
+            Encoding::Deflate => write!(f, "deflate"),
+            Encoding::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+impl str::FromStr for Encoding {
+    type Err = ParseEncodingError;
+
+    fn from_str(string: &str) -> Result<Encoding, ParseEncodingError> {
+        Ok(match string {
+            "gzip" | "x-gzip" => Encoding::Gzip,
+            "deflate" => Encoding::Deflate,
+            "zstd" => Encoding::Zstd,
+            _ => return Err(ParseEncodingError),
+        })
+    }
+}
+
+#[cfg(feature = "compression")]
+impl_str_serde!(Encoding);
+
+/// The type of an envelope item.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ItemType {
+    /// An event payload.
+    Event,
+    /// A transaction payload.
+    Transaction,
+    /// A security report payload.
+    Security,
+    /// An attachment payload.
+    Attachment,
+    /// A session update.
+    Session,
+    /// Pre-aggregated sessions.
+    Sessions,
+    /// A sampled profile.
+    Profile,
+    /// A session replay event.
+    ReplayEvent,
+    /// The recording of a session replay segment.
+    ReplayRecording,
+    /// Metric buckets in statsd format.
+    Statsd,
+    /// An item type not known to this version of the crate.
+    Unknown(String),
+}
+
+impl fmt::Display for ItemType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ItemType::Event => write!(f, "event"),
+            ItemType::Transaction => write!(f, "transaction"),
+            ItemType::Security => write!(f, "security"),
+            ItemType::Attachment => write!(f, "attachment"),
+            ItemType::Session => write!(f, "session"),
+            ItemType::Sessions => write!(f, "sessions"),
+            ItemType::Profile => write!(f, "profile"),
+            ItemType::ReplayEvent => write!(f, "replay_event"),
+            ItemType::ReplayRecording => write!(f, "replay_recording"),
+            ItemType::Statsd => write!(f, "statsd"),
+            ItemType::Unknown(ref other) => write!(f, "{}", other),
+        }
+    }
+}
+
+impl str::FromStr for ItemType {
+    type Err = Infallible;
+
+    fn from_str(string: &str) -> Result<ItemType, Infallible> {
+        Ok(match string {
+            "event" => ItemType::Event,
+            "transaction" => ItemType::Transaction,
+            "security" => ItemType::Security,
+            "attachment" => ItemType::Attachment,
+            "session" => ItemType::Session,
+            "sessions" => ItemType::Sessions,
+            "profile" => ItemType::Profile,
+            "replay_event" => ItemType::ReplayEvent,
+            "replay_recording" => ItemType::ReplayRecording,
+            "statsd" => ItemType::Statsd,
+            other => ItemType::Unknown(other.to_string()),
+        })
+    }
+}
+
+impl_str_serde!(ItemType);
+
+impl ItemType {
+    /// Returns the item type used to send events of the given type.
+    pub fn from_event_type(ty: &EventType) -> ItemType {
+        match *ty {
+            EventType::Transaction => ItemType::Transaction,
+            ref ty if ty.is_security_report() => ItemType::Security,
+            _ => ItemType::Event,
+        }
+    }
+
+    /// Returns true if the item carries an event payload.
+    pub fn is_event(&self) -> bool {
+        matches!(
+            *self,
+            ItemType::Event | ItemType::Transaction | ItemType::Security
+        )
+    }
+
+    /// Returns true if the item belongs to a session replay.
+    pub fn is_replay(&self) -> bool {
+        matches!(*self, ItemType::ReplayEvent | ItemType::ReplayRecording)
+    }
+
+    /// Returns the data category items of this type are accounted for in.
+    ///
+    /// Returns `None` for unknown item types.
+    pub fn data_category(&self) -> Option<DataCategory> {
+        Some(match *self {
+            ItemType::Event => DataCategory::Error,
+            ItemType::Transaction => DataCategory::Transaction,
+            ItemType::Security => DataCategory::Security,
+            ItemType::Attachment => DataCategory::Attachment,
+            ItemType::Session | ItemType::Sessions => DataCategory::Session,
+            ItemType::Profile => DataCategory::Profile,
+            ItemType::ReplayEvent | ItemType::ReplayRecording => DataCategory::Replay,
+            ItemType::Statsd => DataCategory::MetricBucket,
+            ItemType::Unknown(_) => return None,
+        })
+    }
+
+    /// Returns true if the payload of items of this type is JSON.
+    pub fn is_json(&self) -> bool {
+        self.is_event()
+            || matches!(
+                *self,
+                ItemType::Session | ItemType::Sessions | ItemType::Profile | ItemType::ReplayEvent
+            )
+    }
+}
+
+/// The type of an attachment, sent in the `attachment_type` item header.
+///
+/// Types not known to this crate are kept in `AttachmentType::Unknown`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum AttachmentType {
+    /// A generic attachment without special processing.
+    #[default]
+    Attachment,
+    /// A minidump of a native crash.
+    Minidump,
+    /// An Apple crash report.
+    AppleCrashReport,
+    /// The JSON view hierarchy of the UI at the time of an event.
+    ViewHierarchy,
+    /// The XML crash context of an Unreal Engine crash.
+    UnrealContext,
+    /// The logs of an Unreal Engine crash.
+    UnrealLogs,
+    /// A breakpad symbol file.
+    ///
+    /// This is not sent by SDKs, but returned by `infer` for symbol files
+    /// uploaded as attachments.
+    BreakpadSymbols,
+    /// A proguard mapping file.
+    ///
+    /// This is not sent by SDKs, but returned by `infer` for mapping files
+    /// uploaded as attachments.
+    ProguardMapping,
+    /// An attachment type not known to this crate.
+    Unknown(String),
+}
+
+impl AttachmentType {
+    /// Returns the name of the attachment type.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            AttachmentType::Attachment => "event.attachment",
+            AttachmentType::Minidump => "event.minidump",
+            AttachmentType::AppleCrashReport => "event.applecrashreport",
+            AttachmentType::ViewHierarchy => "event.view_hierarchy",
+            AttachmentType::UnrealContext => "unreal.context",
+            AttachmentType::UnrealLogs => "unreal.logs",
+            AttachmentType::BreakpadSymbols => "breakpad.symbols",
+            AttachmentType::ProguardMapping => "proguard.mapping",
+            AttachmentType::Unknown(ref ty) => ty,
+        }
+    }
+
+    /// Infers the type of an attachment from its filename and contents.
+    ///
+    /// Minidumps are recognized by their magic bytes, Apple crash reports by
+    /// their first header line or the `.crash` extension, breakpad symbol
+    /// files by their `MODULE` record, proguard mappings by their first class
+    /// mapping, and view hierarchies by their filename or top-level keys.  All
+    /// other attachments are generic.
+    pub fn infer(filename: &str, bytes: &[u8]) -> AttachmentType {
+        let filename = filename.to_ascii_lowercase();
+
+        if bytes.starts_with(b"MDMP") || bytes.starts_with(b"PMDM") {
+            AttachmentType::Minidump
+        } else if bytes.starts_with(b"Incident Identifier:") || filename.ends_with(".crash") {
+            AttachmentType::AppleCrashReport
+        } else if bytes.starts_with(b"MODULE ") {
+            AttachmentType::BreakpadSymbols
+        } else if is_proguard_mapping(bytes) {
+            AttachmentType::ProguardMapping
+        } else if filename == "view-hierarchy.json" || is_view_hierarchy(bytes) {
+            AttachmentType::ViewHierarchy
+        } else {
+            AttachmentType::Attachment
+        }
+    }
+}
+
+/// Checks whether a file looks like a proguard mapping.
+///
+/// Mappings start with optional `#` comments followed by a class mapping such
+/// as `com.example.Main -> a.a:`.
+fn is_proguard_mapping(bytes: &[u8]) -> bool {
+    let line = bytes
+        .split(|&byte| byte == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .find(|line| !line.is_empty() && !line.starts_with(b"#"));
+
+    let mapping = line
+        .and_then(|line| str::from_utf8(line).ok())
+        .and_then(|line| line.strip_suffix(':'))
+        .and_then(|line| line.split_once(" -> "));
+
+    let is_class_name = |name: &str| !name.is_empty() && !name.contains(char::is_whitespace);
+    mapping
+        .is_some_and(|(original, obfuscated)| is_class_name(original) && is_class_name(obfuscated))
+}
+
+/// Checks whether a JSON document looks like a view hierarchy.
+fn is_view_hierarchy(bytes: &[u8]) -> bool {
+    if !bytes.starts_with(b"{") {
+        return false;
+    }
+
+    #[derive(Deserialize)]
+    struct Keys {
+        rendering_system: Option<Value>,
+        windows: Option<Value>,
+    }
+
+    serde_json::from_slice::<Keys>(bytes)
+        .is_ok_and(|keys| keys.rendering_system.is_some() && keys.windows.is_some())
+}
+
+impl fmt::Display for AttachmentType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl str::FromStr for AttachmentType {
+    type Err = Infallible;
+
+    fn from_str(string: &str) -> Result<AttachmentType, Infallible> {
+        Ok(match string {
+            "event.attachment" => AttachmentType::Attachment,
+            "event.minidump" => AttachmentType::Minidump,
+            "event.applecrashreport" => AttachmentType::AppleCrashReport,
+            "event.view_hierarchy" => AttachmentType::ViewHierarchy,
+            "unreal.context" => AttachmentType::UnrealContext,
+            "unreal.logs" => AttachmentType::UnrealLogs,
+            "breakpad.symbols" => AttachmentType::BreakpadSymbols,
+            "proguard.mapping" => AttachmentType::ProguardMapping,
+            other => AttachmentType::Unknown(other.to_string()),
+        })
+    }
+}
+
+impl_str_serde!(AttachmentType);
+
+/// The headers of an envelope.
+///
+/// A `sent_at` header that is not a valid timestamp is kept in `other`, so it
+/// neither fails the envelope nor gets lost when re-serializing it.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(remote = "Self")]
+pub struct EnvelopeHeaders {
+    /// The ID of the event contained in the envelope, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<Uuid>,
+    /// The dynamic sampling context of the trace the envelope belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<DynamicSamplingContext>,
+    /// The time the envelope was sent according to the client clock.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub sent_at: Option<DateTime<Utc>>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl Serialize for EnvelopeHeaders {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.sent_at.is_some() && self.other.contains_key("sent_at") {
+            let mut headers = self.clone();
+            headers.other.remove("sent_at");
+            return EnvelopeHeaders::serialize(&headers, serializer);
+        }
+        EnvelopeHeaders::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for EnvelopeHeaders {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut headers = EnvelopeHeaders::deserialize(deserializer)?;
+        if let Some(Ok(sent_at)) = headers.other.get("sent_at").map(DateTime::deserialize) {
+            headers.sent_at = Some(sent_at);
+            headers.other.remove("sent_at");
+        }
+        Ok(headers)
+    }
+}
+
+impl EnvelopeHeaders {
+    /// Returns the clock drift of the client that sent the envelope.
+    ///
+    /// Returns `None` if the envelope has no valid `sent_at` header.
+    pub fn clock_drift(&self, received_at: DateTime<Utc>) -> Option<ClockDrift> {
+        Some(ClockDrift::new(self.sent_at?, received_at))
+    }
+
+    /// Returns the dsn the envelope was sent with.
+    ///
+    /// SDKs send the dsn in the envelope headers when they submit envelopes
+    /// through a tunnel.  Returns `None` if the header is missing and an
+    /// error if it is not a valid dsn.
+    pub fn dsn(&self) -> Option<Result<Dsn, ParseDsnError>> {
+        match self.other.get("dsn")? {
+            Value::String(dsn) => Some(dsn.parse()),
+            _ => Some(Err(ParseDsnError::InvalidUrl)),
+        }
+    }
+
+    /// Sets the dsn header of the envelope.
+    pub fn set_dsn(&mut self, dsn: &Dsn) {
+        self.other
+            .insert("dsn".to_string(), Value::String(dsn.to_string()));
+    }
+}
+
+/// The headers that are required to read an item from an envelope.
+#[derive(Serialize, Deserialize)]
+struct ItemHeaders {
+    #[serde(rename = "type")]
+    ty: ItemType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_encoding: Option<String>,
+    #[serde(flatten)]
+    other: Map<String, Value>,
+}
+
+/// A single item in an envelope.
+///
+/// Items keep the exact bytes of their headers and payload they were parsed
+/// from, which are written back unchanged on serialization.  This includes
+/// items of unknown types and headers not known to this crate, so envelopes
+/// can be forwarded without losing data.
+///
+/// The payload is reference counted, so cloning an item does not copy it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvelopeItem {
+    ty: ItemType,
+    content_encoding: Option<String>,
+    headers: Vec<u8>,
+    payload: Arc<[u8]>,
+}
+
+impl EnvelopeItem {
+    /// Creates a new item of the given type with an explicit payload length.
+    pub fn new(ty: ItemType, payload: Vec<u8>) -> EnvelopeItem {
+        EnvelopeItem::with_headers(ty, None, Map::new(), payload)
+    }
+
+    /// Creates a new item with additional headers.
+    ///
+    /// The `type` and `length` headers are always set from the item type and
+    /// payload and take precedence over the additional headers.
+    pub fn with_extra_headers(
+        ty: ItemType,
+        headers: Map<String, Value>,
+        payload: Vec<u8>,
+    ) -> EnvelopeItem {
+        EnvelopeItem::with_headers(ty, None, headers, payload)
+    }
+
+    /// Creates a new item with a payload compressed with the given encoding.
+    ///
+    /// The encoding is recorded in the `content_encoding` item header.
+    #[cfg(feature = "compression")]
+    pub fn new_compressed(
+        ty: ItemType,
+        payload: &[u8],
+        encoding: Encoding,
+    ) -> io::Result<EnvelopeItem> {
+        let payload = encoding.compress(payload)?;
+        Ok(EnvelopeItem::with_headers(
+            ty,
+            Some(encoding.to_string()),
+            Map::new(),
+            payload,
+        ))
+    }
+
+    fn with_headers(
+        ty: ItemType,
+        content_encoding: Option<String>,
+        mut other: Map<String, Value>,
+        payload: Vec<u8>,
+    ) -> EnvelopeItem {
+        for key in &["type", "length", "content_encoding"] {
+            other.remove(*key);
+        }
+        let headers = serde_json::to_vec(&ItemHeaders {
+            ty: ty.clone(),
+            length: Some(payload.len()),
+            content_encoding: content_encoding.clone(),
+            other,
+        })
+        .unwrap_or_default();
+
+        EnvelopeItem {
+            ty,
+            content_encoding,
+            headers,
+            payload: payload.into(),
+        }
+    }
+
+    /// Returns the type of the item.
+    pub fn ty(&self) -> &ItemType {
+        &self.ty
+    }
+
+    /// Returns true if the type of the item is not known to this crate.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self.ty, ItemType::Unknown(_))
+    }
+
+    /// Returns the `attachment_type` header of an attachment item.
+    ///
+    /// Attachments without the header are generic attachments.  Returns
+    /// `None` if this is not an attachment item.
+    pub fn attachment_type(&self) -> Option<AttachmentType> {
+        if self.ty != ItemType::Attachment {
+            return None;
+        }
+
+        Some(match self.header("attachment_type") {
+            Some(Value::String(ty)) => ty.parse().unwrap_or_default(),
+            _ => AttachmentType::Attachment,
+        })
+    }
+
+    /// Returns the raw JSON headers of the item.
+    pub fn raw_headers(&self) -> &[u8] {
+        &self.headers
+    }
+
+    /// Returns the value of a header, including headers not known to this
+    /// crate.
+    pub fn header(&self, name: &str) -> Option<Value> {
+        let mut headers: Map<String, Value> = serde_json::from_slice(&self.headers).ok()?;
+        headers.remove(name)
+    }
+
+    /// Returns the payload of the item.
+    ///
+    /// This is the raw payload, which may be compressed according to
+    /// `content_encoding`.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Returns the `content_encoding` item header, if any.
+    pub fn content_encoding(&self) -> Option<&str> {
+        self.content_encoding.as_deref()
+    }
+
+    /// Returns the payload of the item, decompressed according to its
+    /// `content_encoding` header.
+    #[cfg(feature = "compression")]
+    pub fn decoded_payload(&self) -> Result<Cow<'_, [u8]>, ParseEnvelopeError> {
+        let encoding = match self.content_encoding.as_deref() {
+            None | Some("identity") => return Ok(Cow::Borrowed(&self.payload)),
+            Some(encoding) => encoding.parse::<Encoding>().map_err(|_| {
+                ParseEnvelopeError::Decompression(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    ParseEncodingError,
+                ))
+            })?,
+        };
+
+        encoding
+            .decompress(&self.payload)
+            .map(Cow::Owned)
+            .map_err(ParseEnvelopeError::Decompression)
+    }
+
+    /// Returns the quantity the item is accounted for with in its data
+    /// category.
+    ///
+    /// Attachments are counted by the size of their payload in bytes and
+    /// statsd items by the number of lines, which is the number of metric
+    /// buckets.  All other items count as one.
+    pub fn quantity(&self) -> usize {
+        match self.ty {
+            ItemType::Attachment => self.payload.len(),
+            ItemType::Statsd => self
+                .payload
+                .split(|&b| b == b'\n')
+                .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+                .count(),
+            _ => 1,
+        }
+    }
+
+    /// Parses the metric buckets of a `statsd` item.
+    ///
+    /// Returns `None` if this is not a statsd item.
+    pub fn metrics(&self) -> Option<Result<Vec<MetricBucket>, ParseMetricError>> {
+        match self.ty {
+            ItemType::Statsd => Some(
+                str::from_utf8(&self.payload)
+                    .map_err(|_| ParseMetricError::InvalidValue)
+                    .and_then(MetricBucket::parse_all),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Parses the payload of a `session` item.
+    ///
+    /// Returns `None` if this is not a session item.
+    pub fn session(&self) -> Option<Result<Session, serde_json::Error>> {
+        match self.ty {
+            ItemType::Session => Some(from_json_slice(&self.payload)),
+            _ => None,
+        }
+    }
+
+    /// Parses the payload of a `sessions` item.
+    ///
+    /// Returns `None` if this is not a sessions item.
+    pub fn session_aggregates(&self) -> Option<Result<SessionAggregates, serde_json::Error>> {
+        match self.ty {
+            ItemType::Sessions => Some(from_json_slice(&self.payload)),
+            _ => None,
+        }
+    }
+
+    /// Parses the payload of a `replay_event` item.
+    ///
+    /// Returns `None` if this is not a replay event item.
+    pub fn replay_event(&self) -> Option<Result<ReplayEvent, serde_json::Error>> {
+        match self.ty {
+            ItemType::ReplayEvent => Some(from_json_slice(&self.payload)),
+            _ => None,
+        }
+    }
+
+    /// Parses the payload of a `replay_recording` item.
+    ///
+    /// Returns `None` if this is not a replay recording item.
+    pub fn replay_recording(&self) -> Option<Result<ReplayRecording, ParseEnvelopeError>> {
+        match self.ty {
+            ItemType::ReplayRecording => Some(ReplayRecording::from_slice(&self.payload)),
+            _ => None,
+        }
+    }
+
+    /// Parses a single item and returns it along with the remaining input.
+    fn parse(slice: &[u8]) -> Result<(EnvelopeItem, &[u8]), ParseEnvelopeError> {
+        let (header_line, rest) = split_line(slice);
+        let headers: ItemHeaders =
+            serde_json::from_slice(header_line).map_err(ParseEnvelopeError::InvalidItemHeaders)?;
+
+        let (payload, rest) = match headers.length {
+            Some(length) => {
+                if rest.len() < length {
+                    return Err(ParseEnvelopeError::UnexpectedEof);
+                }
+                let (payload, rest) = rest.split_at(length);
+                match rest.first() {
+                    None => (payload, rest),
+                    Some(b'\n') => (payload, &rest[1..]),
+                    Some(_) => return Err(ParseEnvelopeError::MissingNewline),
+                }
+            }
+            None => split_line(rest),
+        };
+
+        let item = EnvelopeItem {
+            ty: headers.ty,
+            content_encoding: headers.content_encoding,
+            headers: header_line.to_vec(),
+            payload: payload.into(),
+        };
+
+        Ok((item, rest))
+    }
+}
+
+/// Splits off the first line of the input, dropping the newline.
+fn split_line(slice: &[u8]) -> (&[u8], &[u8]) {
+    match slice.iter().position(|&b| b == b'\n') {
+        Some(pos) => (&slice[..pos], &slice[pos + 1..]),
+        None => (slice, &[]),
+    }
+}
+
+/// The headers of a replay recording.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ReplayRecordingHeaders {
+    /// The sequence number of the recorded segment.
+    #[serde(default)]
+    pub segment_id: u64,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// The payload of a `replay_recording` item.
+///
+/// The payload consists of a line of JSON headers followed by the recording
+/// body, which is usually compressed.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReplayRecording {
+    /// The headers of the recording.
+    pub headers: ReplayRecordingHeaders,
+    /// The raw body of the recording.
+    pub body: Vec<u8>,
+}
+
+impl ReplayRecording {
+    /// Parses a recording from the payload of a `replay_recording` item.
+    pub fn from_slice(slice: &[u8]) -> Result<ReplayRecording, ParseEnvelopeError> {
+        let pos = slice
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or(ParseEnvelopeError::MissingNewline)?;
+        let headers = serde_json::from_slice(&slice[..pos])
+            .map_err(ParseEnvelopeError::InvalidItemHeaders)?;
+
+        Ok(ReplayRecording {
+            headers,
+            body: slice[pos + 1..].to_vec(),
+        })
+    }
+
+    /// Returns true if the body is compressed.
+    ///
+    /// Uncompressed recordings are plain JSON arrays.
+    pub fn is_compressed(&self) -> bool {
+        !self.body.starts_with(b"[")
+    }
+
+    /// Serializes the recording into an item payload.
+    pub fn to_vec(&self) -> Vec<u8> {
+        // serializing the headers cannot fail
+        let mut vec = serde_json::to_vec(&self.headers).unwrap_or_default();
+        vec.push(b'\n');
+        vec.extend_from_slice(&self.body);
+        vec
+    }
+}
+
+/// An envelope along with the time it was received.
+///
+/// The receiving time is not part of the envelope itself and is not
+/// serialized.  It is used together with `sent_at` to correct the clock drift
+/// of the client during normalization.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedEnvelope {
+    /// The received envelope.
+    pub envelope: Envelope,
+    /// The time the envelope was received.
+    pub received_at: DateTime<Utc>,
+}
+
+impl ReceivedEnvelope {
+    /// Creates a received envelope.
+    pub fn new(envelope: Envelope, received_at: DateTime<Utc>) -> ReceivedEnvelope {
+        ReceivedEnvelope {
+            envelope,
+            received_at,
+        }
+    }
+
+    /// Returns the clock drift of the client that sent the envelope.
+    pub fn clock_drift(&self) -> Option<ClockDrift> {
+        self.envelope.headers.clock_drift(self.received_at)
+    }
+
+    /// Returns the normalization settings for events in the envelope.
+    ///
+    /// These are the default settings with the receiving and sending times
+    /// of the envelope.
+    pub fn normalize_config(&self) -> NormalizeConfig {
+        NormalizeConfig {
+            sent_at: self.envelope.headers.sent_at,
+            ..NormalizeConfig::new(self.received_at)
+        }
+    }
+}
+
+/// Parses an envelope from raw bytes.
+///
+/// This is the same as `Envelope::from_slice`.  It never panics, no matter the
+/// input, so it is safe to use on untrusted data.
+pub fn parse_envelope_bytes(bytes: &[u8]) -> Result<Envelope, ParseEnvelopeError> {
+    Envelope::from_slice(bytes)
+}
+
+/// Represents an envelope.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Envelope {
+    headers: EnvelopeHeaders,
+    items: Vec<EnvelopeItem>,
+}
+
+impl Envelope {
+    /// Creates a new empty envelope.
+    pub fn new() -> Envelope {
+        Default::default()
+    }
+
+    /// Parses an envelope from its serialized form.
+    ///
+    /// With the `compression` feature, compressed envelopes are detected and
+    /// decompressed transparently.
+    pub fn from_slice(slice: &[u8]) -> Result<Envelope, ParseEnvelopeError> {
+        #[cfg(feature = "compression")]
+        {
+            if let Some(encoding) = Encoding::detect(slice) {
+                return Envelope::from_slice_encoded(slice, encoding);
+            }
+        }
+
+        Envelope::parse_uncompressed(slice)
+    }
+
+    /// Parses an envelope and checks its JSON payloads against `limits`.
+    ///
+    /// The payloads of items with JSON payloads, such as events and
+    /// sessions, are checked before they are deserialized.  Compressed
+    /// payloads are checked after decompression, which requires the
+    /// `compression` feature; without it they are not checked.
+    pub fn from_slice_with_limits(
+        slice: &[u8],
+        limits: &ParseLimits,
+    ) -> Result<Envelope, ParseEnvelopeError> {
+        let envelope = Envelope::from_slice(slice)?;
+        for item in envelope.items.iter().filter(|item| item.ty.is_json()) {
+            #[cfg(feature = "compression")]
+            let payload = item.decoded_payload()?;
+            #[cfg(not(feature = "compression"))]
+            let payload = match item.content_encoding {
+                None => &item.payload[..],
+                Some(_) => continue,
+            };
+
+            limits
+                .check(payload.as_ref())
+                .map_err(ParseEnvelopeError::LimitExceeded)?;
+        }
+        Ok(envelope)
+    }
+
+    fn parse_uncompressed(slice: &[u8]) -> Result<Envelope, ParseEnvelopeError> {
+        let (header_line, mut rest) = split_line(slice);
+        let headers =
+            serde_json::from_slice(header_line).map_err(ParseEnvelopeError::InvalidHeaders)?;
+
+        let mut items = Vec::new();
+        while !rest.is_empty() {
+            let (item, remaining) = EnvelopeItem::parse(rest)?;
+            items.push(item);
+            rest = remaining;
+        }
+
+        Ok(Envelope { headers, items })
+    }
+
+    /// Parses an envelope compressed with the given encoding.
+    ///
+    /// Use this when the encoding is known from the `Content-Encoding` header
+    /// of the request the envelope was sent with.
+    #[cfg(feature = "compression")]
+    pub fn from_slice_encoded(
+        slice: &[u8],
+        encoding: Encoding,
+    ) -> Result<Envelope, ParseEnvelopeError> {
+        let decompressed = encoding
+            .decompress(slice)
+            .map_err(ParseEnvelopeError::Decompression)?;
+        Envelope::parse_uncompressed(&decompressed)
+    }
+
+    /// Returns the headers of the envelope.
+    pub fn headers(&self) -> &EnvelopeHeaders {
+        &self.headers
+    }
+
+    /// Returns a mutable reference to the headers of the envelope.
+    pub fn headers_mut(&mut self) -> &mut EnvelopeHeaders {
+        &mut self.headers
+    }
+
+    /// Returns the items of the envelope.
+    pub fn items(&self) -> &[EnvelopeItem] {
+        &self.items
+    }
+
+    /// Checks the envelope against the constraints of the protocol.
+    ///
+    /// All violations are returned, the envelope is valid if the list is
+    /// empty.  An envelope may contain at most one event item whose event id
+    /// matches the `event_id` header, attachments require the `event_id`
+    /// header, and the `sent_at` header is required.  Event ids of compressed
+    /// payloads are not checked.
+    pub fn validate(&self) -> Vec<EnvelopeViolation> {
+        #[derive(Deserialize)]
+        struct EventIdHeader {
+            #[serde(default)]
+            event_id: Option<Uuid>,
+        }
+
+        let mut violations = Vec::new();
+        let header_id = self.headers.event_id;
+
+        let events: Vec<_> = self.items.iter().filter(|i| i.ty.is_event()).collect();
+        if events.len() > 1 {
+            violations.push(EnvelopeViolation::MultipleEvents(events.len()));
+        }
+
+        for item in events {
+            if item.content_encoding.is_some() {
+                continue;
+            }
+            let item_id = serde_json::from_slice::<EventIdHeader>(&item.payload)
+                .ok()
+                .and_then(|header| header.event_id);
+            if let Some(item_id) = item_id {
+                if header_id != Some(item_id) {
+                    violations.push(EnvelopeViolation::EventIdMismatch {
+                        header: header_id,
+                        item: item_id,
+                    });
+                }
+            }
+        }
+
+        if header_id.is_none() && self.items.iter().any(|i| i.ty == ItemType::Attachment) {
+            violations.push(EnvelopeViolation::MissingEventId);
+        }
+
+        if self.headers.sent_at.is_none() {
+            violations.push(EnvelopeViolation::MissingSentAt);
+        }
+
+        violations
+    }
+
+    /// Returns the quantities of the items in the envelope per data category.
+    ///
+    /// See `EnvelopeItem::quantity` for how items are counted.  Attachments
+    /// are counted in bytes, so the count of `DataCategory::Attachment` is
+    /// the total size of all attachments.  Items of unknown types are not
+    /// counted.
+    pub fn counts_by_category(&self) -> Map<DataCategory, usize> {
+        let mut counts = Map::new();
+        for item in &self.items {
+            if let Some(category) = item.ty.data_category() {
+                *counts.entry(category).or_insert(0) += item.quantity();
+            }
+        }
+        counts
+    }
+
+    /// Splits the envelope into the items matching `predicate` and the rest.
+    ///
+    /// Both envelopes get the headers of this envelope.  Payloads are shared
+    /// with this envelope rather than copied, which makes it cheap to strip
+    /// rate limited items from an envelope and forward the remainder.
+    pub fn split_by<F>(&self, mut predicate: F) -> (Envelope, Envelope)
+    where
+        F: FnMut(&EnvelopeItem) -> bool,
+    {
+        let (matching, rest) = self.items.iter().cloned().partition(|item| predicate(item));
+        let matching = Envelope {
+            headers: self.headers.clone(),
+            items: matching,
+        };
+        let rest = Envelope {
+            headers: self.headers.clone(),
+            items: rest,
+        };
+        (matching, rest)
+    }
+
+    /// Removes all items not matching `predicate` from the envelope.
+    pub fn retain<F>(&mut self, predicate: F)
+    where
+        F: FnMut(&EnvelopeItem) -> bool,
+    {
+        self.items.retain(predicate);
+    }
+
+    /// Appends an item to the envelope.
+    pub fn add_item(&mut self, item: EnvelopeItem) {
+        self.items.push(item);
+    }
+
+    /// Serializes the envelope into the given writer.
+    pub fn to_writer<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        serde_json::to_writer(&mut writer, &self.headers)?;
+        writer.write_all(b"\n")?;
+
+        for item in &self.items {
+            writer.write_all(&item.headers)?;
+            writer.write_all(b"\n")?;
+            writer.write_all(&item.payload)?;
+            writer.write_all(b"\n")?;
+        }
+
+        Ok(())
+    }
+
+    /// Serializes the envelope into a byte vector.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut vec = Vec::new();
+        // writing into a vector cannot fail
+        self.to_writer(&mut vec).ok();
+        vec
+    }
+
+    /// Serializes the envelope into a byte vector compressed with the given
+    /// encoding.
+    ///
+    /// The encoding must be sent along as `Content-Encoding` header.
+    #[cfg(feature = "compression")]
+    pub fn to_vec_compressed(&self, encoding: Encoding) -> io::Result<Vec<u8>> {
+        encoding.compress(&self.to_vec())
+    }
+}
@@ -0,0 +1,392 @@
+//! A value type for arbitrary additional data.
+//!
+//! `Event::extra`, `Breadcrumb::data`, `Mechanism::data`, `Span::data`,
+//! `Values::data` and `Frame::vars` hold values of arbitrary shape supplied by
+//! the user.  `Value` represents them without exposing the types of a
+//! particular serialization library, so these fields serialize with any `serde`
+//! serializer.  Conversions from and to `serde_json::Value` are provided for
+//! interoperability.
+//!
+//! Fields that only carry JSON through unchanged, such as the flattened
+//! `other` maps for forward compatibility, `Meta` and `EventProcessingError`,
+//! keep using `v7::Value`, which is `serde_json::Value`.
+//!
+//! ```rust
+//! use sentry_types::protocol::extra::Value;
+//! use sentry_types::protocol::v7::Event;
+//!
+//! # #[cfg(feature = "clock")] {
+//! let mut event = Event::new();
+//! event.extra.insert("attempts".into(), 3.into());
+//! event.extra.insert("cached".into(), Value::Bool(true));
+//! assert_eq!(event.extra["attempts"], 3);
+//! # }
+//! ```
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::protocol::v7::Map;
+
+/// An arbitrary value of additional data.
+///
+/// Integers are stored as `I64` whenever they fit, so values compare equal
+/// no matter whether they were deserialized or converted from a Rust
+/// integer.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum Value {
+    /// A missing value.
+    #[default]
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A signed integer.
+    I64(i64),
+    /// An unsigned integer larger than `i64::MAX`.
+    U64(u64),
+    /// A floating point number.
+    F64(f64),
+    /// A string.
+    String(String),
+    /// A list of values.
+    Array(Vec<Value>),
+    /// A map of values by key.
+    Object(Map<String, Value>),
+}
+
+impl Value {
+    /// Returns true if the value is `Null`.
+    pub fn is_null(&self) -> bool {
+        matches!(*self, Value::Null)
+    }
+
+    /// Returns the boolean, if this is a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer as `i64`, if this is an integer that fits.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::I64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer as `u64`, if this is a non-negative integer.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::I64(value) if value >= 0 => Some(value as u64),
+            Value::U64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as `f64`, if this is a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::I64(value) => Some(value as f64),
+            Value::U64(value) => Some(value as f64),
+            Value::F64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string, if this is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the values, if this is an array.
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match *self {
+            Value::Array(ref values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the map, if this is an object.
+    pub fn as_object(&self) -> Option<&Map<String, Value>> {
+        match *self {
+            Value::Object(ref map) => Some(map),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Value {
+        Value::Bool(value)
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Value {
+                    Value::I64(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Value {
+        if value <= i64::MAX as u64 {
+            Value::I64(value as i64)
+        } else {
+            Value::U64(value)
+        }
+    }
+}
+
+impl From<isize> for Value {
+    fn from(value: isize) -> Value {
+        Value::I64(value as i64)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Value {
+        Value::from(value as u64)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Value {
+        Value::F64(value.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Value {
+        Value::F64(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Value {
+        Value::String(value)
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(value: &'a str) -> Value {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(values: Vec<Value>) -> Value {
+        Value::Array(values)
+    }
+}
+
+impl From<Map<String, Value>> for Value {
+    fn from(map: Map<String, Value>) -> Value {
+        Value::Object(map)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Value {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Value {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(value) => Value::Bool(value),
+            serde_json::Value::Number(number) => {
+                if let Some(value) = number.as_i64() {
+                    Value::I64(value)
+                } else if let Some(value) = number.as_u64() {
+                    Value::U64(value)
+                } else {
+                    Value::F64(number.as_f64().unwrap_or_default())
+                }
+            }
+            serde_json::Value::String(value) => Value::String(value),
+            serde_json::Value::Array(values) => {
+                Value::Array(values.into_iter().map(Value::from).collect())
+            }
+            serde_json::Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (key, Value::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<Value> for serde_json::Value {
+    /// Converts the value to JSON.
+    ///
+    /// Floating point numbers that are not finite become `null`, as JSON
+    /// cannot represent them.
+    fn from(value: Value) -> serde_json::Value {
+        match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(value) => value.into(),
+            Value::I64(value) => value.into(),
+            Value::U64(value) => value.into(),
+            Value::F64(value) => serde_json::Number::from_f64(value)
+                .map_or(serde_json::Value::Null, serde_json::Value::Number),
+            Value::String(value) => value.into(),
+            Value::Array(values) => {
+                serde_json::Value::Array(values.into_iter().map(Into::into).collect())
+            }
+            Value::Object(map) => serde_json::Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+macro_rules! impl_partial_eq {
+    ($($ty:ty => $method:ident),*) => {
+        $(
+            impl PartialEq<$ty> for Value {
+                fn eq(&self, other: &$ty) -> bool {
+                    self.$method().map_or(false, |value| value == *other)
+                }
+            }
+        )*
+    };
+}
+
+impl_partial_eq!(bool => as_bool, i64 => as_i64, u64 => as_u64, f64 => as_f64);
+
+impl PartialEq<str> for Value {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == Some(other)
+    }
+}
+
+impl PartialEq<i32> for Value {
+    fn eq(&self, other: &i32) -> bool {
+        self.as_i64() == Some((*other).into())
+    }
+}
+
+impl<'a> PartialEq<&'a str> for Value {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == Some(*other)
+    }
+}
+
+impl PartialEq<String> for Value {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == Some(other.as_str())
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(value) => serializer.serialize_bool(value),
+            Value::I64(value) => serializer.serialize_i64(value),
+            Value::U64(value) => serializer.serialize_u64(value),
+            Value::F64(value) => serializer.serialize_f64(value),
+            Value::String(ref value) => serializer.serialize_str(value),
+            Value::Array(ref values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Value::Object(ref map) => {
+                let mut ser = serializer.serialize_map(Some(map.len()))?;
+                for (key, value) in map {
+                    ser.serialize_entry(key, value)?;
+                }
+                ser.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any value")
+            }
+
+            fn visit_bool<E: de::Error>(self, value: bool) -> Result<Value, E> {
+                Ok(Value::Bool(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Value, E> {
+                Ok(Value::I64(value))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Value, E> {
+                Ok(Value::from(value))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Value, E> {
+                Ok(Value::F64(value))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Value, E> {
+                Ok(Value::String(value.to_string()))
+            }
+
+            fn visit_string<E: de::Error>(self, value: String) -> Result<Value, E> {
+                Ok(Value::String(value))
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+                Ok(Value::Null)
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+                Ok(Value::Null)
+            }
+
+            fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+                Value::deserialize(deserializer)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+                let mut values = Vec::new();
+                while let Some(value) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(Value::Array(values))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Value, A::Error> {
+                let mut map = Map::new();
+                while let Some((key, value)) = access.next_entry()? {
+                    map.insert(key, value);
+                }
+                Ok(Value::Object(map))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
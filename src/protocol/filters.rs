@@ -0,0 +1,550 @@
+//! Inbound data filters.
+//!
+//! These types mirror the inbound filter settings of a Sentry project and
+//! allow evaluating them locally, so that events which would be dropped by
+//! Sentry anyway never have to be sent.
+use std::collections::BTreeSet;
+use std::fmt;
+use std::net::IpAddr;
+use std::str;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::protocol::v7::{Event, IpAddress};
+use crate::utils::glob_match;
+
+/// An error used when parsing `FilterStatKey`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid filter stat key")]
+pub struct ParseFilterStatKeyError;
+
+/// Identifies the inbound filter that rejected an event.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FilterStatKey {
+    /// Filtered by the client IP address.
+    IpAddress,
+    /// Filtered by the release.
+    ReleaseVersion,
+    /// Filtered by the error message.
+    ErrorMessage,
+    /// Filtered because the event was sent from localhost.
+    Localhost,
+    /// Filtered because the error originated in a browser extension.
+    BrowserExtensions,
+    /// Filtered because the event was sent by a legacy browser.
+    LegacyBrowsers,
+    /// Filtered because the event was sent by a web crawler.
+    WebCrawlers,
+}
+
+impl fmt::Display for FilterStatKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FilterStatKey::IpAddress => write!(f, "ip-address"),
+            FilterStatKey::ReleaseVersion => write!(f, "release-version"),
+            FilterStatKey::ErrorMessage => write!(f, "error-message"),
+            FilterStatKey::Localhost => write!(f, "localhost"),
+            FilterStatKey::BrowserExtensions => write!(f, "browser-extensions"),
+            FilterStatKey::LegacyBrowsers => write!(f, "legacy-browsers"),
+            FilterStatKey::WebCrawlers => write!(f, "web-crawlers"),
+        }
+    }
+}
+
+impl str::FromStr for FilterStatKey {
+    type Err = ParseFilterStatKeyError;
+
+    fn from_str(string: &str) -> Result<FilterStatKey, Self::Err> {
+        Ok(match string {
+            "ip-address" => FilterStatKey::IpAddress,
+            "release-version" => FilterStatKey::ReleaseVersion,
+            "error-message" => FilterStatKey::ErrorMessage,
+            "localhost" => FilterStatKey::Localhost,
+            "browser-extensions" => FilterStatKey::BrowserExtensions,
+            "legacy-browsers" => FilterStatKey::LegacyBrowsers,
+            "web-crawlers" => FilterStatKey::WebCrawlers,
+            _ => return Err(ParseFilterStatKeyError),
+        })
+    }
+}
+
+impl_str_serde!(FilterStatKey);
+
+/// A legacy browser that can be filtered.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LegacyBrowser {
+    /// The default set of legacy browsers.
+    Default,
+    /// Internet Explorer before version 9.
+    IePre9,
+    /// Internet Explorer 9.
+    Ie9,
+    /// Internet Explorer 10.
+    Ie10,
+    /// Internet Explorer 11.
+    Ie11,
+    /// Opera before version 15.
+    OperaPre15,
+    /// Opera Mini before version 8.
+    OperaMiniPre8,
+    /// The Android browser before version 4.
+    AndroidPre4,
+    /// Safari before version 6.
+    SafariPre6,
+    /// Edge before version 79.
+    EdgePre79,
+    /// A browser not known to this version of the crate.
+    Unknown(String),
+}
+
+impl fmt::Display for LegacyBrowser {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LegacyBrowser::Default => write!(f, "default"),
+            LegacyBrowser::IePre9 => write!(f, "ie_pre_9"),
+            LegacyBrowser::Ie9 => write!(f, "ie9"),
+            LegacyBrowser::Ie10 => write!(f, "ie10"),
+            LegacyBrowser::Ie11 => write!(f, "ie11"),
+            LegacyBrowser::OperaPre15 => write!(f, "opera_pre_15"),
+            LegacyBrowser::OperaMiniPre8 => write!(f, "opera_mini_pre_8"),
+            LegacyBrowser::AndroidPre4 => write!(f, "android_pre_4"),
+            LegacyBrowser::SafariPre6 => write!(f, "safari_pre_6"),
+            LegacyBrowser::EdgePre79 => write!(f, "edge_pre_79"),
+            LegacyBrowser::Unknown(ref other) => write!(f, "{}", other),
+        }
+    }
+}
+
+impl str::FromStr for LegacyBrowser {
+    type Err = std::convert::Infallible;
+
+    fn from_str(string: &str) -> Result<LegacyBrowser, Self::Err> {
+        Ok(match string {
+            "default" => LegacyBrowser::Default,
+            "ie_pre_9" => LegacyBrowser::IePre9,
+            "ie9" => LegacyBrowser::Ie9,
+            "ie10" => LegacyBrowser::Ie10,
+            "ie11" => LegacyBrowser::Ie11,
+            "opera_pre_15" => LegacyBrowser::OperaPre15,
+            "opera_mini_pre_8" => LegacyBrowser::OperaMiniPre8,
+            "android_pre_4" => LegacyBrowser::AndroidPre4,
+            "safari_pre_6" => LegacyBrowser::SafariPre6,
+            "edge_pre_79" => LegacyBrowser::EdgePre79,
+            other => LegacyBrowser::Unknown(other.to_string()),
+        })
+    }
+}
+
+impl_str_serde!(LegacyBrowser);
+
+impl LegacyBrowser {
+    /// Checks whether a browser with the given name and major version
+    /// belongs to this set of legacy browsers.
+    fn matches(&self, browser: Browser, version: u32) -> bool {
+        match *self {
+            LegacyBrowser::Default => {
+                (browser == Browser::Ie && version <= 11)
+                    || (browser == Browser::Opera && version < 15)
+                    || (browser == Browser::OperaMini && version < 8)
+                    || (browser == Browser::Android && version < 4)
+                    || (browser == Browser::Safari && version < 6)
+            }
+            LegacyBrowser::IePre9 => browser == Browser::Ie && version < 9,
+            LegacyBrowser::Ie9 => browser == Browser::Ie && version == 9,
+            LegacyBrowser::Ie10 => browser == Browser::Ie && version == 10,
+            LegacyBrowser::Ie11 => browser == Browser::Ie && version == 11,
+            LegacyBrowser::OperaPre15 => browser == Browser::Opera && version < 15,
+            LegacyBrowser::OperaMiniPre8 => browser == Browser::OperaMini && version < 8,
+            LegacyBrowser::AndroidPre4 => browser == Browser::Android && version < 4,
+            LegacyBrowser::SafariPre6 => browser == Browser::Safari && version < 6,
+            LegacyBrowser::EdgePre79 => browser == Browser::Edge && version < 79,
+            LegacyBrowser::Unknown(_) => false,
+        }
+    }
+}
+
+/// A browser family detected from a user agent.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Browser {
+    Ie,
+    Opera,
+    OperaMini,
+    Android,
+    Safari,
+    Edge,
+}
+
+/// Parses the major version number at the start of the string.
+fn major_version(string: &str) -> Option<u32> {
+    let end = string
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(string.len());
+    string[..end].parse().ok()
+}
+
+/// Returns the major version following the given marker in the user agent.
+fn version_after(user_agent: &str, marker: &str) -> Option<u32> {
+    let index = user_agent.find(marker)?;
+    major_version(&user_agent[index + marker.len()..])
+}
+
+/// Detects the legacy relevant browser families from a user agent.
+fn detect_browser(user_agent: &str) -> Option<(Browser, u32)> {
+    if let Some(version) = version_after(user_agent, "Opera Mini/") {
+        return Some((Browser::OperaMini, version));
+    }
+    if user_agent.starts_with("Opera/") {
+        let version = version_after(user_agent, "Version/")
+            .or_else(|| version_after(user_agent, "Opera/"))?;
+        return Some((Browser::Opera, version));
+    }
+    if let Some(version) = version_after(user_agent, "Edge/") {
+        return Some((Browser::Edge, version));
+    }
+    if let Some(version) = version_after(user_agent, "MSIE ") {
+        return Some((Browser::Ie, version));
+    }
+    if user_agent.contains("Trident/") {
+        return Some((Browser::Ie, version_after(user_agent, "rv:")?));
+    }
+    if user_agent.contains("Chrome/") || user_agent.contains("CriOS/") {
+        return None;
+    }
+    if let Some(version) = version_after(user_agent, "Android ") {
+        return Some((Browser::Android, version));
+    }
+    if user_agent.contains("Safari/") {
+        return Some((Browser::Safari, version_after(user_agent, "Version/")?));
+    }
+    None
+}
+
+/// The configuration of a filter that can only be toggled.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct FilterConfig {
+    /// Whether the filter is enabled.
+    #[serde(default, rename = "isEnabled")]
+    pub is_enabled: bool,
+}
+
+/// The configuration of the legacy browsers filter.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct LegacyBrowsersFilterConfig {
+    /// Whether the filter is enabled.
+    #[serde(default, rename = "isEnabled")]
+    pub is_enabled: bool,
+    /// The browsers to filter.
+    #[serde(default, rename = "options")]
+    pub browsers: BTreeSet<LegacyBrowser>,
+}
+
+/// The configuration of the client IP filter.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ClientIpsFilterConfig {
+    /// IP addresses or CIDR ranges to filter.
+    #[serde(default, rename = "blacklistedIps")]
+    pub blacklisted_ips: Vec<String>,
+}
+
+/// The configuration of the release filter.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ReleasesFilterConfig {
+    /// Glob patterns of releases to filter.
+    #[serde(default)]
+    pub releases: Vec<String>,
+}
+
+/// The configuration of the error message filter.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ErrorMessagesFilterConfig {
+    /// Glob patterns of error messages to filter.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+/// The inbound filter settings of a project.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct FiltersConfig {
+    /// Filters errors caused by browser extensions.
+    #[serde(default, rename = "browserExtensions")]
+    pub browser_extensions: FilterConfig,
+    /// Filters events sent by legacy browsers.
+    #[serde(default, rename = "legacyBrowsers")]
+    pub legacy_browsers: LegacyBrowsersFilterConfig,
+    /// Filters events sent by web crawlers.
+    #[serde(default, rename = "webCrawlers")]
+    pub web_crawlers: FilterConfig,
+    /// Filters events by the client IP address.
+    #[serde(default, rename = "clientIps")]
+    pub client_ips: ClientIpsFilterConfig,
+    /// Filters events by release.
+    #[serde(default)]
+    pub releases: ReleasesFilterConfig,
+    /// Filters events by error message.
+    #[serde(default, rename = "errorMessages")]
+    pub error_messages: ErrorMessagesFilterConfig,
+    /// Filters events sent from localhost.
+    #[serde(default)]
+    pub localhost: FilterConfig,
+}
+
+impl FiltersConfig {
+    /// Evaluates all filters against an event.
+    ///
+    /// Returns the key of the first filter that rejects the event, or `None`
+    /// if the event should be kept.
+    pub fn apply(&self, event: &Event<'_>) -> Option<FilterStatKey> {
+        if matches_client_ips(&self.client_ips, event) {
+            return Some(FilterStatKey::IpAddress);
+        }
+        if matches_releases(&self.releases, event) {
+            return Some(FilterStatKey::ReleaseVersion);
+        }
+        if matches_error_messages(&self.error_messages, event) {
+            return Some(FilterStatKey::ErrorMessage);
+        }
+        if self.localhost.is_enabled && matches_localhost(event) {
+            return Some(FilterStatKey::Localhost);
+        }
+        if self.browser_extensions.is_enabled && matches_browser_extensions(event) {
+            return Some(FilterStatKey::BrowserExtensions);
+        }
+        if matches_legacy_browsers(&self.legacy_browsers, event) {
+            return Some(FilterStatKey::LegacyBrowsers);
+        }
+        if self.web_crawlers.is_enabled && matches_web_crawlers(event) {
+            return Some(FilterStatKey::WebCrawlers);
+        }
+        None
+    }
+}
+
+fn lowercase(c: char) -> char {
+    c.to_ascii_lowercase()
+}
+
+/// Checks whether an IP address matches an address or CIDR range.
+fn ip_matches(ip: IpAddr, pattern: &str) -> bool {
+    let (network, prefix) = match pattern.split_once('/') {
+        Some((network, prefix)) => match prefix.parse::<u32>() {
+            Ok(prefix) => (network, Some(prefix)),
+            Err(_) => return false,
+        },
+        None => (pattern, None),
+    };
+
+    let network: IpAddr = match network.trim().parse() {
+        Ok(network) => network,
+        Err(_) => return false,
+    };
+
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let prefix = prefix.unwrap_or(32).min(32);
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let prefix = prefix.unwrap_or(128).min(128);
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+fn matches_client_ips(config: &ClientIpsFilterConfig, event: &Event<'_>) -> bool {
+    let ip = match event.user.as_ref().and_then(|user| user.ip_address) {
+        Some(IpAddress::Exact(ip)) => ip,
+        _ => return false,
+    };
+
+    config
+        .blacklisted_ips
+        .iter()
+        .any(|pattern| ip_matches(ip, pattern))
+}
+
+fn matches_releases(config: &ReleasesFilterConfig, event: &Event<'_>) -> bool {
+    let release = match event.release {
+        Some(ref release) => release,
+        None => return false,
+    };
+
+    config
+        .releases
+        .iter()
+        .any(|pattern| glob_match(pattern, release, lowercase))
+}
+
+fn matches_error_messages(config: &ErrorMessagesFilterConfig, event: &Event<'_>) -> bool {
+    if config.patterns.is_empty() {
+        return false;
+    }
+
+    let mut messages = Vec::new();
+    if let Some(ref message) = event.message {
+        messages.push(message.clone());
+    }
+    if let Some(ref logentry) = event.logentry {
+        messages.push(logentry.message.clone());
+    }
+    for exception in &event.exception {
+        messages.push(match exception.value {
+            Some(ref value) if exception.ty.is_empty() => value.clone(),
+            Some(ref value) => format!("{}: {}", exception.ty, value),
+            None => exception.ty.clone(),
+        });
+    }
+
+    messages.iter().any(|message| {
+        config
+            .patterns
+            .iter()
+            .any(|pattern| glob_match(pattern, message, lowercase))
+    })
+}
+
+/// Host names of local development servers.
+const LOCAL_DOMAINS: &[&str] = &["127.0.0.1", "[::1]", "localhost"];
+
+fn matches_localhost(event: &Event<'_>) -> bool {
+    let ip_matches = match event.user.as_ref().and_then(|user| user.ip_address) {
+        Some(IpAddress::Exact(ip)) => ip.is_loopback(),
+        _ => false,
+    };
+
+    let host_matches = event
+        .request
+        .as_ref()
+        .and_then(|request| request.url.as_ref())
+        .and_then(|url| url.host_str())
+        .is_some_and(|host| LOCAL_DOMAINS.contains(&host) || host.ends_with(".localhost"));
+
+    ip_matches || host_matches
+}
+
+/// Error messages caused by well known browser extensions.
+const EXTENSION_ERRORS: &[&str] = &[
+    "top.GLOBALS",
+    "originalCreateNotification",
+    "canvas.contentDocument",
+    "MyApp_RemoveAllHighlights",
+    "http://tt.epicplay.com",
+    "Can't find variable: ZiteReader",
+    "jigsaw is not defined",
+    "ComboSearch is not defined",
+    "http://loading.retry.widdit.com/",
+    "atomicFindClose",
+    "fb_xd_fragment",
+    "bmi_SafeAddOnload",
+    "EBCallBackMessageReceived",
+    "conduitPage",
+];
+
+/// URL prefixes of frames that belong to browser extensions.
+const EXTENSION_URLS: &[&str] = &[
+    "chrome://",
+    "chrome-extension://",
+    "moz-extension://",
+    "safari-extension://",
+    "safari-web-extension://",
+    "resource://",
+    "graph.facebook.com",
+    "connect.facebook.net",
+    "eatdifferent.com.woopra-ns.com",
+    "static.woopra.com/js/woopra.js",
+];
+
+fn matches_browser_extensions(event: &Event<'_>) -> bool {
+    event.exception.iter().any(|exception| {
+        let message_matches = exception.value.as_deref().is_some_and(|value| {
+            EXTENSION_ERRORS
+                .iter()
+                .any(|pattern| value.contains(pattern))
+        });
+
+        let url_matches = exception
+            .stacktrace
+            .as_ref()
+            .and_then(|stacktrace| stacktrace.frames.last())
+            .and_then(|frame| frame.abs_path.as_deref())
+            .is_some_and(|path| EXTENSION_URLS.iter().any(|url| path.contains(url)));
+
+        message_matches || url_matches
+    })
+}
+
+/// Returns the user agent of the request that sent the event.
+fn user_agent<'e>(event: &'e Event<'_>) -> Option<&'e str> {
+    event
+        .request
+        .as_ref()?
+        .headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("user-agent"))
+        .map(|(_, value)| value.as_str())
+}
+
+fn matches_legacy_browsers(config: &LegacyBrowsersFilterConfig, event: &Event<'_>) -> bool {
+    if !config.is_enabled {
+        return false;
+    }
+
+    let (browser, version) = match user_agent(event).and_then(detect_browser) {
+        Some(detected) => detected,
+        None => return false,
+    };
+
+    if config.browsers.is_empty() {
+        return LegacyBrowser::Default.matches(browser, version);
+    }
+
+    config
+        .browsers
+        .iter()
+        .any(|legacy| legacy.matches(browser, version))
+}
+
+/// User agent fragments of well known web crawlers, in lowercase.
+const WEB_CRAWLERS: &[&str] = &[
+    "googlebot",
+    "mediapartners-google",
+    "adsbot-google",
+    "feedfetcher-google",
+    "bingbot",
+    "bingpreview",
+    "baiduspider",
+    "slurp",
+    "sogou",
+    "facebookexternalhit",
+    "ia_archiver",
+    "yandexbot",
+    "ahrefsbot",
+    "pingdom",
+    "lyticsbot",
+    "slackbot",
+    "crawler",
+    "spider",
+];
+
+fn matches_web_crawlers(event: &Event<'_>) -> bool {
+    let user_agent = match user_agent(event) {
+        Some(user_agent) => user_agent.to_ascii_lowercase(),
+        None => return false,
+    };
+
+    WEB_CRAWLERS
+        .iter()
+        .any(|crawler| user_agent.contains(crawler))
+}
+
+impl<'a> Event<'a> {
+    /// Evaluates inbound filters against the event.
+    ///
+    /// This is a shortcut for `FiltersConfig::apply`.
+    pub fn matches_filter(&self, config: &FiltersConfig) -> Option<FilterStatKey> {
+        config.apply(self)
+    }
+}
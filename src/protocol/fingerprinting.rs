@@ -0,0 +1,520 @@
+//! Server-side fingerprinting rules.
+//!
+//! Fingerprinting rules assign a custom fingerprint to events matching a set
+//! of conditions.  Every rule consists of one or more matchers, an arrow and
+//! the fingerprint values, optionally followed by attributes:
+//!
+//! ```text
+//! # group all database outages together
+//! error.type:DatabaseUnavailable -> system-down
+//! error.type:ConnectionError error.value:"*timed out*" -> timeout, {{ transaction }}
+//! logger:my.package.* level:error -> logger-error title="Error from my.package"
+//! ```
+//!
+//! Rules are evaluated in order and the first matching rule wins.  Variables
+//! such as `{{ transaction }}` are kept verbatim and resolved by Sentry, with
+//! the exception of `{{ default }}` which is understood by `grouping`.
+use std::fmt;
+use std::str;
+
+use thiserror::Error;
+
+use crate::protocol::enhancements::{identity, normalize_path, platform_family, tokenize};
+use crate::protocol::v7::{Event, Exception, Frame, Platform};
+use crate::utils::glob_match;
+
+/// Represents an error parsing fingerprinting rules.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ParseFingerprintingError {
+    /// Raised if a matcher is malformed.
+    #[error("invalid matcher on line {0}")]
+    InvalidMatcher(usize),
+    /// Raised if a matcher uses an unsupported key.
+    #[error("unknown matcher `{1}` on line {0}")]
+    UnknownMatcher(usize, String),
+    /// Raised if an attribute after the fingerprint is malformed or unsupported.
+    #[error("invalid attribute `{1}` on line {0}")]
+    InvalidAttribute(usize, String),
+    /// Raised if a quoted value is not terminated.
+    #[error("unterminated string on line {0}")]
+    UnterminatedString(usize),
+    /// Raised if a rule has no matchers.
+    #[error("missing matchers on line {0}")]
+    MissingMatchers(usize),
+    /// Raised if a rule has no `->` or no fingerprint values.
+    #[error("missing fingerprint on line {0}")]
+    MissingFingerprint(usize),
+}
+
+/// The event attribute a fingerprinting matcher is applied to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FingerprintMatcherKey {
+    /// Matches the type of an exception (`error.type`).
+    Type,
+    /// Matches the value of an exception (`error.value`).
+    Value,
+    /// Matches the message or any exception value (`message`).
+    Message,
+    /// Matches the logger of the event (`logger`).
+    Logger,
+    /// Matches the level of the event (`level`).
+    Level,
+    /// Matches the release of the event (`release`).
+    Release,
+    /// Matches the value of a tag (`tags.<name>`).
+    Tag(String),
+    /// Matches the absolute path or filename of a frame (`stack.abs_path`).
+    Path,
+    /// Matches the module of a frame (`stack.module`).
+    Module,
+    /// Matches the function of a frame (`stack.function`).
+    Function,
+    /// Matches the package of a frame (`stack.package`).
+    Package,
+    /// Matches the platform family of a frame (`family`).
+    Family,
+    /// Matches the in-app flag of a frame (`app`).
+    App,
+}
+
+impl FingerprintMatcherKey {
+    fn from_key(key: &str) -> Option<FingerprintMatcherKey> {
+        if let Some(tag) = key.strip_prefix("tags.") {
+            return Some(FingerprintMatcherKey::Tag(tag.to_string()));
+        }
+
+        Some(match key {
+            "error.type" | "type" => FingerprintMatcherKey::Type,
+            "error.value" | "value" => FingerprintMatcherKey::Value,
+            "message" => FingerprintMatcherKey::Message,
+            "logger" => FingerprintMatcherKey::Logger,
+            "level" => FingerprintMatcherKey::Level,
+            "release" => FingerprintMatcherKey::Release,
+            "stack.abs_path" | "stack.path" | "path" => FingerprintMatcherKey::Path,
+            "stack.module" | "module" => FingerprintMatcherKey::Module,
+            "stack.function" | "function" => FingerprintMatcherKey::Function,
+            "stack.package" | "package" => FingerprintMatcherKey::Package,
+            "family" => FingerprintMatcherKey::Family,
+            "app" => FingerprintMatcherKey::App,
+            _ => return None,
+        })
+    }
+
+    fn is_exception_key(&self) -> bool {
+        matches!(
+            *self,
+            FingerprintMatcherKey::Type | FingerprintMatcherKey::Value
+        )
+    }
+
+    fn is_frame_key(&self) -> bool {
+        matches!(
+            *self,
+            FingerprintMatcherKey::Path
+                | FingerprintMatcherKey::Module
+                | FingerprintMatcherKey::Function
+                | FingerprintMatcherKey::Package
+                | FingerprintMatcherKey::Family
+                | FingerprintMatcherKey::App
+        )
+    }
+}
+
+impl fmt::Display for FingerprintMatcherKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FingerprintMatcherKey::Type => write!(f, "error.type"),
+            FingerprintMatcherKey::Value => write!(f, "error.value"),
+            FingerprintMatcherKey::Message => write!(f, "message"),
+            FingerprintMatcherKey::Logger => write!(f, "logger"),
+            FingerprintMatcherKey::Level => write!(f, "level"),
+            FingerprintMatcherKey::Release => write!(f, "release"),
+            FingerprintMatcherKey::Tag(ref tag) => write!(f, "tags.{}", tag),
+            FingerprintMatcherKey::Path => write!(f, "stack.abs_path"),
+            FingerprintMatcherKey::Module => write!(f, "stack.module"),
+            FingerprintMatcherKey::Function => write!(f, "stack.function"),
+            FingerprintMatcherKey::Package => write!(f, "stack.package"),
+            FingerprintMatcherKey::Family => write!(f, "family"),
+            FingerprintMatcherKey::App => write!(f, "app"),
+        }
+    }
+}
+
+/// Matches a single attribute of an event against a pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FingerprintMatcher {
+    /// The attribute to match.
+    pub key: FingerprintMatcherKey,
+    /// The glob pattern (or value list for `family` and `app`).
+    pub pattern: String,
+    /// Inverts the result of the matcher.
+    pub negated: bool,
+}
+
+impl FingerprintMatcher {
+    fn glob(&self, value: Option<&str>) -> bool {
+        value.is_some_and(|value| glob_match(&self.pattern, value, identity))
+    }
+
+    /// Checks the event level attributes, ignoring exceptions and frames.
+    fn matches_event(&self, event: &Event<'_>) -> bool {
+        let matches = match self.key {
+            FingerprintMatcherKey::Message => {
+                self.glob(event.message.as_deref())
+                    || self.glob(event.logentry.as_ref().map(|l| l.message.as_str()))
+                    || event
+                        .exception
+                        .iter()
+                        .any(|exception| self.glob(exception.value.as_deref()))
+            }
+            FingerprintMatcherKey::Logger => self.glob(event.logger.as_deref()),
+            FingerprintMatcherKey::Level => self.glob(Some(&event.level.to_string())),
+            FingerprintMatcherKey::Release => self.glob(event.release.as_deref()),
+            FingerprintMatcherKey::Tag(ref tag) => {
+                self.glob(event.tags.get(tag.as_str()).map(String::as_str))
+            }
+            _ => return true,
+        };
+        self.negated != matches
+    }
+
+    fn matches_exception(&self, exception: &Exception) -> bool {
+        let matches = match self.key {
+            FingerprintMatcherKey::Type => self.glob(Some(&exception.ty)),
+            FingerprintMatcherKey::Value => self.glob(exception.value.as_deref()),
+            _ => return true,
+        };
+        self.negated != matches
+    }
+
+    fn matches_frame(&self, frame: &Frame, platform: &Platform) -> bool {
+        let matches = match self.key {
+            FingerprintMatcherKey::Path => [frame.abs_path.as_deref(), frame.filename.as_deref()]
+                .iter()
+                .flatten()
+                .any(|path| glob_match(&self.pattern, path, normalize_path)),
+            FingerprintMatcherKey::Module => self.glob(frame.module.as_deref()),
+            FingerprintMatcherKey::Function => self.glob(frame.function.as_deref()),
+            FingerprintMatcherKey::Package => frame
+                .package
+                .as_deref()
+                .is_some_and(|package| glob_match(&self.pattern, package, normalize_path)),
+            FingerprintMatcherKey::Family => {
+                let family = platform_family(platform);
+                self.pattern
+                    .split(',')
+                    .any(|value| value == "all" || value == family)
+            }
+            FingerprintMatcherKey::App => {
+                let in_app = frame.in_app == Some(true);
+                match self.pattern.as_str() {
+                    "1" | "yes" | "true" => in_app,
+                    "0" | "no" | "false" => !in_app,
+                    _ => false,
+                }
+            }
+            _ => return true,
+        };
+        self.negated != matches
+    }
+}
+
+impl fmt::Display for FingerprintMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negated {
+            write!(f, "!")?;
+        }
+        write!(f, "{}:", self.key)?;
+        write_value(f, &self.pattern)
+    }
+}
+
+/// A single fingerprinting rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FingerprintRule {
+    /// The matchers that all have to match an event.
+    pub matchers: Vec<FingerprintMatcher>,
+    /// The fingerprint assigned to matching events.
+    pub fingerprint: Vec<String>,
+    /// An optional title for the resulting issue.
+    pub title: Option<String>,
+}
+
+impl FingerprintRule {
+    /// Checks whether all matchers of the rule match the given event.
+    ///
+    /// Exception matchers have to match the same exception, and frame
+    /// matchers have to match the same frame of any stack trace.
+    pub fn matches_event(&self, event: &Event<'_>) -> bool {
+        if !self.matchers.iter().all(|m| m.matches_event(event)) {
+            return false;
+        }
+
+        let exception_matchers: Vec<_> = self
+            .matchers
+            .iter()
+            .filter(|m| m.key.is_exception_key())
+            .collect();
+        if !exception_matchers.is_empty()
+            && !event
+                .exception
+                .iter()
+                .any(|e| exception_matchers.iter().all(|m| m.matches_exception(e)))
+        {
+            return false;
+        }
+
+        let frame_matchers: Vec<_> = self
+            .matchers
+            .iter()
+            .filter(|m| m.key.is_frame_key())
+            .collect();
+        if frame_matchers.is_empty() {
+            return true;
+        }
+
+        let stacktraces = event
+            .stacktrace
+            .iter()
+            .chain(event.exception.iter().filter_map(|e| e.stacktrace.as_ref()))
+            .chain(event.threads.iter().filter_map(|t| t.stacktrace.as_ref()));
+        for stacktrace in stacktraces {
+            for frame in &stacktrace.frames {
+                if frame_matchers
+                    .iter()
+                    .all(|m| m.matches_frame(frame, &event.platform))
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn parse(line: &str, lineno: usize) -> Result<FingerprintRule, ParseFingerprintingError> {
+        let (matchers, fingerprint) =
+            split_arrow(line).ok_or(ParseFingerprintingError::MissingFingerprint(lineno))?;
+
+        let mut rule = FingerprintRule {
+            matchers: Vec::new(),
+            fingerprint: Vec::new(),
+            title: None,
+        };
+
+        let tokens = tokenize(matchers, lineno)
+            .map_err(|_| ParseFingerprintingError::UnterminatedString(lineno))?;
+        for token in tokens {
+            let (negated, token) = match token.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, token.as_str()),
+            };
+            let (key, pattern) = token
+                .split_once(':')
+                .ok_or(ParseFingerprintingError::InvalidMatcher(lineno))?;
+            let key = FingerprintMatcherKey::from_key(key)
+                .ok_or_else(|| ParseFingerprintingError::UnknownMatcher(lineno, key.to_string()))?;
+
+            rule.matchers.push(FingerprintMatcher {
+                key,
+                pattern: pattern.to_string(),
+                negated,
+            });
+        }
+
+        if rule.matchers.is_empty() {
+            return Err(ParseFingerprintingError::MissingMatchers(lineno));
+        }
+
+        for token in tokenize_fingerprint(fingerprint, lineno)? {
+            match token {
+                FingerprintToken::Value(value) if rule.title.is_none() => {
+                    rule.fingerprint.push(value)
+                }
+                FingerprintToken::Attribute(key, value) if key == "title" => {
+                    rule.title = Some(value)
+                }
+                FingerprintToken::Value(value) | FingerprintToken::Attribute(_, value) => {
+                    return Err(ParseFingerprintingError::InvalidAttribute(lineno, value))
+                }
+            }
+        }
+
+        if rule.fingerprint.is_empty() {
+            return Err(ParseFingerprintingError::MissingFingerprint(lineno));
+        }
+
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for FingerprintRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for matcher in &self.matchers {
+            write!(f, "{} ", matcher)?;
+        }
+        write!(f, "->")?;
+        for (idx, value) in self.fingerprint.iter().enumerate() {
+            write!(f, "{}", if idx > 0 { ", " } else { " " })?;
+            if value.starts_with("{{") {
+                write!(f, "{}", value)?;
+            } else {
+                write_value(f, value)?;
+            }
+        }
+        if let Some(ref title) = self.title {
+            write!(f, " title={:?}", title)?;
+        }
+        Ok(())
+    }
+}
+
+/// A list of fingerprinting rules.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FingerprintingRules {
+    /// The rules in the order they are evaluated.
+    pub rules: Vec<FingerprintRule>,
+}
+
+impl FingerprintingRules {
+    /// Parses fingerprinting rules, one rule per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn parse(input: &str) -> Result<FingerprintingRules, ParseFingerprintingError> {
+        let mut rules = Vec::new();
+        for (idx, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            rules.push(FingerprintRule::parse(line, idx + 1)?);
+        }
+        Ok(FingerprintingRules { rules })
+    }
+
+    /// Returns the first rule matching the event.
+    pub fn matching_rule(&self, event: &Event<'_>) -> Option<&FingerprintRule> {
+        self.rules.iter().find(|rule| rule.matches_event(event))
+    }
+
+    /// Applies the first matching rule to the event.
+    ///
+    /// The fingerprint of the event is replaced with the fingerprint of the
+    /// rule.  Returns the matching rule, if any.
+    pub fn apply(&self, event: &mut Event<'_>) -> Option<&FingerprintRule> {
+        let rule = self.matching_rule(event)?;
+        event.fingerprint = rule.fingerprint.iter().cloned().map(Into::into).collect();
+        Some(rule)
+    }
+}
+
+impl str::FromStr for FingerprintingRules {
+    type Err = ParseFingerprintingError;
+
+    fn from_str(string: &str) -> Result<FingerprintingRules, ParseFingerprintingError> {
+        FingerprintingRules::parse(string)
+    }
+}
+
+impl fmt::Display for FingerprintingRules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for rule in &self.rules {
+            writeln!(f, "{}", rule)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a rule at the `->` separating matchers and fingerprint.
+fn split_arrow(line: &str) -> Option<(&str, &str)> {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '-' if !quoted && line[idx..].starts_with("->") => {
+                return Some((&line[..idx], &line[idx + 2..]))
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A token of the fingerprint part of a rule.
+enum FingerprintToken {
+    Value(String),
+    Attribute(String, String),
+}
+
+/// Splits the fingerprint part of a rule into values and attributes.
+///
+/// Values are separated by commas or whitespace.  Variables in double curly
+/// braces may contain whitespace and are normalized to `{{ name }}`.
+fn tokenize_fingerprint(
+    input: &str,
+    lineno: usize,
+) -> Result<Vec<FingerprintToken>, ParseFingerprintingError> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() {
+            return Ok(tokens);
+        }
+
+        if let Some(variable) = rest.strip_prefix("{{") {
+            let end = variable
+                .find("}}")
+                .ok_or(ParseFingerprintingError::MissingFingerprint(lineno))?;
+            let name = variable[..end].trim();
+            tokens.push(FingerprintToken::Value(format!("{{{{ {} }}}}", name)));
+            rest = &variable[end + 2..];
+            continue;
+        }
+
+        let mut value = String::new();
+        let mut key = None;
+        let mut chars = rest.chars();
+        rest = "";
+        while let Some(c) = chars.next() {
+            match c {
+                c if c.is_whitespace() || c == ',' => {
+                    rest = chars.as_str();
+                    break;
+                }
+                '=' if key.is_none() => key = Some(std::mem::take(&mut value)),
+                '"' => loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => value.push(escaped),
+                            None => {
+                                return Err(ParseFingerprintingError::UnterminatedString(lineno))
+                            }
+                        },
+                        Some(c) => value.push(c),
+                        None => return Err(ParseFingerprintingError::UnterminatedString(lineno)),
+                    }
+                },
+                c => value.push(c),
+            }
+        }
+
+        tokens.push(match key {
+            Some(key) => FingerprintToken::Attribute(key, value),
+            None => FingerprintToken::Value(value),
+        });
+    }
+}
+
+/// Writes a value, quoting it if necessary.
+fn write_value(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == ',') {
+        write!(f, "{:?}", value)
+    } else {
+        write!(f, "{}", value)
+    }
+}
@@ -0,0 +1,185 @@
+//! Computation of grouping hashes.
+//!
+//! Sentry groups events into issues by hashing a set of grouping components.
+//! This module mirrors the server defaults as closely as practical, so that
+//! hashes can be pre-computed before an event is sent, for instance for
+//! sampling or deduplication.  Hashes computed here are not guaranteed to
+//! match the ones computed by Sentry for every event.
+//!
+//! Hashes are the hex encoded MD5 digest of the concatenated component
+//! values.  Up to two variants are computed: an `app` variant considering
+//! only in-app frames and a `system` variant considering all frames.
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::v7::{Event, Frame, Map, Stacktrace, Value};
+
+/// Placeholders in fingerprints that refer to the default grouping.
+const DEFAULT_FINGERPRINT_VALUES: &[&str] = &["{{ default }}", "{{default}}"];
+
+/// The grouping configuration of a project.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct GroupingConfig {
+    /// The id of the grouping strategy, such as `newstyle:2023-01-11`.
+    pub id: String,
+    /// The encoded base enhancements of the strategy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enhancements: Option<String>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Computes a grouping hash from a list of component values.
+pub fn hash_from_values<I, S>(values: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut context = md5::Context::new();
+    for value in values {
+        context.consume(value.as_ref().as_bytes());
+    }
+    format!("{:x}", context.compute())
+}
+
+/// Returns the grouping components of a frame.
+fn frame_components(frame: &Frame) -> Vec<&str> {
+    let mut components = Vec::new();
+
+    if let Some(module) = frame.module.as_deref() {
+        components.push(module);
+    } else if let Some(filename) = frame.filename.as_deref().or(frame.abs_path.as_deref()) {
+        // query strings and directories are too volatile for grouping
+        let filename = filename.split('?').next().unwrap_or(filename);
+        components.push(filename.rsplit(&['/', '\\'][..]).next().unwrap_or(filename));
+    }
+
+    if let Some(function) = frame.function.as_deref() {
+        components.push(function);
+    } else if let Some(context_line) = frame.context_line.as_deref() {
+        components.push(context_line.trim());
+    }
+
+    components
+}
+
+/// Returns the grouping components of a stack trace.
+///
+/// With `app_only` set, only in-app frames contribute.  Returns `None` if no
+/// frame contributes.
+fn stacktrace_components(stacktrace: &Stacktrace, app_only: bool) -> Option<Vec<&str>> {
+    let components: Vec<&str> = stacktrace
+        .frames
+        .iter()
+        .filter(|frame| !app_only || frame.in_app == Some(true))
+        .flat_map(frame_components)
+        .collect();
+
+    if components.is_empty() {
+        None
+    } else {
+        Some(components)
+    }
+}
+
+/// Returns the default grouping components of an event for a variant.
+fn default_components(event: &Event<'_>, app_only: bool) -> Option<Vec<String>> {
+    if !event.exception.is_empty() {
+        let mut components = Vec::new();
+        let mut has_stacktrace = false;
+
+        for exception in &event.exception {
+            components.push(exception.ty.clone());
+            let frames = exception
+                .stacktrace
+                .as_ref()
+                .and_then(|stacktrace| stacktrace_components(stacktrace, app_only));
+            match frames {
+                Some(frames) => {
+                    has_stacktrace = true;
+                    components.extend(frames.into_iter().map(str::to_string));
+                }
+                None => components.extend(exception.value.clone()),
+            }
+        }
+
+        return if app_only && !has_stacktrace {
+            None
+        } else {
+            Some(components)
+        };
+    }
+
+    let stacktrace = event
+        .stacktrace
+        .as_ref()
+        .or_else(|| event.threads.iter().find_map(|t| t.stacktrace.as_ref()));
+    if let Some(stacktrace) = stacktrace {
+        if let Some(frames) = stacktrace_components(stacktrace, app_only) {
+            return Some(frames.into_iter().map(str::to_string).collect());
+        }
+    }
+
+    if app_only {
+        return None;
+    }
+
+    let message = event
+        .logentry
+        .as_ref()
+        .map(|logentry| logentry.message.clone())
+        .or_else(|| event.message.clone())?;
+    Some(vec![message])
+}
+
+/// Computes the grouping hashes of an event.
+///
+/// A custom fingerprint without the `{{ default }}` placeholder replaces the
+/// default grouping entirely.  Otherwise, all additional fingerprint values
+/// are appended to the components of every default variant.  The `app`
+/// variant is only computed if the event has in-app frames.  Duplicate
+/// hashes are removed.
+pub fn compute_hashes(event: &Event<'_>) -> Vec<String> {
+    let fingerprint: Vec<&str> = event.fingerprint.iter().map(|v| v.as_ref()).collect();
+    let uses_default = fingerprint
+        .iter()
+        .any(|value| DEFAULT_FINGERPRINT_VALUES.contains(value));
+
+    if !uses_default {
+        return vec![hash_from_values(fingerprint)];
+    }
+
+    let extra: Vec<&str> = fingerprint
+        .into_iter()
+        .filter(|value| !DEFAULT_FINGERPRINT_VALUES.contains(value))
+        .collect();
+
+    let mut hashes = Vec::new();
+    for &app_only in &[true, false] {
+        if let Some(components) = default_components(event, app_only) {
+            let values = components
+                .iter()
+                .map(String::as_str)
+                .chain(extra.iter().cloned());
+            let hash = hash_from_values(values);
+            if !hashes.contains(&hash) {
+                hashes.push(hash);
+            }
+        }
+    }
+
+    if hashes.is_empty() && !extra.is_empty() {
+        hashes.push(hash_from_values(extra));
+    }
+
+    hashes
+}
+
+impl<'a> Event<'a> {
+    /// Computes the grouping hashes of the event.
+    ///
+    /// This is a shortcut for `grouping::compute_hashes`.
+    pub fn compute_hashes(&self) -> Vec<String> {
+        compute_hashes(self)
+    }
+}
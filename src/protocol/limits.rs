@@ -0,0 +1,157 @@
+//! Limits for untrusted payloads.
+//!
+//! Deeply nested or huge JSON payloads cost memory and stack space out of
+//! proportion to their size on the wire.  `ParseLimits` checks the raw bytes
+//! in a single pass before they are deserialized, so such payloads are
+//! rejected early with a `LimitError`:
+//!
+//! ```rust
+//! use sentry_types::protocol::limits::{parse_event_bytes_with_limits, ParseLimits};
+//!
+//! let limits = ParseLimits {
+//!     max_depth: 4,
+//!     ..Default::default()
+//! };
+//! let nested = br#"{"extra":{"a":{"b":{"c":{}}}}}"#;
+//! assert!(parse_event_bytes_with_limits(nested, &limits).is_err());
+//! ```
+use thiserror::Error;
+
+use crate::protocol::v7::{parse_event_bytes, Event};
+
+/// The default maximum nesting depth of JSON payloads.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// The default maximum number of elements in a JSON array.
+pub const DEFAULT_MAX_ARRAY_LENGTH: usize = 100_000;
+
+/// The default maximum length of a JSON string in bytes.
+pub const DEFAULT_MAX_STRING_LENGTH: usize = 1024 * 1024;
+
+/// An error raised if a payload exceeds a `ParseLimits` setting.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum LimitError {
+    /// Raised if objects or arrays are nested deeper than the limit.
+    #[error("payload is nested deeper than {0} levels")]
+    Depth(usize),
+    /// Raised if an array has more elements than the limit.
+    #[error("array has more than {0} elements")]
+    ArrayLength(usize),
+    /// Raised if a string or object key is longer than the limit.
+    #[error("string is longer than {0} bytes")]
+    StringLength(usize),
+}
+
+/// An error raised by `parse_event_bytes_with_limits`.
+#[derive(Debug, Error)]
+pub enum ParseEventError {
+    /// Raised if the payload exceeds the limits.
+    #[error("event payload exceeds limits")]
+    LimitExceeded(#[from] LimitError),
+    /// Raised if the payload is not a valid event.
+    #[error("invalid event payload")]
+    InvalidJson(#[from] serde_json::Error),
+}
+
+/// Limits for the structure of JSON payloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// The maximum nesting depth of objects and arrays.
+    pub max_depth: usize,
+    /// The maximum number of elements in an array.
+    pub max_array_length: usize,
+    /// The maximum length of strings and object keys in bytes, as encoded.
+    pub max_string_length: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> ParseLimits {
+        ParseLimits {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_array_length: DEFAULT_MAX_ARRAY_LENGTH,
+            max_string_length: DEFAULT_MAX_STRING_LENGTH,
+        }
+    }
+}
+
+impl ParseLimits {
+    /// Checks a JSON payload against the limits.
+    ///
+    /// This only looks at the structure of the payload and does not validate
+    /// it, invalid JSON is left to the deserializer to reject.
+    pub fn check(&self, bytes: &[u8]) -> Result<(), LimitError> {
+        // one entry per open container, holding the element count of arrays
+        let mut stack: Vec<Option<usize>> = Vec::new();
+        let mut expect_element = false;
+        let mut string_length = None;
+        let mut escaped = false;
+
+        for &byte in bytes {
+            if let Some(length) = string_length.as_mut() {
+                if escaped {
+                    escaped = false;
+                } else if byte == b'\\' {
+                    escaped = true;
+                } else if byte == b'"' {
+                    string_length = None;
+                    continue;
+                }
+                *length += 1;
+                if *length > self.max_string_length {
+                    return Err(LimitError::StringLength(self.max_string_length));
+                }
+                continue;
+            }
+
+            match byte {
+                b' ' | b'\t' | b'\r' | b'\n' | b':' => continue,
+                b']' | b'}' => {
+                    stack.pop();
+                    expect_element = false;
+                    continue;
+                }
+                b',' => {
+                    expect_element = matches!(stack.last(), Some(Some(_)));
+                    continue;
+                }
+                _ => {}
+            }
+
+            // the byte starts a value or continues a number or literal
+            if expect_element {
+                expect_element = false;
+                if let Some(Some(count)) = stack.last_mut() {
+                    *count += 1;
+                    if *count > self.max_array_length {
+                        return Err(LimitError::ArrayLength(self.max_array_length));
+                    }
+                }
+            }
+
+            match byte {
+                b'"' => string_length = Some(0),
+                b'[' | b'{' => {
+                    stack.push(if byte == b'[' { Some(0) } else { None });
+                    if stack.len() > self.max_depth {
+                        return Err(LimitError::Depth(self.max_depth));
+                    }
+                    expect_element = byte == b'[';
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses an event from raw JSON bytes after checking them against `limits`.
+///
+/// See `parse_event_bytes` for parsing without limits.
+pub fn parse_event_bytes_with_limits(
+    bytes: &[u8],
+    limits: &ParseLimits,
+) -> Result<Event<'static>, ParseEventError> {
+    limits.check(bytes)?;
+    Ok(parse_event_bytes(bytes)?)
+}
@@ -0,0 +1,404 @@
+//! Types for custom metrics.
+//!
+//! Metrics are sent in `statsd` envelope items using a statsd-like text
+//! format with one bucket per line:
+//!
+//! ```text
+//! endpoint.response_time@millisecond:36:49:57|d|#route:user_index|T1615889440
+//! ```
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str;
+
+use chrono::{DateTime, TimeZone, Utc};
+use thiserror::Error;
+
+/// An error used when parsing `MetricType`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid metric type")]
+pub struct ParseMetricTypeError;
+
+/// Represents an error parsing a metric bucket.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMetricError {
+    /// Raised if the metric name or unit is invalid.
+    #[error("invalid metric name")]
+    InvalidName,
+    /// Raised if a value cannot be parsed for the metric type.
+    #[error("invalid metric value")]
+    InvalidValue,
+    /// Raised if the metric type is missing or unknown.
+    #[error("invalid metric type")]
+    InvalidType,
+    /// Raised if the tags are malformed.
+    #[error("invalid metric tags")]
+    InvalidTags,
+    /// Raised if the timestamp is malformed.
+    #[error("invalid metric timestamp")]
+    InvalidTimestamp,
+}
+
+/// The type of a metric.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MetricType {
+    /// Counts occurrences, summing up all values.
+    Counter,
+    /// Records a distribution of values.
+    Distribution,
+    /// Counts unique values.
+    Set,
+    /// Stores the last value along with summary statistics.
+    Gauge,
+}
+
+impl fmt::Display for MetricType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MetricType::Counter => write!(f, "c"),
+            MetricType::Distribution => write!(f, "d"),
+            MetricType::Set => write!(f, "s"),
+            MetricType::Gauge => write!(f, "g"),
+        }
+    }
+}
+
+impl str::FromStr for MetricType {
+    type Err = ParseMetricTypeError;
+
+    fn from_str(string: &str) -> Result<MetricType, Self::Err> {
+        Ok(match string {
+            "c" => MetricType::Counter,
+            "d" => MetricType::Distribution,
+            "s" => MetricType::Set,
+            "g" => MetricType::Gauge,
+            _ => return Err(ParseMetricTypeError),
+        })
+    }
+}
+
+impl_str_serde!(MetricType);
+
+/// The aggregated value of a gauge.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GaugeValue {
+    /// The last value reported.
+    pub last: f64,
+    /// The smallest value reported.
+    pub min: f64,
+    /// The largest value reported.
+    pub max: f64,
+    /// The sum of all values reported.
+    pub sum: f64,
+    /// The number of values reported.
+    pub count: u64,
+}
+
+impl GaugeValue {
+    /// Creates a gauge from a single value.
+    pub fn single(value: f64) -> GaugeValue {
+        GaugeValue {
+            last: value,
+            min: value,
+            max: value,
+            sum: value,
+            count: 1,
+        }
+    }
+
+    /// Adds a value to the gauge.
+    pub fn insert(&mut self, value: f64) {
+        self.last = value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+/// The value of a metric bucket.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BucketValue {
+    /// The sum of all counted values.
+    Counter(f64),
+    /// All values of a distribution.
+    Distribution(Vec<f64>),
+    /// The unique values of a set.
+    Set(BTreeSet<u32>),
+    /// The aggregated gauge.
+    Gauge(GaugeValue),
+}
+
+impl BucketValue {
+    /// Returns the type of the metric.
+    pub fn ty(&self) -> MetricType {
+        match *self {
+            BucketValue::Counter(_) => MetricType::Counter,
+            BucketValue::Distribution(_) => MetricType::Distribution,
+            BucketValue::Set(_) => MetricType::Set,
+            BucketValue::Gauge(_) => MetricType::Gauge,
+        }
+    }
+
+    fn parse(values: &str, ty: MetricType) -> Result<BucketValue, ParseMetricError> {
+        let floats = || -> Result<Vec<f64>, ParseMetricError> {
+            values
+                .split(':')
+                .map(|value| {
+                    value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|value| value.is_finite())
+                        .ok_or(ParseMetricError::InvalidValue)
+                })
+                .collect()
+        };
+
+        Ok(match ty {
+            MetricType::Counter => BucketValue::Counter(floats()?.iter().sum()),
+            MetricType::Distribution => BucketValue::Distribution(floats()?),
+            MetricType::Set => BucketValue::Set(
+                values
+                    .split(':')
+                    .map(|value| value.parse().map_err(|_| ParseMetricError::InvalidValue))
+                    .collect::<Result<_, _>>()?,
+            ),
+            MetricType::Gauge => match floats()?[..] {
+                [value] => BucketValue::Gauge(GaugeValue::single(value)),
+                [last, min, max, sum, count] if count >= 0.0 && count.fract() == 0.0 => {
+                    BucketValue::Gauge(GaugeValue {
+                        last,
+                        min,
+                        max,
+                        sum,
+                        count: count as u64,
+                    })
+                }
+                _ => return Err(ParseMetricError::InvalidValue),
+            },
+        })
+    }
+}
+
+impl fmt::Display for BucketValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write_all<T: fmt::Display>(
+            f: &mut fmt::Formatter,
+            values: impl IntoIterator<Item = T>,
+        ) -> fmt::Result {
+            for (idx, value) in values.into_iter().enumerate() {
+                if idx > 0 {
+                    write!(f, ":")?;
+                }
+                write!(f, "{}", value)?;
+            }
+            Ok(())
+        }
+
+        match *self {
+            BucketValue::Counter(value) => write!(f, "{}", value),
+            BucketValue::Distribution(ref values) => write_all(f, values),
+            BucketValue::Set(ref values) => write_all(f, values),
+            BucketValue::Gauge(ref gauge) => write!(
+                f,
+                "{}:{}:{}:{}:{}",
+                gauge.last, gauge.min, gauge.max, gauge.sum, gauge.count
+            ),
+        }
+    }
+}
+
+/// The unit used when a metric does not declare one.
+pub const DEFAULT_METRIC_UNIT: &str = "none";
+
+/// Represents an aggregated metric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricBucket {
+    /// The name of the metric.
+    pub name: String,
+    /// The unit of the metric values.
+    pub unit: String,
+    /// The value of the metric, which also determines its type.
+    pub value: BucketValue,
+    /// The start of the time window the bucket was aggregated in.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Tags of the metric.
+    pub tags: BTreeMap<String, String>,
+}
+
+impl MetricBucket {
+    /// Creates a new bucket without unit and tags.
+    pub fn new<S: Into<String>>(name: S, value: BucketValue) -> MetricBucket {
+        MetricBucket {
+            name: name.into(),
+            unit: DEFAULT_METRIC_UNIT.into(),
+            value,
+            timestamp: None,
+            tags: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the type of the metric.
+    pub fn ty(&self) -> MetricType {
+        self.value.ty()
+    }
+
+    /// Parses all buckets from the payload of a `statsd` item.
+    ///
+    /// Empty lines are skipped.
+    pub fn parse_all(payload: &str) -> Result<Vec<MetricBucket>, ParseMetricError> {
+        payload
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// Serializes buckets into the payload of a `statsd` item.
+    pub fn to_statsd(buckets: &[MetricBucket]) -> String {
+        let mut payload = String::new();
+        for bucket in buckets {
+            payload.push_str(&bucket.to_string());
+            payload.push('\n');
+        }
+        payload
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-')
+}
+
+fn is_valid_unit(unit: &str) -> bool {
+    !unit.is_empty() && unit.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn escape_tag_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\\' => escaped.push_str("\\\\"),
+            '|' => escaped.push_str("\\u{7c}"),
+            ',' => escaped.push_str("\\u{2c}"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape_tag_value(value: &str) -> Result<String, ParseMetricError> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('\\') => unescaped.push('\\'),
+            Some('u') => {
+                let rest = chars.as_str();
+                let end = rest.find('}').ok_or(ParseMetricError::InvalidTags)?;
+                let code = rest
+                    .strip_prefix('{')
+                    .and_then(|_| u32::from_str_radix(&rest[1..end], 16).ok())
+                    .and_then(std::char::from_u32)
+                    .ok_or(ParseMetricError::InvalidTags)?;
+                unescaped.push(code);
+                chars = rest[end + 1..].chars();
+            }
+            _ => return Err(ParseMetricError::InvalidTags),
+        }
+    }
+    Ok(unescaped)
+}
+
+impl fmt::Display for MetricBucket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}@{}:{}|{}",
+            self.name,
+            self.unit,
+            self.value,
+            self.ty()
+        )?;
+
+        for (idx, (key, value)) in self.tags.iter().enumerate() {
+            write!(f, "{}", if idx == 0 { "|#" } else { "," })?;
+            write!(f, "{}:{}", key, escape_tag_value(value))?;
+        }
+
+        if let Some(timestamp) = self.timestamp {
+            write!(f, "|T{}", timestamp.timestamp())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl str::FromStr for MetricBucket {
+    type Err = ParseMetricError;
+
+    fn from_str(string: &str) -> Result<MetricBucket, Self::Err> {
+        let mut components = string.split('|');
+
+        let (name_and_unit, values) = components
+            .next()
+            .and_then(|first| first.split_once(':'))
+            .ok_or(ParseMetricError::InvalidValue)?;
+        let (name, unit) = match name_and_unit.split_once('@') {
+            Some((name, unit)) => (name, unit),
+            None => (name_and_unit, DEFAULT_METRIC_UNIT),
+        };
+        if !is_valid_name(name) || !is_valid_unit(unit) {
+            return Err(ParseMetricError::InvalidName);
+        }
+
+        let ty = components
+            .next()
+            .and_then(|ty| ty.parse().ok())
+            .ok_or(ParseMetricError::InvalidType)?;
+        let value = BucketValue::parse(values, ty)?;
+
+        let mut tags = BTreeMap::new();
+        let mut timestamp = None;
+        for component in components {
+            if let Some(tag_list) = component.strip_prefix('#') {
+                for tag in tag_list.split(',').filter(|tag| !tag.is_empty()) {
+                    let (key, value) = tag.split_once(':').unwrap_or((tag, ""));
+                    if key.is_empty() {
+                        return Err(ParseMetricError::InvalidTags);
+                    }
+                    tags.insert(key.to_string(), unescape_tag_value(value)?);
+                }
+            } else if let Some(ts) = component.strip_prefix('T') {
+                let ts = ts.parse().map_err(|_| ParseMetricError::InvalidTimestamp)?;
+                timestamp = Some(
+                    Utc.timestamp_opt(ts, 0)
+                        .single()
+                        .ok_or(ParseMetricError::InvalidTimestamp)?,
+                );
+            }
+            // unknown components are ignored for forward compatibility
+        }
+
+        Ok(MetricBucket {
+            name: name.to_string(),
+            unit: unit.to_string(),
+            value,
+            timestamp,
+            tags,
+        })
+    }
+}
@@ -1,7 +1,48 @@
 //! This module exposes the types for the Sentry protocol in different versions.
+use std::convert::TryFrom;
+use std::fmt;
+use std::str;
 
+use thiserror::Error;
+
+#[cfg(feature = "with_protocol")]
+pub mod enhancements;
+#[cfg(feature = "with_protocol")]
+pub mod envelope;
+#[cfg(feature = "with_protocol")]
+pub mod extra;
+#[cfg(feature = "with_protocol")]
+pub mod filters;
+#[cfg(feature = "with_protocol")]
+pub mod fingerprinting;
+#[cfg(feature = "with_protocol")]
+pub mod grouping;
+#[cfg(feature = "with_protocol")]
+pub mod limits;
+#[cfg(feature = "with_protocol")]
+pub mod metrics;
+#[cfg(feature = "with_protocol")]
+pub mod normalize;
+#[cfg(feature = "with_protocol")]
+pub mod outcome;
+#[cfg(feature = "with_protocol")]
+pub mod project;
+#[cfg(feature = "with_protocol")]
+pub mod quota;
+#[cfg(feature = "with_protocol")]
+pub mod raw;
+#[cfg(feature = "with_protocol")]
+pub mod response;
+#[cfg(feature = "with_protocol")]
+pub mod session;
+#[cfg(feature = "with_protocol")]
+pub mod tunnel;
 #[cfg(feature = "with_protocol")]
 pub mod v7;
+#[cfg(feature = "with_protocol")]
+pub mod view;
+#[cfg(feature = "with_protocol")]
+pub mod view_hierarchy;
 
 /// The latest version of the protocol.
 pub const LATEST: u16 = 7;
@@ -11,3 +52,101 @@ pub const LATEST: u16 = 7;
 pub mod latest {
     pub use super::v7::*;
 }
+
+/// An error used when parsing `Version`.
+#[derive(Debug, Error, Copy, Clone, PartialEq, Eq)]
+#[error("unsupported protocol version")]
+pub struct ParseVersionError;
+
+/// A version of the Sentry protocol.
+///
+/// New versions will be added over time, so matches on this enum must
+/// include a wildcard arm.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Version {
+    /// Version 7 of the protocol, implemented in `protocol::v7`.
+    V7,
+}
+
+impl Version {
+    /// Returns the latest protocol version.
+    pub fn latest() -> Version {
+        Version::V7
+    }
+
+    /// Returns the version number as sent in `sentry_version`.
+    pub fn as_u16(self) -> u16 {
+        match self {
+            Version::V7 => 7,
+        }
+    }
+
+    /// Parses an event sent with this protocol version.
+    ///
+    /// The event is converted into the latest protocol version.
+    #[cfg(feature = "with_protocol")]
+    pub fn parse_event(self, bytes: &[u8]) -> Result<latest::Event<'static>, serde_json::Error> {
+        match self {
+            Version::V7 => v7::parse_event_bytes(bytes),
+        }
+    }
+}
+
+impl Default for Version {
+    fn default() -> Version {
+        Version::latest()
+    }
+}
+
+impl TryFrom<u16> for Version {
+    type Error = ParseVersionError;
+
+    fn try_from(version: u16) -> Result<Version, ParseVersionError> {
+        match version {
+            7 => Ok(Version::V7),
+            _ => Err(ParseVersionError),
+        }
+    }
+}
+
+impl From<Version> for u16 {
+    fn from(version: Version) -> u16 {
+        version.as_u16()
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_u16())
+    }
+}
+
+impl str::FromStr for Version {
+    type Err = ParseVersionError;
+
+    fn from_str(string: &str) -> Result<Version, ParseVersionError> {
+        let version: u16 = string.parse().map_err(|_| ParseVersionError)?;
+        Version::try_from(version)
+    }
+}
+
+impl_str_serde!(Version);
+
+/// Implemented by the event types of all protocol versions.
+///
+/// Conversions between the event types of different versions are provided
+/// through `From`, so that events of any supported version can be turned into
+/// the event type of `protocol::latest`.
+#[cfg(feature = "with_protocol")]
+pub trait ProtocolEvent:
+    serde::Serialize + serde::de::DeserializeOwned + Into<latest::Event<'static>>
+{
+    /// The protocol version of the event type.
+    const VERSION: Version;
+}
+
+#[cfg(feature = "with_protocol")]
+impl ProtocolEvent for v7::Event<'static> {
+    const VERSION: Version = Version::V7;
+}
@@ -0,0 +1,494 @@
+//! Normalization of events before they are stored.
+//!
+//! `Event::normalize` applies the steps Sentry runs on every incoming event:
+//! clock drift of the client is corrected, timestamps too far in the past or
+//! future are replaced, empty strings are removed, invalid tags are dropped,
+//! duplicate contexts are dropped, unknown platforms are defaulted,
+//! names of signals and error codes in exception mechanisms are filled in,
+//! breadcrumbs are optionally sorted and deduplicated and long lists are
+//! limited.  Modifications are recorded in the meta data of the event where
+//! the original value would otherwise be lost.  Dropped and replaced values
+//! are additionally recorded in `Event::errors`.
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+
+use crate::protocol::v7::{
+    truncate_value, unique_breadcrumbs, Context, Event, EventProcessingError, Map, Meta, MetaTree,
+    Platform, Stacktrace, Value, DEFAULT_MAX_BREADCRUMBS,
+};
+use crate::utils::datetime_to_timestamp;
+#[cfg(feature = "clock")]
+use crate::utils::now;
+
+/// The maximum number of characters in a tag key.
+pub const MAX_TAG_KEY_LENGTH: usize = 200;
+
+/// The maximum number of characters in a tag value.
+pub const MAX_TAG_VALUE_LENGTH: usize = 200;
+
+/// The default maximum number of frames in a stack trace.
+pub const DEFAULT_MAX_FRAMES: usize = 250;
+
+/// The default minimum clock drift that is corrected.
+///
+/// Smaller drift is more likely caused by network latency or time zone
+/// offsets of the client than by a wrong clock.
+pub const DEFAULT_MIN_CLOCK_DRIFT_SECS: i64 = 55 * 60;
+
+/// The clock drift between a client and the server.
+///
+/// The drift is the difference between the time a request was received by
+/// the server and the time the client sent it according to its own clock,
+/// usually taken from `sent_at` of an envelope.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockDrift {
+    sent_at: DateTime<Utc>,
+    received_at: DateTime<Utc>,
+}
+
+impl ClockDrift {
+    /// Computes the clock drift from the client and server times.
+    pub fn new(sent_at: DateTime<Utc>, received_at: DateTime<Utc>) -> ClockDrift {
+        ClockDrift {
+            sent_at,
+            received_at,
+        }
+    }
+
+    /// Returns the drift, which is positive if the client clock is behind.
+    pub fn drift(&self) -> Duration {
+        self.received_at - self.sent_at
+    }
+
+    /// Returns true if the absolute drift is at least `threshold`.
+    pub fn exceeds(&self, threshold: Duration) -> bool {
+        let drift = self.drift();
+        drift >= threshold || -drift >= threshold
+    }
+
+    /// Shifts a timestamp by the drift.
+    ///
+    /// Returns `None` if the shifted timestamp is out of range.
+    pub fn correct(&self, timestamp: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        timestamp.checked_add_signed(self.drift())
+    }
+
+    /// Shifts a timestamp in place, leaving it unchanged if out of range.
+    fn correct_in_place(&self, timestamp: &mut DateTime<Utc>) {
+        if let Some(corrected) = self.correct(*timestamp) {
+            *timestamp = corrected;
+        }
+    }
+
+    /// Shifts the timestamps of an event by the drift.
+    ///
+    /// This corrects the timestamp of the event, all breadcrumbs, and the
+    /// start timestamp and spans of transactions.  Timestamps that would be
+    /// shifted out of range are left unchanged.  The client and server times
+    /// are recorded as a `clock_drift` error in the meta data of the event
+    /// timestamp, and the client time is recorded as a processing error of
+    /// the event.
+    pub fn apply(&self, event: &mut Event<'_>) {
+        self.correct_in_place(&mut event.timestamp);
+        for breadcrumb in event.breadcrumbs.iter_mut() {
+            self.correct_in_place(&mut breadcrumb.timestamp);
+        }
+
+        if let Some(ref mut start_timestamp) = event.start_timestamp {
+            self.correct_in_place(start_timestamp);
+        }
+        for span in &mut event.spans {
+            self.correct_in_place(&mut span.start_timestamp);
+            if let Some(ref mut timestamp) = span.timestamp {
+                self.correct_in_place(timestamp);
+            }
+        }
+
+        event.meta.get_mut("timestamp").errors.push(json!([
+            "clock_drift",
+            {
+                "sdk_time": self.sent_at.to_rfc3339(),
+                "server_time": self.received_at.to_rfc3339(),
+            }
+        ]));
+        event.errors.push(EventProcessingError::new(
+            EventProcessingError::CLOCK_DRIFT,
+            "sent_at",
+            Some(self.sent_at.to_rfc3339().into()),
+        ));
+    }
+}
+
+/// Corrects the clock drift of an event if it is at least `threshold`.
+///
+/// Returns true if the event timestamps were shifted.
+pub fn correct_clock_drift(
+    event: &mut Event<'_>,
+    sent_at: DateTime<Utc>,
+    received_at: DateTime<Utc>,
+    threshold: Duration,
+) -> bool {
+    let drift = ClockDrift::new(sent_at, received_at);
+    if !drift.exceeds(threshold) {
+        return false;
+    }
+
+    drift.apply(event);
+    true
+}
+
+/// Settings for `Event::normalize`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NormalizeConfig {
+    /// The time the event was received.
+    pub received_at: DateTime<Utc>,
+    /// The time the event was sent according to the client, used to correct
+    /// clock drift.
+    pub sent_at: Option<DateTime<Utc>>,
+    /// The minimum clock drift that is corrected.
+    pub min_clock_drift: Duration,
+    /// The maximum age of an event.  Older timestamps are replaced with the
+    /// time the event was received.
+    pub max_secs_in_past: Option<i64>,
+    /// The maximum amount of time an event may be in the future.  Later
+    /// timestamps are replaced with the time the event was received.
+    pub max_secs_in_future: Option<i64>,
+    /// The maximum number of breadcrumbs.  The oldest breadcrumbs are removed.
+    pub max_breadcrumbs: usize,
+    /// Sorts breadcrumbs by timestamp and removes duplicates with the same
+    /// timestamp, message and category before they are limited.  The
+    /// original number of breadcrumbs is recorded in `_meta`.
+    pub sort_breadcrumbs: bool,
+    /// The maximum number of frames per stack trace.  Frames are removed
+    /// from the middle of the stack trace.
+    pub max_frames: usize,
+}
+
+impl NormalizeConfig {
+    /// Creates the default settings for an event received at the given time.
+    pub fn new(received_at: DateTime<Utc>) -> NormalizeConfig {
+        NormalizeConfig {
+            received_at,
+            sent_at: None,
+            min_clock_drift: Duration::seconds(DEFAULT_MIN_CLOCK_DRIFT_SECS),
+            max_secs_in_past: Some(30 * 24 * 3600),
+            max_secs_in_future: Some(60),
+            max_breadcrumbs: DEFAULT_MAX_BREADCRUMBS,
+            sort_breadcrumbs: false,
+            max_frames: DEFAULT_MAX_FRAMES,
+        }
+    }
+}
+
+/// Uses the current time as `received_at`.
+///
+/// This requires the `clock` feature.
+#[cfg(feature = "clock")]
+impl Default for NormalizeConfig {
+    fn default() -> NormalizeConfig {
+        NormalizeConfig::new(now())
+    }
+}
+
+/// Sorts breadcrumbs by timestamp and removes duplicates.
+///
+/// If duplicates were removed, the original number of breadcrumbs is recorded
+/// in the meta data of `breadcrumbs.values`.
+fn sort_breadcrumbs(event: &mut Event<'_>) {
+    let breadcrumbs = &mut event.breadcrumbs.values;
+    let original_length = breadcrumbs.len();
+    breadcrumbs.sort_by_key(|breadcrumb| breadcrumb.timestamp);
+    let mut unique = unique_breadcrumbs(breadcrumbs.iter()).into_iter();
+    breadcrumbs.retain(|_| unique.next().unwrap_or(true));
+
+    if breadcrumbs.len() < original_length {
+        event
+            .meta
+            .get_mut("breadcrumbs.values")
+            .original_length
+            .get_or_insert(original_length as u64);
+    }
+}
+
+/// Creates a meta data error in the format used by Sentry.
+fn meta_error(kind: &str, reason: &str) -> Value {
+    json!([kind, { "reason": reason }])
+}
+
+/// Replaces timestamps outside of the accepted range.
+fn normalize_timestamp(event: &mut Event<'_>, config: &NormalizeConfig) {
+    let received_at = config.received_at;
+    // bounds out of the range of timestamps accept every timestamp
+    let max_timestamp = config
+        .max_secs_in_future
+        .and_then(Duration::try_seconds)
+        .and_then(|secs| received_at.checked_add_signed(secs));
+    let min_timestamp = config
+        .max_secs_in_past
+        .and_then(Duration::try_seconds)
+        .and_then(|secs| received_at.checked_sub_signed(secs));
+
+    let error = if max_timestamp.is_some_and(|max| event.timestamp > max) {
+        "future_timestamp"
+    } else if min_timestamp.is_some_and(|min| event.timestamp < min) {
+        "past_timestamp"
+    } else {
+        return;
+    };
+
+    let original_value = Value::from(datetime_to_timestamp(&event.timestamp));
+    let meta = event.meta.get_mut("timestamp");
+    meta.errors.push(json!([error]));
+    meta.original_value = Some(original_value.clone());
+    event.errors.push(EventProcessingError::new(
+        error,
+        "timestamp",
+        Some(original_value),
+    ));
+    event.timestamp = received_at;
+}
+
+/// Unsets optional strings that are empty.
+fn remove_empty_strings(event: &mut Event<'_>) {
+    fn clear(value: &mut Option<String>) {
+        if value.as_deref().is_some_and(str::is_empty) {
+            *value = None;
+        }
+    }
+
+    clear(&mut event.culprit);
+    clear(&mut event.transaction);
+    clear(&mut event.message);
+    clear(&mut event.logger);
+    if event.server_name.as_deref().is_some_and(str::is_empty) {
+        event.server_name = None;
+    }
+    if event.dist.as_deref().is_some_and(str::is_empty) {
+        event.dist = None;
+    }
+
+    if let Some(ref mut user) = event.user {
+        clear(&mut user.id);
+        clear(&mut user.email);
+        clear(&mut user.username);
+    }
+}
+
+/// Returns the reason why a tag key is invalid.
+fn validate_tag_key(key: &str) -> Result<(), &'static str> {
+    if key.is_empty() {
+        Err("empty tag key")
+    } else if key.chars().count() > MAX_TAG_KEY_LENGTH {
+        Err("tag key too long")
+    } else if !key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':' | '-' | '@'))
+    {
+        Err("invalid character in tag key")
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the reason why a tag value is invalid.
+fn validate_tag_value(value: &str) -> Result<(), &'static str> {
+    if value.is_empty() {
+        Err("empty tag value")
+    } else if value.contains('\n') {
+        Err("invalid character in tag value")
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the meta data of a tag.
+///
+/// Empty tag keys cannot be addressed by dotted path, so their meta data is
+/// stored on the tags themselves.
+fn tag_meta<'e>(event: &'e mut Event<'_>, key: &str) -> &'e mut Meta {
+    if key.is_empty() {
+        return event.meta.get_mut("tags");
+    }
+    event.meta.get_mut(&MetaTree::join_path(["tags", key]))
+}
+
+/// Removes invalid tags and truncates long tag values.
+///
+/// Removed tags are kept as original value in the meta data of the tag.
+fn normalize_tags(event: &mut Event<'_>) {
+    let tags = std::mem::take(&mut event.tags);
+    for (key, value) in tags {
+        if let Err(reason) = validate_tag_key(&key).and_then(|_| validate_tag_value(&value)) {
+            let meta = tag_meta(event, &key);
+            meta.errors.push(meta_error("invalid_data", reason));
+            meta.original_value = Some(value.clone().into());
+            event.errors.push(EventProcessingError::new(
+                EventProcessingError::INVALID_DATA,
+                MetaTree::join_path(["tags", &key]),
+                Some(value.into()),
+            ));
+            continue;
+        }
+
+        let (value, meta) = truncate_value(value, MAX_TAG_VALUE_LENGTH);
+        if let Some(meta) = meta {
+            *tag_meta(event, &key) = meta;
+        }
+        event.tags.insert(key, value);
+    }
+}
+
+/// Removes contexts with the same canonical key as another context.
+///
+/// The canonical key of a known context is its type, that of an unknown
+/// context its lowercased key.  Of several contexts with the same canonical
+/// key, the one stored under the canonical key is kept, or else the first one.
+/// Removed contexts are kept as original value in the meta data.
+fn normalize_contexts(event: &mut Event<'_>) {
+    let mut seen: Map<String, &str> = Map::new();
+    let mut duplicates = Vec::new();
+    for (key, context) in &event.contexts {
+        let canonical = match *context {
+            Context::Other(..) => key.to_lowercase(),
+            _ => context.type_name().to_string(),
+        };
+        match seen.get(&canonical) {
+            Some(&kept) if kept == canonical || *key != canonical => duplicates.push(key.clone()),
+            Some(&kept) => {
+                duplicates.push(kept.to_string());
+                seen.insert(canonical, key);
+            }
+            None => {
+                seen.insert(canonical, key);
+            }
+        }
+    }
+
+    for key in duplicates {
+        let context = event.contexts.remove(&key);
+        let name = MetaTree::join_path(["contexts", &key]);
+        let meta = &mut event
+            .meta
+            .children
+            .entry("contexts".to_string())
+            .or_default()
+            .children
+            .entry(key)
+            .or_default()
+            .meta;
+        meta.errors.push(meta_error(
+            EventProcessingError::INVALID_DATA,
+            "duplicate context key",
+        ));
+        meta.original_value = context.and_then(|c| serde_json::to_value(c).ok());
+        event.errors.push(EventProcessingError::new(
+            EventProcessingError::INVALID_DATA,
+            name,
+            meta.original_value.clone(),
+        ));
+    }
+}
+
+/// Replaces unknown platforms with `Platform::Other`.
+fn normalize_platform(event: &mut Event<'_>) {
+    if let Platform::Unknown(ref platform) = event.platform {
+        let original_value = Value::from(platform.clone());
+        let meta = event.meta.get_mut("platform");
+        meta.errors.push(meta_error(
+            EventProcessingError::INVALID_DATA,
+            "unknown platform",
+        ));
+        meta.original_value = Some(original_value.clone());
+        event.errors.push(EventProcessingError::new(
+            EventProcessingError::INVALID_DATA,
+            "platform",
+            Some(original_value),
+        ));
+        event.platform = Platform::Other;
+    }
+}
+
+/// Fills in missing names in the meta data of exception mechanisms.
+fn fill_mechanism_names(event: &mut Event<'_>) {
+    for exception in event.exception.iter_mut() {
+        if let Some(mechanism) = &mut exception.mechanism {
+            mechanism.meta.fill_names();
+        }
+    }
+}
+
+/// Removes frames from the middle of a stack trace exceeding `max_frames`.
+fn limit_frames(stacktrace: &mut Stacktrace, max_frames: usize) {
+    let len = stacktrace.frames.len();
+    if len <= max_frames {
+        return;
+    }
+
+    let start = max_frames / 2;
+    let end = len - (max_frames - start);
+    stacktrace.frames.drain(start..end);
+    stacktrace.frames_omitted = Some((start as u64, end as u64));
+}
+
+/// Limits the number of breadcrumbs and stack frames.
+fn limit_lists(event: &mut Event<'_>, config: &NormalizeConfig) {
+    let breadcrumbs = event.breadcrumbs.len();
+    if breadcrumbs > config.max_breadcrumbs {
+        event
+            .breadcrumbs
+            .values
+            .drain(..breadcrumbs - config.max_breadcrumbs);
+        event
+            .meta
+            .get_mut("breadcrumbs.values")
+            .original_length
+            .get_or_insert(breadcrumbs as u64);
+    }
+
+    let stacktraces = event
+        .stacktrace
+        .iter_mut()
+        .chain(
+            event
+                .exception
+                .iter_mut()
+                .filter_map(|e| e.stacktrace.as_mut()),
+        )
+        .chain(
+            event
+                .threads
+                .iter_mut()
+                .filter_map(|t| t.stacktrace.as_mut()),
+        );
+    for stacktrace in stacktraces {
+        limit_frames(stacktrace, config.max_frames);
+    }
+}
+
+/// Normalizes an event.
+///
+/// See the module documentation for the applied steps.
+pub fn normalize_event(event: &mut Event<'_>, config: &NormalizeConfig) {
+    if let Some(sent_at) = config.sent_at {
+        correct_clock_drift(event, sent_at, config.received_at, config.min_clock_drift);
+    }
+    normalize_timestamp(event, config);
+    remove_empty_strings(event);
+    normalize_tags(event);
+    normalize_contexts(event);
+    normalize_platform(event);
+    fill_mechanism_names(event);
+    if config.sort_breadcrumbs {
+        sort_breadcrumbs(event);
+    }
+    limit_lists(event, config);
+}
+
+impl<'a> Event<'a> {
+    /// Normalizes the event.
+    ///
+    /// This is a shortcut for `normalize::normalize_event`.
+    pub fn normalize(&mut self, config: &NormalizeConfig) {
+        normalize_event(self, config)
+    }
+}
@@ -0,0 +1,223 @@
+//! Types for outcomes.
+//!
+//! Outcomes record what happened to data sent to Sentry, such as whether an
+//! event was accepted or dropped due to rate limits.  The serialization
+//! matches the payloads of the outcomes topic.
+use std::fmt;
+use std::str;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::project_id::ProjectId;
+use crate::protocol::quota::DataCategory;
+#[cfg(feature = "clock")]
+use crate::utils::now;
+
+/// An error used when parsing `OutcomeKind`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid outcome")]
+pub struct ParseOutcomeKindError;
+
+/// The kind of an outcome.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OutcomeKind {
+    /// The data was accepted and stored.
+    Accepted,
+    /// The data was dropped by an inbound filter.
+    Filtered,
+    /// The data was dropped due to a quota or rate limit.
+    RateLimited,
+    /// The data was dropped because it was invalid.
+    Invalid,
+    /// The data was dropped because of abuse limits.
+    Abuse,
+    /// The data was dropped by the client before it was sent.
+    ClientDiscard,
+}
+
+impl OutcomeKind {
+    /// Returns the numeric value of the outcome.
+    pub fn value(self) -> u8 {
+        match self {
+            OutcomeKind::Accepted => 0,
+            OutcomeKind::Filtered => 1,
+            OutcomeKind::RateLimited => 2,
+            OutcomeKind::Invalid => 3,
+            OutcomeKind::Abuse => 4,
+            OutcomeKind::ClientDiscard => 5,
+        }
+    }
+
+    /// Returns the outcome with the given numeric value.
+    pub fn from_value(value: u8) -> Option<OutcomeKind> {
+        Some(match value {
+            0 => OutcomeKind::Accepted,
+            1 => OutcomeKind::Filtered,
+            2 => OutcomeKind::RateLimited,
+            3 => OutcomeKind::Invalid,
+            4 => OutcomeKind::Abuse,
+            5 => OutcomeKind::ClientDiscard,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for OutcomeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OutcomeKind::Accepted => write!(f, "accepted"),
+            OutcomeKind::Filtered => write!(f, "filtered"),
+            OutcomeKind::RateLimited => write!(f, "rate_limited"),
+            OutcomeKind::Invalid => write!(f, "invalid"),
+            OutcomeKind::Abuse => write!(f, "abuse"),
+            OutcomeKind::ClientDiscard => write!(f, "client_discard"),
+        }
+    }
+}
+
+impl str::FromStr for OutcomeKind {
+    type Err = ParseOutcomeKindError;
+
+    fn from_str(string: &str) -> Result<OutcomeKind, ParseOutcomeKindError> {
+        Ok(match string {
+            "accepted" => OutcomeKind::Accepted,
+            "filtered" => OutcomeKind::Filtered,
+            "rate_limited" => OutcomeKind::RateLimited,
+            "invalid" => OutcomeKind::Invalid,
+            "abuse" => OutcomeKind::Abuse,
+            "client_discard" => OutcomeKind::ClientDiscard,
+            _ => return Err(ParseOutcomeKindError),
+        })
+    }
+}
+
+impl Serialize for OutcomeKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.value())
+    }
+}
+
+impl<'de> Deserialize<'de> for OutcomeKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u8::deserialize(deserializer)?;
+        OutcomeKind::from_value(value)
+            .ok_or_else(|| serde::de::Error::custom(ParseOutcomeKindError))
+    }
+}
+
+/// A single outcome, as emitted to the outcomes topic.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Outcome {
+    /// The time at which the outcome was recorded.
+    pub timestamp: DateTime<Utc>,
+    /// The ID of the organization.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_id: Option<u64>,
+    /// The ID of the project.
+    pub project_id: ProjectId,
+    /// The ID of the project key used to send the data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<u64>,
+    /// The kind of the outcome.
+    pub outcome: OutcomeKind,
+    /// A machine readable reason code for the outcome.
+    ///
+    /// Accepted outcomes do not carry a reason.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The ID of the event, if the outcome refers to a single event.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<Uuid>,
+    /// The remote address of the client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_addr: Option<String>,
+    /// The name of the service that recorded the outcome.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The category of the data.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "category")]
+    pub category: Option<DataCategory>,
+    /// The number of items, or bytes for attachments, the outcome refers to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<u32>,
+}
+
+impl Outcome {
+    /// Creates an outcome for a project, recorded now.
+    ///
+    /// This requires the `clock` feature.
+    #[cfg(feature = "clock")]
+    pub fn new(outcome: OutcomeKind, project_id: ProjectId) -> Outcome {
+        Outcome {
+            timestamp: now(),
+            org_id: None,
+            project_id,
+            key_id: None,
+            outcome,
+            reason: None,
+            event_id: None,
+            remote_addr: None,
+            source: None,
+            category: None,
+            quantity: None,
+        }
+    }
+
+    /// Creates an outcome for a quantity of data of a category.
+    ///
+    /// The reason is set for all outcomes except `OutcomeKind::Accepted`.
+    /// This requires the `clock` feature.
+    #[cfg(feature = "clock")]
+    pub fn with_reason(
+        outcome: OutcomeKind,
+        reason: Option<&str>,
+        project_id: ProjectId,
+        category: DataCategory,
+        quantity: u32,
+    ) -> Outcome {
+        Outcome {
+            reason: reason
+                .filter(|_| outcome != OutcomeKind::Accepted)
+                .map(str::to_string),
+            category: Some(category),
+            quantity: Some(quantity),
+            ..Outcome::new(outcome, project_id)
+        }
+    }
+}
+
+mod category {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        category: &Option<DataCategory>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let value = match *category {
+            Some(DataCategory::Unknown(ref other)) => other.parse().ok(),
+            Some(ref category) => category.value(),
+            None => None,
+        };
+
+        match (value, category) {
+            (Some(value), _) => serializer.serialize_some(&value),
+            (None, None) => serializer.serialize_none(),
+            (None, Some(category)) => Err(serde::ser::Error::custom(format!(
+                "data category {} has no numeric value",
+                category
+            ))),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DataCategory>, D::Error> {
+        Ok(Option::<u8>::deserialize(deserializer)?.map(|value| {
+            DataCategory::from_value(value)
+                .unwrap_or_else(|| DataCategory::Unknown(value.to_string()))
+        }))
+    }
+}
@@ -0,0 +1,200 @@
+//! Types for project configs.
+//!
+//! These are the parts of the project configs served by Sentry that are
+//! needed to accept and filter data for a project.
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::project_id::ProjectId;
+use crate::protocol::filters::FiltersConfig;
+use crate::protocol::grouping::GroupingConfig;
+use crate::protocol::quota::Quota;
+use crate::protocol::v7::{Map, Value};
+use crate::utils::constant_time_eq;
+
+/// An error used when parsing `ProjectKeyStatus`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid project key status")]
+pub struct ParseProjectKeyStatusError;
+
+/// The status of a project key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ProjectKeyStatus {
+    /// The key accepts data.
+    #[default]
+    Enabled,
+    /// The key rejects all data.
+    Disabled,
+}
+
+impl fmt::Display for ProjectKeyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProjectKeyStatus::Enabled => write!(f, "enabled"),
+            ProjectKeyStatus::Disabled => write!(f, "disabled"),
+        }
+    }
+}
+
+impl str::FromStr for ProjectKeyStatus {
+    type Err = ParseProjectKeyStatusError;
+
+    fn from_str(string: &str) -> Result<ProjectKeyStatus, ParseProjectKeyStatusError> {
+        Ok(match string {
+            "enabled" => ProjectKeyStatus::Enabled,
+            "disabled" => ProjectKeyStatus::Disabled,
+            _ => return Err(ParseProjectKeyStatusError),
+        })
+    }
+}
+
+impl_str_serde!(ProjectKeyStatus);
+
+/// The configuration of a project key (DSN).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProjectKeyConfig {
+    /// The public key of the project key.
+    #[serde(rename = "publicKey")]
+    pub public_key: String,
+    /// The numeric ID of the project key.
+    #[serde(default, rename = "numericId", skip_serializing_if = "Option::is_none")]
+    pub numeric_id: Option<u64>,
+    /// Whether the key accepts data.
+    #[serde(default)]
+    pub status: ProjectKeyStatus,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl ProjectKeyConfig {
+    /// Returns true if the key accepts data.
+    pub fn is_enabled(&self) -> bool {
+        self.status == ProjectKeyStatus::Enabled
+    }
+}
+
+fn default_allowed_domains() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
+/// The configuration of a project.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProjectConfig {
+    /// The domains allowed to send data from browsers.  `*` allows all
+    /// domains.
+    #[serde(default = "default_allowed_domains", rename = "allowedDomains")]
+    pub allowed_domains: Vec<String>,
+    /// The public keys of relays trusted to forward data for this project.
+    #[serde(default, rename = "trustedRelays")]
+    pub trusted_relays: Vec<String>,
+    /// The inbound filter settings.
+    #[serde(default, rename = "filterSettings")]
+    pub filter_settings: FiltersConfig,
+    /// The quotas of the project.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub quotas: Vec<Quota>,
+    /// The feature flags enabled for the project.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub features: BTreeSet<String>,
+    /// The grouping configuration of the project.
+    #[serde(
+        default,
+        rename = "groupingConfig",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub grouping_config: Option<GroupingConfig>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl Default for ProjectConfig {
+    fn default() -> ProjectConfig {
+        ProjectConfig {
+            allowed_domains: default_allowed_domains(),
+            trusted_relays: Vec::new(),
+            filter_settings: FiltersConfig::default(),
+            quotas: Vec::new(),
+            features: BTreeSet::new(),
+            grouping_config: None,
+            other: Map::new(),
+        }
+    }
+}
+
+impl ProjectConfig {
+    /// Returns true if the feature flag is enabled.
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.contains(feature)
+    }
+
+    /// Returns true if the relay with the given public key is trusted.
+    pub fn is_trusted_relay(&self, public_key: &str) -> bool {
+        self.trusted_relays.iter().any(|key| key == public_key)
+    }
+}
+
+/// The state of a project as served in project configs.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ProjectState {
+    /// The ID of the project.
+    #[serde(default, rename = "projectId", skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<ProjectId>,
+    /// The ID of the organization of the project.
+    #[serde(
+        default,
+        rename = "organizationId",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub organization_id: Option<u64>,
+    /// The slug of the project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    /// Whether the project is disabled and rejects all data.
+    #[serde(default)]
+    pub disabled: bool,
+    /// The keys of the project.
+    #[serde(default, rename = "publicKeys")]
+    pub public_keys: Vec<ProjectKeyConfig>,
+    /// The time at which the project was last changed.
+    #[serde(
+        default,
+        rename = "lastChange",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_change: Option<DateTime<Utc>>,
+    /// The configuration of the project.
+    #[serde(default)]
+    pub config: ProjectConfig,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl ProjectState {
+    /// Returns the configuration of a project key.
+    ///
+    /// Keys are compared in constant time.
+    pub fn public_key_config(&self, public_key: &str) -> Option<&ProjectKeyConfig> {
+        self.public_keys
+            .iter()
+            .find(|config| constant_time_eq(&config.public_key, public_key))
+    }
+
+    /// Returns true if the project accepts data sent with the given key.
+    ///
+    /// Data is only accepted if the project is enabled and the key is known
+    /// and enabled.
+    pub fn accepts_key(&self, public_key: &str) -> bool {
+        !self.disabled
+            && self
+                .public_key_config(public_key)
+                .is_some_and(ProjectKeyConfig::is_enabled)
+    }
+}
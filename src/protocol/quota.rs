@@ -0,0 +1,265 @@
+//! Types for quotas and rate limits.
+//!
+//! Sentry enforces quotas per data category, such as errors or attachments,
+//! and scope, such as an organization or a project key.
+use std::convert::Infallible;
+use std::fmt;
+use std::str;
+
+use serde::{Deserialize, Serialize};
+
+use crate::project_id::ProjectId;
+
+/// A category of data that is accounted for by quotas and outcomes.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DataCategory {
+    /// Reserved for data without a more specific category.
+    Default,
+    /// Error events and events of unknown type.
+    Error,
+    /// Transaction events.
+    Transaction,
+    /// Security reports such as CSP violations.
+    Security,
+    /// Attachments, accounted for in bytes.
+    Attachment,
+    /// Release health sessions.
+    Session,
+    /// Profiles.
+    Profile,
+    /// Session replays.
+    Replay,
+    /// Check-ins of cron monitors.
+    Monitor,
+    /// Buckets of metrics.
+    MetricBucket,
+    /// A category not known to this version of the crate.
+    Unknown(String),
+}
+
+impl DataCategory {
+    /// Returns the name of the category.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            DataCategory::Default => "default",
+            DataCategory::Error => "error",
+            DataCategory::Transaction => "transaction",
+            DataCategory::Security => "security",
+            DataCategory::Attachment => "attachment",
+            DataCategory::Session => "session",
+            DataCategory::Profile => "profile",
+            DataCategory::Replay => "replay",
+            DataCategory::Monitor => "monitor",
+            DataCategory::MetricBucket => "metric_bucket",
+            DataCategory::Unknown(ref other) => other,
+        }
+    }
+
+    /// Returns the numeric value of the category used in outcomes.
+    ///
+    /// Returns `None` for unknown categories.
+    pub fn value(&self) -> Option<u8> {
+        Some(match *self {
+            DataCategory::Default => 0,
+            DataCategory::Error => 1,
+            DataCategory::Transaction => 2,
+            DataCategory::Security => 3,
+            DataCategory::Attachment => 4,
+            DataCategory::Session => 5,
+            DataCategory::Profile => 6,
+            DataCategory::Replay => 7,
+            DataCategory::Monitor => 10,
+            DataCategory::MetricBucket => 15,
+            DataCategory::Unknown(_) => return None,
+        })
+    }
+
+    /// Returns the category with the given numeric value.
+    pub fn from_value(value: u8) -> Option<DataCategory> {
+        Some(match value {
+            0 => DataCategory::Default,
+            1 => DataCategory::Error,
+            2 => DataCategory::Transaction,
+            3 => DataCategory::Security,
+            4 => DataCategory::Attachment,
+            5 => DataCategory::Session,
+            6 => DataCategory::Profile,
+            7 => DataCategory::Replay,
+            10 => DataCategory::Monitor,
+            15 => DataCategory::MetricBucket,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for DataCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl str::FromStr for DataCategory {
+    type Err = Infallible;
+
+    fn from_str(string: &str) -> Result<DataCategory, Infallible> {
+        Ok(match string {
+            "default" => DataCategory::Default,
+            "error" => DataCategory::Error,
+            "transaction" => DataCategory::Transaction,
+            "security" => DataCategory::Security,
+            "attachment" => DataCategory::Attachment,
+            "session" => DataCategory::Session,
+            "profile" => DataCategory::Profile,
+            "replay" => DataCategory::Replay,
+            "monitor" => DataCategory::Monitor,
+            "metric_bucket" => DataCategory::MetricBucket,
+            other => DataCategory::Unknown(other.to_string()),
+        })
+    }
+}
+
+impl_str_serde!(DataCategory);
+
+/// The scope a quota or rate limit applies to.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QuotaScope {
+    /// The organization of the project.
+    Organization,
+    /// The project.
+    Project,
+    /// A single project key (DSN).
+    Key,
+    /// A scope not known to this version of the crate.
+    Unknown(String),
+}
+
+impl fmt::Display for QuotaScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            QuotaScope::Organization => write!(f, "organization"),
+            QuotaScope::Project => write!(f, "project"),
+            QuotaScope::Key => write!(f, "key"),
+            QuotaScope::Unknown(ref other) => write!(f, "{}", other),
+        }
+    }
+}
+
+impl str::FromStr for QuotaScope {
+    type Err = Infallible;
+
+    fn from_str(string: &str) -> Result<QuotaScope, Infallible> {
+        Ok(match string {
+            "organization" => QuotaScope::Organization,
+            "project" => QuotaScope::Project,
+            "key" => QuotaScope::Key,
+            other => QuotaScope::Unknown(other.to_string()),
+        })
+    }
+}
+
+impl_str_serde!(QuotaScope);
+
+/// Identifies the organization, project and key data was sent to.
+///
+/// Quotas are matched against a scoping to determine whether they apply.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Scoping {
+    /// The ID of the organization.
+    pub organization_id: u64,
+    /// The ID of the project.
+    pub project_id: ProjectId,
+    /// The public key of the project key.
+    pub public_key: String,
+    /// The numeric ID of the project key, if known.
+    pub key_id: Option<u64>,
+}
+
+impl Scoping {
+    /// Returns the ID of the given scope as a string.
+    ///
+    /// Returns `None` for unknown scopes and keys without an ID.
+    pub fn scope_id(&self, scope: &QuotaScope) -> Option<String> {
+        match *scope {
+            QuotaScope::Organization => Some(self.organization_id.to_string()),
+            QuotaScope::Project => Some(self.project_id.to_string()),
+            QuotaScope::Key => self.key_id.map(|id| id.to_string()),
+            QuotaScope::Unknown(_) => None,
+        }
+    }
+}
+
+/// The specification of a quota from a project config.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Quota {
+    /// The ID of the quota, used to track its consumption.
+    ///
+    /// May only be omitted for quotas rejecting all data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The categories the quota applies to.  An empty list applies to all
+    /// categories.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<DataCategory>,
+    /// The scope the quota is counted in.
+    pub scope: QuotaScope,
+    /// The ID of the scope the quota applies to.  If missing, the quota
+    /// applies to all items of its scope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope_id: Option<String>,
+    /// The maximum number of items in the window.  `None` means unlimited,
+    /// and `Some(0)` rejects all data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    /// The size of the window in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<u64>,
+    /// The reason code reported when the quota is exceeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason_code: Option<String>,
+    /// The metric namespace the quota applies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
+impl Quota {
+    /// Returns true if this quota can be enforced.
+    ///
+    /// Quotas with unknown scopes or categories are invalid, as are limited
+    /// quotas without an ID or window.  Invalid quotas are ignored.
+    pub fn is_valid(&self) -> bool {
+        if let QuotaScope::Unknown(_) = self.scope {
+            return false;
+        }
+
+        if self
+            .categories
+            .iter()
+            .any(|category| matches!(category, DataCategory::Unknown(_)))
+        {
+            return false;
+        }
+
+        match self.limit {
+            Some(0) | None => true,
+            Some(_) => self.id.is_some() && self.window.is_some_and(|window| window > 0),
+        }
+    }
+
+    /// Returns true if this quota applies to data of a category sent with the
+    /// given scoping.
+    pub fn matches(&self, category: &DataCategory, scoping: &Scoping) -> bool {
+        if !self.is_valid() {
+            return false;
+        }
+
+        if !self.categories.is_empty() && !self.categories.contains(category) {
+            return false;
+        }
+
+        match self.scope_id {
+            Some(ref scope_id) => scoping.scope_id(&self.scope).as_ref() == Some(scope_id),
+            None => true,
+        }
+    }
+}
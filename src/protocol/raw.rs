@@ -0,0 +1,137 @@
+//! A pass-through representation of events.
+//!
+//! `RawEvent` keeps the original JSON of an event and only parses the few
+//! fields needed to route it, which makes it suitable for proxies that
+//! forward events without modifying them.  Serializing a `RawEvent` writes
+//! the original JSON unchanged.
+use std::convert::TryFrom;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use chrono::{DateTime, Utc};
+use serde::ser::{self, Serialize, Serializer};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use uuid::Uuid;
+
+use crate::protocol::v7::{parse_event_bytes, Event, EventType};
+use crate::utils::ts_seconds_float_opt;
+
+/// The fields of an event parsed by `RawEvent`.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct EventHeader {
+    /// The ID of the event.
+    #[serde(default)]
+    pub event_id: Option<Uuid>,
+    /// The type of the event, such as `transaction`.
+    #[serde(default, rename = "type")]
+    pub ty: Option<EventType>,
+    /// The timestamp of the event.
+    #[serde(default, with = "ts_seconds_float_opt")]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+struct RawEventInner {
+    bytes: Box<[u8]>,
+    header: OnceLock<Option<EventHeader>>,
+}
+
+/// An event kept as its original JSON.
+///
+/// The header fields are parsed on first access and cached.  Cloning is cheap
+/// as clones share the payload and the parsed header.
+#[derive(Clone)]
+pub struct RawEvent {
+    inner: Arc<RawEventInner>,
+}
+
+impl RawEvent {
+    /// Creates a raw event from its JSON payload.
+    ///
+    /// The payload is not validated.  Invalid payloads have no header and
+    /// fail to parse into an `Event`.
+    pub fn new<B: Into<Box<[u8]>>>(bytes: B) -> RawEvent {
+        RawEvent {
+            inner: Arc::new(RawEventInner {
+                bytes: bytes.into(),
+                header: OnceLock::new(),
+            }),
+        }
+    }
+
+    /// Returns the original JSON payload.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.inner.bytes
+    }
+
+    /// Returns the parsed header fields.
+    ///
+    /// Returns `None` if the payload is not a JSON object or the header
+    /// fields are invalid.
+    pub fn header(&self) -> Option<&EventHeader> {
+        self.inner
+            .header
+            .get_or_init(|| serde_json::from_slice(&self.inner.bytes).ok())
+            .as_ref()
+    }
+
+    /// Returns the ID of the event.
+    pub fn event_id(&self) -> Option<Uuid> {
+        self.header()?.event_id
+    }
+
+    /// Returns the type of the event.
+    pub fn ty(&self) -> Option<&EventType> {
+        self.header()?.ty.as_ref()
+    }
+
+    /// Returns the timestamp of the event.
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.header()?.timestamp
+    }
+
+    /// Parses the full event.
+    pub fn parse(&self) -> Result<Event<'static>, serde_json::Error> {
+        parse_event_bytes(&self.inner.bytes)
+    }
+}
+
+impl fmt::Debug for RawEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RawEvent")
+            .field("len", &self.inner.bytes.len())
+            .field("header", &self.inner.header.get())
+            .finish()
+    }
+}
+
+/// Serializes the original JSON payload.
+///
+/// With `serde_json`, the payload is written verbatim.  Serialization fails if
+/// the payload is not valid JSON.
+impl Serialize for RawEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let raw: &RawValue = serde_json::from_slice(self.as_bytes()).map_err(ser::Error::custom)?;
+        raw.serialize(serializer)
+    }
+}
+
+impl PartialEq for RawEvent {
+    fn eq(&self, other: &RawEvent) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl From<Vec<u8>> for RawEvent {
+    fn from(bytes: Vec<u8>) -> RawEvent {
+        RawEvent::new(bytes)
+    }
+}
+
+impl<'a> TryFrom<&Event<'a>> for RawEvent {
+    type Error = serde_json::Error;
+
+    fn try_from(event: &Event<'a>) -> Result<RawEvent, serde_json::Error> {
+        serde_json::to_vec(event).map(RawEvent::new)
+    }
+}
@@ -0,0 +1,223 @@
+//! Types for responses of the Sentry ingestion endpoints.
+//!
+//! Successful submissions are answered with the ID of the stored event.
+//! Rejected submissions carry an error description in the body, and rate
+//! limits are communicated in the `X-Sentry-Rate-Limits` and `Retry-After`
+//! headers.
+use std::fmt;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::protocol::quota::{DataCategory, QuotaScope};
+use crate::protocol::v7::event::serialize_id;
+use crate::protocol::v7::{Map, Value};
+
+/// The name of the header carrying rate limits.
+pub const RATE_LIMITS_HEADER: &str = "X-Sentry-Rate-Limits";
+
+/// The name of the header carrying the time to wait before retrying.
+pub const RETRY_AFTER_HEADER: &str = "Retry-After";
+
+/// The default time to wait before retrying if no valid time is given.
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// The response to a successful submission.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StoreResponse {
+    /// The ID of the stored event.
+    ///
+    /// This is the nil UUID for envelopes without an event.
+    #[serde(default, serialize_with = "serialize_id")]
+    pub id: Uuid,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// The response to a rejected submission.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ApiErrorResponse {
+    /// A description of the error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// The descriptions of the errors that caused this error.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub causes: Vec<String>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl fmt::Display for ApiErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.detail.as_deref().unwrap_or("unknown error"))?;
+        for cause in &self.causes {
+            write!(f, "\n  caused by: {}", cause)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single rate limit from the `X-Sentry-Rate-Limits` header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimit {
+    /// The time to wait before sending data of the limited categories.
+    pub retry_after: Duration,
+    /// The limited categories.  An empty list limits all categories.
+    pub categories: Vec<DataCategory>,
+    /// The scope of the rate limit.
+    pub scope: Option<QuotaScope>,
+    /// A machine readable reason for the rate limit.
+    pub reason_code: Option<String>,
+    /// The metric namespaces the rate limit applies to, if the categories
+    /// include metrics.  An empty list limits all namespaces.
+    pub namespaces: Vec<String>,
+}
+
+impl RateLimit {
+    /// Returns true if the rate limit applies to the given category.
+    pub fn applies_to(&self, category: &DataCategory) -> bool {
+        self.categories.is_empty() || self.categories.contains(category)
+    }
+
+    /// Parses a single entry of the `X-Sentry-Rate-Limits` header.
+    ///
+    /// Returns `None` if the entry does not start with a valid time.
+    fn parse(entry: &str) -> Option<RateLimit> {
+        let mut parts = entry.trim().split(':');
+        let retry_after = parse_seconds(parts.next()?)?;
+
+        let list = |part: Option<&str>| -> Vec<String> {
+            part.unwrap_or("")
+                .split(';')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+
+        let categories = list(parts.next())
+            .iter()
+            .map(|category| category.parse().unwrap_or(DataCategory::Default))
+            .collect();
+        let scope = parts
+            .next()
+            .filter(|s| !s.is_empty())
+            .and_then(|s| s.parse().ok());
+        let reason_code = parts.next().filter(|s| !s.is_empty()).map(str::to_string);
+        let namespaces = list(parts.next());
+
+        Some(RateLimit {
+            retry_after,
+            categories,
+            scope,
+            reason_code,
+            namespaces,
+        })
+    }
+}
+
+impl fmt::Display for RateLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:", self.retry_after.as_secs())?;
+        for (i, category) in self.categories.iter().enumerate() {
+            if i > 0 {
+                write!(f, ";")?;
+            }
+            write!(f, "{}", category)?;
+        }
+        write!(f, ":")?;
+        if let Some(ref scope) = self.scope {
+            write!(f, "{}", scope)?;
+        }
+        if let Some(ref reason_code) = self.reason_code {
+            write!(f, ":{}", reason_code)?;
+        }
+        if !self.namespaces.is_empty() {
+            if self.reason_code.is_none() {
+                write!(f, ":")?;
+            }
+            write!(f, ":{}", self.namespaces.join(";"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Rate limits parsed from the `X-Sentry-Rate-Limits` header.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RateLimits {
+    /// The individual rate limits.
+    pub limits: Vec<RateLimit>,
+}
+
+impl RateLimits {
+    /// Parses the value of the `X-Sentry-Rate-Limits` header.
+    ///
+    /// Invalid entries are skipped, as clients are expected to deal with
+    /// rate limits they do not understand.
+    pub fn parse(header: &str) -> RateLimits {
+        RateLimits {
+            limits: header.split(',').filter_map(RateLimit::parse).collect(),
+        }
+    }
+
+    /// Returns true if there are no rate limits.
+    pub fn is_empty(&self) -> bool {
+        self.limits.is_empty()
+    }
+
+    /// Returns the time to wait before sending data of a category.
+    ///
+    /// If multiple rate limits apply, the longest time is returned.  Returns
+    /// `None` if the category is not limited.
+    pub fn retry_after(&self, category: &DataCategory) -> Option<Duration> {
+        self.limits
+            .iter()
+            .filter(|limit| limit.applies_to(category))
+            .map(|limit| limit.retry_after)
+            .max()
+    }
+}
+
+impl fmt::Display for RateLimits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, limit) in self.limits.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", limit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a non-negative number of seconds, which may be fractional.
+fn parse_seconds(string: &str) -> Option<Duration> {
+    let seconds: f64 = string.trim().parse().ok()?;
+    if seconds.is_finite() && seconds >= 0.0 && seconds < u64::MAX as f64 {
+        Some(Duration::from_secs_f64(seconds))
+    } else {
+        None
+    }
+}
+
+/// Parses the value of the `Retry-After` header.
+///
+/// The value is either a number of seconds or an HTTP date, which is
+/// resolved relative to `now`.  Dates in the past result in a zero duration.
+/// Returns `None` for invalid values.
+pub fn parse_retry_after(header: &str, now: DateTime<Utc>) -> Option<Duration> {
+    if let Some(seconds) = parse_seconds(header) {
+        return Some(seconds);
+    }
+
+    let date = DateTime::parse_from_rfc2822(header.trim()).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::from_secs(0)),
+    )
+}
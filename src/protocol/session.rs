@@ -0,0 +1,479 @@
+//! Types for release health sessions.
+//!
+//! Sessions are sent in `session` envelope items and track the health of a
+//! single run of an application or a single request.  SDKs for server
+//! applications instead send `SessionAggregates` in `sessions` items, which
+//! count sessions per hour rather than sending each of them.
+use std::fmt;
+use std::str;
+
+use chrono::{DateTime, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::duration::Duration;
+use crate::protocol::envelope::{EnvelopeItem, ItemType};
+use crate::protocol::v7::{Event, IpAddress};
+#[cfg(feature = "clock")]
+use crate::utils::{now, random_uuid};
+
+/// An error used when parsing `SessionStatus`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid session status")]
+pub struct ParseSessionStatusError;
+
+/// The status of a session.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum SessionStatus {
+    /// The session is healthy.  This does not necessarily indicate that the
+    /// session is still running.
+    #[default]
+    Ok,
+    /// The session terminated normally.
+    Exited,
+    /// The session terminated in a crash.
+    Crashed,
+    /// The session had an unexpected abrupt termination (not crashing).
+    Abnormal,
+}
+
+impl str::FromStr for SessionStatus {
+    type Err = ParseSessionStatusError;
+
+    fn from_str(string: &str) -> Result<SessionStatus, Self::Err> {
+        Ok(match string {
+            "ok" => SessionStatus::Ok,
+            "exited" => SessionStatus::Exited,
+            "crashed" => SessionStatus::Crashed,
+            "abnormal" => SessionStatus::Abnormal,
+            _ => return Err(ParseSessionStatusError),
+        })
+    }
+}
+
+impl fmt::Display for SessionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SessionStatus::Ok => write!(f, "ok"),
+            SessionStatus::Exited => write!(f, "exited"),
+            SessionStatus::Crashed => write!(f, "crashed"),
+            SessionStatus::Abnormal => write!(f, "abnormal"),
+        }
+    }
+}
+
+impl_str_serde!(SessionStatus);
+
+impl SessionStatus {
+    /// Returns true if the session has ended with this status.
+    ///
+    /// Every status except `Ok` is terminal.
+    pub fn is_terminal(self) -> bool {
+        self != SessionStatus::Ok
+    }
+
+    /// Returns true if a session with this status may be updated to `next`.
+    ///
+    /// A healthy session can move to any status, while a session that ended
+    /// keeps its terminal status.  Repeating the current status is always
+    /// allowed, since SDKs may send the final update more than once.
+    pub fn can_transition_to(self, next: SessionStatus) -> bool {
+        !self.is_terminal() || self == next
+    }
+}
+
+/// An error raised when an update is not valid for a session.
+///
+/// See `Session::update`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SessionUpdateError {
+    /// Raised if the update changes the status of a session that ended.
+    #[error("invalid session status transition from {from} to {to}")]
+    InvalidTransition {
+        /// The current status of the session.
+        from: SessionStatus,
+        /// The status of the update.
+        to: SessionStatus,
+    },
+    /// Raised if the update has fewer errors than the session.
+    #[error("session errors decreased from {current} to {errors}")]
+    ErrorsDecreased {
+        /// The current number of errors of the session.
+        current: u64,
+        /// The number of errors of the update.
+        errors: u64,
+    },
+}
+
+/// An error used when parsing `AbnormalMechanism`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid abnormal mechanism")]
+pub struct ParseAbnormalMechanismError;
+
+/// The mechanism that caused an abnormal session.
+///
+/// This is used to compute the ANR rate of a release.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AbnormalMechanism {
+    /// The application was not responding while in the foreground.
+    AnrForeground,
+    /// The application was not responding while in the background.
+    AnrBackground,
+}
+
+impl AbnormalMechanism {
+    /// Determines the abnormal mechanism from an ANR event.
+    ///
+    /// Returns `None` if none of the exceptions in the event were reported by
+    /// an ANR mechanism.
+    pub fn from_event(event: &Event<'_>) -> Option<AbnormalMechanism> {
+        let exception = event.exception.iter().find(|exc| {
+            exc.mechanism
+                .as_ref()
+                .is_some_and(|mechanism| is_anr_mechanism(&mechanism.ty))
+        })?;
+
+        let background = exception
+            .value
+            .as_deref()
+            .is_some_and(|value| value.starts_with("Background ANR"));
+
+        Some(if background {
+            AbnormalMechanism::AnrBackground
+        } else {
+            AbnormalMechanism::AnrForeground
+        })
+    }
+}
+
+/// Mechanism types used by SDKs to report application-not-responding errors.
+fn is_anr_mechanism(ty: &str) -> bool {
+    ty.eq_ignore_ascii_case("anr") || ty == "AppExitInfo"
+}
+
+impl str::FromStr for AbnormalMechanism {
+    type Err = ParseAbnormalMechanismError;
+
+    fn from_str(string: &str) -> Result<AbnormalMechanism, Self::Err> {
+        Ok(match string {
+            "anr_foreground" => AbnormalMechanism::AnrForeground,
+            "anr_background" => AbnormalMechanism::AnrBackground,
+            _ => return Err(ParseAbnormalMechanismError),
+        })
+    }
+}
+
+impl fmt::Display for AbnormalMechanism {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AbnormalMechanism::AnrForeground => write!(f, "anr_foreground"),
+            AbnormalMechanism::AnrBackground => write!(f, "anr_background"),
+        }
+    }
+}
+
+impl_str_serde!(AbnormalMechanism);
+
+/// Additional attributes of a session.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct SessionAttributes {
+    /// The release the session belongs to.
+    #[serde(default)]
+    pub release: String,
+    /// The environment the session belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    /// The ip address of the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_address: Option<IpAddress>,
+    /// The user agent of the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+/// An update to a session sent by the SDK.
+///
+/// Fields that are `None` are left unchanged.  See `Session::update`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SessionUpdate {
+    /// The logical clock of the update.
+    pub sequence: Option<u64>,
+    /// The timestamp of the update.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The duration of the session so far.
+    pub duration: Option<Duration>,
+    /// The new status of the session.
+    pub status: Option<SessionStatus>,
+    /// The total number of errors that occurred during the session.
+    pub errors: Option<u64>,
+    /// The mechanism that caused the session to be abnormal.
+    pub abnormal_mechanism: Option<AbnormalMechanism>,
+}
+
+/// Represents a single session.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Session {
+    /// The unique ID of the session.
+    #[serde(rename = "sid")]
+    #[cfg_attr(feature = "clock", serde(default = "crate::utils::random_uuid"))]
+    pub session_id: Uuid,
+    /// An optional ID identifying the user of the session.
+    #[serde(rename = "did", default, skip_serializing_if = "Option::is_none")]
+    pub distinct_id: Option<String>,
+    /// An optional logical clock of the session updates.
+    #[serde(rename = "seq", default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    /// The timestamp of the session update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The timestamp of when the session started.
+    pub started: DateTime<Utc>,
+    /// Indicates that this is the first update of the session.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub init: bool,
+    /// The optional duration of the session, transmitted in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<Duration>,
+    /// The status of the session.
+    #[serde(default)]
+    pub status: SessionStatus,
+    /// The number of errors that occurred during the session.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub errors: u64,
+    /// The mechanism that caused the session to be abnormal, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abnormal_mechanism: Option<AbnormalMechanism>,
+    /// Additional attributes of the session.
+    #[serde(rename = "attrs")]
+    pub attributes: SessionAttributes,
+}
+
+impl Session {
+    /// Creates a new session for the given release starting now.
+    ///
+    /// This requires the `clock` feature.
+    #[cfg(feature = "clock")]
+    pub fn new<S: Into<String>>(release: S) -> Session {
+        Session {
+            session_id: random_uuid(),
+            distinct_id: None,
+            sequence: None,
+            timestamp: None,
+            started: now(),
+            init: true,
+            duration: None,
+            status: SessionStatus::Ok,
+            errors: 0,
+            abnormal_mechanism: None,
+            attributes: SessionAttributes {
+                release: release.into(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Applies an update to the session.
+    ///
+    /// The update is rejected if it changes the status of a session that
+    /// already ended or lowers its error count, in which case the session is
+    /// left unchanged.  Applying an update clears `init`, since only the
+    /// first update of a session is the initial one.
+    pub fn update(&mut self, update: SessionUpdate) -> Result<(), SessionUpdateError> {
+        if let Some(status) = update.status {
+            if !self.status.can_transition_to(status) {
+                return Err(SessionUpdateError::InvalidTransition {
+                    from: self.status,
+                    to: status,
+                });
+            }
+        }
+        if let Some(errors) = update.errors {
+            if errors < self.errors {
+                return Err(SessionUpdateError::ErrorsDecreased {
+                    current: self.errors,
+                    errors,
+                });
+            }
+        }
+
+        self.init = false;
+        if let Some(sequence) = update.sequence {
+            self.sequence = Some(sequence);
+        }
+        if let Some(timestamp) = update.timestamp {
+            self.timestamp = Some(timestamp);
+        }
+        if let Some(duration) = update.duration {
+            self.duration = Some(duration);
+        }
+        if let Some(status) = update.status {
+            self.status = status;
+        }
+        if let Some(errors) = update.errors {
+            self.errors = errors;
+        }
+        if let Some(mechanism) = update.abnormal_mechanism {
+            self.abnormal_mechanism = Some(mechanism);
+        }
+        Ok(())
+    }
+
+    /// Creates a `session` envelope item.
+    pub fn to_envelope_item(&self) -> EnvelopeItem {
+        // serializing a session cannot fail
+        let payload = serde_json::to_vec(self).unwrap_or_default();
+        EnvelopeItem::new(ItemType::Session, payload)
+    }
+
+    /// Records the abnormal mechanism if the given event is an ANR event.
+    ///
+    /// Returns `true` if the event was an ANR event.
+    pub fn record_anr_event(&mut self, event: &Event<'_>) -> bool {
+        match AbnormalMechanism::from_event(event) {
+            Some(mechanism) => {
+                self.abnormal_mechanism = Some(mechanism);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// An error raised when a session cannot be added to `SessionAggregates`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("session attributes do not match the aggregates")]
+pub struct SessionAggregateError;
+
+/// The counts of sessions that started in the same hour and, if set, belong
+/// to the same distinct id.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionAggregateItem {
+    /// The start of the hour the sessions started in.
+    pub started: DateTime<Utc>,
+    /// The distinct id of the sessions, if any.
+    #[serde(rename = "did", default, skip_serializing_if = "Option::is_none")]
+    pub distinct_id: Option<String>,
+    /// The number of sessions that exited without errors.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub exited: u64,
+    /// The number of sessions that exited with errors.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub errored: u64,
+    /// The number of sessions that terminated abnormally.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub abnormal: u64,
+    /// The number of sessions that crashed.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub crashed: u64,
+}
+
+impl SessionAggregateItem {
+    /// Creates a bucket without any sessions.
+    pub fn new(started: DateTime<Utc>, distinct_id: Option<String>) -> SessionAggregateItem {
+        SessionAggregateItem {
+            started,
+            distinct_id,
+            exited: 0,
+            errored: 0,
+            abnormal: 0,
+            crashed: 0,
+        }
+    }
+
+    /// Returns the total number of sessions in the bucket.
+    pub fn total(&self) -> u64 {
+        self.exited
+            .saturating_add(self.errored)
+            .saturating_add(self.abnormal)
+            .saturating_add(self.crashed)
+    }
+
+    /// Returns the key the buckets are ordered by.
+    fn key(&self) -> (DateTime<Utc>, Option<&str>) {
+        (self.started, self.distinct_id.as_deref())
+    }
+}
+
+/// Pre-aggregated sessions of a single release and environment.
+///
+/// This is sent instead of individual sessions by SDKs for server
+/// applications, where a session is a single request.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionAggregates {
+    /// The buckets of sessions, ordered by start time and distinct id.
+    pub aggregates: Vec<SessionAggregateItem>,
+    /// The attributes shared by all sessions.
+    #[serde(rename = "attrs")]
+    pub attributes: SessionAttributes,
+}
+
+impl SessionAggregates {
+    /// Creates empty aggregates for sessions with the given attributes.
+    pub fn new(attributes: SessionAttributes) -> SessionAggregates {
+        SessionAggregates {
+            aggregates: Vec::new(),
+            attributes,
+        }
+    }
+
+    /// Creates a `sessions` envelope item.
+    pub fn to_envelope_item(&self) -> EnvelopeItem {
+        // serializing session aggregates cannot fail
+        let payload = serde_json::to_vec(self).unwrap_or_default();
+        EnvelopeItem::new(ItemType::Sessions, payload)
+    }
+
+    /// Adds a session to the bucket of the hour it started in and its
+    /// distinct id.
+    ///
+    /// Sessions with status `ok` or `exited` count as `errored` if they had
+    /// errors and as `exited` otherwise.  Fails if the release or environment
+    /// of the session differs from the aggregates.
+    ///
+    /// The buckets are sorted first, so `aggregates` may be modified in any
+    /// order between calls.
+    pub fn push(&mut self, session: &Session) -> Result<(), SessionAggregateError> {
+        if session.attributes.release != self.attributes.release
+            || session.attributes.environment != self.attributes.environment
+        {
+            return Err(SessionAggregateError);
+        }
+
+        let started = session
+            .started
+            .duration_trunc(chrono::Duration::hours(1))
+            .unwrap_or(session.started);
+        let key = (started, session.distinct_id.as_deref());
+        self.aggregates.sort_by(|a, b| a.key().cmp(&b.key()));
+        let index = match self
+            .aggregates
+            .binary_search_by(|item| item.key().cmp(&key))
+        {
+            Ok(index) => index,
+            Err(index) => {
+                let item = SessionAggregateItem::new(started, session.distinct_id.clone());
+                self.aggregates.insert(index, item);
+                index
+            }
+        };
+
+        let item = &mut self.aggregates[index];
+        let count = match session.status {
+            SessionStatus::Ok | SessionStatus::Exited if session.errors > 0 => &mut item.errored,
+            SessionStatus::Ok | SessionStatus::Exited => &mut item.exited,
+            SessionStatus::Abnormal => &mut item.abnormal,
+            SessionStatus::Crashed => &mut item.crashed,
+        };
+        *count = count.saturating_add(1);
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+//! Helpers for tunneling envelopes through a custom endpoint.
+//!
+//! A tunnel is an endpoint on the customer's own server that receives
+//! envelopes from SDKs and forwards them to Sentry, so that requests are not
+//! blocked by ad blockers.  SDKs configured with a tunnel send the dsn in the
+//! envelope headers.  `Tunnel` parses such envelopes, checks that the dsn
+//! belongs to a project the tunnel forwards for and returns the url to
+//! forward the envelope to.
+//!
+//! ```rust
+//! use sentry_types::protocol::tunnel::Tunnel;
+//!
+//! let tunnel = Tunnel::new(vec!["https://public@sentry.example.com/42".parse().unwrap()]);
+//! let request = tunnel
+//!     .accept(b"{\"dsn\":\"https://public@sentry.example.com/42\"}\n")
+//!     .unwrap();
+//! assert_eq!(
+//!     request.upstream_url().as_str(),
+//!     "https://sentry.example.com/api/42/envelope/"
+//! );
+//! ```
+use thiserror::Error;
+use url::Url;
+
+use crate::dsn::{Dsn, ParseDsnError};
+use crate::protocol::envelope::{Envelope, ParseEnvelopeError};
+
+/// An error raised when a tunnel rejects an envelope.
+#[derive(Debug, Error)]
+pub enum TunnelError {
+    /// Raised if the request body is not a valid envelope.
+    #[error("invalid envelope")]
+    InvalidEnvelope(#[from] ParseEnvelopeError),
+    /// Raised if the envelope headers do not contain a dsn.
+    #[error("missing dsn in envelope headers")]
+    MissingDsn,
+    /// Raised if the dsn in the envelope headers is invalid.
+    #[error("invalid dsn in envelope headers")]
+    InvalidDsn(#[source] ParseDsnError),
+    /// Raised if the dsn does not belong to a project of the tunnel.
+    #[error("dsn is not allowed by the tunnel")]
+    DsnNotAllowed,
+}
+
+/// Accepts envelopes for a fixed set of projects.
+///
+/// A dsn is allowed if it has the origin, path and project id of one of the
+/// dsns the tunnel was created with.  The public key is not compared, so
+/// envelopes sent with any key of an allowed project are accepted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tunnel {
+    allowed: Vec<Dsn>,
+}
+
+impl Tunnel {
+    /// Creates a tunnel that forwards envelopes of the given projects.
+    pub fn new(allowed: Vec<Dsn>) -> Tunnel {
+        Tunnel { allowed }
+    }
+
+    /// Returns the dsns of the projects the tunnel forwards for.
+    pub fn allowed(&self) -> &[Dsn] {
+        &self.allowed
+    }
+
+    /// Returns true if the tunnel forwards envelopes sent with `dsn`.
+    pub fn is_allowed(&self, dsn: &Dsn) -> bool {
+        self.allowed.iter().any(|allowed| {
+            allowed.project_id() == dsn.project_id()
+                && allowed.path() == dsn.path()
+                && allowed.origin() == dsn.origin()
+        })
+    }
+
+    /// Parses the body of a tunnel request and validates its dsn.
+    pub fn accept(&self, bytes: &[u8]) -> Result<TunnelRequest, TunnelError> {
+        let request = TunnelRequest::from_slice(bytes)?;
+        if !self.is_allowed(request.dsn()) {
+            return Err(TunnelError::DsnNotAllowed);
+        }
+        Ok(request)
+    }
+}
+
+/// An envelope received by a tunnel together with its dsn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TunnelRequest {
+    dsn: Dsn,
+    envelope: Envelope,
+}
+
+impl TunnelRequest {
+    /// Parses the body of a tunnel request.
+    ///
+    /// This only checks that the envelope carries a valid dsn.  Use
+    /// `Tunnel::accept` to also check that the dsn is allowed.
+    pub fn from_slice(bytes: &[u8]) -> Result<TunnelRequest, TunnelError> {
+        let envelope = Envelope::from_slice(bytes)?;
+        let dsn = envelope
+            .headers()
+            .dsn()
+            .ok_or(TunnelError::MissingDsn)?
+            .map_err(TunnelError::InvalidDsn)?;
+        Ok(TunnelRequest { dsn, envelope })
+    }
+
+    /// Returns the dsn of the envelope.
+    pub fn dsn(&self) -> &Dsn {
+        &self.dsn
+    }
+
+    /// Returns the envelope.
+    pub fn envelope(&self) -> &Envelope {
+        &self.envelope
+    }
+
+    /// Consumes the request and returns the envelope.
+    pub fn into_envelope(self) -> Envelope {
+        self.envelope
+    }
+
+    /// Returns the url the envelope is forwarded to.
+    pub fn upstream_url(&self) -> Url {
+        self.dsn.envelope_api_url()
+    }
+
+    /// Replaces the dsn of the envelope.
+    ///
+    /// Use this to forward envelopes to a different Sentry server or project
+    /// than the SDK was configured with, such as an internal Relay.
+    pub fn rewrite_dsn(&mut self, dsn: Dsn) {
+        self.envelope.headers_mut().set_dsn(&dsn);
+        self.dsn = dsn;
+    }
+
+    /// Serializes the envelope for forwarding.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.envelope.to_vec()
+    }
+}
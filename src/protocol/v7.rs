@@ -8,21 +8,32 @@
 
 use std::borrow::Cow;
 use std::cmp;
+use std::collections::{HashSet, VecDeque};
+use std::convert::{Infallible, TryFrom};
 use std::fmt;
 use std::iter::FromIterator;
+use std::mem;
 use std::net::{AddrParseError, IpAddr};
 use std::ops;
 use std::str;
+use std::sync::Arc;
 
-use ::debugid::DebugId;
+use ::debugid::{CodeId, DebugId};
 use chrono::{DateTime, Utc};
+use sentry_types_core::hex;
 use serde::Serializer;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use url::Url;
 use uuid::Uuid;
 
-use crate::utils::ts_seconds_float;
+use crate::intern::ThinString;
+use crate::protocol::extra;
+use crate::utils::{
+    from_json_slice, truncate_string, ts_seconds_float, ts_seconds_float_opt, write_canonical_json,
+};
+#[cfg(feature = "clock")]
+use crate::utils::{now, random_uuid};
 
 /// An arbitrary (JSON) value.
 pub mod value {
@@ -36,12 +47,21 @@ pub mod map {
 
 /// Represents a debug ID.
 pub mod debugid {
-    pub use debugid::{BreakpadFormat, DebugId, ParseDebugIdError};
+    pub use debugid::{BreakpadFormat, CodeId, DebugId, ParseCodeIdError, ParseDebugIdError};
 }
 
 /// An arbitrary (JSON) value.
+///
+/// This is used for JSON that is passed through unchanged, such as the
+/// flattened `other` maps, meta data and processing errors.  Data supplied by
+/// users is held in `extra::Value` instead.
 pub use self::value::Value;
 
+pub use sentry_types_core::{
+    EventId, Level, ParseEventIdError, ParseLevelError, ParseSpanIdError, ParseTraceIdError,
+    SpanId, TraceId,
+};
+
 /// The internally useed map type.
 pub use self::map::Map;
 
@@ -51,21 +71,79 @@ pub use self::map::Map;
 /// arbitrary other fields. All other fields will be collected into `Values::data` when
 /// deserializing and re-serialized in the same place. The shorthand array notation is always
 /// reserialized as object.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
 pub struct Values<T> {
     /// The values of the collection.
     pub values: Vec<T>,
+    /// Additional arbitrary fields next to the values.
+    #[serde(flatten)]
+    pub data: Map<String, extra::Value>,
 }
 
 impl<T> Values<T> {
     /// Creates an empty values struct.
     pub fn new() -> Values<T> {
-        Values { values: Vec::new() }
+        Values {
+            values: Vec::new(),
+            data: Map::new(),
+        }
     }
 
     /// Checks whether this struct is empty in both values and data.
     pub fn is_empty(&self) -> bool {
-        self.values.is_empty()
+        self.values.is_empty() && self.data.is_empty()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Values<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::{self, MapAccess, SeqAccess, Visitor};
+        use std::marker::PhantomData;
+
+        struct ValuesVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for ValuesVisitor<T> {
+            type Value = Values<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an array or an object with values")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Values<T>, A::Error> {
+                // record paths of lenient fields as in the object form
+                lenient::with_segment(format_args!("values"), || {
+                    let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(256));
+                    while let Some(value) = seq.next_element()? {
+                        values.push(value);
+                    }
+                    Ok(values.into())
+                })
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Values<T>, A::Error> {
+                let mut values = None;
+                let mut data = Map::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == "values" {
+                        if values.is_some() {
+                            return Err(de::Error::duplicate_field("values"));
+                        }
+                        values = Some(map.next_value()?);
+                    } else {
+                        data.insert(key, map.next_value()?);
+                    }
+                }
+                Ok(Values {
+                    values: values.unwrap_or_default(),
+                    data,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(ValuesVisitor(PhantomData))
     }
 }
 
@@ -78,7 +156,10 @@ impl<T> Default for Values<T> {
 
 impl<T> From<Vec<T>> for Values<T> {
     fn from(values: Vec<T>) -> Self {
-        Values { values }
+        Values {
+            values,
+            data: Map::new(),
+        }
     }
 }
 
@@ -150,6 +231,93 @@ impl<T> IntoIterator for Values<T> {
     }
 }
 
+/// The maximum number of characters in a logger name.
+pub const MAX_LOGGER_LENGTH: usize = 64;
+/// The maximum number of characters in a culprit.
+pub const MAX_CULPRIT_LENGTH: usize = 200;
+/// The maximum number of characters in a transaction name.
+pub const MAX_TRANSACTION_LENGTH: usize = 200;
+/// The maximum number of characters in a server name.
+pub const MAX_SERVER_NAME_LENGTH: usize = 256;
+/// The maximum number of characters in a release.
+pub const MAX_RELEASE_LENGTH: usize = 200;
+/// The maximum number of characters in a distribution.
+pub const MAX_DIST_LENGTH: usize = 64;
+/// The maximum number of characters in an environment.
+pub const MAX_ENVIRONMENT_LENGTH: usize = 64;
+/// The maximum number of characters in a message.
+pub const MAX_MESSAGE_LENGTH: usize = 8192;
+/// The maximum number of characters in an email address.
+pub const MAX_EMAIL_LENGTH: usize = 75;
+/// The maximum number of characters in identifiers such as user IDs.
+pub const MAX_IDENTIFIER_LENGTH: usize = 128;
+
+/// Truncates a string to the given number of characters.
+///
+/// Returns the meta data describing the truncation if the value was too long.
+pub(crate) fn truncate_value(mut value: String, max_chars: usize) -> (String, Option<Meta>) {
+    let original_length = match truncate_string(&mut value, max_chars) {
+        Some(original_length) => original_length,
+        None => return (value, None),
+    };
+
+    let meta = Meta {
+        remarks: vec![Remark {
+            rule_id: "!limit".into(),
+            ty: RemarkType::Substituted,
+            range: Some((0, max_chars)),
+        }],
+        original_length: Some(original_length as u64),
+        ..Default::default()
+    };
+
+    (value, Some(meta))
+}
+
+/// Implements setters for optional string fields that enforce a maximum length.
+///
+/// Every setter truncates values exceeding the maximum number of characters
+/// and returns the meta data describing the truncation.  With the `meta`
+/// prefix, the meta data is additionally stored in the `meta` tree of the
+/// type under the name of the field.
+macro_rules! impl_bounded_setters {
+    (meta $ty:ty { $($setter:ident => $field:ident: $max:expr),* $(,)? }) => {
+        impl<'a> $ty {
+            $(
+                #[doc = concat!(
+                    "Sets the `", stringify!($field), "` field, truncating it to `",
+                    stringify!($max), "` characters.\n\n",
+                    "If the value was truncated, the returned meta data is also ",
+                    "recorded in the meta data of the field."
+                )]
+                pub fn $setter<S: Into<String>>(&mut self, value: S) -> Option<Meta> {
+                    let (value, meta) = truncate_value(value.into(), $max);
+                    self.$field = Some(value.into());
+                    *self.meta.get_mut(stringify!($field)) = meta.clone().unwrap_or_default();
+                    meta
+                }
+            )*
+        }
+    };
+    ($ty:ty { $($setter:ident => $field:ident: $max:expr),* $(,)? }) => {
+        impl $ty {
+            $(
+                #[doc = concat!(
+                    "Sets the `", stringify!($field), "` field, truncating it to `",
+                    stringify!($max), "` characters.\n\n",
+                    "Returns the meta data describing the truncation if the value ",
+                    "was too long."
+                )]
+                pub fn $setter<S: Into<String>>(&mut self, value: S) -> Option<Meta> {
+                    let (value, meta) = truncate_value(value.into(), $max);
+                    self.$field = Some(value.into());
+                    meta
+                }
+            )*
+        }
+    };
+}
+
 /// Represents a log entry message.
 ///
 /// A log message is similar to the `message` attribute on the event itself but
@@ -157,6 +325,7 @@ impl<T> IntoIterator for Values<T> {
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
 pub struct LogEntry {
     /// The log message with parameters replaced by `%s`
+    #[serde(default)]
     pub message: String,
     /// Positional parameters to be inserted into the log entry.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -184,7 +353,7 @@ pub struct Frame {
     /// Note that this might also include a class name if that is something the
     /// language natively considers to be part of the stack (for instance in Java).
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub module: Option<String>,
+    pub module: Option<ThinString>,
     /// The name of the package that contains the frame.
     ///
     /// For instance this can be a dylib for native languages, the name of the jar
@@ -193,15 +362,23 @@ pub struct Frame {
     pub package: Option<String>,
     /// The filename (basename only).
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub filename: Option<String>,
+    pub filename: Option<ThinString>,
     /// If known the absolute path.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub abs_path: Option<String>,
     /// The line number if known.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
     pub lineno: Option<u64>,
     /// The column number if known.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
     pub colno: Option<u64>,
     /// The sources of the lines leading up to the current line.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -213,11 +390,15 @@ pub struct Frame {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub post_context: Vec<String>,
     /// In-app indicator.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_bool_opt"
+    )]
     pub in_app: Option<bool>,
     /// Optional local variables.
     #[serde(default, skip_serializing_if = "Map::is_empty")]
-    pub vars: Map<String, Value>,
+    pub vars: Map<ThinString, extra::Value>,
     /// If known the location of the image.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub image_addr: Option<Addr>,
@@ -229,20 +410,130 @@ pub struct Frame {
     pub symbol_addr: Option<Addr>,
 }
 
+/// The default number of source lines around the current line of a frame.
+pub const DEFAULT_CONTEXT_LINES: usize = 5;
+
+/// The maximum number of characters of a source context line.
+///
+/// Longer lines are trimmed to a window around the column.
+pub const MAX_CONTEXT_LINE_LENGTH: usize = 150;
+
+/// The number of characters kept of trimmed source context lines.
+const TRIMMED_CONTEXT_LINE_LENGTH: usize = 140;
+
+/// Trims a source line exceeding `MAX_CONTEXT_LINE_LENGTH` characters.
+///
+/// A window around `colno` (1-based) is kept and cut off parts are marked
+/// with `{snip}`.
+fn trim_context_line(line: &str, colno: Option<u64>) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let len = chars.len();
+    if len <= MAX_CONTEXT_LINE_LENGTH {
+        return line.to_string();
+    }
+
+    let colno = colno.map_or(0, |colno| cmp::min(colno as usize, len));
+    let mut start = colno.saturating_sub(60);
+    if start < 5 {
+        start = 0;
+    }
+    let mut end = cmp::min(start + TRIMMED_CONTEXT_LINE_LENGTH, len);
+    if end > len - 5 {
+        end = len;
+    }
+    if end == len {
+        start = end.saturating_sub(TRIMMED_CONTEXT_LINE_LENGTH);
+    }
+
+    let mut rv = String::new();
+    if start > 0 {
+        rv.push_str("{snip} ");
+    }
+    rv.extend(&chars[start..end]);
+    if end < len {
+        rv.push_str(" {snip}");
+    }
+    rv
+}
+
+impl Frame {
+    /// Sets the source context of the frame from the source of its file.
+    ///
+    /// This sets `lineno` to the given 1-based line number, `context_line`
+    /// to that line, and `pre_context` and `post_context` to at most
+    /// `radius` lines before and after it.  Lines longer than
+    /// `MAX_CONTEXT_LINE_LENGTH` are trimmed around `colno`.
+    ///
+    /// Returns `false` and leaves the frame unchanged if the line does not
+    /// exist in the source.
+    pub fn set_source_context(&mut self, source: &str, lineno: u64, radius: usize) -> bool {
+        let lines: Vec<&str> = source.lines().collect();
+        let index = match usize::try_from(lineno).ok().and_then(|l| l.checked_sub(1)) {
+            Some(index) if index < lines.len() => index,
+            _ => return false,
+        };
+
+        let pre_start = index.saturating_sub(radius);
+        let post_end = cmp::min(index.saturating_add(radius).saturating_add(1), lines.len());
+
+        self.lineno = Some(lineno);
+        self.pre_context = lines[pre_start..index]
+            .iter()
+            .map(|line| trim_context_line(line, None))
+            .collect();
+        self.context_line = Some(trim_context_line(lines[index], self.colno));
+        self.post_context = lines[index + 1..post_end]
+            .iter()
+            .map(|line| trim_context_line(line, None))
+            .collect();
+        true
+    }
+
+    /// Demangles the symbol of the frame.
+    ///
+    /// Rust symbols in the legacy and v0 schemes are demangled without their
+    /// hash suffix, and C++ symbols in the Itanium scheme with their
+    /// arguments.  Returns `None` if the frame has no symbol or it is not
+    /// mangled in one of these schemes.
+    ///
+    /// This requires the `demangle` feature.
+    #[cfg(feature = "demangle")]
+    pub fn demangled_function(&self) -> Option<String> {
+        let symbol = self.symbol.as_deref()?;
+        if let Ok(demangled) = rustc_demangle::try_demangle(symbol) {
+            return Some(format!("{:#}", demangled));
+        }
+
+        let options = cpp_demangle::DemangleOptions::default();
+        cpp_demangle::Symbol::new(symbol)
+            .ok()?
+            .demangle(&options)
+            .ok()
+    }
+}
+
 /// Represents template debug info.
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
 pub struct TemplateInfo {
     /// The filename (basename only).
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub filename: Option<String>,
+    pub filename: Option<ThinString>,
     /// If known the absolute path.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub abs_path: Option<String>,
     /// The line number if known.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
     pub lineno: Option<u64>,
     /// The column number if known.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
     pub colno: Option<u64>,
     /// The sources of the lines leading up to the current line.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -259,14 +550,14 @@ pub struct TemplateInfo {
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct Stacktrace {
     /// The list of frames in the stacktrace.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub frames: Vec<Frame>,
     /// Optionally a segment of frames removed (`start`, `end`).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub frames_omitted: Option<(u64, u64)>,
     /// Optional register values of the thread.
-    #[serde(default, skip_serializing_if = "Map::is_empty")]
-    pub registers: Map<String, RegVal>,
+    #[serde(default, skip_serializing_if = "Registers::is_empty")]
+    pub registers: Registers,
 }
 
 impl Stacktrace {
@@ -282,6 +573,172 @@ impl Stacktrace {
             })
         }
     }
+
+    /// Parses a backtrace in the text format of the Rust standard library.
+    ///
+    /// Both the short and the full format (`RUST_BACKTRACE=full`) are
+    /// supported.  The hash suffix of symbols is removed for `function`, and
+    /// the file location is split into `abs_path`, `filename`, `lineno` and
+    /// `colno`.  Lines that are not part of a frame are ignored.  The frames
+    /// are reversed to put the innermost frame last.
+    ///
+    /// Returns `None` if the backtrace contains no frames.
+    pub fn from_backtrace_str(backtrace: &str) -> Option<Stacktrace> {
+        let mut frames: Vec<Frame> = Vec::new();
+
+        for line in backtrace.lines() {
+            let line = line.trim();
+            if let Some(location) = line.strip_prefix("at ") {
+                if let Some(frame) = frames.last_mut() {
+                    set_backtrace_location(frame, location);
+                }
+                continue;
+            }
+
+            let rest = match line.split_once(": ") {
+                Some((index, rest)) if index.parse::<u64>().is_ok() => rest,
+                _ => continue,
+            };
+
+            let (addr, symbol) = match rest.trim_start().split_once(" - ") {
+                Some((addr, symbol)) if addr.starts_with("0x") => {
+                    (u64::from_str_radix(&addr[2..], 16).ok(), symbol)
+                }
+                _ => (None, rest.trim()),
+            };
+
+            frames.push(Frame {
+                function: Some(strip_symbol_hash(symbol).to_string()),
+                symbol: Some(symbol.to_string()),
+                instruction_addr: addr.map(Addr),
+                ..Default::default()
+            });
+        }
+
+        Stacktrace::from_frames_reversed(frames)
+    }
+
+    /// Sets the source context of frames from in-memory sources.
+    ///
+    /// For every frame with a line number, `lookup` is called with the
+    /// absolute path and then the filename of the frame until it returns the
+    /// source of the file.  The context is set with `DEFAULT_CONTEXT_LINES`
+    /// lines around the line number, see `Frame::set_source_context`.
+    ///
+    /// Returns the number of frames that were updated.
+    pub fn apply_source_context<'s, F>(&mut self, lookup: F) -> usize
+    where
+        F: Fn(&str) -> Option<&'s str>,
+    {
+        let mut updated = 0;
+        for frame in &mut self.frames {
+            let lineno = match frame.lineno {
+                Some(lineno) => lineno,
+                None => continue,
+            };
+
+            let source = [frame.abs_path.as_deref(), frame.filename.as_deref()]
+                .iter()
+                .flatten()
+                .find_map(|path| lookup(path));
+            if let Some(source) = source {
+                if frame.set_source_context(source, lineno, DEFAULT_CONTEXT_LINES) {
+                    updated += 1;
+                }
+            }
+        }
+        updated
+    }
+
+    /// Fills in missing functions of frames by demangling their symbols.
+    ///
+    /// See `Frame::demangled_function` for the supported schemes.  Returns the
+    /// number of frames that were updated.
+    ///
+    /// This requires the `demangle` feature.
+    #[cfg(feature = "demangle")]
+    pub fn demangle_functions(&mut self) -> usize {
+        let mut updated = 0;
+        for frame in &mut self.frames {
+            if frame.function.is_none() {
+                frame.function = frame.demangled_function();
+                updated += frame.function.is_some() as usize;
+            }
+        }
+        updated
+    }
+
+    /// Sets `in_app` on frames by matching prefixes.
+    ///
+    /// Frames whose module, package or absolute path starts with one of the
+    /// `in_app_include` prefixes are marked as in-app.  Otherwise, frames
+    /// matching one of the `in_app_exclude` prefixes are marked as not
+    /// in-app.  Frames that already have `in_app` set are left unchanged.
+    pub fn mark_in_app(&mut self, in_app_include: &[&str], in_app_exclude: &[&str]) {
+        for frame in &mut self.frames {
+            if frame.in_app.is_some() {
+                continue;
+            }
+
+            let matches = |prefixes: &[&str]| {
+                [
+                    frame.module.as_deref(),
+                    frame.package.as_deref(),
+                    frame.abs_path.as_deref(),
+                ]
+                .iter()
+                .flatten()
+                .any(|value| prefixes.iter().any(|prefix| value.starts_with(prefix)))
+            };
+
+            if matches(in_app_include) {
+                frame.in_app = Some(true);
+            } else if matches(in_app_exclude) {
+                frame.in_app = Some(false);
+            }
+        }
+    }
+}
+
+impl<'a> From<&'a std::backtrace::Backtrace> for Stacktrace {
+    /// Converts a captured backtrace, which is empty if it was not captured.
+    fn from(backtrace: &'a std::backtrace::Backtrace) -> Stacktrace {
+        Stacktrace::from_backtrace_str(&backtrace.to_string()).unwrap_or_default()
+    }
+}
+
+/// Removes the `::h0123456789abcdef` hash suffix of Rust symbols.
+fn strip_symbol_hash(symbol: &str) -> &str {
+    match symbol.rsplit_once("::h") {
+        Some((function, hash))
+            if hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()) =>
+        {
+            function
+        }
+        _ => symbol,
+    }
+}
+
+/// Sets the file location of a frame from a `path:line:column` string.
+fn set_backtrace_location(frame: &mut Frame, location: &str) {
+    let mut path = location;
+    let mut numbers = Vec::new();
+    while numbers.len() < 2 {
+        match path.rsplit_once(':') {
+            Some((rest, number)) if number.parse::<u64>().is_ok() => {
+                numbers.push(number.parse().ok());
+                path = rest;
+            }
+            _ => break,
+        }
+    }
+    numbers.reverse();
+
+    let filename = path.rsplit(&['/', '\\'][..]).next().unwrap_or(path);
+    frame.filename = Some(filename.into());
+    frame.abs_path = Some(path.to_string());
+    frame.lineno = numbers.first().copied().flatten();
+    frame.colno = numbers.get(1).copied().flatten();
 }
 
 /// Represents a thread id.
@@ -394,9 +851,9 @@ impl<T> From<*mut T> for Addr {
     }
 }
 
-impl Into<u64> for Addr {
-    fn into(self) -> u64 {
-        self.0
+impl From<Addr> for u64 {
+    fn from(addr: Addr) -> u64 {
+        addr.0
     }
 }
 
@@ -446,9 +903,98 @@ impl<T> From<*mut T> for RegVal {
     }
 }
 
-impl Into<u64> for RegVal {
-    fn into(self) -> u64 {
-        self.0
+impl From<RegVal> for u64 {
+    fn from(addr: RegVal) -> u64 {
+        addr.0
+    }
+}
+
+/// Names of registers holding the instruction pointer on common architectures.
+const INSTRUCTION_POINTER_REGISTERS: &[&str] = &["pc", "rip", "eip", "ip"];
+
+/// Register values by register name.
+///
+/// Values are parsed from numbers as well as hex or decimal strings, and are
+/// always serialized as `0x` prefixed hex strings.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Registers(pub Map<String, RegVal>);
+
+impl Registers {
+    /// Creates an empty register map.
+    pub fn new() -> Registers {
+        Registers(Map::new())
+    }
+
+    /// Returns true if there are no register values.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the value of the instruction pointer register, if present.
+    ///
+    /// This looks for the `pc`, `rip`, `eip` and `ip` registers.
+    pub fn instruction_pointer(&self) -> Option<RegVal> {
+        INSTRUCTION_POINTER_REGISTERS
+            .iter()
+            .find_map(|name| self.0.get(*name).copied())
+    }
+}
+
+impl From<Map<String, RegVal>> for Registers {
+    fn from(registers: Map<String, RegVal>) -> Registers {
+        Registers(registers)
+    }
+}
+
+impl<K: Into<String>, V: Into<RegVal>> FromIterator<(K, V)> for Registers {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Registers {
+        Registers(
+            iter.into_iter()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl<K: Into<String>, V: Into<RegVal>> Extend<(K, V)> for Registers {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.0.extend(
+            iter.into_iter()
+                .map(|(name, value)| (name.into(), value.into())),
+        )
+    }
+}
+
+impl<'a> IntoIterator for &'a Registers {
+    type Item = <&'a Map<String, RegVal> as IntoIterator>::Item;
+    type IntoIter = <&'a Map<String, RegVal> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl IntoIterator for Registers {
+    type Item = <Map<String, RegVal> as IntoIterator>::Item;
+    type IntoIter = <Map<String, RegVal> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl ops::Deref for Registers {
+    type Target = Map<String, RegVal>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl ops::DerefMut for Registers {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
     }
 }
 
@@ -469,33 +1015,110 @@ pub struct Thread {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub raw_stacktrace: Option<Stacktrace>,
     /// True if this is the crashed thread.
-    #[serde(default, skip_serializing_if = "is_false")]
+    #[serde(
+        default,
+        skip_serializing_if = "is_false",
+        deserialize_with = "lenient::deserialize_bool"
+    )]
     pub crashed: bool,
     /// Indicates that the thread was not suspended when the
     /// event was created.
-    #[serde(default, skip_serializing_if = "is_false")]
+    #[serde(
+        default,
+        skip_serializing_if = "is_false",
+        deserialize_with = "lenient::deserialize_bool"
+    )]
     pub current: bool,
 }
 
+impl Thread {
+    /// Returns the register values of the thread.
+    ///
+    /// Registers are stored on the stacktrace, falling back to the raw
+    /// stacktrace.  Returns `None` if neither carries registers.
+    pub fn registers(&self) -> Option<&Registers> {
+        self.stacktrace
+            .iter()
+            .chain(self.raw_stacktrace.iter())
+            .map(|stacktrace| &stacktrace.registers)
+            .find(|registers| !registers.is_empty())
+    }
+}
+
 /// POSIX signal with optional extended data.
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
 pub struct CError {
     /// The error code as specified by ISO C99, POSIX.1-2001 or POSIX.1-2008.
+    #[serde(default)]
     pub number: i32,
     /// Optional name of the errno constant.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 }
 
+impl CError {
+    /// Returns the name of an errno constant, such as `EACCES`.
+    ///
+    /// Only the numbers shared by Linux, Darwin and Windows are known.
+    pub fn name_for(number: i32) -> Option<&'static str> {
+        Some(match number {
+            1 => "EPERM",
+            2 => "ENOENT",
+            3 => "ESRCH",
+            4 => "EINTR",
+            5 => "EIO",
+            6 => "ENXIO",
+            7 => "E2BIG",
+            8 => "ENOEXEC",
+            9 => "EBADF",
+            10 => "ECHILD",
+            12 => "ENOMEM",
+            13 => "EACCES",
+            14 => "EFAULT",
+            15 => "ENOTBLK",
+            16 => "EBUSY",
+            17 => "EEXIST",
+            18 => "EXDEV",
+            19 => "ENODEV",
+            20 => "ENOTDIR",
+            21 => "EISDIR",
+            22 => "EINVAL",
+            23 => "ENFILE",
+            24 => "EMFILE",
+            25 => "ENOTTY",
+            26 => "ETXTBSY",
+            27 => "EFBIG",
+            28 => "ENOSPC",
+            29 => "ESPIPE",
+            30 => "EROFS",
+            31 => "EMLINK",
+            32 => "EPIPE",
+            33 => "EDOM",
+            34 => "ERANGE",
+            _ => return None,
+        })
+    }
+
+    /// Sets the name from the number if it is missing.
+    pub fn fill_name(&mut self) {
+        if self.name.is_none() {
+            self.name = CError::name_for(self.number).map(Into::into);
+        }
+    }
+}
+
 impl From<i32> for CError {
     fn from(number: i32) -> CError {
-        CError { number, name: None }
+        CError {
+            number,
+            name: CError::name_for(number).map(Into::into),
+        }
     }
 }
 
-impl Into<i32> for CError {
-    fn into(self) -> i32 {
-        self.number
+impl From<CError> for i32 {
+    fn from(value: CError) -> i32 {
+        value.number
     }
 }
 
@@ -503,20 +1126,53 @@ impl Into<i32> for CError {
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
 pub struct MachException {
     /// The mach exception type.
+    #[serde(default)]
     pub exception: i32,
     /// The mach exception code.
+    #[serde(default)]
     pub code: u64,
     /// The mach exception subcode.
+    #[serde(default)]
     pub subcode: u64,
     /// Optional name of the mach exception.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 }
 
+impl MachException {
+    /// Returns the name of a mach exception type, such as `EXC_BAD_ACCESS`.
+    pub fn name_for(exception: i32) -> Option<&'static str> {
+        Some(match exception {
+            1 => "EXC_BAD_ACCESS",
+            2 => "EXC_BAD_INSTRUCTION",
+            3 => "EXC_ARITHMETIC",
+            4 => "EXC_EMULATION",
+            5 => "EXC_SOFTWARE",
+            6 => "EXC_BREAKPOINT",
+            7 => "EXC_SYSCALL",
+            8 => "EXC_MACH_SYSCALL",
+            9 => "EXC_RPC_ALERT",
+            10 => "EXC_CRASH",
+            11 => "EXC_RESOURCE",
+            12 => "EXC_GUARD",
+            13 => "EXC_CORPSE_NOTIFY",
+            _ => return None,
+        })
+    }
+
+    /// Sets the name from the exception type if it is missing.
+    pub fn fill_name(&mut self) {
+        if self.name.is_none() {
+            self.name = MachException::name_for(self.exception).map(Into::into);
+        }
+    }
+}
+
 /// POSIX signal with optional extended data.
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
 pub struct PosixSignal {
     /// The POSIX signal number.
+    #[serde(default)]
     pub number: i32,
     /// An optional signal code present on Apple systems.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -529,32 +1185,104 @@ pub struct PosixSignal {
     pub code_name: Option<String>,
 }
 
+impl PosixSignal {
+    /// Returns the name of a signal, such as `SIGSEGV`.
+    ///
+    /// Only the numbers shared by Linux and Darwin are known.
+    pub fn name_for(number: i32) -> Option<&'static str> {
+        Some(match number {
+            1 => "SIGHUP",
+            2 => "SIGINT",
+            3 => "SIGQUIT",
+            4 => "SIGILL",
+            5 => "SIGTRAP",
+            6 => "SIGABRT",
+            8 => "SIGFPE",
+            9 => "SIGKILL",
+            11 => "SIGSEGV",
+            13 => "SIGPIPE",
+            14 => "SIGALRM",
+            15 => "SIGTERM",
+            _ => return None,
+        })
+    }
+
+    /// Returns the name of a signal code, such as `SEGV_MAPERR`.
+    ///
+    /// Signal codes are only sent by Apple systems, so the Darwin values are
+    /// used.
+    pub fn code_name_for(number: i32, code: i32) -> Option<&'static str> {
+        Some(match (number, code) {
+            (4, 1) => "ILL_ILLOPC",
+            (4, 2) => "ILL_ILLTRP",
+            (4, 3) => "ILL_PRVOPC",
+            (4, 4) => "ILL_ILLOPN",
+            (4, 5) => "ILL_ILLADR",
+            (4, 6) => "ILL_PRVREG",
+            (4, 7) => "ILL_COPROC",
+            (4, 8) => "ILL_BADSTK",
+            (8, 1) => "FPE_FLTDIV",
+            (8, 2) => "FPE_FLTOVF",
+            (8, 3) => "FPE_FLTUND",
+            (8, 4) => "FPE_FLTRES",
+            (8, 5) => "FPE_FLTINV",
+            (8, 6) => "FPE_FLTSUB",
+            (8, 7) => "FPE_INTDIV",
+            (8, 8) => "FPE_INTOVF",
+            (10, 1) => "BUS_ADRALN",
+            (10, 2) => "BUS_ADRERR",
+            (10, 3) => "BUS_OBJERR",
+            (11, 0) => "SEGV_NOOP",
+            (11, 1) => "SEGV_MAPERR",
+            (11, 2) => "SEGV_ACCERR",
+            _ => return None,
+        })
+    }
+
+    /// Sets the signal and code names from their numbers if they are missing.
+    pub fn fill_names(&mut self) {
+        if self.name.is_none() {
+            self.name = PosixSignal::name_for(self.number).map(Into::into);
+        }
+        if self.code_name.is_none() {
+            self.code_name = self
+                .code
+                .and_then(|code| PosixSignal::code_name_for(self.number, code))
+                .map(Into::into);
+        }
+    }
+}
+
 impl From<i32> for PosixSignal {
     fn from(number: i32) -> PosixSignal {
-        PosixSignal {
+        let mut signal = PosixSignal {
             number,
             code: None,
             name: None,
             code_name: None,
-        }
+        };
+        signal.fill_names();
+        signal
     }
 }
 
 impl From<(i32, i32)> for PosixSignal {
     fn from(tuple: (i32, i32)) -> PosixSignal {
         let (number, code) = tuple;
-        PosixSignal {
+        let mut signal = PosixSignal {
             number,
             code: Some(code),
             name: None,
             code_name: None,
-        }
+        };
+        signal.fill_names();
+        signal
     }
 }
 
-impl Into<i32> for PosixSignal {
-    fn into(self) -> i32 {
-        self.number
+impl From<PosixSignal> for i32 {
+    fn from(value: PosixSignal) -> i32 {
+        value.number
     }
 }
 
@@ -576,13 +1304,26 @@ impl MechanismMeta {
     fn is_empty(&self) -> bool {
         self.errno.is_none() && self.signal.is_none() && self.mach_exception.is_none()
     }
+
+    /// Fills in missing names of the errno, signal and mach exception.
+    pub fn fill_names(&mut self) {
+        if let Some(errno) = &mut self.errno {
+            errno.fill_name();
+        }
+        if let Some(signal) = &mut self.signal {
+            signal.fill_names();
+        }
+        if let Some(mach_exception) = &mut self.mach_exception {
+            mach_exception.fill_name();
+        }
+    }
 }
 
 /// Represents a single exception.
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct Mechanism {
     /// The mechanism type identifier.
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default)]
     pub ty: String,
     /// Human readable detail description.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -591,14 +1332,47 @@ pub struct Mechanism {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub help_link: Option<Url>,
     /// An optional flag indicating whether this exception was handled.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_bool_opt"
+    )]
     pub handled: Option<bool>,
     /// An optional flag indicating a synthetic exception.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_bool_opt"
+    )]
     pub synthetic: Option<bool>,
+    /// An optional source of the exception within its parent, such as
+    /// `__context__` or `errors[0]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// An optional flag indicating that the exception is an exception group.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_bool_opt"
+    )]
+    pub is_exception_group: Option<bool>,
+    /// An optional identifier of the exception, unique within the event.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub exception_id: Option<u64>,
+    /// An optional identifier of the parent exception in an exception group.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub parent_id: Option<u64>,
     /// Additional attributes depending on the mechanism type.
     #[serde(default, skip_serializing_if = "Map::is_empty")]
-    pub data: Map<String, Value>,
+    pub data: Map<String, extra::Value>,
     /// Operating system or runtime meta information.
     #[serde(default, skip_serializing_if = "MechanismMeta::is_empty")]
     pub meta: MechanismMeta,
@@ -608,10 +1382,10 @@ pub struct Mechanism {
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct Exception {
     /// The type of the exception.
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default)]
     pub ty: String,
     /// The optional value of the exception.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
     /// An optional module for this exception.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -630,650 +1404,3821 @@ pub struct Exception {
     pub mechanism: Option<Mechanism>,
 }
 
-/// An error used when parsing `Level`.
-#[derive(Debug, Error)]
-#[error("invalid level")]
-pub struct ParseLevelError;
-
-/// Represents the level of severity of an event or breadcrumb.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum Level {
-    /// Indicates very spammy debug information.
-    Debug,
-    /// Informational messages.
-    Info,
-    /// A warning.
-    Warning,
-    /// An error.
-    Error,
-    /// Similar to error but indicates a critical event that usually causes a shutdown.
-    Fatal,
+impl_bounded_setters!(Exception {
+    set_value => value: MAX_MESSAGE_LENGTH,
+});
+
+/// The platform an event was created on.
+///
+/// Sentry only accepts a fixed set of platform identifiers.  Identifiers not
+/// known to this crate are kept in `Platform::Unknown`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Platform {
+    /// ActionScript 3.
+    As3,
+    /// C.
+    C,
+    /// ColdFusion.
+    Cfml,
+    /// Apple platforms (iOS, macOS, ...).
+    Cocoa,
+    /// C# and other .NET languages.
+    CSharp,
+    /// Elixir.
+    Elixir,
+    /// Go.
+    Go,
+    /// Groovy.
+    Groovy,
+    /// Haskell.
+    Haskell,
+    /// Java and other JVM languages.
+    Java,
+    /// JavaScript in the browser.
+    JavaScript,
+    /// Native code (C, C++, Rust, ...).
+    Native,
+    /// Node.js.
+    Node,
+    /// Objective-C.
+    ObjC,
+    /// Perl.
+    Perl,
+    /// PHP.
+    Php,
+    /// Python.
+    Python,
+    /// Ruby.
+    Ruby,
+    /// Any other platform.
+    #[default]
+    Other,
+    /// A platform identifier not known to this crate.
+    Unknown(String),
 }
 
-impl Default for Level {
-    fn default() -> Level {
-        Level::Info
+impl Platform {
+    /// Returns the identifier of the platform.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            Platform::As3 => "as3",
+            Platform::C => "c",
+            Platform::Cfml => "cfml",
+            Platform::Cocoa => "cocoa",
+            Platform::CSharp => "csharp",
+            Platform::Elixir => "elixir",
+            Platform::Go => "go",
+            Platform::Groovy => "groovy",
+            Platform::Haskell => "haskell",
+            Platform::Java => "java",
+            Platform::JavaScript => "javascript",
+            Platform::Native => "native",
+            Platform::Node => "node",
+            Platform::ObjC => "objc",
+            Platform::Perl => "perl",
+            Platform::Php => "php",
+            Platform::Python => "python",
+            Platform::Ruby => "ruby",
+            Platform::Other => "other",
+            Platform::Unknown(ref other) => other,
+        }
     }
-}
 
-impl str::FromStr for Level {
-    type Err = ParseLevelError;
+    /// A quick way to check if the platform is `other`.
+    pub fn is_other(&self) -> bool {
+        *self == Platform::Other
+    }
 
-    fn from_str(string: &str) -> Result<Level, Self::Err> {
-        Ok(match string {
-            "debug" => Level::Debug,
-            "info" | "log" => Level::Info,
-            "warning" => Level::Warning,
-            "error" => Level::Error,
-            "fatal" => Level::Fatal,
-            _ => return Err(ParseLevelError),
-        })
+    /// Returns `true` if the platform is one accepted by Sentry.
+    pub fn is_known(&self) -> bool {
+        !matches!(*self, Platform::Unknown(..))
     }
 }
 
-impl fmt::Display for Level {
+impl fmt::Display for Platform {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            Level::Debug => write!(f, "debug"),
-            Level::Info => write!(f, "info"),
-            Level::Warning => write!(f, "warning"),
-            Level::Error => write!(f, "error"),
-            Level::Fatal => write!(f, "fatal"),
-        }
+        write!(f, "{}", self.as_str())
     }
 }
 
-impl Level {
-    /// A quick way to check if the level is `debug`.
-    pub fn is_debug(&self) -> bool {
-        *self == Level::Debug
-    }
+impl str::FromStr for Platform {
+    type Err = Infallible;
 
-    /// A quick way to check if the level is `info`.
-    pub fn is_info(&self) -> bool {
-        *self == Level::Info
+    fn from_str(string: &str) -> Result<Platform, Self::Err> {
+        Ok(match string {
+            "as3" => Platform::As3,
+            "c" => Platform::C,
+            "cfml" => Platform::Cfml,
+            "cocoa" => Platform::Cocoa,
+            "csharp" => Platform::CSharp,
+            "elixir" => Platform::Elixir,
+            "go" => Platform::Go,
+            "groovy" => Platform::Groovy,
+            "haskell" => Platform::Haskell,
+            "java" => Platform::Java,
+            "javascript" => Platform::JavaScript,
+            "native" => Platform::Native,
+            "node" => Platform::Node,
+            "objc" => Platform::ObjC,
+            "perl" => Platform::Perl,
+            "php" => Platform::Php,
+            "python" => Platform::Python,
+            "ruby" => Platform::Ruby,
+            "other" => Platform::Other,
+            other => Platform::Unknown(other.to_string()),
+        })
     }
+}
 
-    /// A quick way to check if the level is `warning`.
-    pub fn is_warning(&self) -> bool {
-        *self == Level::Warning
+impl<'a> From<&'a str> for Platform {
+    fn from(string: &'a str) -> Platform {
+        match string.parse() {
+            Ok(platform) => platform,
+            Err(never) => match never {},
+        }
     }
+}
 
-    /// A quick way to check if the level is `error`.
-    pub fn is_error(&self) -> bool {
-        *self == Level::Error
+impl PartialEq<str> for Platform {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
     }
+}
 
-    /// A quick way to check if the level is `fatal`.
-    pub fn is_fatal(&self) -> bool {
-        *self == Level::Fatal
+impl<'a> PartialEq<&'a str> for Platform {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
     }
 }
 
-impl_str_serde!(Level);
+impl_str_serde!(Platform);
 
-mod breadcrumb {
-    use super::*;
+/// An error used when parsing `SpanStatus`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid span status")]
+pub struct ParseSpanStatusError;
 
-    pub fn default_timestamp() -> DateTime<Utc> {
-        Utc::now()
+/// The status of a span or trace.
+///
+/// The statuses mirror the canonical gRPC status codes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SpanStatus {
+    /// The operation completed successfully.
+    Ok,
+    /// The operation was cancelled (typically by the caller).
+    Cancelled,
+    /// An unknown error occurred.
+    Unknown,
+    /// The client specified an invalid argument.
+    InvalidArgument,
+    /// The deadline expired before the operation could complete.
+    DeadlineExceeded,
+    /// The requested entity was not found.
+    NotFound,
+    /// The entity the client attempted to create already exists.
+    AlreadyExists,
+    /// The caller does not have permission to execute the operation.
+    PermissionDenied,
+    /// Some resource has been exhausted, for instance a rate limit.
+    ResourceExhausted,
+    /// The system is not in a state required for the operation.
+    FailedPrecondition,
+    /// The operation was aborted, typically due to a concurrency issue.
+    Aborted,
+    /// The operation was attempted past the valid range.
+    OutOfRange,
+    /// The operation is not implemented or not supported.
+    Unimplemented,
+    /// An internal error occurred.
+    InternalError,
+    /// The service is currently unavailable.
+    Unavailable,
+    /// Unrecoverable data loss or corruption occurred.
+    DataLoss,
+    /// The request does not have valid authentication credentials.
+    Unauthenticated,
+}
+
+impl SpanStatus {
+    /// Determines the span status from an HTTP response status code.
+    pub fn from_http_status(status: u16) -> SpanStatus {
+        match status {
+            100..=399 => SpanStatus::Ok,
+            401 => SpanStatus::Unauthenticated,
+            403 => SpanStatus::PermissionDenied,
+            404 => SpanStatus::NotFound,
+            409 => SpanStatus::AlreadyExists,
+            413 => SpanStatus::FailedPrecondition,
+            429 => SpanStatus::ResourceExhausted,
+            499 => SpanStatus::Cancelled,
+            400..=499 => SpanStatus::InvalidArgument,
+            501 => SpanStatus::Unimplemented,
+            503 => SpanStatus::Unavailable,
+            504 => SpanStatus::DeadlineExceeded,
+            500..=599 => SpanStatus::InternalError,
+            _ => SpanStatus::Unknown,
+        }
     }
 
-    pub fn default_type() -> String {
-        "default".to_string()
+    /// Determines the span status from a gRPC status code.
+    ///
+    /// Returns `None` for codes that are not defined by gRPC.
+    pub fn from_grpc_code(code: i32) -> Option<SpanStatus> {
+        Some(match code {
+            0 => SpanStatus::Ok,
+            1 => SpanStatus::Cancelled,
+            2 => SpanStatus::Unknown,
+            3 => SpanStatus::InvalidArgument,
+            4 => SpanStatus::DeadlineExceeded,
+            5 => SpanStatus::NotFound,
+            6 => SpanStatus::AlreadyExists,
+            7 => SpanStatus::PermissionDenied,
+            8 => SpanStatus::ResourceExhausted,
+            9 => SpanStatus::FailedPrecondition,
+            10 => SpanStatus::Aborted,
+            11 => SpanStatus::OutOfRange,
+            12 => SpanStatus::Unimplemented,
+            13 => SpanStatus::InternalError,
+            14 => SpanStatus::Unavailable,
+            15 => SpanStatus::DataLoss,
+            16 => SpanStatus::Unauthenticated,
+            _ => return None,
+        })
     }
 
-    pub fn is_default_type(ty: &str) -> bool {
-        ty == "default"
+    /// Returns the gRPC status code of this status.
+    pub fn grpc_code(self) -> i32 {
+        match self {
+            SpanStatus::Ok => 0,
+            SpanStatus::Cancelled => 1,
+            SpanStatus::Unknown => 2,
+            SpanStatus::InvalidArgument => 3,
+            SpanStatus::DeadlineExceeded => 4,
+            SpanStatus::NotFound => 5,
+            SpanStatus::AlreadyExists => 6,
+            SpanStatus::PermissionDenied => 7,
+            SpanStatus::ResourceExhausted => 8,
+            SpanStatus::FailedPrecondition => 9,
+            SpanStatus::Aborted => 10,
+            SpanStatus::OutOfRange => 11,
+            SpanStatus::Unimplemented => 12,
+            SpanStatus::InternalError => 13,
+            SpanStatus::Unavailable => 14,
+            SpanStatus::DataLoss => 15,
+            SpanStatus::Unauthenticated => 16,
+        }
     }
 
-    pub fn default_level() -> Level {
-        Level::Info
+    /// A quick way to check if the status is `ok`.
+    pub fn is_ok(&self) -> bool {
+        *self == SpanStatus::Ok
     }
 }
 
-/// Represents a single breadcrumb.
+impl str::FromStr for SpanStatus {
+    type Err = ParseSpanStatusError;
+
+    fn from_str(string: &str) -> Result<SpanStatus, Self::Err> {
+        Ok(match string {
+            "ok" => SpanStatus::Ok,
+            "cancelled" => SpanStatus::Cancelled,
+            "unknown" | "unknown_error" => SpanStatus::Unknown,
+            "invalid_argument" => SpanStatus::InvalidArgument,
+            "deadline_exceeded" => SpanStatus::DeadlineExceeded,
+            "not_found" => SpanStatus::NotFound,
+            "already_exists" => SpanStatus::AlreadyExists,
+            "permission_denied" => SpanStatus::PermissionDenied,
+            "resource_exhausted" => SpanStatus::ResourceExhausted,
+            "failed_precondition" => SpanStatus::FailedPrecondition,
+            "aborted" => SpanStatus::Aborted,
+            "out_of_range" => SpanStatus::OutOfRange,
+            "unimplemented" => SpanStatus::Unimplemented,
+            "internal_error" | "internal" => SpanStatus::InternalError,
+            "unavailable" => SpanStatus::Unavailable,
+            "data_loss" => SpanStatus::DataLoss,
+            "unauthenticated" => SpanStatus::Unauthenticated,
+            _ => return Err(ParseSpanStatusError),
+        })
+    }
+}
+
+impl fmt::Display for SpanStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SpanStatus::Ok => write!(f, "ok"),
+            SpanStatus::Cancelled => write!(f, "cancelled"),
+            SpanStatus::Unknown => write!(f, "unknown"),
+            SpanStatus::InvalidArgument => write!(f, "invalid_argument"),
+            SpanStatus::DeadlineExceeded => write!(f, "deadline_exceeded"),
+            SpanStatus::NotFound => write!(f, "not_found"),
+            SpanStatus::AlreadyExists => write!(f, "already_exists"),
+            SpanStatus::PermissionDenied => write!(f, "permission_denied"),
+            SpanStatus::ResourceExhausted => write!(f, "resource_exhausted"),
+            SpanStatus::FailedPrecondition => write!(f, "failed_precondition"),
+            SpanStatus::Aborted => write!(f, "aborted"),
+            SpanStatus::OutOfRange => write!(f, "out_of_range"),
+            SpanStatus::Unimplemented => write!(f, "unimplemented"),
+            SpanStatus::InternalError => write!(f, "internal_error"),
+            SpanStatus::Unavailable => write!(f, "unavailable"),
+            SpanStatus::DataLoss => write!(f, "data_loss"),
+            SpanStatus::Unauthenticated => write!(f, "unauthenticated"),
+        }
+    }
+}
+
+impl_str_serde!(SpanStatus);
+
+/// Represents a tracing span of a transaction.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct Breadcrumb {
-    /// The timestamp of the breadcrumb.  This is required.
-    #[serde(default = "breadcrumb::default_timestamp", with = "ts_seconds_float")]
-    pub timestamp: DateTime<Utc>,
-    /// The type of the breadcrumb.
-    #[serde(
-        rename = "type",
-        default = "breadcrumb::default_type",
-        skip_serializing_if = "breadcrumb::is_default_type"
-    )]
-    pub ty: String,
-    /// The optional category of the breadcrumb.
+pub struct Span {
+    /// The ID of the span.
+    pub span_id: SpanId,
+    /// The ID of the trace the span belongs to.
+    pub trace_id: TraceId,
+    /// The ID of the parent span, if any.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub category: Option<String>,
-    /// The non optional level of the breadcrumb.  It
-    /// defaults to info.
+    pub parent_span_id: Option<SpanId>,
+    /// The operation of the span, such as `db.query`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub op: Option<String>,
+    /// A human readable description of the span.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The status of the span.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<SpanStatus>,
+    /// The timestamp of when the span started.
+    #[serde(with = "ts_seconds_float")]
+    pub start_timestamp: DateTime<Utc>,
+    /// The timestamp of when the span finished.
     #[serde(
-        default = "breadcrumb::default_level",
-        skip_serializing_if = "Level::is_info"
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "ts_seconds_float_opt"
     )]
-    pub level: Level,
-    /// An optional human readbale message for the breadcrumb.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub message: Option<String>,
-    /// Arbitrary breadcrumb data that should be send along.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Optional tags of the span.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub tags: Map<ThinString, String>,
+    /// Optional arbitrary data of the span.
     #[serde(default, skip_serializing_if = "Map::is_empty")]
-    pub data: Map<String, Value>,
+    pub data: Map<ThinString, extra::Value>,
 }
 
-impl Default for Breadcrumb {
-    fn default() -> Breadcrumb {
-        Breadcrumb {
-            timestamp: breadcrumb::default_timestamp(),
-            ty: breadcrumb::default_type(),
-            category: Default::default(),
-            level: breadcrumb::default_level(),
-            message: Default::default(),
-            data: Default::default(),
-        }
-    }
-}
+/// An error used when parsing `SentryTrace`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid sentry-trace header")]
+pub struct ParseSentryTraceError;
 
-/// An IP address, either IPv4, IPv6 or Auto.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash)]
-pub enum IpAddress {
-    /// The IP address needs to be infered from the user's context.
-    Auto,
-    /// The exact given IP address (v4 or v6).
-    Exact(IpAddr),
+/// An error used when parsing a W3C `traceparent` header.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid traceparent header")]
+pub struct ParseTraceParentError;
+
+/// The trace propagation information of the `sentry-trace` header.
+///
+/// The header is formatted as `{trace_id}-{span_id}-{sampled}` where the
+/// sampled flag is optional.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SentryTrace {
+    /// The ID of the trace.
+    pub trace_id: TraceId,
+    /// The ID of the parent span.
+    pub span_id: SpanId,
+    /// The sampling decision, if one was made.
+    pub sampled: Option<bool>,
 }
 
-impl PartialEq<IpAddr> for IpAddress {
-    fn eq(&self, other: &IpAddr) -> bool {
-        match *self {
-            IpAddress::Auto => false,
-            IpAddress::Exact(ref addr) => addr == other,
+impl SentryTrace {
+    /// Creates new trace propagation information.
+    pub fn new(trace_id: TraceId, span_id: SpanId, sampled: Option<bool>) -> SentryTrace {
+        SentryTrace {
+            trace_id,
+            span_id,
+            sampled,
         }
     }
-}
 
-impl cmp::PartialOrd<IpAddr> for IpAddress {
-    fn partial_cmp(&self, other: &IpAddr) -> Option<cmp::Ordering> {
-        match *self {
-            IpAddress::Auto => None,
-            IpAddress::Exact(ref addr) => addr.partial_cmp(other),
+    /// Parses a W3C `traceparent` header.
+    ///
+    /// A set sampled flag maps to `Some(true)`.  Since an unset flag does not
+    /// tell whether the caller made a sampling decision, it maps to `None`.
+    pub fn from_traceparent(header: &str) -> Result<SentryTrace, ParseTraceParentError> {
+        let mut parts = header.trim().split('-');
+        let version = parts.next().ok_or(ParseTraceParentError)?;
+        let trace_id = parts.next().ok_or(ParseTraceParentError)?;
+        let span_id = parts.next().ok_or(ParseTraceParentError)?;
+        let flags = parts.next().ok_or(ParseTraceParentError)?;
+
+        // future versions may append fields, version 00 must not
+        let mut version_byte = [0; 1];
+        hex::decode(version, &mut version_byte).ok_or(ParseTraceParentError)?;
+        if version_byte[0] == 0xff || (version_byte[0] == 0 && parts.next().is_some()) {
+            return Err(ParseTraceParentError);
         }
+
+        let is_lower_hex = |s: &str| !s.bytes().any(|b| b.is_ascii_uppercase());
+        if !is_lower_hex(trace_id) || !is_lower_hex(span_id) {
+            return Err(ParseTraceParentError);
+        }
+
+        let trace_id: TraceId = trace_id.parse().map_err(|_| ParseTraceParentError)?;
+        let span_id: SpanId = span_id.parse().map_err(|_| ParseTraceParentError)?;
+        if trace_id == TraceId::default() || span_id == SpanId::default() {
+            return Err(ParseTraceParentError);
+        }
+
+        let mut flags_byte = [0; 1];
+        hex::decode(flags, &mut flags_byte).ok_or(ParseTraceParentError)?;
+        let sampled = if flags_byte[0] & 0x01 != 0 {
+            Some(true)
+        } else {
+            None
+        };
+
+        Ok(SentryTrace::new(trace_id, span_id, sampled))
     }
-}
 
-impl Default for IpAddress {
-    fn default() -> IpAddress {
-        IpAddress::Auto
+    /// Formats the trace propagation information as W3C `traceparent` header.
+    pub fn to_traceparent(&self) -> String {
+        let flags = if self.sampled == Some(true) { 1 } else { 0 };
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, flags)
     }
 }
 
-impl fmt::Display for IpAddress {
+impl fmt::Display for SentryTrace {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            IpAddress::Auto => write!(f, "{{{{auto}}}}"),
-            IpAddress::Exact(ref addr) => write!(f, "{}", addr),
+        write!(f, "{}-{}", self.trace_id, self.span_id)?;
+        match self.sampled {
+            Some(true) => write!(f, "-1"),
+            Some(false) => write!(f, "-0"),
+            None => Ok(()),
         }
     }
 }
 
-impl From<IpAddr> for IpAddress {
-    fn from(addr: IpAddr) -> IpAddress {
-        IpAddress::Exact(addr)
+impl str::FromStr for SentryTrace {
+    type Err = ParseSentryTraceError;
+
+    fn from_str(string: &str) -> Result<SentryTrace, Self::Err> {
+        let mut parts = string.trim().splitn(3, '-');
+        let trace_id = parts
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or(ParseSentryTraceError)?;
+        let span_id = parts
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or(ParseSentryTraceError)?;
+        let sampled = match parts.next() {
+            None => None,
+            Some("1") => Some(true),
+            Some("0") => Some(false),
+            Some(_) => return Err(ParseSentryTraceError),
+        };
+        Ok(SentryTrace::new(trace_id, span_id, sampled))
     }
 }
 
-impl str::FromStr for IpAddress {
-    type Err = AddrParseError;
+impl_str_serde!(SentryTrace);
 
-    fn from_str(string: &str) -> Result<IpAddress, AddrParseError> {
-        match string {
-            "{{auto}}" => Ok(IpAddress::Auto),
-            other => other.parse().map(IpAddress::Exact),
-        }
-    }
-}
+/// An error used when parsing `TraceState`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid tracestate header")]
+pub struct ParseTraceStateError;
 
-impl_str_serde!(IpAddress);
+/// The maximum number of entries in a W3C `tracestate` header.
+pub const MAX_TRACE_STATE_ENTRIES: usize = 32;
 
-/// Represents user info.
-#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
-pub struct User {
-    /// The ID of the user.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub id: Option<String>,
-    /// The email address of the user.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub email: Option<String>,
-    /// The remote ip address of the user.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub ip_address: Option<IpAddress>,
-    /// A human readable username of the user.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub username: Option<String>,
-    /// Additional arbitrary fields for forwards compatibility.
-    #[serde(flatten)]
-    pub other: Map<String, Value>,
+/// Represents a W3C `tracestate` header.
+///
+/// The header is a list of vendor specific `key=value` entries where the most
+/// recently updated entry comes first.  Sentry uses the `sentry` key as well
+/// as multi-tenant keys ending in `@sentry`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct TraceState {
+    entries: Vec<(String, String)>,
 }
 
-/// Represents http request data.
-#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
-pub struct Request {
-    /// The current URL of the request.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub url: Option<Url>,
-    /// The HTTP request method.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub method: Option<String>,
-    /// Optionally some associated request data (human readable)
-    // XXX: this makes absolutely no sense because of unicode
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub data: Option<String>,
-    /// Optionally the encoded query string.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub query_string: Option<String>,
-    /// An encoded cookie string if available.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub cookies: Option<String>,
-    /// HTTP request headers.
-    #[serde(default, skip_serializing_if = "Map::is_empty")]
-    pub headers: Map<String, String>,
-    /// Optionally a CGI/WSGI etc. environment dictionary.
-    #[serde(default, skip_serializing_if = "Map::is_empty")]
-    pub env: Map<String, String>,
-}
+impl TraceState {
+    /// Creates an empty trace state.
+    pub fn new() -> TraceState {
+        Default::default()
+    }
 
-/// Holds information about the system SDK.
-///
-/// This is relevant for iOS and other platforms that have a system
-/// SDK.  Not to be confused with the client SDK.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct SystemSdkInfo {
-    /// The internal name of the SDK
-    pub sdk_name: String,
-    /// the major version of the SDK as integer or 0
-    pub version_major: u32,
-    /// the minor version of the SDK as integer or 0
-    pub version_minor: u32,
-    /// the patch version of the SDK as integer or 0
-    pub version_patchlevel: u32,
+    /// Returns the entries of the trace state.
+    pub fn entries(&self) -> &[(String, String)] {
+        &self.entries
+    }
+
+    /// Returns the value of the given key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the value of the `sentry` entry.
+    pub fn sentry(&self) -> Option<&str> {
+        self.get("sentry")
+    }
+
+    /// Inserts or updates an entry, moving it to the front.
+    pub fn insert<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) {
+        let key = key.into();
+        self.entries.retain(|(k, _)| *k != key);
+        self.entries.insert(0, (key, value.into()));
+        self.entries.truncate(MAX_TRACE_STATE_ENTRIES);
+    }
+
+    /// Inserts or updates the `sentry` entry, moving it to the front.
+    pub fn set_sentry<V: Into<String>>(&mut self, value: V) {
+        self.insert("sentry", value);
+    }
+
+    /// Replaces all Sentry entries with those of another trace state.
+    ///
+    /// Entries of other vendors are retained.  The Sentry entries of `other`
+    /// are placed at the front in their original order.
+    pub fn merge_sentry(&mut self, other: &TraceState) {
+        let mut entries: Vec<_> = other
+            .entries
+            .iter()
+            .filter(|(key, _)| is_sentry_key(key))
+            .cloned()
+            .collect();
+        entries.extend(
+            self.entries
+                .drain(..)
+                .filter(|(key, _)| !is_sentry_key(key)),
+        );
+        entries.truncate(MAX_TRACE_STATE_ENTRIES);
+        self.entries = entries;
+    }
 }
 
-/// Represents a debug image.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(rename_all = "snake_case", tag = "type")]
-pub enum DebugImage {
-    /// Apple debug images (machos).  This is currently also used for
-    /// non apple platforms with similar debug setups.
-    Apple(AppleDebugImage),
-    /// Symbolic (new style) debug infos.
-    Symbolic(SymbolicDebugImage),
-    /// A reference to a proguard debug file.
-    Proguard(ProguardDebugImage),
+fn is_sentry_key(key: &str) -> bool {
+    key == "sentry" || key.ends_with("@sentry")
 }
 
-impl DebugImage {
-    /// Returns the name of the type on sentry.
-    pub fn type_name(&self) -> &str {
-        match *self {
-            DebugImage::Apple(..) => "apple",
-            DebugImage::Symbolic(..) => "symbolic",
-            DebugImage::Proguard(..) => "proguard",
+impl fmt::Display for TraceState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, (key, value)) in self.entries.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}={}", key, value)?;
         }
+        Ok(())
     }
 }
 
-macro_rules! into_debug_image {
-    ($kind:ident, $ty:ty) => {
-        impl From<$ty> for DebugImage {
-            fn from(data: $ty) -> DebugImage {
-                DebugImage::$kind(data)
+impl str::FromStr for TraceState {
+    type Err = ParseTraceStateError;
+
+    fn from_str(string: &str) -> Result<TraceState, Self::Err> {
+        let mut entries: Vec<(String, String)> = Vec::new();
+        for member in string.split(',') {
+            let member = member.trim();
+            if member.is_empty() {
+                continue;
+            }
+
+            let (key, value) = member.split_once('=').ok_or(ParseTraceStateError)?;
+            if key.is_empty() || value.is_empty() || entries.iter().any(|(k, _)| k == key) {
+                return Err(ParseTraceStateError);
             }
+            entries.push((key.to_string(), value.to_string()));
         }
-    };
-}
 
-/// Represents an apple debug image in the debug meta.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct AppleDebugImage {
-    /// The name of the debug image (usually filename)
-    pub name: String,
-    /// The optional CPU architecture of the debug image.
-    pub arch: Option<String>,
-    /// Alternatively a macho cpu type.
-    pub cpu_type: Option<u32>,
-    /// Alternatively a macho cpu subtype.
-    pub cpu_subtype: Option<u32>,
-    /// The starting address of the image.
-    pub image_addr: Addr,
-    /// The size of the image in bytes.
-    pub image_size: u64,
-    /// The address where the image is loaded at runtime.
-    #[serde(default, skip_serializing_if = "Addr::is_null")]
-    pub image_vmaddr: Addr,
-    /// The unique UUID of the image.
-    pub uuid: Uuid,
+        if entries.len() > MAX_TRACE_STATE_ENTRIES {
+            return Err(ParseTraceStateError);
+        }
+        Ok(TraceState { entries })
+    }
 }
 
-/// Represents a symbolic debug image.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct SymbolicDebugImage {
-    /// The name of the debug image (usually filename)
-    pub name: String,
-    /// The optional CPU architecture of the debug image.
-    pub arch: Option<String>,
-    /// The starting address of the image.
-    pub image_addr: Addr,
-    /// The size of the image in bytes.
-    pub image_size: u64,
-    /// The address where the image is loaded at runtime.
-    #[serde(default, skip_serializing_if = "Addr::is_null")]
-    pub image_vmaddr: Addr,
-    /// The unique debug id of the image.
-    pub id: DebugId,
-}
+impl_str_serde!(TraceState);
 
-/// Represents a proguard mapping file reference.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct ProguardDebugImage {
-    /// The UUID of the associated proguard file.
-    pub uuid: Uuid,
-}
+/// Deserialization that tolerates values sent with the wrong JSON type.
+mod lenient {
+    use super::*;
+    use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+    use serde::de::{self, IntoDeserializer};
+    use std::cell::RefCell;
+    use std::marker::PhantomData;
 
-into_debug_image!(Apple, AppleDebugImage);
+    thread_local! {
+        static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+    }
+
+    /// A value that was coerced or dropped by a lenient deserializer.
+    pub struct Record {
+        /// The path of the field, empty if it is not known.
+        pub path: String,
+        /// The value as it was sent.
+        pub value: Value,
+        /// True if the value was dropped instead of coerced.
+        pub dropped: bool,
+        /// Why the value was dropped, if known to the deserializer.
+        pub reason: Option<String>,
+    }
+
+    /// The records and the path of the value being deserialized.
+    #[derive(Default)]
+    struct State {
+        records: Vec<Record>,
+        /// The path joined like `MetaTree::join_path`.
+        path: String,
+        /// The lengths of `path` before each of its segments.
+        segments: Vec<usize>,
+    }
+
+    /// Restores the previous state when dropped, even on panics.
+    struct StateGuard(Option<State>);
+
+    impl Drop for StateGuard {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            STATE.with(|state| *state.borrow_mut() = previous);
+        }
+    }
+
+    /// Runs `f` and returns all values coerced or dropped within it.
+    ///
+    /// Paths are only known for values deserialized through `Tracked`.
+    pub fn collect<R, F: FnOnce() -> R>(f: F) -> (R, Vec<Record>) {
+        let previous = STATE.with(|state| state.borrow_mut().replace(State::default()));
+        let _guard = StateGuard(previous);
+        let rv = f();
+        let state = STATE.with(|state| state.borrow_mut().take());
+        (rv, state.map(|state| state.records).unwrap_or_default())
+    }
+
+    fn push_record(
+        name: Option<&'static str>,
+        value: Value,
+        dropped: bool,
+        reason: Option<String>,
+    ) {
+        STATE.with(|state| {
+            if let Some(ref mut state) = *state.borrow_mut() {
+                let path = match name {
+                    Some(name) if state.path.is_empty() => name.to_string(),
+                    _ => state.path.clone(),
+                };
+                state.records.push(Record {
+                    path,
+                    value,
+                    dropped,
+                    reason,
+                });
+            }
+        });
+    }
+
+    /// Records a coerced or dropped value if `collect` is active.
+    pub fn record(value: Value, dropped: bool) {
+        push_record(None, value, dropped, None);
+    }
+
+    /// Records a value of a named field that was dropped for a reason.
+    ///
+    /// The name is used if the path of the field is not known.
+    pub fn record_invalid(name: &'static str, value: Value, reason: String) {
+        push_record(Some(name), value, true, Some(reason));
+    }
+
+    /// Appends a segment to the current path while `f` runs.
+    pub fn with_segment<R>(segment: fmt::Arguments<'_>, f: impl FnOnce() -> R) -> R {
+        let tracked = STATE.with(|state| match *state.borrow_mut() {
+            Some(ref mut state) => {
+                if !state.segments.is_empty() {
+                    state.path.push('.');
+                }
+                state.segments.push(state.path.len());
+                let start = state.path.len();
+                fmt::write(&mut state.path, segment).ok();
+                // escape like `MetaTree::join_path`
+                if state.path[start..].contains(['.', '\\']) {
+                    let escaped = state.path[start..]
+                        .replace('\\', "\\\\")
+                        .replace('.', "\\.");
+                    state.path.truncate(start);
+                    state.path.push_str(&escaped);
+                }
+                true
+            }
+            None => false,
+        });
+        let rv = f();
+        if tracked {
+            STATE.with(|state| {
+                if let Some(ref mut state) = *state.borrow_mut() {
+                    if let Some(start) = state.segments.pop() {
+                        state.path.truncate(start.saturating_sub(1));
+                    }
+                }
+            });
+        }
+        rv
+    }
+
+    /// Deserializes a value through `Tracked`, continuing its path.
+    ///
+    /// This is used for values that serde buffers before deserializing them,
+    /// such as the variants of internally tagged enums.
+    pub fn tracked<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: de::Deserializer<'de>,
+    {
+        T::deserialize(Tracked(deserializer))
+    }
+
+    /// A deserializer tracking the path of the values within it.
+    ///
+    /// Map keys and sequence indexes are appended to the path of `collect`
+    /// while their values are deserialized, so that lenient deserializers
+    /// can record the field they dropped or coerced.
+    pub struct Tracked<D>(pub D);
+
+    macro_rules! forward_deserialize {
+        ($wrap:path; $($method:ident($($arg:ident: $ty:ty),*),)*) => {
+            $(
+                fn $method<V: de::Visitor<'de>>(
+                    self,
+                    $($arg: $ty,)*
+                    visitor: V,
+                ) -> Result<V::Value, Self::Error> {
+                    self.0.$method($($arg,)* $wrap(visitor))
+                }
+            )*
+        };
+    }
+
+    impl<'de, D: de::Deserializer<'de>> de::Deserializer<'de> for Tracked<D> {
+        type Error = D::Error;
+
+        forward_deserialize! {
+            TrackedVisitor;
+            deserialize_any(), deserialize_bool(), deserialize_i8(), deserialize_i16(),
+            deserialize_i32(), deserialize_i64(), deserialize_i128(), deserialize_u8(),
+            deserialize_u16(), deserialize_u32(), deserialize_u64(), deserialize_u128(),
+            deserialize_f32(), deserialize_f64(), deserialize_char(), deserialize_str(),
+            deserialize_string(), deserialize_bytes(), deserialize_byte_buf(),
+            deserialize_option(), deserialize_unit(),
+            deserialize_unit_struct(name: &'static str),
+            deserialize_newtype_struct(name: &'static str),
+            deserialize_seq(), deserialize_tuple(len: usize),
+            deserialize_tuple_struct(name: &'static str, len: usize),
+            deserialize_map(),
+            deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+            deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+            deserialize_identifier(), deserialize_ignored_any(),
+        }
+
+        fn is_human_readable(&self) -> bool {
+            self.0.is_human_readable()
+        }
+    }
+
+    macro_rules! forward_visit {
+        ($($method:ident($ty:ty),)*) => {
+            $(
+                fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                    self.0.$method(v)
+                }
+            )*
+        };
+    }
+
+    struct TrackedVisitor<V>(V);
+
+    impl<'de, V: de::Visitor<'de>> de::Visitor<'de> for TrackedVisitor<V> {
+        type Value = V::Value;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            self.0.expecting(f)
+        }
+
+        forward_visit! {
+            visit_bool(bool), visit_i8(i8), visit_i16(i16), visit_i32(i32), visit_i64(i64),
+            visit_i128(i128), visit_u8(u8), visit_u16(u16), visit_u32(u32), visit_u64(u64),
+            visit_u128(u128), visit_f32(f32), visit_f64(f64), visit_char(char),
+            visit_str(&str), visit_borrowed_str(&'de str), visit_string(String),
+            visit_bytes(&[u8]), visit_borrowed_bytes(&'de [u8]), visit_byte_buf(Vec<u8>),
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            self.0.visit_none()
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            self.0.visit_unit()
+        }
+
+        fn visit_some<D: de::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+            self.0.visit_some(Tracked(d))
+        }
+
+        fn visit_newtype_struct<D>(self, d: D) -> Result<Self::Value, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            self.0.visit_newtype_struct(Tracked(d))
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            self.0.visit_seq(TrackedSeq {
+                inner: seq,
+                index: 0,
+            })
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            self.0.visit_map(TrackedMap {
+                inner: map,
+                key: String::new(),
+            })
+        }
+
+        fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+            self.0.visit_enum(data)
+        }
+    }
+
+    /// Deserializes a value through `Tracked`.
+    struct TrackedSeed<T>(T);
+
+    impl<'de, T: de::DeserializeSeed<'de>> de::DeserializeSeed<'de> for TrackedSeed<T> {
+        type Value = T::Value;
+
+        fn deserialize<D: de::Deserializer<'de>>(self, d: D) -> Result<T::Value, D::Error> {
+            self.0.deserialize(Tracked(d))
+        }
+    }
+
+    struct TrackedSeq<A> {
+        inner: A,
+        index: usize,
+    }
+
+    impl<'de, A: de::SeqAccess<'de>> de::SeqAccess<'de> for TrackedSeq<A> {
+        type Error = A::Error;
+
+        fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, A::Error>
+        where
+            T: de::DeserializeSeed<'de>,
+        {
+            let inner = &mut self.inner;
+            let rv = with_segment(format_args!("{}", self.index), || {
+                inner.next_element_seed(TrackedSeed(seed))
+            });
+            self.index += 1;
+            rv
+        }
+
+        fn size_hint(&self) -> Option<usize> {
+            self.inner.size_hint()
+        }
+    }
+
+    struct TrackedMap<A> {
+        inner: A,
+        /// The last key, if it was a string or number.
+        key: String,
+    }
+
+    impl<'de, A: de::MapAccess<'de>> de::MapAccess<'de> for TrackedMap<A> {
+        type Error = A::Error;
+
+        fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+        where
+            K: de::DeserializeSeed<'de>,
+        {
+            self.key.clear();
+            self.inner.next_key_seed(KeySeed {
+                inner: seed,
+                key: &mut self.key,
+            })
+        }
+
+        fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, A::Error>
+        where
+            V: de::DeserializeSeed<'de>,
+        {
+            let inner = &mut self.inner;
+            with_segment(format_args!("{}", self.key), || {
+                inner.next_value_seed(TrackedSeed(seed))
+            })
+        }
+
+        fn size_hint(&self) -> Option<usize> {
+            self.inner.size_hint()
+        }
+    }
+
+    /// Deserializes a map key, copying it into `key`.
+    struct KeySeed<'k, T> {
+        inner: T,
+        key: &'k mut String,
+    }
+
+    impl<'de, 'k, T: de::DeserializeSeed<'de>> de::DeserializeSeed<'de> for KeySeed<'k, T> {
+        type Value = T::Value;
+
+        fn deserialize<D: de::Deserializer<'de>>(self, d: D) -> Result<T::Value, D::Error> {
+            self.inner.deserialize(KeyDeserializer {
+                inner: d,
+                key: self.key,
+            })
+        }
+    }
+
+    struct KeyDeserializer<'k, D> {
+        inner: D,
+        key: &'k mut String,
+    }
+
+    macro_rules! forward_key_deserialize {
+        ($($method:ident($($arg:ident: $ty:ty),*),)*) => {
+            $(
+                fn $method<V: de::Visitor<'de>>(
+                    self,
+                    $($arg: $ty,)*
+                    visitor: V,
+                ) -> Result<V::Value, Self::Error> {
+                    self.inner.$method($($arg,)* KeyVisitor {
+                        inner: visitor,
+                        key: self.key,
+                    })
+                }
+            )*
+        };
+    }
+
+    impl<'de, 'k, D: de::Deserializer<'de>> de::Deserializer<'de> for KeyDeserializer<'k, D> {
+        type Error = D::Error;
+
+        forward_key_deserialize! {
+            deserialize_any(), deserialize_bool(), deserialize_i8(), deserialize_i16(),
+            deserialize_i32(), deserialize_i64(), deserialize_i128(), deserialize_u8(),
+            deserialize_u16(), deserialize_u32(), deserialize_u64(), deserialize_u128(),
+            deserialize_f32(), deserialize_f64(), deserialize_char(), deserialize_str(),
+            deserialize_string(), deserialize_bytes(), deserialize_byte_buf(),
+            deserialize_option(), deserialize_unit(),
+            deserialize_unit_struct(name: &'static str),
+            deserialize_newtype_struct(name: &'static str),
+            deserialize_seq(), deserialize_tuple(len: usize),
+            deserialize_tuple_struct(name: &'static str, len: usize),
+            deserialize_map(),
+            deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+            deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+            deserialize_identifier(), deserialize_ignored_any(),
+        }
+
+        fn is_human_readable(&self) -> bool {
+            self.inner.is_human_readable()
+        }
+    }
+
+    struct KeyVisitor<'k, V> {
+        inner: V,
+        key: &'k mut String,
+    }
+
+    macro_rules! capture_visit {
+        ($($method:ident($ty:ty),)*) => {
+            $(
+                fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                    fmt::write(self.key, format_args!("{}", v)).ok();
+                    self.inner.$method(v)
+                }
+            )*
+        };
+    }
+
+    impl<'de, 'k, V: de::Visitor<'de>> de::Visitor<'de> for KeyVisitor<'k, V> {
+        type Value = V::Value;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            self.inner.expecting(f)
+        }
+
+        capture_visit! {
+            visit_bool(bool), visit_i8(i8), visit_i16(i16), visit_i32(i32), visit_i64(i64),
+            visit_i128(i128), visit_u8(u8), visit_u16(u16), visit_u32(u32), visit_u64(u64),
+            visit_u128(u128), visit_f32(f32), visit_f64(f64), visit_char(char),
+            visit_str(&str), visit_borrowed_str(&'de str), visit_string(String),
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            self.inner.visit_bytes(v)
+        }
+
+        fn visit_borrowed_bytes<E: de::Error>(self, v: &'de [u8]) -> Result<Self::Value, E> {
+            self.inner.visit_borrowed_bytes(v)
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+            self.inner.visit_byte_buf(v)
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            self.inner.visit_none()
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            self.inner.visit_unit()
+        }
+
+        fn visit_some<D: de::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+            self.inner.visit_some(d)
+        }
+
+        fn visit_newtype_struct<D>(self, d: D) -> Result<Self::Value, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            self.inner.visit_newtype_struct(d)
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            self.inner.visit_seq(seq)
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            self.inner.visit_map(map)
+        }
+
+        fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+            self.inner.visit_enum(data)
+        }
+    }
+
+    /// Deserializes a value the lenient deserializers cannot use and drops it.
+    fn drop_map<'de, A: de::MapAccess<'de>>(map: A) -> Result<(), A::Error> {
+        let value = Value::deserialize(MapAccessDeserializer::new(map))?;
+        record(value, true);
+        Ok(())
+    }
+
+    /// Deserializes a value the lenient deserializers cannot use and drops it.
+    fn drop_seq<'de, A: de::SeqAccess<'de>>(seq: A) -> Result<(), A::Error> {
+        let value = Value::deserialize(SeqAccessDeserializer::new(seq))?;
+        record(value, true);
+        Ok(())
+    }
+
+    /// Deserializes optional values from either strings or their JSON form.
+    ///
+    /// Many SDKs send numbers as strings, such as `"lineno": "42"`.
+    /// Surrounding whitespace of strings is ignored.  Values that cannot be
+    /// parsed are dropped instead of failing the entire payload.
+    pub fn deserialize_from_str<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: str::FromStr + de::DeserializeOwned,
+        D: de::Deserializer<'de>,
+    {
+        struct FromStrVisitor<T>(PhantomData<T>);
+
+        impl<T: de::DeserializeOwned> FromStrVisitor<T> {
+            fn convert<'de, V, E>(value: V) -> Option<T>
+            where
+                V: IntoDeserializer<'de, E> + Into<Value> + Clone,
+                E: de::Error,
+            {
+                let rv = T::deserialize(value.clone().into_deserializer()).ok();
+                if rv.is_none() {
+                    record(value.into(), true);
+                }
+                rv
+            }
+        }
+
+        impl<'de, T> de::Visitor<'de> for FromStrVisitor<T>
+        where
+            T: str::FromStr + de::DeserializeOwned,
+        {
+            type Value = Option<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a value or its string representation")
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_some<D: de::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+                d.deserialize_any(self)
+            }
+
+            fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+                Ok(Self::convert::<_, E>(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(Self::convert::<_, E>(v))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(Self::convert::<_, E>(v))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                Ok(Self::convert::<_, E>(v))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                let rv = v.trim().parse().ok();
+                record(v.into(), rv.is_none());
+                Ok(rv)
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                drop_map(map)?;
+                Ok(None)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                drop_seq(seq)?;
+                Ok(None)
+            }
+        }
+
+        deserializer.deserialize_any(FromStrVisitor(PhantomData))
+    }
+
+    /// Deserializes optional booleans from numbers and strings.
+    ///
+    /// Besides JSON booleans, `0` and `1` as well as the strings `"true"`,
+    /// `"false"`, `"yes"`, `"no"`, `"1"` and `"0"` are accepted.  All other
+    /// values are dropped instead of failing the entire payload.
+    pub fn deserialize_bool_opt<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct BoolVisitor;
+
+        impl BoolVisitor {
+            fn convert<V: Into<Value>>(value: V, rv: Option<bool>) -> Option<bool> {
+                record(value.into(), rv.is_none());
+                rv
+            }
+        }
+
+        impl<'de> de::Visitor<'de> for BoolVisitor {
+            type Value = Option<bool>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a boolean, 0, 1, or a boolean string")
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_some<D: de::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+                d.deserialize_any(self)
+            }
+
+            fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+                Ok(Some(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                let rv = match v {
+                    0 => Some(false),
+                    1 => Some(true),
+                    _ => None,
+                };
+                Ok(Self::convert(v, rv))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                let rv = match v {
+                    0 => Some(false),
+                    1 => Some(true),
+                    _ => None,
+                };
+                Ok(Self::convert(v, rv))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                Ok(Self::convert(v, None))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                let rv = match v.trim() {
+                    s if s.eq_ignore_ascii_case("true") || s.eq_ignore_ascii_case("yes") => {
+                        Some(true)
+                    }
+                    s if s.eq_ignore_ascii_case("false") || s.eq_ignore_ascii_case("no") => {
+                        Some(false)
+                    }
+                    "1" => Some(true),
+                    "0" => Some(false),
+                    _ => None,
+                };
+                Ok(Self::convert(v, rv))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                drop_map(map)?;
+                Ok(None)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                drop_seq(seq)?;
+                Ok(None)
+            }
+        }
+
+        deserializer.deserialize_any(BoolVisitor)
+    }
+
+    /// Deserializes booleans like `deserialize_bool_opt`, defaulting to false.
+    pub fn deserialize_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Ok(deserialize_bool_opt(deserializer)?.unwrap_or(false))
+    }
+
+    /// Deserializes a validated name, dropping it if it is invalid.
+    ///
+    /// Numbers are accepted as names.
+    fn deserialize_name<'de, T, E, D, F>(
+        deserializer: D,
+        name: &'static str,
+        new: F,
+    ) -> Result<Option<T>, D::Error>
+    where
+        E: fmt::Display,
+        D: de::Deserializer<'de>,
+        F: FnOnce(String) -> Result<T, E>,
+    {
+        let string = match Option::<Value>::deserialize(deserializer)? {
+            None => return Ok(None),
+            Some(Value::String(string)) => string,
+            Some(Value::Number(number)) => {
+                push_record(Some(name), Value::Number(number.clone()), false, None);
+                number.to_string()
+            }
+            Some(value) => {
+                record_invalid(name, value, "expected a string".into());
+                return Ok(None);
+            }
+        };
+
+        match new(string.clone()) {
+            Ok(value) => Ok(Some(value)),
+            Err(error) => {
+                record_invalid(name, string.into(), error.to_string());
+                Ok(None)
+            }
+        }
+    }
+
+    /// Deserializes a release, dropping it if it is invalid.
+    pub fn deserialize_release<'de, 'a, D>(deserializer: D) -> Result<Option<Release<'a>>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserialize_name(deserializer, "release", Release::new)
+    }
+
+    /// Deserializes an environment, dropping it if it is invalid.
+    pub fn deserialize_environment<'de, 'a, D>(
+        deserializer: D,
+    ) -> Result<Option<Environment<'a>>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserialize_name(deserializer, "environment", Environment::new)
+    }
+}
+
+mod dsc {
+    use super::*;
+
+    /// Serializes optional values as strings, as mandated by the protocol.
+    pub fn serialize_as_str<T: fmt::Display, S: Serializer>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.serialize_some(&value.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+/// The dynamic sampling context of a trace.
+///
+/// This is propagated in the `baggage` HTTP header as `sentry-` prefixed
+/// entries and sent in the `trace` header of envelopes, so that all events in
+/// a trace are sampled consistently.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DynamicSamplingContext {
+    /// The ID of the trace.
+    pub trace_id: TraceId,
+    /// The public key of the DSN of the SDK that started the trace.
+    pub public_key: String,
+    /// The release of the SDK that started the trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<String>,
+    /// The environment of the SDK that started the trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    /// The user segment of the user that started the trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_segment: Option<String>,
+    /// The name of the transaction that started the trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<String>,
+    /// The sample rate of the transaction that started the trace.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "dsc::serialize_as_str",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub sample_rate: Option<f64>,
+    /// Whether the transaction that started the trace was sampled.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "dsc::serialize_as_str",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub sampled: Option<bool>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl DynamicSamplingContext {
+    /// Creates a new sampling context for a trace.
+    pub fn new<S: Into<String>>(trace_id: TraceId, public_key: S) -> DynamicSamplingContext {
+        DynamicSamplingContext {
+            trace_id,
+            public_key: public_key.into(),
+            release: None,
+            environment: None,
+            user_segment: None,
+            transaction: None,
+            sample_rate: None,
+            sampled: None,
+            other: Map::new(),
+        }
+    }
+
+    /// Parses the sampling context from the value of a `baggage` header.
+    ///
+    /// Only `sentry-` prefixed entries are considered.  Returns `None` if the
+    /// trace ID or public key are missing or invalid.
+    pub fn from_baggage(baggage: &str) -> Option<DynamicSamplingContext> {
+        let mut trace_id = None;
+        let mut public_key = None;
+        let mut other = Map::new();
+
+        for entry in baggage.split(',') {
+            // properties after a semicolon are not relevant to sentry
+            let entry = entry.split(';').next().unwrap_or("");
+            let mut kv = entry.splitn(2, '=');
+            let key = match kv.next().unwrap_or("").trim().strip_prefix("sentry-") {
+                Some(key) => key,
+                None => continue,
+            };
+            let value = percent_encoding::percent_decode_str(kv.next().unwrap_or("").trim())
+                .decode_utf8_lossy()
+                .into_owned();
+
+            match key {
+                "trace_id" => trace_id = value.parse().ok(),
+                "public_key" => public_key = Some(value),
+                _ => {
+                    other.insert(key.to_string(), Value::String(value));
+                }
+            }
+        }
+
+        let mut dsc = DynamicSamplingContext::new(trace_id?, public_key?);
+        dsc.release = take_string(&mut other, "release");
+        dsc.environment = take_string(&mut other, "environment");
+        dsc.user_segment = take_string(&mut other, "user_segment");
+        dsc.transaction = take_string(&mut other, "transaction");
+        dsc.sample_rate = take_string(&mut other, "sample_rate").and_then(|v| v.parse().ok());
+        dsc.sampled = take_string(&mut other, "sampled").and_then(|v| v.parse().ok());
+        dsc.other = other;
+        Some(dsc)
+    }
+
+    /// Formats the sampling context as `sentry-` prefixed `baggage` entries.
+    pub fn to_baggage(&self) -> String {
+        let mut entries = vec![
+            ("trace_id", self.trace_id.to_string()),
+            ("public_key", self.public_key.clone()),
+        ];
+        let optional = [
+            ("release", self.release.clone()),
+            ("environment", self.environment.clone()),
+            ("user_segment", self.user_segment.clone()),
+            ("transaction", self.transaction.clone()),
+            ("sample_rate", self.sample_rate.map(|v| v.to_string())),
+            ("sampled", self.sampled.map(|v| v.to_string())),
+        ];
+        entries.extend(
+            optional
+                .iter()
+                .filter_map(|(key, value)| Some((*key, value.clone()?))),
+        );
+        entries.extend(
+            self.other
+                .iter()
+                .filter_map(|(key, value)| Some((key.as_str(), value.as_str()?.to_string()))),
+        );
+
+        entries
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "sentry-{}={}",
+                    key,
+                    percent_encoding::utf8_percent_encode(value, BAGGAGE_ENCODE_SET)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Characters that must be percent-encoded in baggage values.
+const BAGGAGE_ENCODE_SET: &percent_encoding::AsciiSet = &percent_encoding::CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'%')
+    .add(b',')
+    .add(b';')
+    .add(b'\\');
+
+fn take_string(map: &mut Map<String, Value>, key: &str) -> Option<String> {
+    match map.remove(key)? {
+        Value::String(string) => Some(string),
+        _ => None,
+    }
+}
+
+mod breadcrumb {
+    use super::*;
+
+    #[cfg(feature = "clock")]
+    pub fn default_timestamp() -> DateTime<Utc> {
+        now()
+    }
+
+    pub fn default_type() -> String {
+        "default".to_string()
+    }
+
+    pub fn is_default_type(ty: &str) -> bool {
+        ty == "default"
+    }
+
+    pub fn default_level() -> Level {
+        Level::Info
+    }
+}
+
+/// Represents a single breadcrumb.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Breadcrumb {
+    /// The timestamp of the breadcrumb.  This is required.
+    #[serde(with = "ts_seconds_float")]
+    #[cfg_attr(feature = "clock", serde(default = "breadcrumb::default_timestamp"))]
+    pub timestamp: DateTime<Utc>,
+    /// The type of the breadcrumb.
+    #[serde(
+        rename = "type",
+        default = "breadcrumb::default_type",
+        skip_serializing_if = "breadcrumb::is_default_type"
+    )]
+    pub ty: String,
+    /// The optional category of the breadcrumb.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// The non optional level of the breadcrumb.  It
+    /// defaults to info.
+    #[serde(
+        default = "breadcrumb::default_level",
+        skip_serializing_if = "Level::is_info"
+    )]
+    pub level: Level,
+    /// An optional human readbale message for the breadcrumb.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Arbitrary breadcrumb data that should be send along.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub data: Map<ThinString, extra::Value>,
+}
+
+/// Creates a breadcrumb recorded now.
+///
+/// This requires the `clock` feature.
+#[cfg(feature = "clock")]
+impl Default for Breadcrumb {
+    fn default() -> Breadcrumb {
+        Breadcrumb {
+            timestamp: breadcrumb::default_timestamp(),
+            ty: breadcrumb::default_type(),
+            category: Default::default(),
+            level: breadcrumb::default_level(),
+            message: Default::default(),
+            data: Default::default(),
+        }
+    }
+}
+
+impl_bounded_setters!(Breadcrumb {
+    set_message => message: MAX_MESSAGE_LENGTH,
+});
+
+/// Returns for every breadcrumb whether it is the first with its timestamp,
+/// message and category.
+pub(crate) fn unique_breadcrumbs<'b, I>(breadcrumbs: I) -> Vec<bool>
+where
+    I: IntoIterator<Item = &'b Breadcrumb>,
+{
+    let mut seen = HashSet::new();
+    breadcrumbs
+        .into_iter()
+        .map(|b| seen.insert((b.timestamp, b.message.as_deref(), b.category.as_deref())))
+        .collect()
+}
+
+/// The default number of breadcrumbs kept by `Breadcrumbs`.
+pub const DEFAULT_MAX_BREADCRUMBS: usize = 100;
+
+/// A bounded list of breadcrumbs.
+///
+/// Once the capacity is reached, pushing a breadcrumb drops the oldest one.
+/// The number of dropped breadcrumbs is tracked, and recorded in the meta
+/// data of an event by `Event::set_breadcrumbs`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Breadcrumbs {
+    values: VecDeque<Breadcrumb>,
+    capacity: usize,
+    dropped: usize,
+}
+
+impl Breadcrumbs {
+    /// Creates an empty list holding at most `capacity` breadcrumbs.
+    pub fn new(capacity: usize) -> Breadcrumbs {
+        Breadcrumbs {
+            values: VecDeque::with_capacity(capacity.min(DEFAULT_MAX_BREADCRUMBS)),
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    /// Returns the maximum number of breadcrumbs kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of breadcrumbs kept.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if no breadcrumbs are kept.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the number of breadcrumbs dropped so far.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Appends a breadcrumb, dropping the oldest one if the list is full.
+    pub fn push(&mut self, breadcrumb: Breadcrumb) {
+        if self.capacity == 0 {
+            self.dropped += 1;
+            return;
+        }
+
+        if self.values.len() >= self.capacity {
+            self.values.pop_front();
+            self.dropped += 1;
+        }
+        self.values.push_back(breadcrumb);
+    }
+
+    /// Iterates over the breadcrumbs from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = &Breadcrumb> {
+        self.values.iter()
+    }
+
+    /// Sorts the breadcrumbs by timestamp, from oldest to newest.
+    ///
+    /// The sort is stable, so breadcrumbs with the same timestamp keep the
+    /// order they were recorded in.  Recorders on multiple threads may push
+    /// breadcrumbs out of order.
+    pub fn sort_by_timestamp(&mut self) {
+        self.values
+            .make_contiguous()
+            .sort_by_key(|breadcrumb| breadcrumb.timestamp);
+    }
+
+    /// Removes breadcrumbs with the same timestamp, message and category as
+    /// an earlier breadcrumb.
+    ///
+    /// Removed breadcrumbs are not counted as dropped.
+    pub fn dedup(&mut self) {
+        let mut unique = unique_breadcrumbs(&self.values).into_iter();
+        self.values.retain(|_| unique.next().unwrap_or(true));
+    }
+
+    /// Removes all breadcrumbs and resets the dropped count.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.dropped = 0;
+    }
+
+    /// Converts the list into `Values`, discarding the dropped count.
+    pub fn into_values(self) -> Values<Breadcrumb> {
+        Vec::from(self.values).into()
+    }
+}
+
+impl Default for Breadcrumbs {
+    fn default() -> Breadcrumbs {
+        Breadcrumbs::new(DEFAULT_MAX_BREADCRUMBS)
+    }
+}
+
+impl Extend<Breadcrumb> for Breadcrumbs {
+    fn extend<I: IntoIterator<Item = Breadcrumb>>(&mut self, iter: I) {
+        for breadcrumb in iter {
+            self.push(breadcrumb);
+        }
+    }
+}
+
+impl FromIterator<Breadcrumb> for Breadcrumbs {
+    /// Collects breadcrumbs into a list with the default capacity.
+    fn from_iter<I: IntoIterator<Item = Breadcrumb>>(iter: I) -> Breadcrumbs {
+        let mut breadcrumbs = Breadcrumbs::default();
+        breadcrumbs.extend(iter);
+        breadcrumbs
+    }
+}
+
+impl<'a> IntoIterator for &'a Breadcrumbs {
+    type Item = &'a Breadcrumb;
+    type IntoIter = std::collections::vec_deque::Iter<'a, Breadcrumb>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.iter()
+    }
+}
+
+impl IntoIterator for Breadcrumbs {
+    type Item = Breadcrumb;
+    type IntoIter = std::collections::vec_deque::IntoIter<Breadcrumb>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+impl From<Breadcrumbs> for Values<Breadcrumb> {
+    fn from(breadcrumbs: Breadcrumbs) -> Values<Breadcrumb> {
+        breadcrumbs.into_values()
+    }
+}
+
+/// An IP address, either IPv4, IPv6 or Auto.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash, Default)]
+pub enum IpAddress {
+    /// The IP address needs to be infered from the user's context.
+    #[default]
+    Auto,
+    /// The exact given IP address (v4 or v6).
+    Exact(IpAddr),
+}
+
+impl IpAddress {
+    /// Returns true if the address is to be inferred by the server.
+    pub fn is_auto(&self) -> bool {
+        *self == IpAddress::Auto
+    }
+
+    /// Returns the exact address, if given.
+    pub fn as_addr(&self) -> Option<&IpAddr> {
+        match *self {
+            IpAddress::Auto => None,
+            IpAddress::Exact(ref addr) => Some(addr),
+        }
+    }
+
+    /// Converts into the exact address, if given.
+    pub fn into_addr(self) -> Option<IpAddr> {
+        match self {
+            IpAddress::Auto => None,
+            IpAddress::Exact(addr) => Some(addr),
+        }
+    }
+}
+
+impl PartialEq<IpAddr> for IpAddress {
+    fn eq(&self, other: &IpAddr) -> bool {
+        match *self {
+            IpAddress::Auto => false,
+            IpAddress::Exact(ref addr) => addr == other,
+        }
+    }
+}
+
+impl cmp::PartialOrd<IpAddr> for IpAddress {
+    fn partial_cmp(&self, other: &IpAddr) -> Option<cmp::Ordering> {
+        match *self {
+            IpAddress::Auto => None,
+            IpAddress::Exact(ref addr) => addr.partial_cmp(other),
+        }
+    }
+}
+
+impl fmt::Display for IpAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IpAddress::Auto => write!(f, "{{{{auto}}}}"),
+            IpAddress::Exact(ref addr) => write!(f, "{}", addr),
+        }
+    }
+}
+
+impl From<IpAddr> for IpAddress {
+    fn from(addr: IpAddr) -> IpAddress {
+        IpAddress::Exact(addr)
+    }
+}
+
+impl str::FromStr for IpAddress {
+    type Err = AddrParseError;
+
+    fn from_str(string: &str) -> Result<IpAddress, AddrParseError> {
+        match string {
+            "{{auto}}" => Ok(IpAddress::Auto),
+            other => other.parse().map(IpAddress::Exact),
+        }
+    }
+}
+
+impl_str_serde!(IpAddress);
+
+/// Represents user info.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct User {
+    /// The ID of the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The email address of the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// The remote ip address of the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_address: Option<IpAddress>,
+    /// A human readable username of the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl_bounded_setters!(User {
+    set_id => id: MAX_IDENTIFIER_LENGTH,
+    set_email => email: MAX_EMAIL_LENGTH,
+    set_username => username: MAX_IDENTIFIER_LENGTH,
+});
+
+/// An error returned for invalid keys in `Request::env`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid environment key {0:?}")]
+pub struct InvalidEnvKeyError(pub String);
+
+/// Returns true if the key is valid in `Request::env`.
+fn is_valid_env_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| !c.is_whitespace() && !c.is_control() && c != '=')
+}
+
+/// Normalizes a header name to dash-case with capitalized words.
+///
+/// Underscores are replaced by dashes, so `user_agent` and `USER-AGENT` both
+/// become `User-Agent`.
+pub fn normalize_header_name(name: &str) -> String {
+    let mut rv = String::with_capacity(name.len());
+    let mut capitalize = true;
+    for c in name.trim().chars() {
+        if c == '-' || c == '_' {
+            rv.push('-');
+            capitalize = true;
+        } else if capitalize {
+            rv.extend(c.to_uppercase());
+            capitalize = false;
+        } else {
+            rv.extend(c.to_lowercase());
+        }
+    }
+    rv
+}
+
+/// Deserializes request headers from a map or a list of pairs, normalizing
+/// header names.  Values of duplicate headers are joined with commas.
+fn deserialize_headers<'de, D>(deserializer: D) -> Result<Map<String, String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Headers {
+        Map(Map<String, String>),
+        Pairs(Vec<(String, String)>),
+    }
+
+    let pairs = match Headers::deserialize(deserializer)? {
+        Headers::Map(map) => map.into_iter().collect(),
+        Headers::Pairs(pairs) => pairs,
+    };
+
+    let mut headers = Map::new();
+    for (name, value) in pairs {
+        match headers.entry(normalize_header_name(&name)) {
+            map::Entry::Occupied(mut entry) => {
+                let existing: &mut String = entry.get_mut();
+                existing.push_str(", ");
+                existing.push_str(&value);
+            }
+            map::Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+        }
+    }
+    Ok(headers)
+}
+
+/// Represents http request data.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Request {
+    /// The current URL of the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<Url>,
+    /// The HTTP request method.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// Optionally some associated request data (human readable)
+    // XXX: this makes absolutely no sense because of unicode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    /// Optionally the encoded query string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_string: Option<String>,
+    /// Optionally the fragment of the URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fragment: Option<String>,
+    /// An encoded cookie string if available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cookies: Option<String>,
+    /// HTTP request headers.
+    ///
+    /// Header names are normalized to dash-case with capitalized words, such
+    /// as `Content-Type`, when deserializing.
+    #[serde(
+        default,
+        skip_serializing_if = "Map::is_empty",
+        deserialize_with = "deserialize_headers"
+    )]
+    pub headers: Map<String, String>,
+    /// Optionally a CGI/WSGI etc. environment dictionary.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub env: Map<String, String>,
+}
+
+impl Request {
+    /// Sets the URL of the request.
+    ///
+    /// The query string and fragment are split off the URL into the
+    /// `query_string` and `fragment` fields.
+    pub fn set_url(&mut self, url: &Url) {
+        let mut url = url.clone();
+        self.query_string = url.query().map(str::to_string);
+        self.fragment = url.fragment().map(str::to_string);
+        url.set_query(None);
+        url.set_fragment(None);
+        self.url = Some(url);
+    }
+
+    /// Returns the full URL of the request including query string and
+    /// fragment.
+    ///
+    /// Query strings and fragments already part of `url` are replaced.
+    pub fn full_url(&self) -> Option<Url> {
+        let mut url = self.url.clone()?;
+        if let Some(ref query_string) = self.query_string {
+            url.set_query(Some(query_string.trim_start_matches('?')));
+        }
+        if let Some(ref fragment) = self.fragment {
+            url.set_fragment(Some(fragment.trim_start_matches('#')));
+        }
+        Some(url)
+    }
+
+    /// Returns the value of a header.
+    ///
+    /// Header names are compared case-insensitively, treating underscores
+    /// like dashes.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = normalize_header_name(name);
+        self.headers
+            .iter()
+            .find(|(key, _)| normalize_header_name(key) == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Validates the keys of the environment dictionary.
+    ///
+    /// Keys must be non-empty and may not contain whitespace, control
+    /// characters or `=`.
+    pub fn validate_env(&self) -> Result<(), InvalidEnvKeyError> {
+        match self.env.keys().find(|key| !is_valid_env_key(key)) {
+            Some(key) => Err(InvalidEnvKeyError(key.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the remote address from the `REMOTE_ADDR` environment key.
+    ///
+    /// Returns `None` if the key is missing or not a valid address.
+    pub fn remote_addr(&self) -> Option<IpAddress> {
+        self.env.get("REMOTE_ADDR")?.parse().ok()
+    }
+
+    /// Sets the `REMOTE_ADDR` environment key.
+    pub fn set_remote_addr(&mut self, addr: IpAddress) {
+        self.env.insert("REMOTE_ADDR".into(), addr.to_string());
+    }
+}
+
+/// Holds information about the system SDK.
+///
+/// This is relevant for iOS and other platforms that have a system
+/// SDK.  Not to be confused with the client SDK.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SystemSdkInfo {
+    /// The internal name of the SDK
+    pub sdk_name: String,
+    /// the major version of the SDK as integer or 0
+    pub version_major: u32,
+    /// the minor version of the SDK as integer or 0
+    pub version_minor: u32,
+    /// the patch version of the SDK as integer or 0
+    pub version_patchlevel: u32,
+}
+
+/// Represents a debug image.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum DebugImage {
+    /// Apple debug images (machos).  This is currently also used for
+    /// non apple platforms with similar debug setups.
+    Apple(AppleDebugImage),
+    /// Symbolic (new style) debug infos.
+    Symbolic(SymbolicDebugImage),
+    /// A reference to a proguard debug file.
+    Proguard(ProguardDebugImage),
+}
+
+impl DebugImage {
+    /// Returns the name of the type on sentry.
+    pub fn type_name(&self) -> &str {
+        match *self {
+            DebugImage::Apple(..) => "apple",
+            DebugImage::Symbolic(..) => "symbolic",
+            DebugImage::Proguard(..) => "proguard",
+        }
+    }
+
+    /// Returns the normalized debug id of the image.
+    pub fn debug_id(&self) -> DebugId {
+        match *self {
+            DebugImage::Apple(ref image) => image.debug_id(),
+            DebugImage::Symbolic(ref image) => image.debug_id(),
+            DebugImage::Proguard(ref image) => image.debug_id(),
+        }
+    }
+
+    /// Returns the code id of the image, if known.
+    pub fn code_id(&self) -> Option<&CodeId> {
+        match *self {
+            DebugImage::Symbolic(ref image) => image.code_id.as_ref(),
+            DebugImage::Apple(..) | DebugImage::Proguard(..) => None,
+        }
+    }
+}
+
+/// Creates a debug id from an ELF build id.
+///
+/// Like Breakpad, this interprets the first 16 bytes of the build id as a
+/// little endian GUID.  Shorter build ids are padded with zeros.  Returns
+/// `None` for empty build ids.
+pub fn debug_id_from_build_id(build_id: &[u8]) -> Option<DebugId> {
+    if build_id.is_empty() {
+        return None;
+    }
+
+    let mut bytes = [0u8; 16];
+    let len = cmp::min(build_id.len(), 16);
+    bytes[..len].copy_from_slice(&build_id[..len]);
+    bytes[0..4].reverse();
+    bytes[4..6].reverse();
+    bytes[6..8].reverse();
+    Some(DebugId::from_uuid(Uuid::from_bytes(bytes)))
+}
+
+/// Creates a code id from an ELF build id.
+///
+/// The code id is the hex encoded build id.
+pub fn code_id_from_build_id(build_id: &[u8]) -> CodeId {
+    CodeId::from_binary(build_id)
+}
+
+/// Creates a code id of a PE image.
+///
+/// The code id is made of the `TimeDateStamp` and `SizeOfImage` fields of the
+/// PE header, which is how symbol servers look up Windows executables.
+pub fn code_id_from_pe(timestamp: u32, size_of_image: u32) -> CodeId {
+    CodeId::new(format!("{:08x}{:x}", timestamp, size_of_image))
+}
+
+macro_rules! into_debug_image {
+    ($kind:ident, $ty:ty) => {
+        impl From<$ty> for DebugImage {
+            fn from(data: $ty) -> DebugImage {
+                DebugImage::$kind(data)
+            }
+        }
+    };
+}
+
+/// Represents an apple debug image in the debug meta.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AppleDebugImage {
+    /// The name of the debug image (usually filename)
+    pub name: String,
+    /// The optional CPU architecture of the debug image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    /// Alternatively a macho cpu type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_type: Option<u32>,
+    /// Alternatively a macho cpu subtype.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_subtype: Option<u32>,
+    /// The starting address of the image.
+    pub image_addr: Addr,
+    /// The size of the image in bytes.
+    pub image_size: u64,
+    /// The address where the image is loaded at runtime.
+    #[serde(default, skip_serializing_if = "Addr::is_null")]
+    pub image_vmaddr: Addr,
+    /// The unique UUID of the image.
+    pub uuid: Uuid,
+}
+
+impl AppleDebugImage {
+    /// Returns the debug id of the image, which is its UUID.
+    pub fn debug_id(&self) -> DebugId {
+        DebugId::from_uuid(self.uuid)
+    }
+}
+
+/// Represents a symbolic debug image.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SymbolicDebugImage {
+    /// The name of the debug image (usually filename)
+    pub name: String,
+    /// The optional CPU architecture of the debug image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    /// The starting address of the image.
+    pub image_addr: Addr,
+    /// The size of the image in bytes.
+    pub image_size: u64,
+    /// The address where the image is loaded at runtime.
+    #[serde(default, skip_serializing_if = "Addr::is_null")]
+    pub image_vmaddr: Addr,
+    /// The unique debug id of the image.
+    #[serde(alias = "debug_id")]
+    pub id: DebugId,
+    /// The code id of the image, such as an ELF build id or a PE code id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_id: Option<CodeId>,
+}
+
+impl SymbolicDebugImage {
+    /// Returns the normalized debug id of the image.
+    ///
+    /// If the debug id is nil and the code id holds an ELF build id of at
+    /// least 16 bytes, the debug id is derived from the build id.
+    pub fn debug_id(&self) -> DebugId {
+        if self.id.is_nil() {
+            let from_code_id = self
+                .code_id
+                .as_ref()
+                .map(CodeId::as_str)
+                .filter(|code_id| code_id.len() >= 32)
+                .and_then(|code_id| {
+                    let mut build_id = vec![0; code_id.len() / 2];
+                    hex::decode(code_id, &mut build_id)?;
+                    debug_id_from_build_id(&build_id)
+                });
+            if let Some(debug_id) = from_code_id {
+                return debug_id;
+            }
+        }
+        self.id
+    }
+}
+
+/// Represents a proguard mapping file reference.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProguardDebugImage {
+    /// The UUID of the associated proguard file.
+    pub uuid: Uuid,
+}
+
+impl ProguardDebugImage {
+    /// Returns the debug id of the mapping file, which is its UUID.
+    pub fn debug_id(&self) -> DebugId {
+        DebugId::from_uuid(self.uuid)
+    }
+}
+
+into_debug_image!(Apple, AppleDebugImage);
 into_debug_image!(Symbolic, SymbolicDebugImage);
 into_debug_image!(Proguard, ProguardDebugImage);
 
-/// Represents debug meta information.
-#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
-pub struct DebugMeta {
-    /// Optional system SDK information.
+/// Represents debug meta information.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct DebugMeta {
+    /// Optional system SDK information.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sdk_info: Option<SystemSdkInfo>,
+    /// A list of debug information files.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<DebugImage>,
+}
+
+impl DebugMeta {
+    /// Returns true if the debug meta is empty.
+    ///
+    /// This is used by the serializer to entirely skip the section.
+    pub fn is_empty(&self) -> bool {
+        self.sdk_info.is_none() && self.images.is_empty()
+    }
+}
+
+/// Information on the SDK client.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClientSdkInfo {
+    /// The name of the SDK.
+    pub name: String,
+    /// The version of the SDK.
+    pub version: String,
+    /// An optional list of integrations that are enabled in this SDK.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub integrations: Vec<String>,
+    /// An optional list of packages that are installed in the SDK's environment.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<ClientSdkPackage>,
+}
+
+/// Represents an installed package relevant to the SDK.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClientSdkPackage {
+    /// The name of the package installed.
+    pub name: String,
+    /// The version of the package.
+    pub version: String,
+}
+
+/// Typed contextual data.
+///
+/// Types like `OsContext` can be directly converted with `.into()`
+/// to `Context`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum Context {
+    /// Device data.
+    #[serde(deserialize_with = "lenient::tracked")]
+    Device(Box<DeviceContext>),
+    /// Operating system data.
+    #[serde(deserialize_with = "lenient::tracked")]
+    Os(Box<OsContext>),
+    /// Runtime data.
+    #[serde(deserialize_with = "lenient::tracked")]
+    Runtime(Box<RuntimeContext>),
+    /// Application data.
+    #[serde(deserialize_with = "lenient::tracked")]
+    App(Box<AppContext>),
+    /// Web browser data.
+    #[serde(deserialize_with = "lenient::tracked")]
+    Browser(Box<BrowserContext>),
+    /// Generic other context data.
+    #[serde(rename = "unknown")]
+    Other(Map<String, Value>),
+}
+
+impl Context {
+    /// Returns the name of the type for sentry.
+    pub fn type_name(&self) -> &str {
+        match *self {
+            Context::Device(..) => "device",
+            Context::Os(..) => "os",
+            Context::Runtime(..) => "runtime",
+            Context::App(..) => "app",
+            Context::Browser(..) => "browser",
+            Context::Other(..) => "unknown",
+        }
+    }
+
+    /// Returns the type of a context sent without one under the given key.
+    ///
+    /// Keys naming a known context type, ignoring case, are aliases for that
+    /// type.  All other keys result in `unknown`.
+    pub fn type_from_key(key: &str) -> &'static str {
+        ["device", "os", "runtime", "app", "browser"]
+            .iter()
+            .find(|ty| key.eq_ignore_ascii_case(ty))
+            .copied()
+            .unwrap_or("unknown")
+    }
+}
+
+mod contexts {
+    use std::fmt;
+
+    use serde::de::{DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+
+    use super::*;
+
+    /// Deserializes contexts, inferring missing types from their keys.
+    ///
+    /// The keys are retained as sent.  Contexts are read directly from the
+    /// deserializer, a missing `type` is supplied as an extra map entry.  Of
+    /// repeated keys the first context is kept and the others are recorded.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Map<String, Context>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(ContextsVisitor)
+    }
+
+    struct ContextsVisitor;
+
+    impl<'de> Visitor<'de> for ContextsVisitor {
+        type Value = Map<String, Context>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map of contexts")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut contexts = Map::new();
+            while let Some(key) = map.next_key::<String>()? {
+                let ty = Context::type_from_key(&key);
+                let context = map.next_value_seed(ContextSeed(ty))?;
+                match contexts.entry(key) {
+                    // keep the first context like `Event::normalize` does
+                    map::Entry::Occupied(entry) => {
+                        let value = serde_json::to_value(&context).unwrap_or(Value::Null);
+                        lenient::with_segment(format_args!("{}", entry.key()), || {
+                            lenient::record_invalid(
+                                "contexts",
+                                value,
+                                "duplicate context key".into(),
+                            )
+                        });
+                    }
+                    map::Entry::Vacant(entry) => {
+                        entry.insert(context);
+                    }
+                }
+            }
+            Ok(contexts)
+        }
+    }
+
+    /// Deserializes a context, using the given type if it has none.
+    struct ContextSeed(&'static str);
+
+    impl<'de> DeserializeSeed<'de> for ContextSeed {
+        type Value = Context;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Context, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            Context::deserialize(DefaultType {
+                inner: deserializer,
+                ty: self.0,
+            })
+        }
+    }
+
+    /// A deserializer adding a `type` entry to maps that do not have one.
+    struct DefaultType<D> {
+        inner: D,
+        ty: &'static str,
+    }
+
+    impl<'de, D: serde::Deserializer<'de>> serde::Deserializer<'de> for DefaultType<D> {
+        type Error = D::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+            self.inner.deserialize_any(DefaultType {
+                inner: visitor,
+                ty: self.ty,
+            })
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct enum identifier ignored_any
+        }
+    }
+
+    impl<'de, V: Visitor<'de>> Visitor<'de> for DefaultType<V> {
+        type Value = V::Value;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            self.inner.expecting(formatter)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+            self.inner.visit_map(DefaultTypeMap {
+                inner: map,
+                ty: Some(self.ty),
+                pending: false,
+            })
+        }
+    }
+
+    /// Yields the entries of a map, followed by `type` if it was missing.
+    struct DefaultTypeMap<A> {
+        inner: A,
+        /// The type to add, cleared once a `type` key was seen.
+        ty: Option<&'static str>,
+        /// Whether the next value is the added type.
+        pending: bool,
+    }
+
+    impl<'de, A: MapAccess<'de>> MapAccess<'de> for DefaultTypeMap<A> {
+        type Error = A::Error;
+
+        fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+        where
+            K: DeserializeSeed<'de>,
+        {
+            if self.pending {
+                return Ok(None);
+            }
+            match self.inner.next_key::<String>()? {
+                Some(key) => {
+                    if key == "type" {
+                        self.ty = None;
+                    }
+                    seed.deserialize(key.into_deserializer()).map(Some)
+                }
+                None if self.ty.is_some() => {
+                    self.pending = true;
+                    seed.deserialize("type".into_deserializer()).map(Some)
+                }
+                None => Ok(None),
+            }
+        }
+
+        fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, A::Error>
+        where
+            T: DeserializeSeed<'de>,
+        {
+            match (self.pending, self.ty) {
+                (true, Some(ty)) => {
+                    self.ty = None;
+                    seed.deserialize(ty.into_deserializer())
+                }
+                _ => self.inner.next_value_seed(seed),
+            }
+        }
+    }
+}
+
+/// Optional device screen orientation
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    /// Portrait device orientation.
+    Portrait,
+    /// Landscape device orientation.
+    Landscape,
+}
+
+/// Holds device information.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DeviceContext {
+    /// The name of the device.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The family of the device model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family: Option<String>,
+    /// The device model (human readable).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// The device model (internal identifier).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_id: Option<String>,
+    /// The native cpu architecture of the device.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    /// The current battery level (0-100).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub battery_level: Option<f32>,
+    /// The current screen orientation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orientation: Option<Orientation>,
+    /// Simulator/prod indicator.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_bool_opt"
+    )]
+    pub simulator: Option<bool>,
+    /// Total memory available in byts.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub memory_size: Option<u64>,
+    /// How much memory is still available in bytes.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub free_memory: Option<u64>,
+    /// How much memory is usable for the app in bytes.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub usable_memory: Option<u64>,
+    /// Total storage size of the device in bytes.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub storage_size: Option<u64>,
+    /// How much storage is free in bytes.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub free_storage: Option<u64>,
+    /// Total size of the attached external storage in bytes (eg: android SDK card).
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub external_storage_size: Option<u64>,
+    /// Free size of the attached external storage in bytes (eg: android SDK card).
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub external_free_storage: Option<u64>,
+    /// Optionally an indicator when the device was booted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boot_time: Option<DateTime<Utc>>,
+    /// The timezone of the device.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// The number of logical processors of the device.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub processor_count: Option<u64>,
+    /// The processor frequency in MHz.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub processor_frequency: Option<u64>,
+    /// The kind of device (for instance `Mobile`, `Tablet` or `Desktop`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_type: Option<String>,
+    /// The status of the battery (for instance `Charging` or `Discharging`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub battery_status: Option<String>,
+    /// The screen density in dots per inch.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub screen_dpi: Option<u64>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Holds operating system information.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct OsContext {
+    /// The name of the operating system.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The version of the operating system.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The internal build number of the operating system.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<String>,
+    /// The current kernel version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kernel_version: Option<String>,
+    /// An indicator if the os is rooted (mobile mostly).
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_bool_opt"
+    )]
+    pub rooted: Option<bool>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Holds information about the runtime.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RuntimeContext {
+    /// The name of the runtime (for instance JVM).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The version of the runtime.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Holds app information.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AppContext {
+    /// Optional start time of the app.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_start_time: Option<DateTime<Utc>>,
+    /// Optional device app hash (app specific device ID)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_app_hash: Option<String>,
+    /// Optional build identicator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_type: Option<String>,
+    /// Optional app identifier (dotted bundle id).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_identifier: Option<String>,
+    /// Application name as it appears on the platform.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    /// Application version as it appears on the platform.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_version: Option<String>,
+    /// Internal build ID as it appears on the platform.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_build: Option<String>,
+    /// Amount of memory used by the app in bytes.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_from_str"
+    )]
+    pub app_memory: Option<u64>,
+    /// Whether the app was in the foreground.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_bool_opt"
+    )]
+    pub in_foreground: Option<bool>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Holds information about the web browser.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct BrowserContext {
+    /// The name of the browser (for instance "Chrome").
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub sdk_info: Option<SystemSdkInfo>,
-    /// A list of debug information files.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub images: Vec<DebugImage>,
+    pub name: Option<String>,
+    /// The version of the browser.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
 }
 
-impl DebugMeta {
-    /// Returns true if the debug meta is empty.
-    ///
-    /// This is used by the serializer to entirely skip the section.
+macro_rules! into_context {
+    ($kind:ident, $ty:ty) => {
+        impl From<$ty> for Context {
+            fn from(data: $ty) -> Self {
+                Context::$kind(Box::new(data))
+            }
+        }
+    };
+}
+
+into_context!(App, AppContext);
+into_context!(Device, DeviceContext);
+into_context!(Os, OsContext);
+into_context!(Runtime, RuntimeContext);
+into_context!(Browser, BrowserContext);
+
+/// An error used when parsing `RemarkType`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid remark type")]
+pub struct ParseRemarkTypeError;
+
+/// Describes how a value was modified.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RemarkType {
+    /// The value was annotated without being modified.
+    Annotated,
+    /// The value was removed entirely.
+    Removed,
+    /// The value was replaced by a different value.
+    Substituted,
+    /// Parts of the value were masked.
+    Masked,
+    /// The value was replaced by a hash.
+    Pseudonymized,
+    /// The value was encrypted.
+    Encrypted,
+}
+
+impl str::FromStr for RemarkType {
+    type Err = ParseRemarkTypeError;
+
+    fn from_str(string: &str) -> Result<RemarkType, Self::Err> {
+        Ok(match string {
+            "a" => RemarkType::Annotated,
+            "x" => RemarkType::Removed,
+            "s" => RemarkType::Substituted,
+            "m" => RemarkType::Masked,
+            "p" => RemarkType::Pseudonymized,
+            "e" => RemarkType::Encrypted,
+            _ => return Err(ParseRemarkTypeError),
+        })
+    }
+}
+
+impl fmt::Display for RemarkType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RemarkType::Annotated => write!(f, "a"),
+            RemarkType::Removed => write!(f, "x"),
+            RemarkType::Substituted => write!(f, "s"),
+            RemarkType::Masked => write!(f, "m"),
+            RemarkType::Pseudonymized => write!(f, "p"),
+            RemarkType::Encrypted => write!(f, "e"),
+        }
+    }
+}
+
+impl_str_serde!(RemarkType);
+
+/// Records a modification of a value.
+///
+/// Remarks are serialized as arrays in the form `[rule_id, type]` or
+/// `[rule_id, type, range_start, range_end]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Remark {
+    /// The identifier of the rule that caused the modification.
+    pub rule_id: String,
+    /// The kind of modification.
+    pub ty: RemarkType,
+    /// The character range of the modified value, if applicable.
+    pub range: Option<(usize, usize)>,
+}
+
+impl Remark {
+    /// Creates a new remark covering the entire value.
+    pub fn new<S: Into<String>>(ty: RemarkType, rule_id: S) -> Remark {
+        Remark {
+            rule_id: rule_id.into(),
+            ty,
+            range: None,
+        }
+    }
+}
+
+impl Serialize for Remark {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = serializer.serialize_seq(None)?;
+        seq.serialize_element(&self.rule_id)?;
+        seq.serialize_element(&self.ty)?;
+        if let Some((start, end)) = self.range {
+            seq.serialize_element(&start)?;
+            seq.serialize_element(&end)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Remark {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Remark, D::Error> {
+        use serde::de::{self, SeqAccess, Visitor};
+
+        struct RemarkVisitor;
+
+        impl<'de> Visitor<'de> for RemarkVisitor {
+            type Value = Remark;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a remark array")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Remark, A::Error> {
+                let rule_id = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let ty = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let start = seq.next_element()?;
+                let end = seq.next_element()?;
+                // skip any additional elements for forwards compatibility
+                while seq.next_element::<de::IgnoredAny>()?.is_some() {}
+
+                Ok(Remark {
+                    rule_id,
+                    ty,
+                    range: start.and_then(|start| Some((start, end?))),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(RemarkVisitor)
+    }
+}
+
+/// Meta data about a single value in the protocol.
+///
+/// This describes modifications and errors that occurred while processing
+/// the value.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Meta {
+    /// Remarks about modifications of the value.
+    #[serde(rename = "rem", default, skip_serializing_if = "Vec::is_empty")]
+    pub remarks: Vec<Remark>,
+    /// Errors that occurred while processing the value.
+    #[serde(rename = "err", default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<Value>,
+    /// The length of the original value if it was truncated.
+    #[serde(rename = "len", default, skip_serializing_if = "Option::is_none")]
+    pub original_length: Option<u64>,
+    /// The original value if it was replaced.
+    #[serde(rename = "val", default, skip_serializing_if = "Option::is_none")]
+    pub original_value: Option<Value>,
+}
+
+impl Meta {
+    /// Returns true if there is no meta data.
     pub fn is_empty(&self) -> bool {
-        self.sdk_info.is_none() && self.images.is_empty()
+        self.remarks.is_empty()
+            && self.errors.is_empty()
+            && self.original_length.is_none()
+            && self.original_value.is_none()
+    }
+}
+
+/// A tree of meta data mirroring the structure of an event.
+///
+/// This is serialized as the `_meta` key of an event where the meta data of
+/// every node is stored in the `""` key.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct MetaTree {
+    /// The meta data of this node.
+    #[serde(rename = "", default, skip_serializing_if = "Meta::is_empty")]
+    pub meta: Meta,
+    /// The meta data of child values.
+    #[serde(flatten)]
+    pub children: Map<String, MetaTree>,
+}
+
+impl MetaTree {
+    /// Returns true if the tree does not contain any meta data.
+    pub fn is_empty(&self) -> bool {
+        self.meta.is_empty() && self.children.values().all(MetaTree::is_empty)
+    }
+
+    /// Splits a dotted path into its keys.
+    ///
+    /// Dots and backslashes within keys are escaped with a backslash, so
+    /// `"tags.app\\.version"` refers to the key `app.version` in `tags`.  The
+    /// empty path refers to the root and has no keys.
+    pub fn split_path(path: &str) -> Vec<String> {
+        if path.is_empty() {
+            return Vec::new();
+        }
+
+        let mut keys = vec![String::new()];
+        let mut chars = path.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => keys.last_mut().unwrap().extend(chars.next()),
+                '.' => keys.push(String::new()),
+                c => keys.last_mut().unwrap().push(c),
+            }
+        }
+        keys
+    }
+
+    /// Joins keys into a dotted path, escaping dots and backslashes.
+    pub fn join_path<I, S>(keys: I) -> String
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut path = String::new();
+        for (i, key) in keys.into_iter().enumerate() {
+            if i > 0 {
+                path.push('.');
+            }
+            for c in key.as_ref().chars() {
+                if c == '.' || c == '\\' {
+                    path.push('\\');
+                }
+                path.push(c);
+            }
+        }
+        path
+    }
+
+    /// Returns the subtree at a dotted path such as `"breadcrumbs.values"`.
+    pub fn get_tree(&self, path: &str) -> Option<&MetaTree> {
+        let mut node = self;
+        for key in MetaTree::split_path(path) {
+            node = node.children.get(&key)?;
+        }
+        Some(node)
+    }
+
+    /// Returns the meta data at a dotted path such as `"breadcrumbs.values.0"`.
+    pub fn get(&self, path: &str) -> Option<&Meta> {
+        self.get_tree(path).map(|node| &node.meta)
+    }
+
+    /// Returns mutable meta data at a dotted path, inserting it if missing.
+    pub fn get_mut(&mut self, path: &str) -> &mut Meta {
+        let mut node = self;
+        for key in MetaTree::split_path(path) {
+            node = node.children.entry(key).or_default();
+        }
+        &mut node.meta
+    }
+
+    /// Returns the number of errors in this node and all of its descendants.
+    pub fn error_count(&self) -> usize {
+        self.meta.errors.len() + self.errors_in_children()
+    }
+
+    /// Returns the number of errors in the descendants of this node.
+    ///
+    /// For the meta data of a list, such as `breadcrumbs.values`, this is the
+    /// number of errors in all of its elements.
+    pub fn errors_in_children(&self) -> usize {
+        self.children.values().map(MetaTree::error_count).sum()
+    }
+
+    /// Creates a tree from meta data keyed by dotted paths.
+    pub fn from_paths<I, S>(paths: I) -> MetaTree
+    where
+        I: IntoIterator<Item = (S, Meta)>,
+        S: AsRef<str>,
+    {
+        let mut tree = MetaTree::default();
+        for (path, meta) in paths {
+            *tree.get_mut(path.as_ref()) = meta;
+        }
+        tree
+    }
+
+    /// Returns all non-empty meta data in the tree keyed by dotted paths.
+    ///
+    /// Meta data of the root is keyed by the empty path.  This is the inverse
+    /// of `MetaTree::from_paths`.
+    pub fn to_paths(&self) -> Map<String, Meta> {
+        fn collect(node: &MetaTree, keys: &mut Vec<String>, paths: &mut Map<String, Meta>) {
+            if !node.meta.is_empty() {
+                paths.insert(MetaTree::join_path(keys.iter()), node.meta.clone());
+            }
+            for (key, child) in &node.children {
+                keys.push(key.clone());
+                collect(child, keys, paths);
+                keys.pop();
+            }
+        }
+
+        let mut paths = Map::new();
+        collect(self, &mut Vec::new(), &mut paths);
+        paths
+    }
+}
+
+/// Describes how the name of a transaction was determined.
+///
+/// Sources not known to this crate are kept in `TransactionSource::Unknown`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TransactionSource {
+    /// The full URL path, which potentially contains identifiers.
+    Url,
+    /// A parametrized route of a web framework.
+    Route,
+    /// The name of a view handler.
+    View,
+    /// The name of a component of a frontend framework.
+    Component,
+    /// The name of a background task.
+    Task,
+    /// A name set explicitly by the user.
+    Custom,
+    /// A transaction source not known to this crate.
+    Unknown(String),
+}
+
+impl TransactionSource {
+    /// Returns the name of the transaction source.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            TransactionSource::Url => "url",
+            TransactionSource::Route => "route",
+            TransactionSource::View => "view",
+            TransactionSource::Component => "component",
+            TransactionSource::Task => "task",
+            TransactionSource::Custom => "custom",
+            TransactionSource::Unknown(ref source) => source,
+        }
+    }
+}
+
+impl str::FromStr for TransactionSource {
+    type Err = Infallible;
+
+    fn from_str(string: &str) -> Result<TransactionSource, Self::Err> {
+        Ok(match string {
+            "url" => TransactionSource::Url,
+            "route" => TransactionSource::Route,
+            "view" => TransactionSource::View,
+            "component" => TransactionSource::Component,
+            "task" => TransactionSource::Task,
+            "custom" => TransactionSource::Custom,
+            other => TransactionSource::Unknown(other.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for TransactionSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl_str_serde!(TransactionSource);
+
+/// Additional information about the name of the transaction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TransactionInfo {
+    /// Describes how the name of the transaction was determined.
+    pub source: TransactionSource,
+}
+
+/// Conventional logger names.
+pub mod loggers {
+    /// The root logger of logging frameworks such as Python's `logging`.
+    pub const ROOT: &str = "root";
+    /// The logger of events created from Rust panics.
+    pub const PANIC: &str = "panic";
+    /// The logger of events created from content security policy reports.
+    pub const CSP: &str = "csp";
+    /// The logger of events created from unhandled JavaScript errors.
+    pub const JAVASCRIPT: &str = "javascript";
+}
+
+/// Raised if a logger name would be rejected by Sentry.
+#[derive(Debug, Error, Copy, Clone, PartialEq, Eq)]
+pub enum InvalidLoggerError {
+    /// Raised if the logger name is empty.
+    #[error("empty logger name")]
+    Empty,
+    /// Raised if the logger name exceeds `MAX_LOGGER_LENGTH` characters.
+    #[error("logger name too long")]
+    TooLong,
+    /// Raised if the logger name contains whitespace or control characters.
+    #[error("invalid character in logger name")]
+    InvalidCharacter,
+}
+
+/// Checks if a logger name is accepted by Sentry.
+pub fn validate_logger(logger: &str) -> Result<(), InvalidLoggerError> {
+    if logger.is_empty() {
+        Err(InvalidLoggerError::Empty)
+    } else if logger.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        Err(InvalidLoggerError::InvalidCharacter)
+    } else if logger.chars().count() > MAX_LOGGER_LENGTH {
+        Err(InvalidLoggerError::TooLong)
+    } else {
+        Ok(())
+    }
+}
+
+/// Turns an arbitrary string into a valid logger name.
+///
+/// Invalid characters are replaced with underscores and overly long names
+/// are shortened from the front, preferably at a `.` separator, since the
+/// trailing components are the most specific ones.
+fn sanitize_logger(logger: &str) -> String {
+    let sanitized: Vec<char> = logger
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    if sanitized.len() <= MAX_LOGGER_LENGTH {
+        return sanitized.into_iter().collect();
+    }
+
+    let mut tail = &sanitized[sanitized.len() - (MAX_LOGGER_LENGTH - 1)..];
+    if let Some(pos) = tail.iter().position(|&c| c == '.') {
+        if pos + 1 < tail.len() {
+            tail = &tail[pos + 1..];
+        }
+    }
+
+    std::iter::once('*').chain(tail.iter().cloned()).collect()
+}
+
+/// Raised if a release name would be rejected by Sentry.
+#[derive(Debug, Error, Copy, Clone, PartialEq, Eq)]
+pub enum InvalidReleaseError {
+    /// Raised if the release is empty.
+    #[error("empty release name")]
+    Empty,
+    /// Raised if the release exceeds `MAX_RELEASE_LENGTH` characters.
+    #[error("release name too long")]
+    TooLong,
+    /// Raised if the release contains newlines, tabs or slashes.
+    #[error("invalid character in release name")]
+    InvalidCharacter,
+    /// Raised if the release is one of the names reserved by Sentry.
+    #[error("restricted release name")]
+    RestrictedName,
+}
+
+/// Raised if an environment name would be rejected by Sentry.
+#[derive(Debug, Error, Copy, Clone, PartialEq, Eq)]
+pub enum InvalidEnvironmentError {
+    /// Raised if the environment is empty.
+    #[error("empty environment name")]
+    Empty,
+    /// Raised if the environment exceeds `MAX_ENVIRONMENT_LENGTH` characters.
+    #[error("environment name too long")]
+    TooLong,
+    /// Raised if the environment contains whitespace or slashes.
+    #[error("invalid character in environment name")]
+    InvalidCharacter,
+    /// Raised if the environment is one of the names reserved by Sentry.
+    #[error("restricted environment name")]
+    RestrictedName,
+}
+
+/// Implements the shared API of validated name newtypes.
+macro_rules! impl_validated_name {
+    ($ty:ident, $err:ident) => {
+        impl<'a> $ty<'a> {
+            /// Returns the name as string slice.
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// Converts the name into an owned version.
+            pub fn into_owned(self) -> $ty<'static> {
+                $ty(Cow::Owned(self.0.into_owned()))
+            }
+        }
+
+        impl<'a> ops::Deref for $ty<'a> {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl<'a> AsRef<str> for $ty<'a> {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl<'a> fmt::Display for $ty<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl str::FromStr for $ty<'static> {
+            type Err = $err;
+
+            fn from_str(string: &str) -> Result<$ty<'static>, $err> {
+                $ty::new(string.to_string())
+            }
+        }
+
+        impl<'a> PartialEq<str> for $ty<'a> {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl<'a, 'b> PartialEq<&'b str> for $ty<'a> {
+            fn eq(&self, other: &&'b str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl<'a> Serialize for $ty<'a> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.0)
+            }
+        }
+
+        impl<'de, 'a> Deserialize<'de> for $ty<'a> {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let string = String::deserialize(deserializer)?;
+                $ty::new(string).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+/// A validated release name.
+///
+/// Releases are trimmed on creation and must not be empty, exceed
+/// `MAX_RELEASE_LENGTH` characters, contain newlines, tabs or slashes, or be
+/// one of the reserved names `.`, `..` and `latest`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Release<'a>(Cow<'a, str>);
+
+impl<'a> Release<'a> {
+    /// Creates a new release, validating and normalizing the name.
+    pub fn new<S: Into<Cow<'a, str>>>(name: S) -> Result<Release<'a>, InvalidReleaseError> {
+        let name = trim_cow(name.into());
+        if name.is_empty() {
+            Err(InvalidReleaseError::Empty)
+        } else if name.chars().count() > MAX_RELEASE_LENGTH {
+            Err(InvalidReleaseError::TooLong)
+        } else if name.contains(&['\n', '\r', '\t', '/', '\\'][..]) {
+            Err(InvalidReleaseError::InvalidCharacter)
+        } else if name == "." || name == ".." || name.eq_ignore_ascii_case("latest") {
+            Err(InvalidReleaseError::RestrictedName)
+        } else {
+            Ok(Release(name))
+        }
+    }
+}
+
+impl_validated_name!(Release, InvalidReleaseError);
+
+/// A validated environment name.
+///
+/// Environments are trimmed on creation and must not be empty, exceed
+/// `MAX_ENVIRONMENT_LENGTH` characters, contain whitespace or slashes, or be
+/// the reserved name `None`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Environment<'a>(Cow<'a, str>);
+
+impl<'a> Environment<'a> {
+    /// Creates a new environment, validating and normalizing the name.
+    pub fn new<S: Into<Cow<'a, str>>>(name: S) -> Result<Environment<'a>, InvalidEnvironmentError> {
+        let name = trim_cow(name.into());
+        if name.is_empty() {
+            Err(InvalidEnvironmentError::Empty)
+        } else if name.chars().count() > MAX_ENVIRONMENT_LENGTH {
+            Err(InvalidEnvironmentError::TooLong)
+        } else if name.contains(|c: char| c.is_whitespace() || c == '/') {
+            Err(InvalidEnvironmentError::InvalidCharacter)
+        } else if name == "None" {
+            Err(InvalidEnvironmentError::RestrictedName)
+        } else {
+            Ok(Environment(name))
+        }
+    }
+}
+
+impl_validated_name!(Environment, InvalidEnvironmentError);
+
+/// Trims surrounding whitespace, only allocating if necessary.
+fn trim_cow(string: Cow<'_, str>) -> Cow<'_, str> {
+    match string {
+        Cow::Borrowed(s) => Cow::Borrowed(s.trim()),
+        Cow::Owned(s) if s.trim().len() == s.len() => Cow::Owned(s),
+        Cow::Owned(s) => Cow::Owned(s.trim().to_string()),
+    }
+}
+
+pub(crate) mod event {
+    use super::*;
+
+    #[cfg(feature = "clock")]
+    pub fn default_id() -> Uuid {
+        random_uuid()
+    }
+
+    pub fn serialize_id<S: Serializer>(uuid: &Uuid, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_some(&uuid.to_simple_ref().to_string())
+    }
+
+    pub fn default_level() -> Level {
+        Level::Error
+    }
+
+    static DEFAULT_FINGERPRINT: &[Cow<'static, str>] = &[Cow::Borrowed("{{ default }}")];
+
+    pub fn default_fingerprint<'a>() -> Cow<'a, [Cow<'a, str>]> {
+        Cow::Borrowed(DEFAULT_FINGERPRINT)
+    }
+
+    #[allow(clippy::ptr_arg)]
+    pub fn is_default_fingerprint<'a>(fp: &[Cow<'a, str>]) -> bool {
+        fp.len() == 1 && (fp[0] == "{{ default }}" || fp[0] == "{{default}}")
+    }
+
+    #[cfg(feature = "clock")]
+    pub fn default_timestamp() -> DateTime<Utc> {
+        now()
     }
 }
 
-/// Information on the SDK client.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct ClientSdkInfo {
-    /// The name of the SDK.
-    pub name: String,
-    /// The version of the SDK.
-    pub version: String,
-    /// An optional list of integrations that are enabled in this SDK.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub integrations: Vec<String>,
-    /// An optional list of packages that are installed in the SDK's environment.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub packages: Vec<ClientSdkPackage>,
+/// A single sample of a profile.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ProfileSample {
+    /// The index of the sampled stack in the `stacks` of the profile.
+    #[serde(default)]
+    pub stack_id: usize,
+    /// The thread that was sampled.
+    #[serde(default)]
+    pub thread_id: ThreadId,
+    /// The time of the sample relative to the start of the profile.
+    #[serde(default)]
+    pub elapsed_since_start_ns: u64,
 }
 
-/// Represents an installed package relevant to the SDK.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct ClientSdkPackage {
-    /// The name of the package installed.
-    pub name: String,
-    /// The version of the package.
-    pub version: String,
+/// Metadata of a thread in a profile.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ThreadMetadata {
+    /// The name of the thread.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The scheduling priority of the thread.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u32>,
 }
 
-/// Typed contextual data.
+/// The sampled data of a profile.
 ///
-/// Types like `OsContext` can be directly converted with `.into()`
-/// to `Context`.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(rename_all = "snake_case", tag = "type")]
-pub enum Context {
-    /// Device data.
-    Device(Box<DeviceContext>),
-    /// Operating system data.
-    Os(Box<OsContext>),
-    /// Runtime data.
-    Runtime(Box<RuntimeContext>),
-    /// Application data.
-    App(Box<AppContext>),
-    /// Web browser data.
-    Browser(Box<BrowserContext>),
-    /// Generic other context data.
-    #[serde(rename = "unknown")]
-    Other(Map<String, Value>),
+/// Stacks are lists of indices into `frames`, with the leaf frame first.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ProfileData {
+    /// The samples taken.
+    #[serde(default)]
+    pub samples: Vec<ProfileSample>,
+    /// The unique stacks referenced by samples.
+    #[serde(default)]
+    pub stacks: Vec<Vec<usize>>,
+    /// The unique frames referenced by stacks.
+    #[serde(default)]
+    pub frames: Vec<Frame>,
+    /// Metadata of the sampled threads keyed by thread ID.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub thread_metadata: Map<String, ThreadMetadata>,
 }
 
-impl Context {
-    /// Returns the name of the type for sentry.
-    pub fn type_name(&self) -> &str {
-        match *self {
-            Context::Device(..) => "device",
-            Context::Os(..) => "os",
-            Context::Runtime(..) => "runtime",
-            Context::App(..) => "app",
-            Context::Browser(..) => "browser",
-            Context::Other(..) => "unknown",
-        }
+impl ProfileData {
+    /// Returns the frames of the stack of a sample, leaf frame first.
+    ///
+    /// Returns `None` if the sample references an unknown stack or frame.
+    pub fn sample_frames(&self, sample: &ProfileSample) -> Option<Vec<&Frame>> {
+        self.stacks
+            .get(sample.stack_id)?
+            .iter()
+            .map(|&frame_id| self.frames.get(frame_id))
+            .collect()
     }
 }
 
-/// Optional device screen orientation
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "lowercase")]
-pub enum Orientation {
-    /// Portrait device orientation.
-    Portrait,
-    /// Landscape device orientation.
-    Landscape,
-}
-
-/// Holds device information.
-#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
-pub struct DeviceContext {
-    /// The name of the device.
+/// The device a profile was recorded on.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ProfileDevice {
+    /// The CPU architecture of the device.
+    #[serde(default)]
+    pub architecture: String,
+    /// Whether the device is an emulator.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_bool_opt"
+    )]
+    pub is_emulator: Option<bool>,
+    /// The locale of the device.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    /// The family of the device model.
+    pub locale: Option<String>,
+    /// The manufacturer of the device.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub family: Option<String>,
-    /// The device model (human readable).
+    pub manufacturer: Option<String>,
+    /// The model of the device.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
-    /// The device model (internal identifier).
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub model_id: Option<String>,
-    /// The native cpu architecture of the device.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub arch: Option<String>,
-    /// The current battery level (0-100).
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub battery_level: Option<f32>,
-    /// The current screen orientation.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub orientation: Option<Orientation>,
-    /// Simulator/prod indicator.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub simulator: Option<bool>,
-    /// Total memory available in byts.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub memory_size: Option<u64>,
-    /// How much memory is still available in bytes.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub free_memory: Option<u64>,
-    /// How much memory is usable for the app in bytes.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub usable_memory: Option<u64>,
-    /// Total storage size of the device in bytes.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub storage_size: Option<u64>,
-    /// How much storage is free in bytes.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub free_storage: Option<u64>,
-    /// Total size of the attached external storage in bytes (eg: android SDK card).
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub external_storage_size: Option<u64>,
-    /// Free size of the attached external storage in bytes (eg: android SDK card).
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub external_free_storage: Option<u64>,
-    /// Optionally an indicator when the device was booted.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub boot_time: Option<DateTime<Utc>>,
-    /// The timezone of the device.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub timezone: Option<String>,
-    /// Additional arbitrary fields for forwards compatibility.
-    #[serde(flatten)]
-    pub other: Map<String, Value>,
 }
 
-/// Holds operating system information.
-#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
-pub struct OsContext {
+/// The operating system a profile was recorded on.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ProfileOs {
     /// The name of the operating system.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
+    #[serde(default)]
+    pub name: String,
     /// The version of the operating system.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub version: Option<String>,
+    #[serde(default)]
+    pub version: String,
     /// The internal build number of the operating system.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub build: Option<String>,
-    /// The current kernel version.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub kernel_version: Option<String>,
-    /// An indicator if the os is rooted (mobile mostly).
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub rooted: Option<bool>,
-    /// Additional arbitrary fields for forwards compatibility.
-    #[serde(flatten)]
-    pub other: Map<String, Value>,
+    pub build_number: Option<String>,
 }
 
-/// Holds information about the runtime.
-#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
-pub struct RuntimeContext {
-    /// The name of the runtime (for instance JVM).
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
+/// The runtime a profile was recorded in.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ProfileRuntime {
+    /// The name of the runtime.
+    #[serde(default)]
+    pub name: String,
     /// The version of the runtime.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub version: Option<String>,
-    /// Additional arbitrary fields for forwards compatibility.
-    #[serde(flatten)]
-    pub other: Map<String, Value>,
+    #[serde(default)]
+    pub version: String,
 }
 
-/// Holds app information.
-#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
-pub struct AppContext {
-    /// Optional start time of the app.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub app_start_time: Option<DateTime<Utc>>,
-    /// Optional device app hash (app specific device ID)
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub device_app_hash: Option<String>,
-    /// Optional build identicator.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub build_type: Option<String>,
-    /// Optional app identifier (dotted bundle id).
+/// The transaction a profile was recorded for.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ProfileTransaction {
+    /// The event ID of the transaction.
+    #[serde(default, serialize_with = "event::serialize_id")]
+    pub id: Uuid,
+    /// The name of the transaction.
+    #[serde(default)]
+    pub name: String,
+    /// The trace ID of the transaction.
+    #[serde(default)]
+    pub trace_id: TraceId,
+    /// The thread the transaction was active on.
+    #[serde(default)]
+    pub active_thread_id: u64,
+}
+
+/// Represents a sampled profile.
+///
+/// Missing ids and timestamps are generated with the `clock` feature.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Profile {
+    /// The version of the profile format.
+    #[serde(default = "profile::default_version")]
+    pub version: String,
+    /// The unique ID of the profile.
+    #[serde(serialize_with = "event::serialize_id")]
+    #[cfg_attr(feature = "clock", serde(default = "event::default_id"))]
+    pub event_id: Uuid,
+    /// The time the profile was started.
+    #[cfg_attr(feature = "clock", serde(default = "event::default_timestamp"))]
+    pub timestamp: DateTime<Utc>,
+    /// The platform of the profiled application.
+    #[serde(default)]
+    pub platform: Platform,
+    /// The release of the profiled application.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub app_identifier: Option<String>,
-    /// Application name as it appears on the platform.
+    pub release: Option<String>,
+    /// The environment of the profiled application.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub app_name: Option<String>,
-    /// Application version as it appears on the platform.
+    pub environment: Option<String>,
+    /// The device the profile was recorded on.
+    #[serde(default)]
+    pub device: ProfileDevice,
+    /// The operating system the profile was recorded on.
+    #[serde(default)]
+    pub os: ProfileOs,
+    /// The runtime the profile was recorded in.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub app_version: Option<String>,
-    /// Internal build ID as it appears on the platform.
+    pub runtime: Option<ProfileRuntime>,
+    /// The transaction the profile belongs to.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub app_build: Option<String>,
+    pub transaction: Option<ProfileTransaction>,
+    /// Debug information for symbolication.
+    #[serde(default, skip_serializing_if = "DebugMeta::is_empty")]
+    pub debug_meta: DebugMeta,
+    /// The sampled data.
+    #[serde(default)]
+    pub profile: ProfileData,
     /// Additional arbitrary fields for forwards compatibility.
     #[serde(flatten)]
     pub other: Map<String, Value>,
 }
 
-/// Holds information about the web browser.
-#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
-pub struct BrowserContext {
-    /// The name of the browser (for instance "Chrome").
+mod profile {
+    pub fn default_version() -> String {
+        "1".into()
+    }
+}
+
+/// Creates a profile started now.
+///
+/// This requires the `clock` feature.
+#[cfg(feature = "clock")]
+impl Default for Profile {
+    fn default() -> Profile {
+        Profile {
+            version: profile::default_version(),
+            event_id: random_uuid(),
+            timestamp: now(),
+            platform: Default::default(),
+            release: None,
+            environment: None,
+            device: Default::default(),
+            os: Default::default(),
+            runtime: None,
+            transaction: None,
+            debug_meta: Default::default(),
+            profile: Default::default(),
+            other: Default::default(),
+        }
+    }
+}
+
+/// Represents a session replay event.
+///
+/// Replay events describe a segment of a session replay.  The recording of
+/// the segment is sent separately in a `replay_recording` envelope item.
+/// Missing ids and timestamps are generated with the `clock` feature.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReplayEvent {
+    /// The ID of the replay.
+    #[serde(serialize_with = "event::serialize_id")]
+    #[cfg_attr(feature = "clock", serde(default = "event::default_id"))]
+    pub replay_id: Uuid,
+    /// The sequence number of the segment within the replay.
+    #[serde(default)]
+    pub segment_id: u64,
+    /// The type of the replay, such as `session` or `buffer`.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    /// The version of the browser.
+    pub replay_type: Option<String>,
+    /// The timestamp of the end of the segment.
+    #[serde(with = "ts_seconds_float")]
+    #[cfg_attr(feature = "clock", serde(default = "event::default_timestamp"))]
+    pub timestamp: DateTime<Utc>,
+    /// The timestamp of when the replay started.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "ts_seconds_float_opt"
+    )]
+    pub replay_start_timestamp: Option<DateTime<Utc>>,
+    /// The URLs visited during the segment.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub urls: Vec<String>,
+    /// The IDs of error events that occurred during the segment.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub error_ids: Vec<Uuid>,
+    /// The IDs of traces that occurred during the segment.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trace_ids: Vec<TraceId>,
+    /// A platform identifier for this replay.
+    #[serde(default, skip_serializing_if = "Platform::is_other")]
+    pub platform: Platform,
+    /// A release identifier.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub version: Option<String>,
+    pub release: Option<String>,
+    /// An optional distribution identifer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dist: Option<String>,
+    /// An optional environment identifier.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    /// Optionally user data of the replay.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<User>,
+    /// Optionally HTTP request data of the replay.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<Request>,
+    /// Optional contexts.
+    #[serde(
+        default,
+        deserialize_with = "contexts::deserialize",
+        skip_serializing_if = "Map::is_empty"
+    )]
+    pub contexts: Map<String, Context>,
+    /// Optional tags attached to the replay.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub tags: Map<String, String>,
+    /// SDK metadata
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sdk: Option<ClientSdkInfo>,
     /// Additional arbitrary fields for forwards compatibility.
     #[serde(flatten)]
     pub other: Map<String, Value>,
 }
 
-macro_rules! into_context {
-    ($kind:ident, $ty:ty) => {
-        impl From<$ty> for Context {
-            fn from(data: $ty) -> Self {
-                Context::$kind(Box::new(data))
-            }
+/// Creates a replay event for a new replay ending now.
+///
+/// This requires the `clock` feature.
+#[cfg(feature = "clock")]
+impl Default for ReplayEvent {
+    fn default() -> ReplayEvent {
+        ReplayEvent {
+            replay_id: random_uuid(),
+            segment_id: 0,
+            replay_type: None,
+            timestamp: now(),
+            replay_start_timestamp: None,
+            urls: Vec::new(),
+            error_ids: Vec::new(),
+            trace_ids: Vec::new(),
+            platform: Default::default(),
+            release: None,
+            dist: None,
+            environment: None,
+            user: None,
+            request: None,
+            contexts: Default::default(),
+            tags: Default::default(),
+            sdk: None,
+            other: Default::default(),
         }
-    };
+    }
 }
 
-into_context!(App, AppContext);
-into_context!(Device, DeviceContext);
-into_context!(Os, OsContext);
-into_context!(Runtime, RuntimeContext);
-into_context!(Browser, BrowserContext);
-
-mod event {
-    use super::*;
+/// The type of an event.
+///
+/// Types not known to this crate are kept in `EventType::Unknown`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum EventType {
+    /// An error or crash.
+    Error,
+    /// A message or other event without an exception.
+    #[default]
+    Default,
+    /// A performance monitoring transaction.
+    Transaction,
+    /// A content security policy violation report.
+    Csp,
+    /// An HTTP public key pinning violation report.
+    Hpkp,
+    /// A certificate transparency violation report.
+    ExpectCt,
+    /// An OCSP stapling violation report.
+    ExpectStaple,
+    /// User feedback sent as an event.
+    UserReportV2,
+    /// An event type not known to this crate.
+    Unknown(String),
+}
 
-    pub fn default_id() -> Uuid {
-        Uuid::new_v4()
+impl EventType {
+    /// Returns the name of the event type.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            EventType::Error => "error",
+            EventType::Default => "default",
+            EventType::Transaction => "transaction",
+            EventType::Csp => "csp",
+            EventType::Hpkp => "hpkp",
+            EventType::ExpectCt => "expectct",
+            EventType::ExpectStaple => "expectstaple",
+            EventType::UserReportV2 => "userreportv2",
+            EventType::Unknown(ref ty) => ty,
+        }
     }
 
-    pub fn serialize_id<S: Serializer>(uuid: &Uuid, serializer: S) -> Result<S::Ok, S::Error> {
-        serializer.serialize_some(&uuid.to_simple_ref().to_string())
+    /// Returns true if this is a security report.
+    pub fn is_security_report(&self) -> bool {
+        matches!(
+            *self,
+            EventType::Csp | EventType::Hpkp | EventType::ExpectCt | EventType::ExpectStaple
+        )
     }
+}
 
-    pub fn default_level() -> Level {
-        Level::Error
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
     }
+}
 
-    pub fn default_platform() -> Cow<'static, str> {
-        Cow::Borrowed("other")
-    }
+impl str::FromStr for EventType {
+    type Err = Infallible;
 
-    pub fn is_default_platform(value: &str) -> bool {
-        value == "other"
+    fn from_str(string: &str) -> Result<EventType, Self::Err> {
+        Ok(match string {
+            "error" => EventType::Error,
+            "default" => EventType::Default,
+            "transaction" => EventType::Transaction,
+            "csp" => EventType::Csp,
+            "hpkp" => EventType::Hpkp,
+            "expectct" => EventType::ExpectCt,
+            "expectstaple" => EventType::ExpectStaple,
+            "userreportv2" => EventType::UserReportV2,
+            other => EventType::Unknown(other.to_string()),
+        })
     }
+}
 
-    static DEFAULT_FINGERPRINT: &[Cow<'static, str>] = &[Cow::Borrowed("{{ default }}")];
+impl_str_serde!(EventType);
 
-    pub fn default_fingerprint<'a>() -> Cow<'a, [Cow<'a, str>]> {
-        Cow::Borrowed(DEFAULT_FINGERPRINT)
-    }
+/// An error that occurred while processing an event.
+///
+/// Sentry shows these errors as "Processing Errors" of the event.  The
+/// associated constants contain the error types known to Sentry.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct EventProcessingError {
+    /// The type of the error, such as `invalid_data`.
+    #[serde(rename = "type", default)]
+    pub ty: String,
+    /// The path of the affected field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The original value of the affected field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+}
 
-    #[allow(clippy::ptr_arg)]
-    pub fn is_default_fingerprint<'a>(fp: &[Cow<'a, str>]) -> bool {
-        fp.len() == 1 && ((&fp)[0] == "{{ default }}" || (&fp)[0] == "{{default}}")
+impl EventProcessingError {
+    /// A field contained invalid data.
+    pub const INVALID_DATA: &'static str = "invalid_data";
+    /// A field was not valid for the event.
+    pub const INVALID_ATTRIBUTE: &'static str = "invalid_attribute";
+    /// A required field was missing.
+    pub const MISSING_ATTRIBUTE: &'static str = "missing_attribute";
+    /// A value exceeded its maximum length.
+    pub const VALUE_TOO_LONG: &'static str = "value_too_long";
+    /// The timestamp was too far in the future.
+    pub const FUTURE_TIMESTAMP: &'static str = "future_timestamp";
+    /// The timestamp was too far in the past.
+    pub const PAST_TIMESTAMP: &'static str = "past_timestamp";
+    /// The clock of the client was off and its timestamps were corrected.
+    pub const CLOCK_DRIFT: &'static str = "clock_drift";
+    /// The environment was not valid.
+    pub const INVALID_ENVIRONMENT: &'static str = "invalid_environment";
+
+    /// Creates an error of the given type for a field.
+    pub fn new<T, N>(ty: T, name: N, value: Option<Value>) -> EventProcessingError
+    where
+        T: Into<String>,
+        N: Into<String>,
+    {
+        EventProcessingError {
+            ty: ty.into(),
+            name: Some(name.into()),
+            value,
+        }
     }
+}
 
-    pub fn default_timestamp() -> DateTime<Utc> {
-        Utc::now()
+/// Keys of tags that Sentry derives from event fields.
+///
+/// Sentry fills these tags from the fields of the event, overriding tags set
+/// by the client.  Use `Event::tag` and `Event::set_tag` to access them
+/// through the fields.
+pub mod tag_keys {
+    /// The level of the event.
+    pub const LEVEL: &str = "level";
+    /// The logger of the event.
+    pub const LOGGER: &str = "logger";
+    /// The server name of the event.
+    pub const SERVER_NAME: &str = "server_name";
+    /// The transaction of the event.
+    pub const TRANSACTION: &str = "transaction";
+    /// The environment of the event.
+    pub const ENVIRONMENT: &str = "environment";
+    /// The release of the event.
+    pub const RELEASE: &str = "sentry:release";
+    /// The distribution of the event.
+    pub const DIST: &str = "sentry:dist";
+    /// Whether the exceptions of the event were handled.
+    pub const HANDLED: &str = "handled";
+    /// The mechanism type of the first exception of the event.
+    pub const MECHANISM: &str = "mechanism";
+
+    /// Returns the canonical key of a tag derived from an event field.
+    ///
+    /// `release` and `dist` are accepted as aliases of their `sentry:`
+    /// prefixed keys, as clients commonly set them without the prefix.
+    pub fn canonical(key: &str) -> Option<&'static str> {
+        Some(match key {
+            LEVEL => LEVEL,
+            LOGGER => LOGGER,
+            SERVER_NAME => SERVER_NAME,
+            TRANSACTION => TRANSACTION,
+            ENVIRONMENT => ENVIRONMENT,
+            RELEASE | "release" => RELEASE,
+            DIST | "dist" => DIST,
+            HANDLED => HANDLED,
+            MECHANISM => MECHANISM,
+            _ => return None,
+        })
     }
 }
 
+/// An error returned by `Event::set_tag`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SetTagError {
+    /// Raised if the value is not a valid release.
+    #[error("invalid release")]
+    InvalidRelease(#[from] InvalidReleaseError),
+    /// Raised if the value is not a valid environment.
+    #[error("invalid environment")]
+    InvalidEnvironment(#[from] InvalidEnvironmentError),
+    /// Raised if the value is not a valid level.
+    #[error("invalid level")]
+    InvalidLevel,
+    /// Raised if the tag is computed from other fields and cannot be set.
+    #[error("tag is derived from the event")]
+    Derived,
+}
+
 /// Represents a full event for Sentry.
+///
+/// Fields that are parsed leniently drop values they cannot parse instead of
+/// rejecting the event.  Dropped values are recorded in `errors` and as an
+/// error with the original value in the `meta` of the field.  Coerced values
+/// are marked with a `!coerce` remark in the `meta` of the field.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(remote = "Self")]
 pub struct Event<'a> {
     /// The ID of the event
-    #[serde(default = "event::default_id", serialize_with = "event::serialize_id")]
+    #[serde(serialize_with = "event::serialize_id")]
+    #[cfg_attr(feature = "clock", serde(default = "event::default_id"))]
     pub event_id: Uuid,
     /// The level of the event (defaults to error)
     #[serde(
@@ -1281,6 +5226,9 @@ pub struct Event<'a> {
         skip_serializing_if = "Level::is_error"
     )]
     pub level: Level,
+    /// The type of the event, inferred by Sentry if missing.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub ty: Option<EventType>,
     /// An optional fingerprint configuration to override the default.
     #[serde(
         default = "event::default_fingerprint",
@@ -1293,6 +5241,9 @@ pub struct Event<'a> {
     /// The transaction name of the event.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub transaction: Option<String>,
+    /// Additional information about the transaction name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_info: Option<TransactionInfo>,
     /// A message to be sent with the event.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
@@ -1307,28 +5258,41 @@ pub struct Event<'a> {
     #[serde(default, skip_serializing_if = "Map::is_empty")]
     pub modules: Map<String, String>,
     /// A platform identifier for this event.
-    #[serde(
-        default = "event::default_platform",
-        skip_serializing_if = "event::is_default_platform"
-    )]
-    pub platform: Cow<'a, str>,
+    #[serde(default, skip_serializing_if = "Platform::is_other")]
+    pub platform: Platform,
     /// The timestamp of when the event was created.
     ///
     /// This can be set to `None` in which case the server will set a timestamp.
-    #[serde(default = "event::default_timestamp", with = "ts_seconds_float")]
+    #[serde(with = "ts_seconds_float")]
+    #[cfg_attr(feature = "clock", serde(default = "event::default_timestamp"))]
     pub timestamp: DateTime<Utc>,
+    /// The timestamp of when a transaction started.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "ts_seconds_float_opt"
+    )]
+    pub start_timestamp: Option<DateTime<Utc>>,
     /// Optionally the server (or device) name of this event.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub server_name: Option<Cow<'a, str>>,
     /// A release identifier.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub release: Option<Cow<'a, str>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_release"
+    )]
+    pub release: Option<Release<'a>>,
     /// An optional distribution identifer.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub dist: Option<Cow<'a, str>>,
     /// An optional environment identifier.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub environment: Option<Cow<'a, str>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "lenient::deserialize_environment"
+    )]
+    pub environment: Option<Environment<'a>>,
     /// Optionally user data to be sent along.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub user: Option<User>,
@@ -1336,7 +5300,11 @@ pub struct Event<'a> {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub request: Option<Request>,
     /// Optional contexts.
-    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "contexts::deserialize",
+        skip_serializing_if = "Map::is_empty"
+    )]
     pub contexts: Map<String, Context>,
     /// List of breadcrumbs to send along.
     #[serde(default, skip_serializing_if = "Values::is_empty")]
@@ -1353,34 +5321,95 @@ pub struct Event<'a> {
     /// A list of threads.
     #[serde(default, skip_serializing_if = "Values::is_empty")]
     pub threads: Values<Thread>,
+    /// The spans of a transaction.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<Span>,
     /// Optional tags to be attached to the event.
     #[serde(default, skip_serializing_if = "Map::is_empty")]
-    pub tags: Map<String, String>,
+    pub tags: Map<ThinString, String>,
     /// Optional extra information to be sent with the event.
     #[serde(default, skip_serializing_if = "Map::is_empty")]
-    pub extra: Map<String, Value>,
+    pub extra: Map<ThinString, extra::Value>,
     /// Debug meta information.
     #[serde(default, skip_serializing_if = "DebugMeta::is_empty")]
     pub debug_meta: Cow<'a, DebugMeta>,
     /// SDK metadata
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sdk: Option<Cow<'a, ClientSdkInfo>>,
+    /// Errors that occurred while processing the event.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<EventProcessingError>,
+    /// Meta data about modifications of the event's values.
+    #[serde(rename = "_meta", default, skip_serializing_if = "MetaTree::is_empty")]
+    pub meta: MetaTree,
+}
+
+impl<'a> Serialize for Event<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Event::serialize(self, serializer)
+    }
 }
 
+impl<'de, 'a> Deserialize<'de> for Event<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Event<'a>, D::Error> {
+        let (event, records) =
+            lenient::collect(|| Event::deserialize(lenient::Tracked(deserializer)));
+        let mut event = event?;
+        for record in records {
+            let meta = event.meta.get_mut(&record.path);
+            if record.dropped {
+                let kind = EventProcessingError::INVALID_DATA;
+                meta.errors.push(match record.reason {
+                    Some(reason) => serde_json::json!([kind, { "reason": reason }]),
+                    None => kind.into(),
+                });
+                meta.original_value = Some(record.value.clone());
+                event.errors.push(EventProcessingError {
+                    ty: kind.into(),
+                    name: Some(record.path).filter(|path| !path.is_empty()),
+                    value: Some(record.value),
+                });
+            } else {
+                let remark = Remark::new(RemarkType::Substituted, "!coerce");
+                if !meta.remarks.contains(&remark) {
+                    meta.remarks.push(remark);
+                }
+            }
+        }
+        Ok(event)
+    }
+}
+
+/// Creates an event with a random id, recorded now.
+///
+/// This requires the `clock` feature.
+#[cfg(feature = "clock")]
 impl<'a> Default for Event<'a> {
     fn default() -> Self {
+        Event::with_id(event::default_id(), event::default_timestamp())
+    }
+}
+
+impl<'a> Event<'a> {
+    /// Creates a new event with the given id and timestamp.
+    ///
+    /// Unlike `Event::new`, this does not require the `clock` feature.
+    pub fn with_id(event_id: Uuid, timestamp: DateTime<Utc>) -> Event<'a> {
         Event {
-            event_id: event::default_id(),
+            event_id,
             level: event::default_level(),
+            ty: None,
             fingerprint: event::default_fingerprint(),
             culprit: Default::default(),
             transaction: Default::default(),
+            transaction_info: Default::default(),
             message: Default::default(),
             logentry: Default::default(),
             logger: Default::default(),
             modules: Default::default(),
-            platform: event::default_platform(),
-            timestamp: event::default_timestamp(),
+            platform: Default::default(),
+            timestamp,
+            start_timestamp: None,
             server_name: Default::default(),
             release: Default::default(),
             dist: Default::default(),
@@ -1393,25 +5422,494 @@ impl<'a> Default for Event<'a> {
             stacktrace: Default::default(),
             template: Default::default(),
             threads: Default::default(),
+            spans: Default::default(),
             tags: Default::default(),
             extra: Default::default(),
             debug_meta: Default::default(),
             sdk: Default::default(),
+            errors: Default::default(),
+            meta: Default::default(),
+        }
+    }
+}
+
+impl_bounded_setters!(meta Event<'a> {
+    set_culprit => culprit: MAX_CULPRIT_LENGTH,
+    set_transaction => transaction: MAX_TRANSACTION_LENGTH,
+    set_message => message: MAX_MESSAGE_LENGTH,
+    set_server_name => server_name: MAX_SERVER_NAME_LENGTH,
+    set_dist => dist: MAX_DIST_LENGTH,
+});
+
+/// Truncates and validates a name for the setters of `Event`.
+///
+/// Invalid names are dropped and described by the returned meta data.
+fn truncate_name<T, E, F>(value: String, max_chars: usize, new: F) -> (Option<T>, Option<Meta>)
+where
+    E: fmt::Display,
+    F: FnOnce(String) -> Result<T, E>,
+{
+    let original_value = value.clone();
+    let (value, meta) = truncate_value(value, max_chars);
+    match new(value) {
+        Ok(value) => (Some(value), meta),
+        Err(error) => {
+            let kind = EventProcessingError::INVALID_DATA;
+            let meta = Meta {
+                errors: vec![serde_json::json!([kind, { "reason": error.to_string() }])],
+                original_value: Some(original_value.into()),
+                ..Default::default()
+            };
+            (None, Some(meta))
         }
     }
 }
 
+impl<'a> Event<'a> {
+    /// Sets the `release` field, truncating it to `MAX_RELEASE_LENGTH` characters.
+    ///
+    /// If the value was truncated or is not a valid release, the returned meta
+    /// data is also recorded in the meta data of the field.  Invalid releases
+    /// clear the field.
+    pub fn set_release<S: Into<String>>(&mut self, value: S) -> Option<Meta> {
+        let (release, meta) = truncate_name(value.into(), MAX_RELEASE_LENGTH, Release::new);
+        self.release = release;
+        *self.meta.get_mut("release") = meta.clone().unwrap_or_default();
+        meta
+    }
+
+    /// Sets the `environment` field, truncating it to `MAX_ENVIRONMENT_LENGTH`
+    /// characters.
+    ///
+    /// If the value was truncated or is not a valid environment, the returned
+    /// meta data is also recorded in the meta data of the field.  Invalid
+    /// environments clear the field.
+    pub fn set_environment<S: Into<String>>(&mut self, value: S) -> Option<Meta> {
+        let (environment, meta) =
+            truncate_name(value.into(), MAX_ENVIRONMENT_LENGTH, Environment::new);
+        self.environment = environment;
+        *self.meta.get_mut("environment") = meta.clone().unwrap_or_default();
+        meta
+    }
+}
+
 impl<'a> Event<'a> {
     /// Creates a new event with the current timestamp and random id.
+    ///
+    /// This requires the `clock` feature.
+    #[cfg(feature = "clock")]
     pub fn new() -> Event<'a> {
         Default::default()
     }
 
+    /// Sets the logger, fixing up names that would be rejected by Sentry.
+    ///
+    /// Invalid names are sanitized and shortened instead of being rejected.
+    /// If the name had to be changed, a remark along with the original length
+    /// is recorded in the meta data of the `logger` field, replacing any
+    /// previous meta data.  Names that are empty after sanitization unset the
+    /// logger and are recorded as an `invalid_data` processing error.
+    pub fn set_logger_lenient(&mut self, logger: &str) {
+        self.errors
+            .retain(|error| error.name.as_deref() != Some("logger"));
+        let meta = self.meta.get_mut("logger");
+        *meta = Meta::default();
+
+        if validate_logger(logger).is_ok() {
+            self.logger = Some(logger.to_string());
+            return;
+        }
+
+        let sanitized = sanitize_logger(logger);
+        meta.original_length = Some(logger.chars().count() as u64);
+        if sanitized.is_empty() {
+            meta.remarks
+                .push(Remark::new(RemarkType::Removed, "@logger:remove"));
+            self.logger = None;
+            self.errors.push(EventProcessingError::new(
+                EventProcessingError::INVALID_DATA,
+                "logger",
+                Some(logger.into()),
+            ));
+        } else {
+            meta.remarks
+                .push(Remark::new(RemarkType::Substituted, "@logger:replace"));
+            self.logger = Some(sanitized);
+        }
+    }
+
+    /// Sets the breadcrumbs of the event from a bounded list.
+    ///
+    /// If breadcrumbs were dropped, the total number of breadcrumbs is
+    /// recorded as original length in the meta data of `breadcrumbs.values`.
+    pub fn set_breadcrumbs(&mut self, breadcrumbs: Breadcrumbs) {
+        let dropped = breadcrumbs.dropped();
+        let original_length = breadcrumbs.len() + dropped;
+        self.breadcrumbs = breadcrumbs.into_values();
+
+        if dropped > 0 {
+            self.meta.get_mut("breadcrumbs.values").original_length = Some(original_length as u64);
+        }
+    }
+
+    /// Fills unset fields from another, partial event such as a scope.
+    ///
+    /// Values of this event always take precedence:
+    ///
+    /// - Optional fields such as `user`, `request`, `transaction`, `release`
+    ///   or `environment` are copied only if they are unset.  They are not
+    ///   merged field by field.
+    /// - The fingerprint is copied only if this event has the default one.
+    /// - Entries of `tags`, `extra`, `contexts` and `modules` are copied
+    ///   unless this event already has an entry with the same key.
+    /// - Breadcrumbs of `defaults` are inserted before the breadcrumbs of
+    ///   this event, as they were recorded earlier.
+    ///
+    /// Identity fields such as `event_id`, `timestamp`, `level` and the
+    /// payload of the event (exceptions, threads, stack traces) are never
+    /// copied.
+    pub fn merge_defaults(&mut self, defaults: &Event<'_>) {
+        fn fill<T: Clone>(value: &mut Option<T>, default: &Option<T>) {
+            if value.is_none() {
+                *value = default.clone();
+            }
+        }
+
+        fn fill_map<K: Clone + Ord, V: Clone>(map: &mut Map<K, V>, defaults: &Map<K, V>) {
+            for (key, value) in defaults {
+                if !map.contains_key(key) {
+                    map.insert(key.clone(), value.clone());
+                }
+            }
+        }
+
+        fill(&mut self.culprit, &defaults.culprit);
+        fill(&mut self.transaction, &defaults.transaction);
+        fill(&mut self.transaction_info, &defaults.transaction_info);
+        fill(&mut self.logger, &defaults.logger);
+        fill(&mut self.user, &defaults.user);
+        fill(&mut self.request, &defaults.request);
+
+        if event::is_default_fingerprint(&self.fingerprint) {
+            self.fingerprint = Cow::Owned(
+                defaults
+                    .fingerprint
+                    .iter()
+                    .map(|part| Cow::Owned(part.to_string()))
+                    .collect(),
+            );
+        }
+        if self.server_name.is_none() {
+            self.server_name = defaults
+                .server_name
+                .as_ref()
+                .map(|name| Cow::Owned(name.to_string()));
+        }
+        if self.release.is_none() {
+            self.release = defaults.release.clone().map(Release::into_owned);
+        }
+        if self.dist.is_none() {
+            self.dist = defaults
+                .dist
+                .as_ref()
+                .map(|dist| Cow::Owned(dist.to_string()));
+        }
+        if self.environment.is_none() {
+            self.environment = defaults.environment.clone().map(Environment::into_owned);
+        }
+        if self.sdk.is_none() {
+            self.sdk = defaults
+                .sdk
+                .as_ref()
+                .map(|sdk| Cow::Owned(sdk.clone().into_owned()));
+        }
+
+        fill_map(&mut self.tags, &defaults.tags);
+        fill_map(&mut self.extra, &defaults.extra);
+        fill_map(&mut self.contexts, &defaults.contexts);
+        fill_map(&mut self.modules, &defaults.modules);
+
+        if !defaults.breadcrumbs.is_empty() {
+            let own = mem::take(&mut self.breadcrumbs.values);
+            self.breadcrumbs.values = defaults.breadcrumbs.values.clone();
+            self.breadcrumbs.values.extend(own);
+        }
+    }
+
+    /// Returns the type of the event.
+    ///
+    /// If the type is not set, it is inferred like Sentry does: events with
+    /// exceptions are errors, all others are default events.
+    pub fn event_type(&self) -> EventType {
+        match self.ty {
+            Some(ref ty) => ty.clone(),
+            None if !self.exception.is_empty() => EventType::Error,
+            None => EventType::Default,
+        }
+    }
+
+    /// Returns the server name of the event.
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+
+    /// Returns the release of the event.
+    pub fn release(&self) -> Option<&str> {
+        self.release.as_deref()
+    }
+
+    /// Returns the distribution of the event.
+    pub fn dist(&self) -> Option<&str> {
+        self.dist.as_deref()
+    }
+
+    /// Returns the environment of the event.
+    pub fn environment(&self) -> Option<&str> {
+        self.environment.as_deref()
+    }
+
+    /// Returns the transaction of the event as used for the `transaction` tag.
+    pub fn transaction_tag(&self) -> Option<&str> {
+        self.transaction.as_deref()
+    }
+
+    /// Returns whether the exceptions of the event were handled.
+    ///
+    /// Like Sentry, the event counts as unhandled if any exception mechanism
+    /// is unhandled, and as handled if any other is marked handled.
+    pub fn handled(&self) -> Option<bool> {
+        let mut handled = None;
+        for exception in self.exception.iter() {
+            match exception.mechanism.as_ref().and_then(|m| m.handled) {
+                Some(false) => return Some(false),
+                Some(true) => handled = Some(true),
+                None => {}
+            }
+        }
+        handled
+    }
+
+    /// Returns the value of a tag.
+    ///
+    /// Tags derived from event fields, listed in `tag_keys`, are read from
+    /// their fields.  All other tags are read from `tags`.
+    pub fn tag(&self, key: &str) -> Option<Cow<'_, str>> {
+        let value = match tag_keys::canonical(key) {
+            Some(tag_keys::LEVEL) => return Some(Cow::Owned(self.level.to_string())),
+            Some(tag_keys::LOGGER) => self.logger.as_deref(),
+            Some(tag_keys::SERVER_NAME) => self.server_name(),
+            Some(tag_keys::TRANSACTION) => self.transaction_tag(),
+            Some(tag_keys::ENVIRONMENT) => self.environment(),
+            Some(tag_keys::RELEASE) => self.release(),
+            Some(tag_keys::DIST) => self.dist(),
+            Some(tag_keys::HANDLED) => {
+                return self
+                    .handled()
+                    .map(|handled| Cow::Borrowed(if handled { "yes" } else { "no" }))
+            }
+            Some(tag_keys::MECHANISM) => self
+                .exception
+                .iter()
+                .find_map(|e| e.mechanism.as_ref())
+                .map(|m| m.ty.as_str()),
+            _ => self.tags.get(key).map(String::as_str),
+        };
+        value.map(Cow::Borrowed)
+    }
+
+    /// Sets the value of a tag.
+    ///
+    /// Tags derived from event fields, listed in `tag_keys`, are written to
+    /// their fields instead of `tags`, so that Sentry does not override them.
+    /// Values of length limited fields are truncated like with their bounded
+    /// setters, and loggers are sanitized with `set_logger_lenient`.
+    pub fn set_tag<K, V>(&mut self, key: K, value: V) -> Result<(), SetTagError>
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let key = key.into();
+        let value = value.into();
+        match tag_keys::canonical(&key) {
+            Some(tag_keys::LEVEL) => {
+                self.level = value.parse().map_err(|_| SetTagError::InvalidLevel)?;
+            }
+            Some(tag_keys::LOGGER) => self.set_logger_lenient(&value),
+            Some(tag_keys::SERVER_NAME) => {
+                self.set_server_name(value);
+            }
+            Some(tag_keys::TRANSACTION) => {
+                self.set_transaction(value);
+            }
+            Some(tag_keys::ENVIRONMENT) => self.environment = Some(Environment::new(value)?),
+            Some(tag_keys::RELEASE) => self.release = Some(Release::new(value)?),
+            Some(tag_keys::DIST) => {
+                self.set_dist(value);
+            }
+            Some(_) => return Err(SetTagError::Derived),
+            None => {
+                self.tags.insert(key.into(), value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the thread that crashed, if any.
+    pub fn crashed_thread(&self) -> Option<&Thread> {
+        self.threads.iter().find(|thread| thread.crashed)
+    }
+
+    /// Links exceptions without a stack trace to the crashed thread.
+    ///
+    /// The crashed thread is the first thread flagged as `crashed`, otherwise
+    /// the thread referenced by the last exception, the `current` thread or
+    /// the only thread of the event, in this order.  It is then flagged as
+    /// the only crashed thread, and as current if no thread is.  Exceptions
+    /// without a stack trace or thread reference point to it via
+    /// `thread_id`, so that Sentry shows the thread's stack trace.
+    ///
+    /// Returns the id of the crashed thread, or `None` if there is no such
+    /// thread or it has no id.
+    pub fn link_crashed_thread(&mut self) -> Option<ThreadId> {
+        let referenced = self.exception.last().and_then(|e| e.thread_id.as_ref());
+        let index = self
+            .threads
+            .iter()
+            .position(|thread| thread.crashed)
+            .or_else(|| {
+                let referenced = referenced?;
+                self.threads
+                    .iter()
+                    .position(|thread| thread.id.as_ref() == Some(referenced))
+            })
+            .or_else(|| self.threads.iter().position(|thread| thread.current))
+            .or(if self.threads.len() == 1 {
+                Some(0)
+            } else {
+                None
+            })?;
+
+        let has_current = self.threads.iter().any(|thread| thread.current);
+        for (i, thread) in self.threads.iter_mut().enumerate() {
+            thread.crashed = i == index;
+            if i == index && !has_current {
+                thread.current = true;
+            }
+        }
+
+        let thread_id = self.threads[index].id.clone()?;
+        for exception in self.exception.iter_mut() {
+            if exception.stacktrace.is_none() && exception.thread_id.is_none() {
+                exception.thread_id = Some(thread_id.clone());
+            }
+        }
+        Some(thread_id)
+    }
+
+    /// Returns the searchable fields of the event as key/value pairs.
+    ///
+    /// This mirrors how Sentry indexes events: built-in fields come first
+    /// (using the `sentry:` prefixed keys for release, dist and user),
+    /// followed by all tags.  Tags that collide with a built-in key are
+    /// skipped since the built-in value takes precedence.
+    pub fn index_fields(&self) -> Vec<(&str, Cow<'_, str>)> {
+        let mut fields: Vec<(&str, Cow<'_, str>)> = Vec::new();
+        fields.push((tag_keys::LEVEL, Cow::Owned(self.level.to_string())));
+
+        let builtins = [
+            (tag_keys::LOGGER, self.logger.as_deref()),
+            (tag_keys::SERVER_NAME, self.server_name()),
+            (tag_keys::TRANSACTION, self.transaction_tag()),
+            (tag_keys::ENVIRONMENT, self.environment()),
+            (tag_keys::RELEASE, self.release()),
+            (tag_keys::DIST, self.dist()),
+        ];
+        for &(key, value) in builtins.iter() {
+            if let Some(value) = value {
+                fields.push((key, Cow::Borrowed(value)));
+            }
+        }
+
+        if let Some(ref user) = self.user {
+            let ip = user.ip_address.and_then(|ip| match ip {
+                IpAddress::Exact(addr) => Some(addr.to_string()),
+                IpAddress::Auto => None,
+            });
+            let tag = user
+                .id
+                .as_ref()
+                .map(|id| format!("id:{}", id))
+                .or_else(|| user.email.as_ref().map(|email| format!("email:{}", email)))
+                .or_else(|| {
+                    user.username
+                        .as_ref()
+                        .map(|name| format!("username:{}", name))
+                })
+                .or_else(|| ip.as_ref().map(|ip| format!("ip:{}", ip)));
+
+            if let Some(tag) = tag {
+                fields.push(("sentry:user", Cow::Owned(tag)));
+            }
+            if let Some(ref id) = user.id {
+                fields.push(("user.id", Cow::Borrowed(id)));
+            }
+            if let Some(ref email) = user.email {
+                fields.push(("user.email", Cow::Borrowed(email)));
+            }
+            if let Some(ref username) = user.username {
+                fields.push(("user.username", Cow::Borrowed(username)));
+            }
+            if let Some(ip) = ip {
+                fields.push(("user.ip", Cow::Owned(ip)));
+            }
+        }
+
+        if let Some(ref sdk) = self.sdk {
+            fields.push(("sdk.name", Cow::Borrowed(&sdk.name)));
+            fields.push(("sdk.version", Cow::Borrowed(&sdk.version)));
+        }
+
+        for (key, value) in &self.tags {
+            if !fields.iter().any(|(existing, _)| *existing == key.as_str()) {
+                fields.push((key, Cow::Borrowed(value)));
+            }
+        }
+
+        fields
+    }
+
+    /// Returns the differences between this event and another one.
+    ///
+    /// Both events are compared in their serialized form.  Differences are
+    /// keyed by dotted paths as used in `_meta`, such as `"exception.values.0.type"`,
+    /// and sorted by path.  Only the innermost differing values are reported.
+    pub fn diff(&self, other: &Event<'_>) -> Vec<FieldDiff> {
+        let mut diffs = Vec::new();
+        // serializing events cannot fail
+        let left = value::to_value(self).unwrap_or(Value::Null);
+        let right = value::to_value(other).unwrap_or(Value::Null);
+        diff_values(&mut Vec::new(), Some(&left), Some(&right), &mut diffs);
+        diffs
+    }
+
+    /// Serializes the event into canonical JSON.
+    ///
+    /// The output has object keys sorted at every level and no whitespace, so
+    /// equal events always produce the same bytes.  This makes it suitable for
+    /// hashing and signing.
+    pub fn to_canonical_json(&self) -> Result<String, serde_json::Error> {
+        let value = value::to_value(self)?;
+        let mut json = String::new();
+        write_canonical_json(&value, &mut json);
+        Ok(json)
+    }
+
     /// Creates a fully owned version of the event.
     pub fn into_owned(self) -> Event<'static> {
         Event {
             event_id: self.event_id,
             level: self.level,
+            ty: self.ty,
             fingerprint: Cow::Owned(
                 self.fingerprint
                     .iter()
@@ -1420,16 +5918,18 @@ impl<'a> Event<'a> {
             ),
             culprit: self.culprit,
             transaction: self.transaction,
+            transaction_info: self.transaction_info,
             message: self.message,
             logentry: self.logentry,
             logger: self.logger,
             modules: self.modules,
-            platform: Cow::Owned(self.platform.into_owned()),
+            platform: self.platform,
             timestamp: self.timestamp,
+            start_timestamp: self.start_timestamp,
             server_name: self.server_name.map(|x| Cow::Owned(x.into_owned())),
-            release: self.release.map(|x| Cow::Owned(x.into_owned())),
+            release: self.release.map(Release::into_owned),
             dist: self.dist.map(|x| Cow::Owned(x.into_owned())),
-            environment: self.environment.map(|x| Cow::Owned(x.into_owned())),
+            environment: self.environment.map(Environment::into_owned),
             user: self.user,
             request: self.request,
             contexts: self.contexts,
@@ -1438,16 +5938,249 @@ impl<'a> Event<'a> {
             stacktrace: self.stacktrace,
             template: self.template,
             threads: self.threads,
+            spans: self.spans,
             tags: self.tags,
             extra: self.extra,
             debug_meta: Cow::Owned(self.debug_meta.into_owned()),
             sdk: self.sdk.map(|x| Cow::Owned(x.into_owned())),
+            errors: self.errors,
+            meta: self.meta,
+        }
+    }
+}
+
+/// A difference between two events returned by `Event::diff`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    /// The dotted path of the differing field, as used by `MetaTree`.
+    pub path: String,
+    /// The value in the first event, if present.
+    pub left: Option<Value>,
+    /// The value in the second event, if present.
+    pub right: Option<Value>,
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let display = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => "<missing>".into(),
+        };
+        write!(
+            f,
+            "{}: {} != {}",
+            self.path,
+            display(&self.left),
+            display(&self.right)
+        )
+    }
+}
+
+/// Recursively collects differences between two JSON values.
+fn diff_values(
+    keys: &mut Vec<String>,
+    left: Option<&Value>,
+    right: Option<&Value>,
+    diffs: &mut Vec<FieldDiff>,
+) {
+    match (left, right) {
+        (Some(Value::Object(left)), Some(Value::Object(right))) => {
+            let children: std::collections::BTreeSet<&String> =
+                left.keys().chain(right.keys()).collect();
+            for key in children {
+                keys.push(key.clone());
+                diff_values(keys, left.get(key), right.get(key), diffs);
+                keys.pop();
+            }
+        }
+        (Some(Value::Array(left)), Some(Value::Array(right))) => {
+            for idx in 0..cmp::max(left.len(), right.len()) {
+                keys.push(idx.to_string());
+                diff_values(keys, left.get(idx), right.get(idx), diffs);
+                keys.pop();
+            }
         }
+        (left, right) if left != right => diffs.push(FieldDiff {
+            path: MetaTree::join_path(keys.iter()),
+            left: left.cloned(),
+            right: right.cloned(),
+        }),
+        _ => {}
     }
 }
 
+/// Parses an event from raw JSON bytes.
+///
+/// This never panics, no matter the input, so it is safe to use on untrusted
+/// data.  The event is deserialized in a single pass without building an
+/// intermediate `Value` of the payload; only contexts and fields collected
+/// in `other` maps are buffered.  See `benches/event.rs` for benchmarks.
+///
+/// With the `simd-json` feature, the bytes are parsed with simd-json.
+pub fn parse_event_bytes(bytes: &[u8]) -> Result<Event<'static>, serde_json::Error> {
+    from_json_slice(bytes)
+}
+
 impl<'a> fmt::Display for Event<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Event(id: {}, ts: {})", self.event_id, self.timestamp)
     }
 }
+
+/// An event that is cheap to clone.
+///
+/// Clones share the event until one of them is modified through
+/// `SharedEvent::make_mut`, which copies the event only if it is shared.
+/// This allows passing events through processing stages without copying
+/// large breadcrumb and frame lists.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "clock", derive(Default))]
+pub struct SharedEvent(Arc<Event<'static>>);
+
+impl SharedEvent {
+    /// Wraps an event.
+    pub fn new(event: Event<'static>) -> SharedEvent {
+        SharedEvent(Arc::new(event))
+    }
+
+    /// Returns a mutable reference to the event, copying it if it is shared.
+    pub fn make_mut(&mut self) -> &mut Event<'static> {
+        Arc::make_mut(&mut self.0)
+    }
+
+    /// Returns the event, copying it if it is shared.
+    pub fn into_inner(self) -> Event<'static> {
+        Arc::try_unwrap(self.0).unwrap_or_else(|shared| (*shared).clone())
+    }
+
+    /// Returns true if both share the same event.
+    pub fn ptr_eq(&self, other: &SharedEvent) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl ops::Deref for SharedEvent {
+    type Target = Event<'static>;
+
+    fn deref(&self) -> &Event<'static> {
+        &self.0
+    }
+}
+
+impl From<Event<'static>> for SharedEvent {
+    fn from(event: Event<'static>) -> SharedEvent {
+        SharedEvent::new(event)
+    }
+}
+
+impl Serialize for SharedEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SharedEvent {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<SharedEvent, D::Error> {
+        Event::deserialize(deserializer).map(SharedEvent::new)
+    }
+}
+
+/// The order of event fields in payloads emitted by the Sentry server.
+pub const SERVER_FIELD_ORDER: &[&str] = &[
+    "event_id",
+    "level",
+    "type",
+    "version",
+    "timestamp",
+    "platform",
+    "logger",
+    "transaction",
+    "transaction_info",
+    "culprit",
+    "message",
+    "logentry",
+    "server_name",
+    "release",
+    "dist",
+    "environment",
+    "user",
+    "request",
+    "contexts",
+    "breadcrumbs",
+    "exception",
+    "stacktrace",
+    "template",
+    "threads",
+    "tags",
+    "extra",
+    "fingerprint",
+    "modules",
+    "debug_meta",
+    "sdk",
+    "_meta",
+];
+
+/// Serializes an event with the field order of the Sentry server.
+///
+/// Fields listed in `SERVER_FIELD_ORDER` come first in that order, followed
+/// by all other fields sorted by name.  Keys of nested objects are sorted, so
+/// the output can be compared byte for byte with server payloads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ServerOrdered<'s, 'a>(pub &'s Event<'a>);
+
+impl<'s, 'a> Serialize for ServerOrdered<'s, 'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeMap};
+
+        let object = match serde_json::to_value(self.0).map_err(S::Error::custom)? {
+            Value::Object(object) => object,
+            other => return Sorted(&other).serialize(serializer),
+        };
+
+        let mut rest: Vec<_> = object
+            .iter()
+            .filter(|(key, _)| !SERVER_FIELD_ORDER.contains(&key.as_str()))
+            .collect();
+        rest.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut map = serializer.serialize_map(Some(object.len()))?;
+        for key in SERVER_FIELD_ORDER {
+            if let Some(value) = object.get(*key) {
+                map.serialize_entry(key, &Sorted(value))?;
+            }
+        }
+        for (key, value) in rest {
+            map.serialize_entry(key, &Sorted(value))?;
+        }
+        map.end()
+    }
+}
+
+/// Serializes a JSON value with sorted object keys.
+struct Sorted<'v>(&'v Value);
+
+impl<'v> Serialize for Sorted<'v> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{SerializeMap, SerializeSeq};
+
+        match self.0 {
+            Value::Object(object) => {
+                let mut entries: Vec<_> = object.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, &Sorted(value))?;
+                }
+                map.end()
+            }
+            Value::Array(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(&Sorted(value))?;
+                }
+                seq.end()
+            }
+            other => other.serialize(serializer),
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Borrowed views serializing parts of an event.
+//!
+//! Views serialize a subset of the fields of an `Event` without cloning it,
+//! which is useful for logging and previews.  The output uses the same
+//! format as the event itself.
+use std::borrow::Cow;
+
+use chrono::{DateTime, Utc};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use crate::protocol::v7::{Event, MetaTree};
+use crate::utils::ts_seconds_float;
+
+/// The fields serialized by `EventHeaderView`.
+const HEADER_FIELDS: &[&str] = &[
+    "event_id",
+    "level",
+    "type",
+    "timestamp",
+    "platform",
+    "logger",
+    "transaction",
+    "message",
+    "release",
+    "dist",
+    "environment",
+    "tags",
+];
+
+/// The fields serialized by `EventScrubbedView` in addition to the header.
+const SCRUBBED_FIELDS: &[&str] = &[
+    "fingerprint",
+    "culprit",
+    "transaction_info",
+    "logentry",
+    "contexts",
+    "exception",
+    "stacktrace",
+    "template",
+    "threads",
+    "start_timestamp",
+    "sdk",
+    "errors",
+];
+
+/// Serializes a timestamp the way `Event::timestamp` is serialized.
+struct Timestamp<'a>(&'a DateTime<Utc>);
+
+impl Serialize for Timestamp<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ts_seconds_float::serialize(self.0, serializer)
+    }
+}
+
+/// Serializes the meta data of the fields of a view.
+struct ViewMeta<'a> {
+    meta: &'a MetaTree,
+    fields: &'a [&'a [&'a str]],
+}
+
+impl ViewMeta<'_> {
+    fn children(&self) -> impl Iterator<Item = (&String, &MetaTree)> {
+        let fields = self.fields;
+        self.meta.children.iter().filter(move |(key, tree)| {
+            !tree.is_empty() && fields.iter().any(|f| f.contains(&key.as_str()))
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.meta.meta.is_empty() && self.children().next().is_none()
+    }
+}
+
+impl Serialize for ViewMeta<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        if !self.meta.meta.is_empty() {
+            map.serialize_entry("", &self.meta.meta)?;
+        }
+        for (key, tree) in self.children() {
+            map.serialize_entry(key, tree)?;
+        }
+        map.end()
+    }
+}
+
+/// Serializes the fields shared by all views.
+fn serialize_header<M: SerializeMap>(map: &mut M, event: &Event<'_>) -> Result<(), M::Error> {
+    map.serialize_entry("event_id", &event.event_id.to_simple_ref().to_string())?;
+    if !event.level.is_error() {
+        map.serialize_entry("level", &event.level)?;
+    }
+    if let Some(ref ty) = event.ty {
+        map.serialize_entry("type", ty)?;
+    }
+    map.serialize_entry("timestamp", &Timestamp(&event.timestamp))?;
+    if !event.platform.is_other() {
+        map.serialize_entry("platform", &event.platform)?;
+    }
+    if let Some(ref logger) = event.logger {
+        map.serialize_entry("logger", logger)?;
+    }
+    if let Some(ref transaction) = event.transaction {
+        map.serialize_entry("transaction", transaction)?;
+    }
+    if let Some(ref message) = event.message {
+        map.serialize_entry("message", message)?;
+    }
+    if let Some(ref release) = event.release {
+        map.serialize_entry("release", release)?;
+    }
+    if let Some(ref dist) = event.dist {
+        map.serialize_entry("dist", dist)?;
+    }
+    if let Some(ref environment) = event.environment {
+        map.serialize_entry("environment", environment)?;
+    }
+    if !event.tags.is_empty() {
+        map.serialize_entry("tags", &event.tags)?;
+    }
+    Ok(())
+}
+
+/// A view of the identifying fields of an event.
+///
+/// This serializes the ID, level, type, timestamp, platform, logger,
+/// transaction, message, release, dist, environment and tags of the event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventHeaderView<'a, 'e> {
+    event: &'a Event<'e>,
+}
+
+impl<'a, 'e> EventHeaderView<'a, 'e> {
+    /// Creates a header view of an event.
+    pub fn new(event: &'a Event<'e>) -> Self {
+        EventHeaderView { event }
+    }
+}
+
+impl Serialize for EventHeaderView<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        serialize_header(&mut map, self.event)?;
+        map.end()
+    }
+}
+
+/// A view of an event without personal data and bulky metadata.
+///
+/// This serializes all fields of the event except for `user`, `request`,
+/// `server_name`, `breadcrumbs`, `extra`, `modules`, `debug_meta` and `spans`.
+/// Meta data is only serialized for the fields of the view.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventScrubbedView<'a, 'e> {
+    event: &'a Event<'e>,
+}
+
+impl<'a, 'e> EventScrubbedView<'a, 'e> {
+    /// Creates a scrubbed view of an event.
+    pub fn new(event: &'a Event<'e>) -> Self {
+        EventScrubbedView { event }
+    }
+}
+
+impl Serialize for EventScrubbedView<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let event = self.event;
+        let mut map = serializer.serialize_map(None)?;
+        serialize_header(&mut map, event)?;
+
+        if event.fingerprint.as_ref() != [Cow::Borrowed("{{ default }}")] {
+            map.serialize_entry("fingerprint", &event.fingerprint)?;
+        }
+        if let Some(ref culprit) = event.culprit {
+            map.serialize_entry("culprit", culprit)?;
+        }
+        if let Some(ref transaction_info) = event.transaction_info {
+            map.serialize_entry("transaction_info", transaction_info)?;
+        }
+        if let Some(ref logentry) = event.logentry {
+            map.serialize_entry("logentry", logentry)?;
+        }
+        if !event.contexts.is_empty() {
+            map.serialize_entry("contexts", &event.contexts)?;
+        }
+        if !event.exception.is_empty() {
+            map.serialize_entry("exception", &event.exception)?;
+        }
+        if let Some(ref stacktrace) = event.stacktrace {
+            map.serialize_entry("stacktrace", stacktrace)?;
+        }
+        if let Some(ref template) = event.template {
+            map.serialize_entry("template", template)?;
+        }
+        if !event.threads.is_empty() {
+            map.serialize_entry("threads", &event.threads)?;
+        }
+        if let Some(ref start_timestamp) = event.start_timestamp {
+            map.serialize_entry("start_timestamp", &Timestamp(start_timestamp))?;
+        }
+        if let Some(ref sdk) = event.sdk {
+            map.serialize_entry("sdk", sdk)?;
+        }
+        if !event.errors.is_empty() {
+            map.serialize_entry("errors", &event.errors)?;
+        }
+        let meta = ViewMeta {
+            meta: &event.meta,
+            fields: &[HEADER_FIELDS, SCRUBBED_FIELDS],
+        };
+        if !meta.is_empty() {
+            map.serialize_entry("_meta", &meta)?;
+        }
+        map.end()
+    }
+}
+
+impl<'a> Event<'a> {
+    /// Returns a view serializing the identifying fields of the event.
+    pub fn header_view(&self) -> EventHeaderView<'_, 'a> {
+        EventHeaderView::new(self)
+    }
+
+    /// Returns a view serializing the event without personal data and
+    /// bulky metadata.
+    pub fn scrubbed_view(&self) -> EventScrubbedView<'_, 'a> {
+        EventScrubbedView::new(self)
+    }
+}
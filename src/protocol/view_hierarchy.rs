@@ -0,0 +1,89 @@
+//! View hierarchies of mobile and desktop applications.
+//!
+//! SDKs capture the tree of UI elements at the time of an event and send it
+//! as a JSON attachment with the `event.view_hierarchy` attachment type.
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::envelope::{AttachmentType, EnvelopeItem, ItemType};
+use crate::protocol::v7::{Map, Value};
+
+/// The filename SDKs use for view hierarchy attachments.
+pub const VIEW_HIERARCHY_FILENAME: &str = "view-hierarchy.json";
+
+/// A single element in a view hierarchy.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ViewHierarchyWindow {
+    /// The type of the element, usually its class name.
+    #[serde(rename = "type", default)]
+    pub ty: String,
+    /// The identifier of the element, such as an accessibility id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    /// The horizontal position of the element.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<f64>,
+    /// The vertical position of the element.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<f64>,
+    /// The width of the element.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    /// The height of the element.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
+    /// The opacity of the element between 0 and 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alpha: Option<f64>,
+    /// Whether the element is visible.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visible: Option<bool>,
+    /// The nested elements.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ViewHierarchyWindow>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl ViewHierarchyWindow {
+    /// Returns the number of elements in this subtree, including itself.
+    pub fn count(&self) -> usize {
+        1 + self.children.iter().map(Self::count).sum::<usize>()
+    }
+}
+
+/// The view hierarchy of an application.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ViewHierarchy {
+    /// The UI framework that rendered the hierarchy, such as `UIKIT`.
+    #[serde(default)]
+    pub rendering_system: String,
+    /// The top level windows of the application.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<ViewHierarchyWindow>,
+    /// Additional arbitrary fields for forwards compatibility.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl ViewHierarchy {
+    /// Parses a view hierarchy from an attachment payload.
+    pub fn from_slice(slice: &[u8]) -> Result<ViewHierarchy, serde_json::Error> {
+        serde_json::from_slice(slice)
+    }
+
+    /// Creates an `event.view_hierarchy` attachment item.
+    pub fn to_attachment(&self) -> EnvelopeItem {
+        let mut headers = Map::new();
+        headers.insert("filename".into(), VIEW_HIERARCHY_FILENAME.into());
+        headers.insert("content_type".into(), "application/json".into());
+        headers.insert(
+            "attachment_type".into(),
+            AttachmentType::ViewHierarchy.as_str().into(),
+        );
+
+        // serializing a view hierarchy cannot fail
+        let payload = serde_json::to_vec(self).unwrap_or_default();
+        EnvelopeItem::with_extra_headers(ItemType::Attachment, headers, payload)
+    }
+}
@@ -0,0 +1,686 @@
+//! Proptest strategies for protocol types.
+//!
+//! The strategies generate values that survive a JSON round trip unchanged,
+//! which makes them suitable for testing serialization as well as code that
+//! transforms events, such as normalization.  The types also implement
+//! `proptest::arbitrary::Arbitrary` using these strategies.
+//!
+//! This module is only available with the `test-support` feature.
+use std::borrow::Cow;
+use std::fmt;
+
+use chrono::{TimeZone, Utc};
+use debugid::{CodeId, DebugId};
+use proptest::arbitrary::Arbitrary;
+use proptest::collection::{btree_map, vec};
+use proptest::option;
+use proptest::prelude::*;
+use url::Url;
+use uuid::Uuid;
+
+use crate::intern::ThinString;
+use crate::protocol::extra;
+use crate::protocol::v7::{
+    Addr, AppContext, AppleDebugImage, Breadcrumb, BrowserContext, ClientSdkInfo, ClientSdkPackage,
+    Context, DebugImage, DebugMeta, DeviceContext, Environment, Event, EventType, Exception, Frame,
+    IpAddress, Level, LogEntry, Map, Mechanism, Orientation, OsContext, Platform,
+    ProguardDebugImage, RegVal, Registers, Release, Request, RuntimeContext, Stacktrace,
+    SymbolicDebugImage, SystemSdkInfo, TemplateInfo, Thread, ThreadId, User, Value,
+};
+
+/// Generates short printable strings.
+pub fn text() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9 _.:-]{0,24}"
+}
+
+/// Generates names usable as keys of additional fields.
+///
+/// Keys are prefixed with `x_` so they never collide with known fields.
+pub fn key() -> impl Strategy<Value = String> {
+    "x_[a-z0-9_]{1,12}"
+}
+
+/// Generates JSON values of limited depth without floating point numbers.
+pub fn value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        text().prop_map(Value::from),
+    ];
+    leaf.prop_recursive(2, 16, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..4).prop_map(Value::Array),
+            btree_map(key(), inner, 0..4).prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+/// Generates maps of additional fields.
+pub fn value_map() -> impl Strategy<Value = Map<String, Value>> {
+    btree_map(key(), value(), 0..4)
+}
+
+/// Generates maps of additional data, such as `Event::extra`.
+pub fn extra_map<K>() -> impl Strategy<Value = Map<K, extra::Value>>
+where
+    K: From<String> + Ord + Clone + fmt::Debug,
+{
+    value_map().prop_map(|map| {
+        map.into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect()
+    })
+}
+
+/// Generates a `Level`.
+pub fn level() -> impl Strategy<Value = Level> {
+    prop_oneof![
+        Just(Level::Debug),
+        Just(Level::Info),
+        Just(Level::Warning),
+        Just(Level::Error),
+        Just(Level::Fatal),
+    ]
+}
+
+/// Generates a known `Platform`.
+pub fn platform() -> impl Strategy<Value = Platform> {
+    prop_oneof![
+        Just(Platform::Other),
+        Just(Platform::Cocoa),
+        Just(Platform::Java),
+        Just(Platform::JavaScript),
+        Just(Platform::Native),
+        Just(Platform::Node),
+        Just(Platform::Python),
+    ]
+}
+
+/// Generates a random event ID.
+pub fn event_id() -> impl Strategy<Value = Uuid> {
+    any::<u128>().prop_map(Uuid::from_u128)
+}
+
+/// Generates timestamps with microsecond precision between 2000 and 2100.
+///
+/// Whole seconds are generated as often as timestamps with a fraction.
+pub fn timestamp() -> impl Strategy<Value = chrono::DateTime<Utc>> {
+    (
+        946_684_800i64..4_102_444_800,
+        prop_oneof![Just(0u32), 1u32..1_000_000],
+    )
+        .prop_map(|(secs, micros)| Utc.timestamp_opt(secs, micros * 1000).unwrap())
+}
+
+/// Generates a valid `Release`.
+pub fn release() -> impl Strategy<Value = Release<'static>> {
+    "[a-z]{1,8}@[0-9]{1,2}\\.[0-9]{1,2}\\.[0-9]{1,2}".prop_map(|name| Release::new(name).unwrap())
+}
+
+/// Generates a valid `Environment`.
+pub fn environment() -> impl Strategy<Value = Environment<'static>> {
+    "[a-z]{1,12}".prop_map(|name| Environment::new(name).unwrap())
+}
+
+/// Generates a `Frame`.
+pub fn frame() -> impl Strategy<Value = Frame> {
+    (
+        option::of(text()),
+        option::of(text()),
+        option::of(text()),
+        option::of(any::<u64>()),
+        option::of(any::<u64>()),
+        option::of(text()),
+        option::of(any::<bool>()),
+        option::of(any::<u64>().prop_map(Addr)),
+    )
+        .prop_map(
+            |(
+                function,
+                module,
+                filename,
+                lineno,
+                colno,
+                context_line,
+                in_app,
+                instruction_addr,
+            )| {
+                Frame {
+                    function,
+                    module: module.map(Into::into),
+                    filename: filename.map(Into::into),
+                    lineno,
+                    colno,
+                    context_line,
+                    in_app,
+                    instruction_addr,
+                    ..Default::default()
+                }
+            },
+        )
+}
+
+/// Generates a `Stacktrace`.
+pub fn stacktrace() -> impl Strategy<Value = Stacktrace> {
+    (
+        vec(frame(), 0..4),
+        btree_map("[a-z]{2,3}", any::<u64>().prop_map(RegVal), 0..3),
+    )
+        .prop_map(|(frames, registers)| Stacktrace {
+            frames,
+            registers: Registers(registers),
+            ..Default::default()
+        })
+}
+
+/// Generates a `Mechanism`.
+pub fn mechanism() -> impl Strategy<Value = Mechanism> {
+    (
+        "[a-z]{1,12}",
+        option::of(text()),
+        option::of(any::<bool>()),
+        extra_map(),
+    )
+        .prop_map(|(ty, description, handled, data)| Mechanism {
+            ty,
+            description,
+            handled,
+            data,
+            ..Default::default()
+        })
+}
+
+/// Generates an `Exception`.
+pub fn exception() -> impl Strategy<Value = Exception> {
+    (
+        "[A-Za-z]{1,16}",
+        option::of(text()),
+        option::of(text()),
+        option::of(stacktrace()),
+        option::of(any::<u64>().prop_map(ThreadId::Int)),
+        option::of(mechanism()),
+    )
+        .prop_map(
+            |(ty, value, module, stacktrace, thread_id, mechanism)| Exception {
+                ty,
+                value,
+                module,
+                stacktrace,
+                thread_id,
+                mechanism,
+                ..Default::default()
+            },
+        )
+}
+
+/// Generates a `Thread`.
+pub fn thread() -> impl Strategy<Value = Thread> {
+    (
+        option::of(any::<u64>().prop_map(ThreadId::Int)),
+        option::of(text()),
+        option::of(stacktrace()),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(|(id, name, stacktrace, crashed, current)| Thread {
+            id,
+            name,
+            stacktrace,
+            crashed,
+            current,
+            ..Default::default()
+        })
+}
+
+/// Generates a `User`.
+pub fn user() -> impl Strategy<Value = User> {
+    (
+        option::of(text()),
+        option::of("[a-z]{1,8}@[a-z]{1,8}\\.com"),
+        option::of(prop_oneof![
+            Just(IpAddress::Auto),
+            any::<[u8; 4]>().prop_map(|ip| IpAddress::Exact(ip.into())),
+        ]),
+        option::of(text()),
+        value_map(),
+    )
+        .prop_map(|(id, email, ip_address, username, other)| User {
+            id,
+            email,
+            ip_address,
+            username,
+            other,
+        })
+}
+
+/// Generates a `Breadcrumb`.
+pub fn breadcrumb() -> impl Strategy<Value = Breadcrumb> {
+    (
+        timestamp(),
+        "[a-z]{1,8}",
+        option::of("[a-z.]{1,12}"),
+        level(),
+        option::of(text()),
+        extra_map(),
+    )
+        .prop_map(
+            |(timestamp, ty, category, level, message, data)| Breadcrumb {
+                timestamp,
+                ty,
+                category,
+                level,
+                message,
+                data,
+            },
+        )
+}
+
+/// Generates a `Request`.
+pub fn request() -> impl Strategy<Value = Request> {
+    (
+        option::of("https://[a-z]{1,8}\\.com/[a-z]{0,8}".prop_map(|url| Url::parse(&url).unwrap())),
+        option::of("GET|POST|PUT|DELETE"),
+        option::of(text()),
+        option::of("[a-z]{1,8}=[a-z0-9]{1,8}"),
+        option::of("[a-z]{1,8}"),
+        option::of("[a-z]{1,8}=[a-z0-9]{1,8}"),
+        btree_map("[A-Z][a-z]{1,8}(-[A-Z][a-z]{1,8})?", text(), 0..3),
+        btree_map("[A-Z][A-Z_]{0,11}", text(), 0..3),
+    )
+        .prop_map(
+            |(url, method, data, query_string, fragment, cookies, headers, env)| Request {
+                url,
+                method,
+                data,
+                query_string,
+                fragment,
+                cookies,
+                headers,
+                env,
+            },
+        )
+}
+
+/// Generates a `LogEntry`.
+pub fn log_entry() -> impl Strategy<Value = LogEntry> {
+    (text(), vec(value(), 0..3)).prop_map(|(message, params)| LogEntry { message, params })
+}
+
+/// Generates a `TemplateInfo`.
+pub fn template_info() -> impl Strategy<Value = TemplateInfo> {
+    (
+        option::of(text()),
+        option::of(text()),
+        option::of(any::<u64>()),
+        option::of(any::<u64>()),
+        vec(text(), 0..3),
+        option::of(text()),
+        vec(text(), 0..3),
+    )
+        .prop_map(
+            |(filename, abs_path, lineno, colno, pre_context, context_line, post_context)| {
+                TemplateInfo {
+                    filename: filename.map(Into::into),
+                    abs_path,
+                    lineno,
+                    colno,
+                    pre_context,
+                    context_line,
+                    post_context,
+                }
+            },
+        )
+}
+
+/// Generates a `DebugId`.
+pub fn debug_id() -> impl Strategy<Value = DebugId> {
+    (any::<u128>(), any::<u32>())
+        .prop_map(|(uuid, appendix)| DebugId::from_parts(Uuid::from_u128(uuid), appendix))
+}
+
+/// Generates a `DebugImage` of any type.
+pub fn debug_image() -> impl Strategy<Value = DebugImage> {
+    let apple = (
+        "[a-zA-Z/]{1,16}",
+        option::of("[a-z0-9_]{1,8}"),
+        option::of(any::<u32>()),
+        option::of(any::<u32>()),
+        any::<u64>(),
+        any::<u64>(),
+        any::<u64>(),
+        any::<u128>(),
+    )
+        .prop_map(
+            |(name, arch, cpu_type, cpu_subtype, image_addr, image_size, image_vmaddr, uuid)| {
+                DebugImage::from(AppleDebugImage {
+                    name,
+                    arch,
+                    cpu_type,
+                    cpu_subtype,
+                    image_addr: Addr(image_addr),
+                    image_size,
+                    image_vmaddr: Addr(image_vmaddr),
+                    uuid: Uuid::from_u128(uuid),
+                })
+            },
+        );
+    let symbolic = (
+        "[a-zA-Z/]{1,16}",
+        option::of("[a-z0-9_]{1,8}"),
+        any::<u64>(),
+        any::<u64>(),
+        any::<u64>(),
+        debug_id(),
+        option::of("[0-9a-f]{8,40}".prop_map(CodeId::new)),
+    )
+        .prop_map(
+            |(name, arch, image_addr, image_size, image_vmaddr, id, code_id)| {
+                DebugImage::from(SymbolicDebugImage {
+                    name,
+                    arch,
+                    image_addr: Addr(image_addr),
+                    image_size,
+                    image_vmaddr: Addr(image_vmaddr),
+                    id,
+                    code_id,
+                })
+            },
+        );
+    let proguard = any::<u128>().prop_map(|uuid| {
+        DebugImage::from(ProguardDebugImage {
+            uuid: Uuid::from_u128(uuid),
+        })
+    });
+    prop_oneof![apple, symbolic, proguard]
+}
+
+/// Generates a `DebugMeta`.
+pub fn debug_meta() -> impl Strategy<Value = DebugMeta> {
+    (
+        option::of(
+            ("[a-z]{1,8}", any::<u32>(), any::<u32>(), any::<u32>()).prop_map(
+                |(sdk_name, version_major, version_minor, version_patchlevel)| SystemSdkInfo {
+                    sdk_name,
+                    version_major,
+                    version_minor,
+                    version_patchlevel,
+                },
+            ),
+        ),
+        vec(debug_image(), 0..3),
+    )
+        .prop_map(|(sdk_info, images)| DebugMeta { sdk_info, images })
+}
+
+/// Generates a `ClientSdkInfo`.
+pub fn client_sdk_info() -> impl Strategy<Value = ClientSdkInfo> {
+    (
+        "[a-z]{1,8}(\\.[a-z]{1,8}){0,2}",
+        "[0-9]{1,2}\\.[0-9]{1,2}\\.[0-9]{1,2}",
+        vec("[a-z]{1,12}", 0..3),
+        vec(("[a-z:]{1,12}", "[0-9]\\.[0-9]"), 0..2),
+    )
+        .prop_map(|(name, version, integrations, packages)| ClientSdkInfo {
+            name,
+            version,
+            integrations,
+            packages: packages
+                .into_iter()
+                .map(|(name, version)| ClientSdkPackage { name, version })
+                .collect(),
+        })
+}
+
+/// Generates a `Context` of any type.
+pub fn context() -> impl Strategy<Value = Context> {
+    let device = (
+        option::of(text()),
+        option::of(text()),
+        option::of(text()),
+        option::of("[a-z0-9_]{1,8}"),
+        option::of(prop_oneof![
+            Just(Orientation::Portrait),
+            Just(Orientation::Landscape)
+        ]),
+        option::of(any::<bool>()),
+        option::of(any::<u64>()),
+        option::of(timestamp()),
+        option::of("[A-Z][a-z]{1,8}/[A-Z][a-z]{1,8}"),
+        value_map(),
+    )
+        .prop_map(
+            |(
+                name,
+                family,
+                model,
+                arch,
+                orientation,
+                simulator,
+                memory_size,
+                boot_time,
+                timezone,
+                other,
+            )| {
+                Context::from(DeviceContext {
+                    name,
+                    family,
+                    model,
+                    arch,
+                    orientation,
+                    simulator,
+                    memory_size,
+                    boot_time,
+                    timezone,
+                    other,
+                    ..Default::default()
+                })
+            },
+        );
+    let os = (
+        option::of(text()),
+        option::of(text()),
+        option::of(text()),
+        option::of(text()),
+        option::of(any::<bool>()),
+        value_map(),
+    )
+        .prop_map(|(name, version, build, kernel_version, rooted, other)| {
+            Context::from(OsContext {
+                name,
+                version,
+                build,
+                kernel_version,
+                rooted,
+                other,
+            })
+        });
+    let runtime =
+        (option::of(text()), option::of(text()), value_map()).prop_map(|(name, version, other)| {
+            Context::from(RuntimeContext {
+                name,
+                version,
+                other,
+            })
+        });
+    let app = (
+        option::of(timestamp()),
+        option::of("[a-z]{1,8}(\\.[a-z]{1,8}){0,2}"),
+        option::of(text()),
+        option::of(text()),
+        option::of(text()),
+        option::of(any::<bool>()),
+        value_map(),
+    )
+        .prop_map(
+            |(
+                app_start_time,
+                app_identifier,
+                app_name,
+                app_version,
+                app_build,
+                in_foreground,
+                other,
+            )| {
+                Context::from(AppContext {
+                    app_start_time,
+                    app_identifier,
+                    app_name,
+                    app_version,
+                    app_build,
+                    in_foreground,
+                    other,
+                    ..Default::default()
+                })
+            },
+        );
+    let browser =
+        (option::of(text()), option::of(text()), value_map()).prop_map(|(name, version, other)| {
+            Context::from(BrowserContext {
+                name,
+                version,
+                other,
+            })
+        });
+    prop_oneof![
+        device,
+        os,
+        runtime,
+        app,
+        browser,
+        value_map().prop_map(Context::Other)
+    ]
+}
+
+/// Generates a known `EventType`.
+pub fn event_type() -> impl Strategy<Value = EventType> {
+    prop_oneof![
+        Just(EventType::Error),
+        Just(EventType::Default),
+        Just(EventType::Transaction),
+        Just(EventType::Csp),
+        Just(EventType::Hpkp),
+        Just(EventType::ExpectCt),
+        Just(EventType::ExpectStaple),
+        Just(EventType::UserReportV2),
+    ]
+}
+
+/// Generates an `Event`.
+pub fn event() -> impl Strategy<Value = Event<'static>> {
+    let header = (
+        event_id(),
+        level(),
+        option::of(vec(text(), 1..3)),
+        option::of(text()),
+        option::of(text()),
+        option::of(text()),
+        option::of("[a-z]{1,8}(\\.[a-z]{1,8}){0,2}"),
+        platform(),
+        timestamp(),
+        option::of(release()),
+        option::of(environment()),
+        option::of(text()),
+    );
+    let body = (
+        option::of(user()),
+        btree_map("[a-z]{1,8}", context(), 0..3),
+        vec(breadcrumb(), 0..3),
+        vec(exception(), 0..3),
+        vec(thread(), 0..2),
+        btree_map("[a-z.]{1,12}".prop_map(ThinString::from), text(), 0..4),
+        extra_map(),
+        btree_map("[a-z-]{1,12}", "[0-9]\\.[0-9]", 0..3),
+    );
+    let details = (
+        option::of("[a-z0-9]{1,8}"),
+        option::of(event_type()),
+        option::of(request()),
+        option::of(client_sdk_info()),
+        option::of(log_entry()),
+        option::of(template_info()),
+        debug_meta(),
+    );
+
+    (header, body, details).prop_map(
+        |(
+            (
+                event_id,
+                level,
+                fingerprint,
+                culprit,
+                transaction,
+                message,
+                logger,
+                platform,
+                timestamp,
+                release,
+                environment,
+                server_name,
+            ),
+            (user, contexts, breadcrumbs, exception, threads, tags, extra, modules),
+            (dist, ty, request, sdk, logentry, template, debug_meta),
+        )| {
+            let mut event = Event {
+                level,
+                ty,
+                culprit,
+                transaction,
+                message,
+                logentry,
+                logger,
+                platform,
+                release,
+                dist: dist.map(Cow::Owned),
+                environment,
+                server_name: server_name.map(Cow::Owned),
+                user,
+                request,
+                contexts,
+                breadcrumbs: breadcrumbs.into(),
+                exception: exception.into(),
+                template,
+                threads: threads.into(),
+                tags,
+                extra,
+                modules,
+                debug_meta: Cow::Owned(debug_meta),
+                sdk: sdk.map(Cow::Owned),
+                ..Event::with_id(event_id, timestamp)
+            };
+            if let Some(fingerprint) = fingerprint {
+                event.fingerprint = fingerprint.into_iter().map(Cow::Owned).collect();
+            }
+            event
+        },
+    )
+}
+
+macro_rules! impl_arbitrary {
+    ($type:ty, $strategy:ident) => {
+        impl Arbitrary for $type {
+            type Parameters = ();
+            type Strategy = BoxedStrategy<$type>;
+
+            fn arbitrary_with(_: ()) -> Self::Strategy {
+                $strategy().boxed()
+            }
+        }
+    };
+}
+
+impl_arbitrary!(Frame, frame);
+impl_arbitrary!(Stacktrace, stacktrace);
+impl_arbitrary!(Mechanism, mechanism);
+impl_arbitrary!(Exception, exception);
+impl_arbitrary!(Thread, thread);
+impl_arbitrary!(User, user);
+impl_arbitrary!(Breadcrumb, breadcrumb);
+impl_arbitrary!(Context, context);
+impl_arbitrary!(Request, request);
+impl_arbitrary!(LogEntry, log_entry);
+impl_arbitrary!(TemplateInfo, template_info);
+impl_arbitrary!(DebugImage, debug_image);
+impl_arbitrary!(DebugMeta, debug_meta);
+impl_arbitrary!(ClientSdkInfo, client_sdk_info);
+impl_arbitrary!(Event<'static>, event);
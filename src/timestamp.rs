@@ -0,0 +1,86 @@
+use std::fmt;
+
+use chrono::{DateTime, TimeZone, Utc};
+
+/// A point in time with microsecond precision.
+///
+/// This converts from and to `DateTime<Utc>`, which is used for timestamps
+/// throughout the protocol.  The numeric constructors allow filling in
+/// timestamps on targets without a system clock, for instance from
+/// `Date.now()` in a browser:
+///
+/// ```rust
+/// use sentry_types::{DateTime, Timestamp, Utc};
+///
+/// let timestamp = Timestamp::from_millis(1_514_103_120_500.0).unwrap();
+/// let datetime: DateTime<Utc> = timestamp.into();
+/// assert_eq!(datetime.timestamp_subsec_millis(), 500);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(DateTime<Utc>);
+
+impl Timestamp {
+    /// Returns the current time.
+    ///
+    /// This requires the `clock` feature.
+    #[cfg(feature = "clock")]
+    pub fn now() -> Timestamp {
+        Timestamp(Utc::now())
+    }
+
+    /// Creates a timestamp from milliseconds since the Unix epoch.
+    ///
+    /// Returns `None` if the value is not finite or out of range.
+    pub fn from_millis(millis: f64) -> Option<Timestamp> {
+        Timestamp::from_secs_f64(millis / 1000.0)
+    }
+
+    /// Creates a timestamp from seconds since the Unix epoch.
+    ///
+    /// Returns `None` if the value is not finite or out of range.
+    pub fn from_secs_f64(secs: f64) -> Option<Timestamp> {
+        if !secs.is_finite() || secs.abs() >= i64::MAX as f64 {
+            return None;
+        }
+
+        let whole = secs.floor();
+        let micros = ((secs - whole) * 1_000_000.0).round() as u32;
+        let (whole, micros) = if micros >= 1_000_000 {
+            (whole as i64 + 1, 0)
+        } else {
+            (whole as i64, micros)
+        };
+
+        Utc.timestamp_opt(whole, micros * 1000)
+            .single()
+            .map(Timestamp)
+    }
+
+    /// Returns the milliseconds since the Unix epoch.
+    pub fn as_millis(&self) -> f64 {
+        self.0.timestamp() as f64 * 1000.0 + f64::from(self.0.timestamp_subsec_micros()) / 1000.0
+    }
+
+    /// Returns the timestamp as `DateTime<Utc>`.
+    pub fn as_datetime(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(datetime: DateTime<Utc>) -> Timestamp {
+        Timestamp(datetime)
+    }
+}
+
+impl From<Timestamp> for DateTime<Utc> {
+    fn from(timestamp: Timestamp) -> DateTime<Utc> {
+        timestamp.0
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.to_rfc3339())
+    }
+}
@@ -1,8 +1,28 @@
+//! Utilities for working with protocol values.
+//!
+//! `truncate_string` shortens strings on character boundaries the same way
+//! the protocol types do when enforcing their size limits.
 #![cfg_attr(not(feature = "with_protocol"), allow(unused))]
 use chrono::{DateTime, LocalResult, TimeZone, Utc};
+use serde::de::DeserializeOwned;
+use subtle::ConstantTimeEq;
+#[cfg(feature = "clock")]
+use uuid::Uuid;
+
+/// Returns the current time.
+#[cfg(feature = "clock")]
+pub(crate) fn now() -> DateTime<Utc> {
+    Utc::now()
+}
+
+/// Returns a random UUID.
+#[cfg(feature = "clock")]
+pub(crate) fn random_uuid() -> Uuid {
+    Uuid::new_v4()
+}
 
 /// Converts a datetime object into a float timestamp.
-pub fn datetime_to_timestamp(dt: &DateTime<Utc>) -> f64 {
+pub(crate) fn datetime_to_timestamp(dt: &DateTime<Utc>) -> f64 {
     if dt.timestamp_subsec_nanos() == 0 {
         dt.timestamp() as f64
     } else {
@@ -10,15 +30,170 @@ pub fn datetime_to_timestamp(dt: &DateTime<Utc>) -> f64 {
     }
 }
 
-pub fn timestamp_to_datetime(ts: f64) -> LocalResult<DateTime<Utc>> {
-    let secs = ts as i64;
-    let micros = (ts.fract() * 1_000_000f64) as u32;
-    Utc.timestamp_opt(secs, micros * 1000)
+/// Converts a float timestamp into a datetime object.
+///
+/// The fraction is rounded to microseconds, so timestamps written by
+/// `datetime_to_timestamp` convert back to the same datetime.
+pub(crate) fn timestamp_to_datetime(ts: f64) -> LocalResult<DateTime<Utc>> {
+    if !ts.is_finite() {
+        return LocalResult::None;
+    }
+
+    let secs = ts.floor();
+    let micros = ((ts - secs) * 1_000_000f64).round() as u32;
+    // out of range seconds saturate and are rejected by chrono
+    let secs = (secs as i64).saturating_add(i64::from(micros / 1_000_000));
+    Utc.timestamp_opt(secs, micros % 1_000_000 * 1000)
+}
+
+/// The maximum nesting depth of JSON payloads parsed with simd-json.
+///
+/// This matches the recursion limit of `serde_json`.
+#[cfg(all(feature = "simd-json", feature = "with_protocol"))]
+const MAX_JSON_DEPTH: usize = 128;
+
+/// Deserializes a protocol payload from JSON bytes.
+///
+/// With the `simd-json` feature, the bytes are copied and parsed with
+/// simd-json.  Its errors are converted into `serde_json::Error`, so callers
+/// do not depend on the backend.  As simd-json has no recursion limit, the
+/// nesting depth is checked before parsing.
+pub(crate) fn from_json_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, serde_json::Error> {
+    #[cfg(all(feature = "simd-json", feature = "with_protocol"))]
+    {
+        use crate::protocol::limits::ParseLimits;
+
+        let limits = ParseLimits {
+            max_depth: MAX_JSON_DEPTH,
+            max_array_length: usize::MAX,
+            max_string_length: usize::MAX,
+        };
+        limits.check(bytes).map_err(serde::de::Error::custom)?;
+        let mut bytes = bytes.to_vec();
+        simd_json::serde::from_slice(&mut bytes).map_err(serde::de::Error::custom)
+    }
+    #[cfg(not(all(feature = "simd-json", feature = "with_protocol")))]
+    {
+        serde_json::from_slice(bytes)
+    }
+}
+
+/// Writes a JSON value with sorted object keys and without whitespace.
+///
+/// Keys are sorted explicitly, so the output does not depend on whether
+/// `serde_json` preserves insertion order.
+pub(crate) fn write_canonical_json(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(value, out);
+            }
+            out.push(']');
+        }
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_canonical_json(value, out);
+            }
+            out.push('}');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+/// The marker appended to truncated strings.
+pub const ELLIPSIS: &str = "...";
+
+/// Truncates a string to at most `max_chars` characters.
+///
+/// The string is cut on a character boundary and `ELLIPSIS` is appended,
+/// counting towards `max_chars`.  Returns the original number of characters
+/// if the string was truncated.
+///
+/// ```rust
+/// use sentry_types::utils::truncate_string;
+///
+/// let mut value = "Grüße, Jürgen".to_string();
+/// assert_eq!(truncate_string(&mut value, 8), Some(13));
+/// assert_eq!(value, "Grüße...");
+/// ```
+pub fn truncate_string(value: &mut String, max_chars: usize) -> Option<usize> {
+    let original_length = value.chars().count();
+    if original_length <= max_chars {
+        return None;
+    }
+
+    let ellipsis = if max_chars >= ELLIPSIS.len() {
+        ELLIPSIS
+    } else {
+        ""
+    };
+    if let Some((index, _)) = value.char_indices().nth(max_chars - ellipsis.len()) {
+        value.truncate(index);
+    }
+    value.push_str(ellipsis);
+
+    Some(original_length)
+}
+
+/// Compares two strings in constant time.
+///
+/// Only the length of the strings may leak through timing, not their
+/// contents.  Use this to compare credentials.
+pub(crate) fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+/// Matches a value against a glob pattern supporting `*` and `?`.
+///
+/// Both the pattern and the value are passed through `normalize` character by
+/// character before matching, which allows for case-insensitive matching.
+pub(crate) fn glob_match<F: Fn(char) -> char>(pattern: &str, value: &str, normalize: F) -> bool {
+    let pattern: Vec<char> = pattern.chars().map(&normalize).collect();
+    let value: Vec<char> = value.chars().map(&normalize).collect();
+
+    let (mut p, mut v) = (0, 0);
+    let mut backtrack = None;
+
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, v));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == value[v] => {
+                p += 1;
+                v += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bv)) => {
+                    p = bp + 1;
+                    v = bv + 1;
+                    backtrack = Some((bp, bv + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
-pub mod ts_seconds_float {
+pub(crate) mod ts_seconds_float {
     use chrono::{DateTime, LocalResult, TimeZone, Utc};
     use serde::{de, ser};
+    use std::convert::TryFrom;
     use std::fmt;
 
     use super::timestamp_to_datetime;
@@ -27,8 +202,8 @@ pub mod ts_seconds_float {
     where
         D: de::Deserializer<'de>,
     {
-        Ok(d.deserialize_any(SecondsTimestampVisitor)
-            .map(|dt| dt.with_timezone(&Utc))?)
+        d.deserialize_any(SecondsTimestampVisitor)
+            .map(|dt| dt.with_timezone(&Utc))
     }
 
     pub fn serialize<S>(dt: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
@@ -71,14 +246,19 @@ pub mod ts_seconds_float {
         where
             E: de::Error,
         {
-            Ok(Utc.timestamp_opt(value, 0).unwrap())
+            Utc.timestamp_opt(value, 0)
+                .single()
+                .ok_or_else(|| E::custom(format!("timestamp out of range: {}", value)))
         }
 
         fn visit_u64<E>(self, value: u64) -> Result<DateTime<Utc>, E>
         where
             E: de::Error,
         {
-            Ok(Utc.timestamp_opt(value as i64, 0).unwrap())
+            i64::try_from(value)
+                .ok()
+                .and_then(|value| Utc.timestamp_opt(value, 0).single())
+                .ok_or_else(|| E::custom(format!("timestamp out of range: {}", value)))
         }
 
         fn visit_str<E>(self, value: &str) -> Result<DateTime<Utc>, E>
@@ -89,3 +269,28 @@ pub mod ts_seconds_float {
         }
     }
 }
+
+pub(crate) mod ts_seconds_float_opt {
+    use chrono::{DateTime, Utc};
+    use serde::{de, ser, Deserialize};
+
+    #[derive(Deserialize)]
+    struct Timestamp(#[serde(with = "super::ts_seconds_float")] DateTime<Utc>);
+
+    pub fn deserialize<'de, D>(d: D) -> Result<Option<DateTime<Utc>>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Ok(Option::<Timestamp>::deserialize(d)?.map(|ts| ts.0))
+    }
+
+    pub fn serialize<S>(dt: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match *dt {
+            Some(ref dt) => super::ts_seconds_float::serialize(dt, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+}
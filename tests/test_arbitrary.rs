@@ -0,0 +1,34 @@
+#![cfg(feature = "test-support")]
+
+use proptest::prelude::*;
+
+use sentry_types::protocol::normalize::NormalizeConfig;
+use sentry_types::protocol::v7::{Breadcrumb, Event};
+use sentry_types::test_support;
+
+proptest! {
+    #[test]
+    fn test_event_roundtrip(event in any::<Event<'static>>()) {
+        let json = serde_json::to_string(&event).unwrap();
+        let parsed: Event = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(parsed, event);
+    }
+
+    #[test]
+    fn test_breadcrumb_roundtrip(breadcrumb in any::<Breadcrumb>()) {
+        let value = serde_json::to_value(&breadcrumb).unwrap();
+        prop_assert_eq!(serde_json::from_value::<Breadcrumb>(value).unwrap(), breadcrumb);
+    }
+
+    #[test]
+    fn test_normalize_idempotent(mut event in test_support::event()) {
+        let config = NormalizeConfig {
+            received_at: event.timestamp,
+            ..Default::default()
+        };
+        event.normalize(&config);
+        let normalized = event.clone();
+        event.normalize(&config);
+        prop_assert_eq!(event, normalized);
+    }
+}
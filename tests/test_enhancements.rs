@@ -0,0 +1,146 @@
+use sentry_types::protocol::enhancements::{
+    Action, ActionRange, Enhancements, FrameFlag, MatcherKey, ParseEnhancementsError,
+};
+use sentry_types::protocol::v7::{Frame, Platform, Stacktrace};
+
+fn frame(module: &str, function: &str) -> Frame {
+    Frame {
+        module: Some(module.into()),
+        function: Some(function.into()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_parse_rules() {
+    let enhancements = Enhancements::parse(
+        "# comment\n\
+         \n\
+         stack.module:foo* +app\n\
+         family:native !app:yes stack.function:\"my func\" ^-group max-frames=3\n",
+    )
+    .unwrap();
+
+    assert_eq!(enhancements.rules.len(), 2);
+    let rule = &enhancements.rules[1];
+    assert_eq!(rule.matchers[0].key, MatcherKey::Family);
+    assert!(rule.matchers[1].negated);
+    assert_eq!(rule.matchers[2].pattern, "my func");
+    assert_eq!(
+        rule.actions,
+        vec![
+            Action::Flag {
+                flag: FrameFlag::Group,
+                value: false,
+                range: Some(ActionRange::Up),
+            },
+            Action::MaxFrames(3),
+        ]
+    );
+
+    assert_eq!(
+        enhancements.to_string(),
+        "stack.module:foo* +app\n\
+         family:native !app:yes stack.function:\"my func\" ^-group max-frames=3\n"
+    );
+    assert_eq!(
+        enhancements.to_string().parse::<Enhancements>().unwrap(),
+        enhancements
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        Enhancements::parse("\nfoo:bar +app"),
+        Err(ParseEnhancementsError::UnknownMatcher(2, "foo".into()))
+    );
+    assert_eq!(
+        Enhancements::parse("module:foo +apps"),
+        Err(ParseEnhancementsError::InvalidAction(1, "+apps".into()))
+    );
+    assert_eq!(
+        Enhancements::parse("module:foo"),
+        Err(ParseEnhancementsError::MissingActions(1))
+    );
+    assert_eq!(
+        Enhancements::parse("+app"),
+        Err(ParseEnhancementsError::MissingMatchers(1))
+    );
+    assert_eq!(
+        Enhancements::parse("module:\"foo +app"),
+        Err(ParseEnhancementsError::UnterminatedString(1))
+    );
+    assert_eq!(
+        Enhancements::parse("module:foo +app module:bar"),
+        Err(ParseEnhancementsError::InvalidAction(
+            1,
+            "module:bar".into()
+        ))
+    );
+}
+
+#[test]
+fn test_apply() {
+    let enhancements = Enhancements::parse(
+        "stack.module:myapp::* +app\n\
+         stack.function:panic_handler ^-group\n\
+         family:native stack.module:std::* -group\n",
+    )
+    .unwrap();
+
+    let mut stacktrace = Stacktrace {
+        frames: vec![
+            frame("std::rt", "lang_start"),
+            frame("myapp::main", "main"),
+            frame("myapp::server", "handle"),
+            frame("std::panicking", "panic_handler"),
+            frame("std::panicking", "begin_panic"),
+        ],
+        ..Default::default()
+    };
+
+    let contributes = enhancements.apply(&mut stacktrace, &Platform::Native);
+    assert_eq!(contributes, vec![false, true, true, false, false]);
+    let in_app: Vec<_> = stacktrace.frames.iter().map(|f| f.in_app).collect();
+    assert_eq!(in_app, vec![None, Some(true), Some(true), None, None]);
+
+    // the family matcher does not apply to other platforms
+    let contributes = enhancements.apply(&mut stacktrace, &Platform::Python);
+    assert_eq!(contributes, vec![true, true, true, true, false]);
+}
+
+#[test]
+fn test_apply_max_frames() {
+    let enhancements = Enhancements::parse("app:yes max-frames=2").unwrap();
+    let mut stacktrace = Stacktrace {
+        frames: vec![
+            Frame {
+                in_app: Some(true),
+                ..frame("a", "a")
+            },
+            frame("b", "b"),
+            frame("c", "c"),
+        ],
+        ..Default::default()
+    };
+
+    let contributes = enhancements.apply(&mut stacktrace, &Platform::Other);
+    assert_eq!(contributes, vec![false, true, true]);
+}
+
+#[test]
+fn test_path_matching() {
+    let enhancements = Enhancements::parse("path:**/vendor/** -app").unwrap();
+    let mut stacktrace = Stacktrace {
+        frames: vec![Frame {
+            abs_path: Some("C:\\Project\\Vendor\\lib.c".into()),
+            in_app: Some(true),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    enhancements.apply(&mut stacktrace, &Platform::Native);
+    assert_eq!(stacktrace.frames[0].in_app, Some(false));
+}
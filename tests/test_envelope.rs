@@ -0,0 +1,445 @@
+use sentry_types::protocol::envelope::{
+    AttachmentType, Envelope, EnvelopeItem, EnvelopeViolation, ItemType, ParseEnvelopeError,
+    ReceivedEnvelope, ReplayRecording,
+};
+use sentry_types::protocol::quota::DataCategory;
+use sentry_types::protocol::v7::EventType;
+
+#[test]
+fn test_envelope_roundtrip_unknown_items() {
+    let bytes = b"{\"event_id\":\"9ec79c33-ec99-42ab-8353-589fcb2e04dc\",\"dsn\":\"https://e12d836b15bb49d7bbf99e64295d995b:@sentry.io/42\"}\n\
+                  {\"type\":\"event\",\"length\":2}\n\
+                  {}\n\
+                  { \"length\": 5, \"type\": \"future_thing\", \"unknown\": [1, 2] }\n\
+                  hello\n\
+                  {\"type\":\"other\",\"flag\":true}\n\
+                  no length given\n";
+
+    let envelope = Envelope::from_slice(bytes).unwrap();
+    assert_eq!(envelope.items().len(), 3);
+    assert_eq!(
+        envelope.items()[1].ty(),
+        &ItemType::Unknown("future_thing".into())
+    );
+    assert_eq!(envelope.items()[1].payload(), b"hello");
+    assert_eq!(
+        envelope.items()[1].raw_headers(),
+        &b"{ \"length\": 5, \"type\": \"future_thing\", \"unknown\": [1, 2] }"[..]
+    );
+    assert_eq!(envelope.items()[2].payload(), b"no length given");
+    assert_eq!(
+        envelope.headers().other["dsn"],
+        "https://e12d836b15bb49d7bbf99e64295d995b:@sentry.io/42"
+    );
+
+    assert_eq!(
+        String::from_utf8(envelope.to_vec()).unwrap(),
+        String::from_utf8(bytes.to_vec()).unwrap()
+    );
+}
+
+#[test]
+fn test_envelope_binary_payload() {
+    let bytes = b"{}\n{\"type\":\"attachment\",\"length\":3}\n\n\xff\n\n";
+    let envelope = Envelope::from_slice(bytes).unwrap();
+    assert_eq!(envelope.items()[0].ty(), &ItemType::Attachment);
+    assert_eq!(envelope.items()[0].payload(), b"\n\xff\n");
+    assert_eq!(envelope.to_vec(), &bytes[..]);
+}
+
+#[test]
+fn test_envelope_build() {
+    let mut envelope = Envelope::new();
+    envelope.add_item(EnvelopeItem::new(ItemType::Event, b"{}".to_vec()));
+    assert_eq!(
+        String::from_utf8(envelope.to_vec()).unwrap(),
+        "{}\n{\"type\":\"event\",\"length\":2}\n{}\n"
+    );
+}
+
+#[test]
+fn test_envelope_errors() {
+    assert!(matches!(
+        Envelope::from_slice(b"garbage\n"),
+        Err(ParseEnvelopeError::InvalidHeaders(_))
+    ));
+    assert!(matches!(
+        Envelope::from_slice(b"{}\n{\"length\":2}\n{}\n"),
+        Err(ParseEnvelopeError::InvalidItemHeaders(_))
+    ));
+    assert!(matches!(
+        Envelope::from_slice(b"{}\n{\"type\":\"event\",\"length\":20}\n{}\n"),
+        Err(ParseEnvelopeError::UnexpectedEof)
+    ));
+    assert!(matches!(
+        Envelope::from_slice(b"{}\n{\"type\":\"event\",\"length\":1}\n{}\n"),
+        Err(ParseEnvelopeError::MissingNewline)
+    ));
+}
+
+#[test]
+fn test_envelope_trace_header() {
+    let bytes = b"{\"trace\":{\"trace_id\":\"4c79f60c11214eb38604f4ae0781bfb2\",\"public_key\":\"public\",\"sample_rate\":\"1\"}}\n";
+    let envelope = Envelope::from_slice(bytes).unwrap();
+    let trace = envelope.headers().trace.as_ref().unwrap();
+    assert_eq!(trace.public_key, "public");
+    assert_eq!(trace.sample_rate, Some(1.0));
+    assert_eq!(envelope.to_vec(), &bytes[..]);
+}
+
+#[test]
+fn test_envelope_profile_item() {
+    let bytes = b"{}\n{\"type\":\"profile\",\"length\":2}\n{}\n";
+    let envelope = Envelope::from_slice(bytes).unwrap();
+    assert_eq!(envelope.items()[0].ty(), &ItemType::Profile);
+    assert_eq!(envelope.to_vec(), bytes.to_vec());
+}
+
+#[test]
+fn test_envelope_replay_items() {
+    let bytes = b"{\"event_id\":\"9ec79c33ec9942ab8353589fcb2e04dc\"}\n\
+                  {\"type\":\"replay_event\"}\n\
+                  {\"type\":\"replay_event\",\"replay_id\":\"9ec79c33ec9942ab8353589fcb2e04dc\",\"segment_id\":3,\"timestamp\":1514103120,\"urls\":[\"https://example.com/\"],\"trace_ids\":[\"4c79f60c11214eb38604f4ae0781bfb2\"]}\n\
+                  {\"type\":\"replay_recording\",\"length\":19}\n\
+                  {\"segment_id\":3}\n[]\n";
+
+    let envelope = Envelope::from_slice(bytes).unwrap();
+    assert!(envelope.items().iter().all(|item| item.ty().is_replay()));
+
+    let event = envelope.items()[0].replay_event().unwrap().unwrap();
+    assert_eq!(event.segment_id, 3);
+    assert_eq!(event.urls, vec!["https://example.com/"]);
+    assert_eq!(
+        event.trace_ids[0].to_string(),
+        "4c79f60c11214eb38604f4ae0781bfb2"
+    );
+    assert!(envelope.items()[0].replay_recording().is_none());
+
+    let recording = envelope.items()[1].replay_recording().unwrap().unwrap();
+    assert_eq!(recording.headers.segment_id, 3);
+    assert_eq!(recording.body, b"[]");
+    assert!(!recording.is_compressed());
+    assert_eq!(recording.to_vec(), envelope.items()[1].payload());
+
+    assert!(matches!(
+        ReplayRecording::from_slice(b"{\"segment_id\":3}"),
+        Err(ParseEnvelopeError::MissingNewline)
+    ));
+}
+
+#[test]
+#[cfg(feature = "compression")]
+fn test_envelope_compression() {
+    use sentry_types::protocol::envelope::Encoding;
+
+    let mut envelope = Envelope::new();
+    envelope.add_item(EnvelopeItem::new(ItemType::Event, b"{}".to_vec()));
+
+    for &encoding in &[Encoding::Gzip, Encoding::Deflate, Encoding::Zstd] {
+        let compressed = envelope.to_vec_compressed(encoding).unwrap();
+        assert_eq!(Encoding::detect(&compressed), Some(encoding));
+        assert_eq!(Envelope::from_slice(&compressed).unwrap(), envelope);
+        assert_eq!(
+            Envelope::from_slice_encoded(&compressed, encoding).unwrap(),
+            envelope
+        );
+        assert_eq!(encoding.to_string().parse::<Encoding>().unwrap(), encoding);
+    }
+
+    assert_eq!(Encoding::detect(&envelope.to_vec()), None);
+    assert!(matches!(
+        Envelope::from_slice(b"\x1f\x8bgarbage"),
+        Err(ParseEnvelopeError::Decompression(_))
+    ));
+}
+
+#[test]
+#[cfg(feature = "compression")]
+fn test_envelope_item_compression() {
+    use sentry_types::protocol::envelope::Encoding;
+
+    let item =
+        EnvelopeItem::new_compressed(ItemType::Attachment, b"hello world", Encoding::Gzip).unwrap();
+    assert_eq!(item.content_encoding(), Some("gzip"));
+    assert_eq!(&*item.decoded_payload().unwrap(), b"hello world");
+
+    let mut envelope = Envelope::new();
+    envelope.add_item(item);
+    let parsed = Envelope::from_slice(&envelope.to_vec()).unwrap();
+    assert_eq!(parsed.items()[0].content_encoding(), Some("gzip"));
+    assert_eq!(
+        &*parsed.items()[0].decoded_payload().unwrap(),
+        b"hello world"
+    );
+
+    let item = EnvelopeItem::new(ItemType::Attachment, b"plain".to_vec());
+    assert_eq!(item.content_encoding(), None);
+    assert_eq!(&*item.decoded_payload().unwrap(), b"plain");
+
+    let bytes = b"{}\n{\"type\":\"attachment\",\"length\":2,\"content_encoding\":\"br\"}\nxx\n";
+    let envelope = Envelope::from_slice(bytes).unwrap();
+    assert!(envelope.items()[0].decoded_payload().is_err());
+}
+
+#[test]
+fn test_item_type_from_event_type() {
+    assert_eq!(
+        ItemType::from_event_type(&EventType::Transaction),
+        ItemType::Transaction
+    );
+    assert_eq!(
+        ItemType::from_event_type(&EventType::Csp),
+        ItemType::Security
+    );
+    assert_eq!(
+        ItemType::from_event_type(&EventType::Error),
+        ItemType::Event
+    );
+    assert!(ItemType::Transaction.is_event());
+    assert!(!ItemType::Attachment.is_event());
+    assert_eq!("security".parse::<ItemType>().unwrap(), ItemType::Security);
+}
+
+#[test]
+fn test_item_extra_headers() {
+    let envelope = Envelope::from_slice(
+        b"{}\n{\"type\":\"future_thing\",\"length\":2,\"filename\":\"a.txt\",\"nested\":{\"a\":1}}\nhi\n",
+    )
+    .unwrap();
+    let item = &envelope.items()[0];
+    assert!(item.is_unknown());
+    assert_eq!(item.header("filename"), Some("a.txt".into()));
+    assert_eq!(item.header("nested").unwrap()["a"], 1);
+    assert_eq!(item.header("missing"), None);
+
+    let mut headers = sentry_types::protocol::v7::Map::new();
+    headers.insert("filename".to_string(), "b.txt".into());
+    headers.insert("length".to_string(), 1000.into());
+    let item = EnvelopeItem::with_extra_headers(ItemType::Attachment, headers, b"abc".to_vec());
+    assert!(!item.is_unknown());
+    assert_eq!(item.header("length"), Some(3.into()));
+    assert_eq!(item.header("filename"), Some("b.txt".into()));
+
+    let mut envelope = Envelope::new();
+    envelope.add_item(item);
+    let parsed = Envelope::from_slice(&envelope.to_vec()).unwrap();
+    assert_eq!(parsed.items()[0].header("filename"), Some("b.txt".into()));
+    assert_eq!(parsed.items()[0].payload(), b"abc");
+}
+
+#[test]
+fn test_envelope_validate() {
+    let valid = Envelope::from_slice(
+        b"{\"event_id\":\"9ec79c33ec9942ab8353589fcb2e04dc\",\"sent_at\":\"2020-02-07T14:16:00Z\"}\n\
+          {\"type\":\"event\"}\n\
+          {\"event_id\":\"9ec79c33ec9942ab8353589fcb2e04dc\"}\n\
+          {\"type\":\"attachment\",\"length\":3}\nabc\n",
+    )
+    .unwrap();
+    assert_eq!(valid.validate(), vec![]);
+
+    let invalid = Envelope::from_slice(
+        b"{}\n\
+          {\"type\":\"event\"}\n\
+          {\"event_id\":\"9ec79c33ec9942ab8353589fcb2e04dc\"}\n\
+          {\"type\":\"transaction\"}\n\
+          {}\n\
+          {\"type\":\"attachment\",\"length\":3}\nabc\n",
+    )
+    .unwrap();
+    assert_eq!(
+        invalid.validate(),
+        vec![
+            EnvelopeViolation::MultipleEvents(2),
+            EnvelopeViolation::EventIdMismatch {
+                header: None,
+                item: "9ec79c33ec9942ab8353589fcb2e04dc".parse().unwrap(),
+            },
+            EnvelopeViolation::MissingEventId,
+            EnvelopeViolation::MissingSentAt,
+        ]
+    );
+    assert_eq!(
+        EnvelopeViolation::MultipleEvents(2).to_string(),
+        "envelope contains 2 event items"
+    );
+}
+
+#[test]
+fn test_received_envelope() {
+    use chrono::{Duration, TimeZone, Utc};
+
+    let envelope = Envelope::from_slice(b"{\"sent_at\":\"2020-02-07T14:16:00Z\"}\n").unwrap();
+    let sent_at = Utc.with_ymd_and_hms(2020, 2, 7, 14, 16, 0).unwrap();
+    assert_eq!(envelope.headers().sent_at, Some(sent_at));
+    assert!(String::from_utf8(envelope.to_vec())
+        .unwrap()
+        .contains("\"sent_at\":\"2020-02-07T14:16:00Z\""));
+
+    let received = ReceivedEnvelope::new(envelope, sent_at + Duration::hours(2));
+    assert_eq!(received.clock_drift().unwrap().drift(), Duration::hours(2));
+    let config = received.normalize_config();
+    assert_eq!(config.sent_at, Some(sent_at));
+    assert_eq!(config.received_at, sent_at + Duration::hours(2));
+
+    let received = ReceivedEnvelope::new(Envelope::new(), sent_at);
+    assert!(received.clock_drift().is_none());
+}
+
+#[test]
+fn test_invalid_sent_at() {
+    use chrono::{TimeZone, Utc};
+
+    for header in &["\"garbage\"", "1700000000"] {
+        let bytes = format!("{{\"sent_at\":{}}}\n", header);
+        let envelope = Envelope::from_slice(bytes.as_bytes()).unwrap();
+        assert_eq!(envelope.headers().sent_at, None);
+        assert_eq!(
+            envelope.headers().other["sent_at"].to_string(),
+            header.to_string()
+        );
+        assert_eq!(envelope.to_vec(), bytes.as_bytes());
+    }
+
+    let mut envelope = Envelope::from_slice(b"{\"sent_at\":\"garbage\"}\n").unwrap();
+    let sent_at = Utc.with_ymd_and_hms(2020, 2, 7, 14, 16, 0).unwrap();
+    envelope.headers_mut().sent_at = Some(sent_at);
+    let bytes = String::from_utf8(envelope.to_vec()).unwrap();
+    assert_eq!(bytes.matches("sent_at").count(), 1);
+}
+
+#[test]
+fn test_attachment_type_infer() {
+    assert_eq!(
+        AttachmentType::infer("upload", b"MDMP\x93\xa7\x00\x00"),
+        AttachmentType::Minidump
+    );
+    assert_eq!(
+        AttachmentType::infer("report.txt", b"Incident Identifier: 1234\n"),
+        AttachmentType::AppleCrashReport
+    );
+    assert_eq!(
+        AttachmentType::infer("App.CRASH", b""),
+        AttachmentType::AppleCrashReport
+    );
+    assert_eq!(
+        AttachmentType::infer("view-hierarchy.json", b"{}"),
+        AttachmentType::ViewHierarchy
+    );
+    assert_eq!(
+        AttachmentType::infer("ui.json", br#"{"rendering_system":"UIKIT","windows":[]}"#),
+        AttachmentType::ViewHierarchy
+    );
+    assert_eq!(
+        AttachmentType::infer("data.json", br#"{"windows":[]}"#),
+        AttachmentType::Attachment
+    );
+    assert_eq!(
+        AttachmentType::infer(
+            "app.sym",
+            b"MODULE Linux x86_64 5A2F9A1D1E3C4B2A8F6D0C7E9B1A2C3D0 app\nFILE 0 main.c\n"
+        ),
+        AttachmentType::BreakpadSymbols
+    );
+    assert_eq!(
+        AttachmentType::infer(
+            "mapping.txt",
+            b"# compiler: R8\r\n# pg_map_id: 1a2b3c\r\ncom.example.Main -> a.a:\r\n    void run() -> a\r\n"
+        ),
+        AttachmentType::ProguardMapping
+    );
+    assert_eq!(
+        AttachmentType::infer("notes.txt", b"# todo\nfix parser -> soon:\n"),
+        AttachmentType::Attachment
+    );
+    assert_eq!(AttachmentType::Minidump.to_string(), "event.minidump");
+    assert_eq!(
+        "proguard.mapping".parse::<AttachmentType>().unwrap(),
+        AttachmentType::ProguardMapping
+    );
+
+    let envelope = Envelope::from_slice(
+        b"{}\n\
+          {\"type\":\"attachment\",\"length\":4,\"attachment_type\":\"event.minidump\"}\nMDMP\n\
+          {\"type\":\"attachment\",\"length\":1}\na\n\
+          {\"type\":\"event\",\"length\":2}\n{}\n",
+    )
+    .unwrap();
+    let types: Vec<_> = envelope
+        .items()
+        .iter()
+        .map(EnvelopeItem::attachment_type)
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            Some(AttachmentType::Minidump),
+            Some(AttachmentType::Attachment),
+            None
+        ]
+    );
+}
+
+#[test]
+fn test_envelope_counts_by_category() {
+    let envelope = Envelope::from_slice(
+        b"{}\n\
+          {\"type\":\"event\",\"length\":2}\n{}\n\
+          {\"type\":\"attachment\",\"length\":4}\nMDMP\n\
+          {\"type\":\"attachment\",\"length\":3}\nabc\n\
+          {\"type\":\"statsd\",\"length\":27}\nfoo:1|c\nbar:2|d\n\nbaz:3|g\n  \n\
+          {\"type\":\"session\",\"length\":2}\n{}\n\
+          {\"type\":\"session\",\"length\":2}\n{}\n\
+          {\"type\":\"future_thing\",\"length\":2}\n{}\n",
+    )
+    .unwrap();
+
+    let counts = envelope.counts_by_category();
+    assert_eq!(counts.len(), 4);
+    assert_eq!(counts[&DataCategory::Error], 1);
+    assert_eq!(counts[&DataCategory::Attachment], 7);
+    assert_eq!(counts[&DataCategory::MetricBucket], 3);
+    assert_eq!(counts[&DataCategory::Session], 2);
+
+    assert_eq!(
+        ItemType::ReplayRecording.data_category(),
+        Some(DataCategory::Replay)
+    );
+    assert_eq!(
+        ItemType::Unknown("future_thing".into()).data_category(),
+        None
+    );
+    assert!(Envelope::new().counts_by_category().is_empty());
+}
+
+#[test]
+fn test_envelope_split_by_and_retain() {
+    let envelope = Envelope::from_slice(
+        b"{\"event_id\":\"9ec79c33-ec99-42ab-8353-589fcb2e04dc\"}\n\
+          {\"type\":\"event\",\"length\":2}\n{}\n\
+          {\"type\":\"attachment\",\"length\":4}\nMDMP\n\
+          {\"type\":\"session\",\"length\":2}\n{}\n",
+    )
+    .unwrap();
+
+    let (attachments, rest) = envelope.split_by(|item| item.ty() == &ItemType::Attachment);
+    assert_eq!(attachments.items().len(), 1);
+    assert_eq!(attachments.headers(), envelope.headers());
+    assert_eq!(
+        attachments.items()[0].payload().as_ptr(),
+        envelope.items()[1].payload().as_ptr()
+    );
+    let types: Vec<_> = rest.items().iter().map(EnvelopeItem::ty).collect();
+    assert_eq!(types, vec![&ItemType::Event, &ItemType::Session]);
+    assert_eq!(rest.headers(), envelope.headers());
+
+    let mut retained = envelope.clone();
+    retained.retain(|item| item.ty().is_event());
+    assert_eq!(retained.items().len(), 1);
+    assert_eq!(retained.items()[0].ty(), &ItemType::Event);
+    assert_eq!(
+        retained.to_vec(),
+        b"{\"event_id\":\"9ec79c33-ec99-42ab-8353-589fcb2e04dc\"}\n\
+          {\"type\":\"event\",\"length\":2}\n{}\n"
+    );
+}
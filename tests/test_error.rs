@@ -0,0 +1,29 @@
+use std::error::Error as _;
+
+use sentry_types::protocol::envelope::Envelope;
+use sentry_types::{Auth, Dsn, Error, ParseAuthError, ParseDsnError};
+
+fn parse_dsn(dsn: &str) -> Result<Dsn, Error> {
+    Ok(dsn.parse()?)
+}
+
+#[test]
+fn test_error_kinds() {
+    let err = parse_dsn("https://domain/42").unwrap_err();
+    assert!(matches!(err, Error::Dsn(ParseDsnError::NoUsername { .. })));
+    assert_eq!(err.to_string(), "invalid dsn");
+    assert_eq!(
+        err.source().unwrap().to_string(),
+        "username is empty at offset 8"
+    );
+
+    let err = Error::from("Sentry sentry_version=x".parse::<Auth>().unwrap_err());
+    assert!(matches!(err, Error::Auth(ParseAuthError::InvalidVersion)));
+
+    let err = Error::from(serde_json::from_str::<serde_json::Value>("{").unwrap_err());
+    assert!(matches!(err, Error::Protocol(_)));
+
+    let err = Error::from(Envelope::from_slice(b"{").unwrap_err());
+    assert!(matches!(err, Error::Envelope(_)));
+    assert!(err.source().is_some());
+}
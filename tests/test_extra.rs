@@ -0,0 +1,82 @@
+#![cfg(feature = "clock")]
+use serde_json::json;
+
+use sentry_types::protocol::extra::Value;
+use sentry_types::protocol::v7::{self, Map};
+
+#[test]
+fn test_value_roundtrip() {
+    let json =
+        r#"{"a":null,"b":true,"c":-1,"d":18446744073709551615,"e":1.5,"f":"x","g":[1,{"h":[]}]}"#;
+    let value: Value = serde_json::from_str(json).unwrap();
+
+    let map = value.as_object().unwrap();
+    assert!(map["a"].is_null());
+    assert_eq!(map["b"], true);
+    assert_eq!(map["c"], -1);
+    assert_eq!(map["d"], Value::U64(u64::MAX));
+    assert_eq!(map["e"], 1.5);
+    assert_eq!(map["f"], "x");
+    assert_eq!(map["g"].as_array().unwrap()[0], 1);
+    assert_eq!(serde_json::to_string(&value).unwrap(), json);
+}
+
+#[test]
+fn test_value_integers() {
+    let deserialized: Value = serde_json::from_str("42").unwrap();
+    assert_eq!(deserialized, Value::I64(42));
+    assert_eq!(deserialized, Value::from(42u64));
+    assert_eq!(deserialized, Value::from(42usize));
+    assert_eq!(deserialized, Value::from(42i8));
+    assert_eq!(deserialized.as_u64(), Some(42));
+    assert_eq!(deserialized.as_f64(), Some(42.0));
+    assert_eq!(Value::from(-1).as_u64(), None);
+    assert_eq!(Value::from(u64::MAX).as_i64(), None);
+}
+
+#[test]
+fn test_value_json_conversions() {
+    let json = json!({"list": [1, -2, 1.5, "s", null, false], "big": u64::MAX});
+    let value = Value::from(json.clone());
+    assert_eq!(value.as_object().unwrap()["big"], Value::U64(u64::MAX));
+    assert_eq!(serde_json::Value::from(value.clone()), json);
+    assert_eq!(serde_json::to_value(&value).unwrap(), json);
+
+    assert_eq!(
+        serde_json::Value::from(Value::F64(f64::NAN)),
+        serde_json::Value::Null
+    );
+    assert_eq!(Value::from(None::<i32>), Value::Null);
+    assert_eq!(Value::from(Some("x")), "x");
+}
+
+#[test]
+fn test_event_extra() {
+    let mut event = v7::Event::new();
+    event.extra.insert("attempts".into(), 3.into());
+    let mut state = Map::new();
+    state.insert("dirty".into(), true.into());
+    event.extra.insert("state".into(), state.into());
+
+    let json = serde_json::to_value(&event).unwrap();
+    assert_eq!(
+        json["extra"],
+        json!({"attempts": 3, "state": {"dirty": true}})
+    );
+
+    let parsed: v7::Event = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.extra, event.extra);
+}
+
+#[test]
+fn test_frame_vars_and_values_data() {
+    let json = json!({
+        "values": [{"vars": {"count": u64::MAX, "name": "x"}}],
+        "extra": [1.5],
+    });
+    let stacktrace: v7::Values<v7::Frame> = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(stacktrace.data["extra"], Value::Array(vec![1.5.into()]));
+    assert_eq!(stacktrace.values[0].vars["count"], Value::U64(u64::MAX));
+    assert_eq!(stacktrace.values[0].vars["name"], "x");
+    assert_eq!(serde_json::to_value(&stacktrace).unwrap(), json);
+}
@@ -0,0 +1,216 @@
+#![cfg(feature = "clock")]
+use sentry_types::protocol::filters::{FilterStatKey, FiltersConfig, LegacyBrowser};
+use sentry_types::protocol::v7::{Event, Exception, Frame, Request, Stacktrace, User};
+
+fn event_with_user_agent(user_agent: &str) -> Event<'static> {
+    let mut request = Request::default();
+    request
+        .headers
+        .insert("User-Agent".into(), user_agent.into());
+    Event {
+        request: Some(request),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_config_deserialize() {
+    let config: FiltersConfig = serde_json::from_str(
+        r#"{
+            "browserExtensions": {"isEnabled": true},
+            "legacyBrowsers": {"isEnabled": true, "options": ["ie9", "future_browser"]},
+            "clientIps": {"blacklistedIps": ["127.0.0.1"]},
+            "errorMessages": {"patterns": ["*ConnectionError*"]}
+        }"#,
+    )
+    .unwrap();
+
+    assert!(config.browser_extensions.is_enabled);
+    assert!(!config.web_crawlers.is_enabled);
+    assert!(config
+        .legacy_browsers
+        .browsers
+        .contains(&LegacyBrowser::Unknown("future_browser".into())));
+    assert_eq!(config.client_ips.blacklisted_ips, vec!["127.0.0.1"]);
+    assert!(config.releases.releases.is_empty());
+    assert_eq!(FilterStatKey::LegacyBrowsers.to_string(), "legacy-browsers");
+}
+
+#[test]
+fn test_empty_config() {
+    let event = event_with_user_agent("Googlebot/2.1 (+http://www.google.com/bot.html)");
+    assert_eq!(FiltersConfig::default().apply(&event), None);
+}
+
+#[test]
+fn test_client_ips() {
+    let mut config = FiltersConfig::default();
+    config.client_ips.blacklisted_ips = vec!["10.0.0.0/8".into(), "2001:db8::1".into()];
+
+    let event = |ip: &str| Event {
+        user: Some(User {
+            ip_address: Some(ip.parse().unwrap()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        config.apply(&event("10.1.2.3")),
+        Some(FilterStatKey::IpAddress)
+    );
+    assert_eq!(
+        config.apply(&event("2001:db8::1")),
+        Some(FilterStatKey::IpAddress)
+    );
+    assert_eq!(config.apply(&event("11.1.2.3")), None);
+    assert_eq!(config.apply(&event("{{auto}}")), None);
+}
+
+#[test]
+fn test_releases_and_messages() {
+    let mut config = FiltersConfig::default();
+    config.releases.releases = vec!["1.0.*".into()];
+    config.error_messages.patterns = vec!["*connectionerror*".into()];
+
+    let event = Event {
+        release: Some("1.0.3".parse().unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(config.apply(&event), Some(FilterStatKey::ReleaseVersion));
+
+    let event = Event {
+        release: Some("2.0".parse().unwrap()),
+        exception: vec![Exception {
+            ty: "ConnectionError".into(),
+            value: Some("timed out".into()),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    };
+    assert_eq!(config.apply(&event), Some(FilterStatKey::ErrorMessage));
+}
+
+#[test]
+fn test_browser_extensions() {
+    let mut config = FiltersConfig::default();
+    config.browser_extensions.is_enabled = true;
+
+    let event = Event {
+        exception: vec![Exception {
+            ty: "Error".into(),
+            value: Some("boom".into()),
+            stacktrace: Some(Stacktrace {
+                frames: vec![Frame {
+                    abs_path: Some("chrome-extension://abcdef/content.js".into()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    };
+    assert_eq!(config.apply(&event), Some(FilterStatKey::BrowserExtensions));
+
+    let event = Event {
+        exception: vec![Exception {
+            ty: "TypeError".into(),
+            value: Some("undefined is not an object (evaluating 'top.GLOBALS')".into()),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    };
+    assert_eq!(config.apply(&event), Some(FilterStatKey::BrowserExtensions));
+}
+
+#[test]
+fn test_legacy_browsers() {
+    let mut config = FiltersConfig::default();
+    config.legacy_browsers.is_enabled = true;
+
+    let ie8 = event_with_user_agent("Mozilla/4.0 (compatible; MSIE 8.0; Windows NT 6.1)");
+    let ie11 =
+        event_with_user_agent("Mozilla/5.0 (Windows NT 6.1; Trident/7.0; rv:11.0) like Gecko");
+    let chrome = event_with_user_agent(
+        "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) \
+         Chrome/120.0.0.0 Safari/537.36",
+    );
+    let safari5 = event_with_user_agent(
+        "Mozilla/5.0 (Macintosh) AppleWebKit/534.57 (KHTML, like Gecko) Version/5.1 Safari/534.57",
+    );
+
+    assert_eq!(config.apply(&ie8), Some(FilterStatKey::LegacyBrowsers));
+    assert_eq!(config.apply(&ie11), Some(FilterStatKey::LegacyBrowsers));
+    assert_eq!(config.apply(&safari5), Some(FilterStatKey::LegacyBrowsers));
+    assert_eq!(config.apply(&chrome), None);
+
+    config
+        .legacy_browsers
+        .browsers
+        .insert(LegacyBrowser::IePre9);
+    assert_eq!(config.apply(&ie8), Some(FilterStatKey::LegacyBrowsers));
+    assert_eq!(config.apply(&ie11), None);
+}
+
+#[test]
+fn test_web_crawlers() {
+    let mut config = FiltersConfig::default();
+    config.web_crawlers.is_enabled = true;
+
+    let bot = event_with_user_agent("Mozilla/5.0 (compatible; bingbot/2.0)");
+    assert_eq!(config.apply(&bot), Some(FilterStatKey::WebCrawlers));
+
+    let user = event_with_user_agent("Mozilla/5.0 (Windows NT 10.0; rv:120.0) Firefox/120.0");
+    assert_eq!(config.apply(&user), None);
+}
+
+#[test]
+fn test_localhost() {
+    let config: FiltersConfig =
+        serde_json::from_str(r#"{"localhost": {"isEnabled": true}}"#).unwrap();
+
+    let with_ip = |ip: &str| Event {
+        user: Some(User {
+            ip_address: Some(ip.parse().unwrap()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let with_url = |url: &str| Event {
+        request: Some(Request {
+            url: Some(url.parse().unwrap()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        with_ip("127.0.0.1").matches_filter(&config),
+        Some(FilterStatKey::Localhost)
+    );
+    assert_eq!(
+        with_ip("::1").matches_filter(&config),
+        Some(FilterStatKey::Localhost)
+    );
+    assert_eq!(
+        with_url("http://localhost:8000/").matches_filter(&config),
+        Some(FilterStatKey::Localhost)
+    );
+    assert_eq!(
+        with_url("http://app.localhost/").matches_filter(&config),
+        Some(FilterStatKey::Localhost)
+    );
+    assert_eq!(with_ip("10.0.0.1").matches_filter(&config), None);
+    assert_eq!(
+        with_url("https://example.com/").matches_filter(&config),
+        None
+    );
+    assert_eq!(
+        with_url("http://localhost:8000/").matches_filter(&FiltersConfig::default()),
+        None
+    );
+}
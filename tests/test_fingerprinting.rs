@@ -0,0 +1,131 @@
+#![cfg(feature = "clock")]
+use sentry_types::protocol::fingerprinting::{
+    FingerprintMatcherKey, FingerprintingRules, ParseFingerprintingError,
+};
+use sentry_types::protocol::v7::{Event, Exception, Frame, Level, Stacktrace};
+
+fn exception(ty: &str, value: &str, function: &str) -> Exception {
+    Exception {
+        ty: ty.into(),
+        value: Some(value.into()),
+        stacktrace: Some(Stacktrace {
+            frames: vec![Frame {
+                function: Some(function.into()),
+                in_app: Some(true),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_parse_rules() {
+    let rules = FingerprintingRules::parse(
+        "# comment\n\
+         \n\
+         error.type:DatabaseUnavailable -> system-down\n\
+         error.type:ConnectionError error.value:\"*timed out*\" -> timeout,{{transaction}}\n\
+         !tags.server:\"web 1\" logger:my.* -> logger-error \"a b\" title=\"Logger Error\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(rules.rules.len(), 3);
+    assert_eq!(rules.rules[1].matchers[1].key, FingerprintMatcherKey::Value);
+    assert_eq!(rules.rules[1].matchers[1].pattern, "*timed out*");
+    assert_eq!(
+        rules.rules[1].fingerprint,
+        vec!["timeout".to_string(), "{{ transaction }}".to_string()]
+    );
+    let rule = &rules.rules[2];
+    assert!(rule.matchers[0].negated);
+    assert_eq!(
+        rule.matchers[0].key,
+        FingerprintMatcherKey::Tag("server".into())
+    );
+    assert_eq!(rule.fingerprint, vec!["logger-error", "a b"]);
+    assert_eq!(rule.title.as_deref(), Some("Logger Error"));
+
+    assert_eq!(
+        rules.to_string(),
+        "error.type:DatabaseUnavailable -> system-down\n\
+         error.type:ConnectionError error.value:\"*timed out*\" -> timeout, {{ transaction }}\n\
+         !tags.server:\"web 1\" logger:my.* -> logger-error, \"a b\" title=\"Logger Error\"\n"
+    );
+    assert_eq!(
+        rules.to_string().parse::<FingerprintingRules>().unwrap(),
+        rules
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        FingerprintingRules::parse("\nerror.type:Foo"),
+        Err(ParseFingerprintingError::MissingFingerprint(2))
+    );
+    assert_eq!(
+        FingerprintingRules::parse("-> foo"),
+        Err(ParseFingerprintingError::MissingMatchers(1))
+    );
+    assert_eq!(
+        FingerprintingRules::parse("error.type:Foo ->"),
+        Err(ParseFingerprintingError::MissingFingerprint(1))
+    );
+    assert_eq!(
+        FingerprintingRules::parse("foo:bar -> baz"),
+        Err(ParseFingerprintingError::UnknownMatcher(1, "foo".into()))
+    );
+    assert_eq!(
+        FingerprintingRules::parse("error.type:Foo -> foo color=red"),
+        Err(ParseFingerprintingError::InvalidAttribute(1, "red".into()))
+    );
+    assert_eq!(
+        FingerprintingRules::parse("message:\"foo -> bar"),
+        Err(ParseFingerprintingError::MissingFingerprint(1))
+    );
+}
+
+#[test]
+fn test_apply() {
+    let rules = FingerprintingRules::parse(
+        "error.type:ConnectionError stack.function:connect -> connection\n\
+         error.type:ConnectionError -> other-connection\n\
+         level:warning message:\"*disk*\" -> disk\n",
+    )
+    .unwrap();
+
+    let mut event = Event {
+        exception: vec![
+            exception("ValueError", "bad value", "connect"),
+            exception("ConnectionError", "refused", "send"),
+        ]
+        .into(),
+        ..Default::default()
+    };
+    // the frame matcher must match within the same event, any stack trace
+    let rule = rules.apply(&mut event).unwrap();
+    assert_eq!(rule.fingerprint, vec!["connection"]);
+    assert_eq!(&event.fingerprint[..], &["connection"]);
+
+    let event = Event {
+        exception: vec![exception("ConnectionError", "refused", "send")].into(),
+        ..Default::default()
+    };
+    assert_eq!(
+        rules.matching_rule(&event).unwrap().fingerprint,
+        vec!["other-connection"]
+    );
+
+    let mut event = Event {
+        level: Level::Warning,
+        message: Some("out of disk space".into()),
+        ..Default::default()
+    };
+    assert!(rules.apply(&mut event).is_some());
+    assert_eq!(&event.fingerprint[..], &["disk"]);
+
+    event.level = Level::Error;
+    assert!(rules.matching_rule(&event).is_none());
+}
@@ -0,0 +1,66 @@
+//! Runs the fuzzing corpus through all public parse entry points.
+//!
+//! Every corpus input is parsed as is, truncated at every position and with
+//! every byte replaced by a few interesting values.  None of this may panic.
+#![cfg(feature = "fuzz_corpus")]
+
+use std::fs;
+use std::path::Path;
+
+use sentry_types::protocol::{envelope, v7};
+use sentry_types::{parse_auth_bytes, parse_dsn_bytes};
+
+const REPLACEMENTS: &[u8] = b"\x00\n\"{}[]:,0x9-.\xff";
+
+fn for_each_input<F: Fn(&[u8])>(target: &str, f: F) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fuzz")
+        .join("corpus")
+        .join(target);
+
+    for entry in fs::read_dir(dir).unwrap() {
+        let input = fs::read(entry.unwrap().path()).unwrap();
+        f(&input);
+
+        for len in 0..input.len() {
+            f(&input[..len]);
+        }
+
+        let mut mutated = input.clone();
+        for pos in 0..input.len() {
+            for &byte in REPLACEMENTS {
+                mutated[pos] = byte;
+                f(&mutated);
+            }
+            mutated[pos] = input[pos];
+        }
+    }
+}
+
+#[test]
+fn test_parse_dsn_corpus() {
+    for_each_input("parse_dsn", |input| {
+        parse_dsn_bytes(input).ok();
+    });
+}
+
+#[test]
+fn test_parse_auth_corpus() {
+    for_each_input("parse_auth", |input| {
+        parse_auth_bytes(input).ok();
+    });
+}
+
+#[test]
+fn test_parse_event_corpus() {
+    for_each_input("parse_event", |input| {
+        v7::parse_event_bytes(input).ok();
+    });
+}
+
+#[test]
+fn test_parse_envelope_corpus() {
+    for_each_input("parse_envelope", |input| {
+        envelope::parse_envelope_bytes(input).ok();
+    });
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "clock")]
+use std::borrow::Cow;
+
+use sentry_types::protocol::grouping::{compute_hashes, hash_from_values};
+use sentry_types::protocol::v7::{Event, Exception, Frame, Stacktrace};
+
+fn frame(module: &str, function: &str, in_app: bool) -> Frame {
+    Frame {
+        module: Some(module.into()),
+        function: Some(function.into()),
+        in_app: Some(in_app),
+        ..Default::default()
+    }
+}
+
+fn exception_event() -> Event<'static> {
+    Event {
+        exception: vec![Exception {
+            ty: "ZeroDivisionError".into(),
+            value: Some("division by zero".into()),
+            stacktrace: Some(Stacktrace {
+                frames: vec![
+                    frame("runpy", "_run_code", false),
+                    frame("app.views", "divide", true),
+                ],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_hash_from_values() {
+    assert_eq!(
+        hash_from_values(Vec::<&str>::new()),
+        "d41d8cd98f00b204e9800998ecf8427e"
+    );
+    assert_eq!(
+        hash_from_values(["foo", "bar"]),
+        hash_from_values(["foobar"])
+    );
+}
+
+#[test]
+fn test_custom_fingerprint() {
+    let mut event = exception_event();
+    event.fingerprint = Cow::Owned(vec!["database-unavailable".into()]);
+    assert_eq!(
+        compute_hashes(&event),
+        vec![hash_from_values(["database-unavailable"])]
+    );
+}
+
+#[test]
+fn test_exception_variants() {
+    let event = exception_event();
+    assert_eq!(
+        event.compute_hashes(),
+        vec![
+            hash_from_values(["ZeroDivisionError", "app.views", "divide"]),
+            hash_from_values([
+                "ZeroDivisionError",
+                "runpy",
+                "_run_code",
+                "app.views",
+                "divide"
+            ]),
+        ]
+    );
+
+    // the exception value does not matter when there is a stack trace
+    let mut other = exception_event();
+    other.exception.values[0].value = Some("other message".into());
+    assert_eq!(compute_hashes(&other), compute_hashes(&event));
+
+    // additional fingerprint values are appended to the default components
+    let mut extended = exception_event();
+    extended.fingerprint = Cow::Owned(vec!["{{ default }}".into(), "tenant-42".into()]);
+    assert_eq!(
+        compute_hashes(&extended)[0],
+        hash_from_values(["ZeroDivisionError", "app.views", "divide", "tenant-42"])
+    );
+}
+
+#[test]
+fn test_without_stacktrace() {
+    let event = Event {
+        exception: vec![Exception {
+            ty: "ValueError".into(),
+            value: Some("invalid literal".into()),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    };
+    assert_eq!(
+        compute_hashes(&event),
+        vec![hash_from_values(["ValueError", "invalid literal"])]
+    );
+
+    let event = Event {
+        message: Some("Hello World".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        compute_hashes(&event),
+        vec![hash_from_values(["Hello World"])]
+    );
+}
+
+#[test]
+fn test_filename_fallback() {
+    let event = Event {
+        stacktrace: Some(Stacktrace {
+            frames: vec![Frame {
+                abs_path: Some("https://example.com/static/app.js?v=123".into()),
+                context_line: Some("  throw new Error();  ".into()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    assert_eq!(
+        compute_hashes(&event),
+        vec![hash_from_values(["app.js", "throw new Error();"])]
+    );
+}
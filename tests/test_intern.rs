@@ -0,0 +1,69 @@
+use sentry_types::protocol::v7::{parse_event_bytes, Frame};
+use sentry_types::{with_interner, ThinString};
+
+const EVENT: &[u8] = br#"{"event_id":"9ec79c33ec9942ab8353589fcb2e04dc","timestamp":0,
+"stacktrace":{"frames":[
+    {"module":"app.views","filename":"views.py"},
+    {"module":"app.views","filename":"views.py"}
+]}}"#;
+
+fn frames(bytes: &[u8]) -> Vec<Frame> {
+    parse_event_bytes(bytes).unwrap().stacktrace.unwrap().frames
+}
+
+#[test]
+fn test_thin_string() {
+    let string = ThinString::from("app.views");
+    assert_eq!(string, "app.views");
+    assert_eq!(string.len(), 9);
+    assert_eq!(format!("{:?}", string), "\"app.views\"");
+    assert_eq!(String::from(string.clone()), "app.views");
+    assert!(string.ptr_eq(&string.clone()));
+    assert!(!string.ptr_eq(&ThinString::from("app.views")));
+}
+
+#[test]
+fn test_interned_deserialization() {
+    let plain = frames(EVENT);
+    let module = plain[0].module.as_ref().unwrap();
+    assert_eq!(module, "app.views");
+    assert!(!module.ptr_eq(plain[1].module.as_ref().unwrap()));
+
+    let interned = with_interner(|| frames(EVENT));
+    assert_eq!(interned, plain);
+    let module = interned[0].module.as_ref().unwrap();
+    assert!(module.ptr_eq(interned[1].module.as_ref().unwrap()));
+    let filename = interned[0].filename.as_ref().unwrap();
+    assert!(filename.ptr_eq(interned[1].filename.as_ref().unwrap()));
+
+    // the interner is only active within the closure
+    let after = frames(EVENT);
+    assert!(!after[0]
+        .module
+        .as_ref()
+        .unwrap()
+        .ptr_eq(after[1].module.as_ref().unwrap()));
+}
+
+#[test]
+fn test_interned_map_keys() {
+    let bytes = br#"{"event_id":"9ec79c33ec9942ab8353589fcb2e04dc","timestamp":0,
+"tags":{"url":"a"},
+"breadcrumbs":[{"timestamp":0,"data":{"url":"b"}},{"timestamp":0,"data":{"url":"c"}}]}"#;
+    let event = with_interner(|| parse_event_bytes(bytes)).unwrap();
+    let tag = event.tags.keys().next().unwrap();
+    assert_eq!(tag, "url");
+    for breadcrumb in &event.breadcrumbs {
+        assert!(tag.ptr_eq(breadcrumb.data.keys().next().unwrap()));
+    }
+}
+
+#[test]
+fn test_nested_interners() {
+    let (outer, inner) = with_interner(|| {
+        let inner = with_interner(|| ThinString::from("a"));
+        (ThinString::from("a"), inner)
+    });
+    assert!(!outer.ptr_eq(&inner));
+    with_interner(|| assert!(ThinString::from("b").ptr_eq(&ThinString::from("b"))));
+}
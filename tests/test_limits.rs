@@ -0,0 +1,94 @@
+use sentry_types::protocol::envelope::{Envelope, ParseEnvelopeError};
+use sentry_types::protocol::limits::{LimitError, ParseLimits};
+use sentry_types::protocol::v7::parse_event_bytes;
+
+fn limits() -> ParseLimits {
+    ParseLimits {
+        max_depth: 3,
+        max_array_length: 3,
+        max_string_length: 8,
+    }
+}
+
+#[test]
+fn test_check_depth() {
+    assert_eq!(limits().check(br#"{"a":[{"b":1}]}"#), Ok(()));
+    assert_eq!(
+        limits().check(br#"{"a":[{"b":[]}]}"#),
+        Err(LimitError::Depth(3))
+    );
+    assert_eq!(limits().check(b"[[[]],[[]]]"), Ok(()));
+}
+
+#[test]
+fn test_check_array_length() {
+    assert_eq!(limits().check(br#"[1, "a,b", {"c": [1]}]"#), Ok(()));
+    assert_eq!(
+        limits().check(b"[true, false, null, 1]"),
+        Err(LimitError::ArrayLength(3))
+    );
+    assert_eq!(limits().check(b"[[1,2,3],[1,2,3],[]]"), Ok(()));
+    assert_eq!(limits().check(b"[ ]"), Ok(()));
+    // object entries are not limited
+    assert_eq!(limits().check(br#"{"a":1,"b":2,"c":3,"d":4}"#), Ok(()));
+}
+
+#[test]
+fn test_check_string_length() {
+    assert_eq!(limits().check(br#"{"12345678":"a\"b\\"}"#), Ok(()));
+    assert_eq!(
+        limits().check(br#"{"a":"123456789"}"#),
+        Err(LimitError::StringLength(8))
+    );
+    assert_eq!(
+        limits().check(br#"{"123456789":1}"#),
+        Err(LimitError::StringLength(8))
+    );
+}
+
+#[test]
+#[cfg(feature = "clock")]
+fn test_parse_event_with_limits() {
+    use sentry_types::protocol::limits::{parse_event_bytes_with_limits, ParseEventError};
+
+    let event = parse_event_bytes_with_limits(br#"{"message":"hello"}"#, &limits()).unwrap();
+    assert_eq!(event.message.as_deref(), Some("hello"));
+
+    let result = parse_event_bytes_with_limits(br#"{"message":"hello world"}"#, &limits());
+    assert!(matches!(
+        result,
+        Err(ParseEventError::LimitExceeded(LimitError::StringLength(8)))
+    ));
+
+    let result = parse_event_bytes_with_limits(br#"{"level":1}"#, &limits());
+    assert!(matches!(result, Err(ParseEventError::InvalidJson(_))));
+
+    let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+    let result = parse_event_bytes_with_limits(deep.as_bytes(), &ParseLimits::default());
+    assert!(matches!(
+        result,
+        Err(ParseEventError::LimitExceeded(LimitError::Depth(64)))
+    ));
+}
+
+#[test]
+fn test_parse_deeply_nested_without_limits() {
+    let deep = format!(
+        "{{\"extra\":{{\"a\":{}{}}}}}",
+        "[".repeat(200_000),
+        "]".repeat(200_000)
+    );
+    assert!(parse_event_bytes(deep.as_bytes()).is_err());
+}
+
+#[test]
+fn test_envelope_with_limits() {
+    let bytes = b"{}\n{\"type\":\"attachment\"}\n[[[[]]]]\n{\"type\":\"event\"}\n{\"a\":[1,2]}\n";
+    assert!(Envelope::from_slice_with_limits(bytes, &limits()).is_ok());
+
+    let bytes = b"{}\n{\"type\":\"event\"}\n{\"a\":[[[]]]}\n";
+    assert!(matches!(
+        Envelope::from_slice_with_limits(bytes, &limits()),
+        Err(ParseEnvelopeError::LimitExceeded(LimitError::Depth(3)))
+    ));
+}
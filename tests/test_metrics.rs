@@ -0,0 +1,107 @@
+use chrono::{TimeZone, Utc};
+use sentry_types::protocol::envelope::{Envelope, EnvelopeItem, ItemType};
+use sentry_types::protocol::metrics::{
+    BucketValue, GaugeValue, MetricBucket, MetricType, ParseMetricError,
+};
+
+#[test]
+fn test_parse_distribution() {
+    let bucket: MetricBucket =
+        "endpoint.response_time@millisecond:36:49:57|d|#route:user_index,env:prod|T1615889440"
+            .parse()
+            .unwrap();
+
+    assert_eq!(bucket.name, "endpoint.response_time");
+    assert_eq!(bucket.unit, "millisecond");
+    assert_eq!(bucket.ty(), MetricType::Distribution);
+    assert_eq!(
+        bucket.value,
+        BucketValue::Distribution(vec![36.0, 49.0, 57.0])
+    );
+    assert_eq!(bucket.tags["route"], "user_index");
+    assert_eq!(
+        bucket.timestamp,
+        Utc.timestamp_opt(1_615_889_440, 0).single()
+    );
+    assert_eq!(
+        bucket.to_string(),
+        "endpoint.response_time@millisecond:36:49:57|d|#env:prod,route:user_index|T1615889440"
+    );
+}
+
+#[test]
+fn test_parse_types() {
+    let counter: MetricBucket = "clicks:1:2.5|c".parse().unwrap();
+    assert_eq!(counter.unit, "none");
+    assert_eq!(counter.value, BucketValue::Counter(3.5));
+
+    let set: MetricBucket = "users@none:3:1:3|s".parse().unwrap();
+    assert_eq!(
+        set.value,
+        BucketValue::Set(vec![1, 3].into_iter().collect())
+    );
+    assert_eq!(set.to_string(), "users@none:1:3|s");
+
+    let gauge: MetricBucket = "memory@byte:10|g".parse().unwrap();
+    assert_eq!(gauge.value, BucketValue::Gauge(GaugeValue::single(10.0)));
+
+    let gauge: MetricBucket = "memory@byte:10:5:20:35:3|g".parse().unwrap();
+    let mut expected = GaugeValue::single(5.0);
+    expected.insert(20.0);
+    expected.insert(10.0);
+    assert_eq!(gauge.value, BucketValue::Gauge(expected));
+    assert_eq!(gauge.to_string(), "memory@byte:10:5:20:35:3|g");
+}
+
+#[test]
+fn test_tag_escaping() {
+    let mut bucket = MetricBucket::new("hits", BucketValue::Counter(1.0));
+    bucket.tags.insert("path".into(), "a|b,c\\d\ne".into());
+
+    let line = bucket.to_string();
+    assert_eq!(line, "hits@none:1|c|#path:a\\u{7c}b\\u{2c}c\\\\d\\ne");
+    assert_eq!(line.parse::<MetricBucket>().unwrap(), bucket);
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        "1abc:1|c".parse::<MetricBucket>(),
+        Err(ParseMetricError::InvalidName)
+    );
+    assert_eq!(
+        "abc:x|c".parse::<MetricBucket>(),
+        Err(ParseMetricError::InvalidValue)
+    );
+    assert_eq!(
+        "abc:1|x".parse::<MetricBucket>(),
+        Err(ParseMetricError::InvalidType)
+    );
+    assert_eq!(
+        "abc:1.5|s".parse::<MetricBucket>(),
+        Err(ParseMetricError::InvalidValue)
+    );
+    assert_eq!(
+        "abc:1|c|#a:\\x".parse::<MetricBucket>(),
+        Err(ParseMetricError::InvalidTags)
+    );
+    assert_eq!(
+        "abc:1|c|Tnow".parse::<MetricBucket>(),
+        Err(ParseMetricError::InvalidTimestamp)
+    );
+}
+
+#[test]
+fn test_statsd_item() {
+    let buckets = vec![
+        MetricBucket::new("a", BucketValue::Counter(1.0)),
+        MetricBucket::new("b", BucketValue::Distribution(vec![1.5, 2.0])),
+    ];
+    let payload = MetricBucket::to_statsd(&buckets);
+    assert_eq!(payload, "a@none:1|c\nb@none:1.5:2|d\n");
+
+    let mut envelope = Envelope::new();
+    envelope.add_item(EnvelopeItem::new(ItemType::Statsd, payload.into_bytes()));
+    let envelope = Envelope::from_slice(&envelope.to_vec()).unwrap();
+    assert_eq!(envelope.items()[0].metrics().unwrap().unwrap(), buckets);
+}
@@ -0,0 +1,487 @@
+#![cfg(feature = "clock")]
+use chrono::{Duration, TimeZone, Utc};
+use serde_json::json;
+
+use sentry_types::protocol::normalize::{correct_clock_drift, ClockDrift, NormalizeConfig};
+use sentry_types::protocol::v7::{
+    Breadcrumb, CError, Event, EventProcessingError, Exception, Frame, MachException, Mechanism,
+    MechanismMeta, Platform, PosixSignal, Stacktrace, User,
+};
+
+fn config() -> NormalizeConfig {
+    NormalizeConfig {
+        received_at: Utc.timestamp_opt(1_514_103_120, 0).unwrap(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_normalize_timestamp() {
+    let config = config();
+
+    let mut event = Event {
+        timestamp: config.received_at + Duration::seconds(10),
+        ..Default::default()
+    };
+    event.normalize(&config);
+    assert_eq!(event.timestamp, config.received_at + Duration::seconds(10));
+    assert!(event.meta.is_empty());
+
+    let mut event = Event {
+        timestamp: config.received_at + Duration::hours(1),
+        ..Default::default()
+    };
+    event.normalize(&config);
+    assert_eq!(event.timestamp, config.received_at);
+    let meta = event.meta.get("timestamp").unwrap();
+    assert_eq!(meta.errors, vec![json!(["future_timestamp"])]);
+    assert_eq!(meta.original_value, Some(json!(1_514_106_720.0)));
+    assert_eq!(
+        event.errors,
+        vec![EventProcessingError::new(
+            EventProcessingError::FUTURE_TIMESTAMP,
+            "timestamp",
+            Some(json!(1_514_106_720.0)),
+        )]
+    );
+
+    let mut event = Event {
+        timestamp: config.received_at - Duration::days(31),
+        ..Default::default()
+    };
+    event.normalize(&config);
+    assert_eq!(event.timestamp, config.received_at);
+    assert_eq!(
+        event.meta.get("timestamp").unwrap().errors,
+        vec![json!(["past_timestamp"])]
+    );
+}
+
+#[test]
+fn test_normalize_timestamp_unbounded() {
+    for secs in &[i64::MAX, i64::MAX / 2, i64::MIN] {
+        let config = NormalizeConfig {
+            max_secs_in_future: Some(*secs),
+            max_secs_in_past: Some(*secs),
+            ..config()
+        };
+        let timestamp = config.received_at - Duration::days(365);
+        let mut event = Event {
+            timestamp,
+            ..Default::default()
+        };
+        event.normalize(&config);
+        assert_eq!(event.timestamp, timestamp);
+        assert!(event.errors.is_empty());
+    }
+}
+
+#[test]
+fn test_normalize_empty_strings() {
+    let mut event = Event {
+        timestamp: config().received_at,
+        culprit: Some("".into()),
+        transaction: Some("/index".into()),
+        server_name: Some("".into()),
+        user: Some(User {
+            id: Some("".into()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    event.normalize(&config());
+    assert_eq!(event.culprit, None);
+    assert_eq!(event.transaction.as_deref(), Some("/index"));
+    assert_eq!(event.server_name, None);
+    assert_eq!(event.user.unwrap().id, None);
+}
+
+#[test]
+fn test_normalize_tags() {
+    let mut event = Event {
+        timestamp: config().received_at,
+        ..Default::default()
+    };
+    event.tags.insert("valid.key".into(), "value".into());
+    event.tags.insert("invalid key".into(), "value".into());
+    event.tags.insert("empty".into(), "".into());
+    event.tags.insert("app.version".into(), "".into());
+    event.tags.insert("long".into(), "x".repeat(250));
+    event.tags.insert("unicode".into(), "🦀".repeat(201));
+    event.normalize(&config());
+
+    assert_eq!(event.tags.len(), 3);
+    assert_eq!(event.tags["valid.key"], "value");
+    assert_eq!(event.tags["long"], format!("{}...", "x".repeat(197)));
+    assert_eq!(event.tags["unicode"], format!("{}...", "🦀".repeat(197)));
+
+    let meta = serde_json::to_value(&event.meta).unwrap();
+    assert_eq!(
+        meta["tags"]["invalid key"][""],
+        json!({
+            "err": [["invalid_data", {"reason": "invalid character in tag key"}]],
+            "val": "value",
+        })
+    );
+    assert_eq!(
+        meta["tags"]["empty"][""]["err"],
+        json!([["invalid_data", {"reason": "empty tag value"}]])
+    );
+    assert_eq!(meta["tags"]["long"][""]["len"], json!(250));
+    assert_eq!(meta["tags"]["unicode"][""]["len"], json!(201));
+
+    let errors = serde_json::to_value(&event.errors).unwrap();
+    assert_eq!(
+        errors,
+        json!([
+            {"type": "invalid_data", "name": "tags.app\\.version", "value": ""},
+            {"type": "invalid_data", "name": "tags.empty", "value": ""},
+            {"type": "invalid_data", "name": "tags.invalid key", "value": "value"},
+        ])
+    );
+}
+
+#[test]
+fn test_normalize_contexts() {
+    let mut event: Event = serde_json::from_value(json!({
+        "timestamp": 0,
+        "contexts": {
+            "OS": {"name": "Linux"},
+            "os": {"name": "Windows"},
+            "Runtime": {"name": "rustc"},
+            "RUNTIME": {"name": "rustc"},
+            "my_os": {"type": "os", "name": "Darwin"},
+        },
+    }))
+    .unwrap();
+    event.timestamp = config().received_at;
+    event.normalize(&config());
+
+    let keys: Vec<_> = event.contexts.keys().map(String::as_str).collect();
+    assert_eq!(keys, vec!["RUNTIME", "os"]);
+
+    let meta = serde_json::to_value(&event.meta).unwrap();
+    assert_eq!(
+        meta["contexts"]["OS"][""],
+        json!({
+            "err": [["invalid_data", {"reason": "duplicate context key"}]],
+            "val": {"type": "os", "name": "Linux"},
+        })
+    );
+    assert!(meta["contexts"]["Runtime"][""]["err"].is_array());
+    assert_eq!(
+        meta["contexts"]["my_os"][""]["val"],
+        json!({"type": "os", "name": "Darwin"})
+    );
+
+    let names: Vec<_> = event.errors.iter().map(|e| e.name.as_deref()).collect();
+    assert_eq!(
+        names,
+        vec![
+            Some("contexts.Runtime"),
+            Some("contexts.my_os"),
+            Some("contexts.OS"),
+        ]
+    );
+}
+
+#[test]
+fn test_normalize_platform() {
+    let mut event = Event {
+        timestamp: config().received_at,
+        platform: "brainfuck".parse().unwrap(),
+        ..Default::default()
+    };
+    event.normalize(&config());
+    assert_eq!(event.platform, Platform::Other);
+    let meta = event.meta.get("platform").unwrap();
+    assert_eq!(meta.original_value, Some(json!("brainfuck")));
+    assert_eq!(
+        meta.errors,
+        vec![json!(["invalid_data", {"reason": "unknown platform"}])]
+    );
+    assert_eq!(
+        event.errors,
+        vec![EventProcessingError::new(
+            EventProcessingError::INVALID_DATA,
+            "platform",
+            Some(json!("brainfuck")),
+        )]
+    );
+}
+
+#[test]
+fn test_normalize_mechanism_names() {
+    let mut event = Event {
+        timestamp: config().received_at,
+        exception: vec![Exception {
+            ty: "EXC_BAD_ACCESS".into(),
+            mechanism: Some(Mechanism {
+                ty: "mach".into(),
+                meta: MechanismMeta {
+                    errno: Some(CError {
+                        number: 13,
+                        name: None,
+                    }),
+                    signal: Some(PosixSignal {
+                        number: 11,
+                        code: Some(1),
+                        name: Some("custom".into()),
+                        code_name: None,
+                    }),
+                    mach_exception: Some(MachException {
+                        exception: 1,
+                        code: 1,
+                        subcode: 8,
+                        name: None,
+                    }),
+                },
+                ..Default::default()
+            }),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    };
+    event.normalize(&config());
+
+    let meta = &event.exception[0].mechanism.as_ref().unwrap().meta;
+    assert_eq!(meta.errno.as_ref().unwrap().name.as_deref(), Some("EACCES"));
+    let signal = meta.signal.as_ref().unwrap();
+    assert_eq!(signal.name.as_deref(), Some("custom"));
+    assert_eq!(signal.code_name.as_deref(), Some("SEGV_MAPERR"));
+    assert_eq!(
+        meta.mach_exception.as_ref().unwrap().name.as_deref(),
+        Some("EXC_BAD_ACCESS")
+    );
+}
+
+#[test]
+fn test_normalize_sort_breadcrumbs() {
+    let breadcrumb = |secs: i64, message: &str| Breadcrumb {
+        timestamp: Utc.timestamp_opt(1_514_103_000 + secs, 0).unwrap(),
+        message: Some(message.into()),
+        ..Default::default()
+    };
+    let breadcrumbs = vec![
+        breadcrumb(3, "d"),
+        breadcrumb(1, "b"),
+        breadcrumb(0, "a"),
+        breadcrumb(1, "b"),
+        breadcrumb(2, "c"),
+    ];
+
+    let mut event = Event {
+        timestamp: config().received_at,
+        breadcrumbs: breadcrumbs.clone().into(),
+        ..Default::default()
+    };
+    event.normalize(&config());
+    assert_eq!(event.breadcrumbs.values, breadcrumbs);
+
+    let config = NormalizeConfig {
+        max_breadcrumbs: 2,
+        sort_breadcrumbs: true,
+        ..config()
+    };
+    let mut event = Event {
+        timestamp: config.received_at,
+        breadcrumbs: breadcrumbs.into(),
+        ..Default::default()
+    };
+    event.normalize(&config);
+
+    let messages: Vec<_> = event
+        .breadcrumbs
+        .iter()
+        .map(|b| b.message.as_deref().unwrap())
+        .collect();
+    assert_eq!(messages, vec!["c", "d"]);
+    assert_eq!(
+        event
+            .meta
+            .get("breadcrumbs.values")
+            .unwrap()
+            .original_length,
+        Some(5)
+    );
+    assert!(event.errors.is_empty());
+}
+
+#[test]
+fn test_normalize_lists() {
+    let config = NormalizeConfig {
+        max_breadcrumbs: 2,
+        max_frames: 4,
+        ..config()
+    };
+
+    let mut event = Event {
+        timestamp: config.received_at,
+        breadcrumbs: (0..5)
+            .map(|i| Breadcrumb {
+                message: Some(i.to_string()),
+                ..Default::default()
+            })
+            .collect(),
+        stacktrace: Some(Stacktrace {
+            frames: (0..10)
+                .map(|i| Frame {
+                    function: Some(i.to_string()),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    event.normalize(&config);
+
+    let messages: Vec<_> = event
+        .breadcrumbs
+        .iter()
+        .map(|b| b.message.as_deref().unwrap())
+        .collect();
+    assert_eq!(messages, vec!["3", "4"]);
+    assert_eq!(
+        event
+            .meta
+            .get("breadcrumbs.values")
+            .unwrap()
+            .original_length,
+        Some(5)
+    );
+
+    let stacktrace = event.stacktrace.unwrap();
+    let functions: Vec<_> = stacktrace
+        .frames
+        .iter()
+        .map(|f| f.function.as_deref().unwrap())
+        .collect();
+    assert_eq!(functions, vec!["0", "1", "8", "9"]);
+    assert_eq!(stacktrace.frames_omitted, Some((2, 8)));
+}
+
+#[test]
+fn test_clock_drift() {
+    let received_at = config().received_at;
+    let sent_at = received_at - Duration::hours(2);
+    let drift = ClockDrift::new(sent_at, received_at);
+    assert_eq!(drift.drift(), Duration::hours(2));
+    assert!(drift.exceeds(Duration::hours(1)));
+    assert!(!drift.exceeds(Duration::hours(3)));
+    assert!(ClockDrift::new(received_at, sent_at).exceeds(Duration::hours(1)));
+
+    let mut event = Event {
+        timestamp: sent_at - Duration::seconds(5),
+        breadcrumbs: vec![Breadcrumb {
+            timestamp: sent_at - Duration::seconds(10),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    };
+
+    assert!(!correct_clock_drift(
+        &mut event,
+        sent_at,
+        received_at,
+        Duration::hours(3)
+    ));
+    assert!(correct_clock_drift(
+        &mut event,
+        sent_at,
+        received_at,
+        Duration::hours(1)
+    ));
+    assert_eq!(event.timestamp, received_at - Duration::seconds(5));
+    assert_eq!(
+        event.breadcrumbs[0].timestamp,
+        received_at - Duration::seconds(10)
+    );
+    assert_eq!(
+        event.meta.get("timestamp").unwrap().errors,
+        vec![json!([
+            "clock_drift",
+            {
+                "sdk_time": "2017-12-24T06:12:00+00:00",
+                "server_time": "2017-12-24T08:12:00+00:00",
+            }
+        ])]
+    );
+}
+
+#[test]
+fn test_clock_drift_spans() {
+    let received_at = config().received_at;
+    let sent_at = received_at - Duration::hours(2);
+    let mut event: Event<'_> = serde_json::from_value(json!({
+        "type": "transaction",
+        "timestamp": 1514095920.5,
+        "start_timestamp": 1514095910.5,
+        "spans": [{
+            "span_id": "b7ad6b7169203331",
+            "trace_id": "0af7651916cd43dd8448eb211c80319c",
+            "start_timestamp": 1514095912.25,
+            "timestamp": 1514095915.0,
+        }],
+    }))
+    .unwrap();
+
+    assert!(correct_clock_drift(
+        &mut event,
+        sent_at,
+        received_at,
+        Duration::hours(1)
+    ));
+    assert_eq!(
+        event.start_timestamp,
+        Some(received_at - Duration::milliseconds(9500))
+    );
+    assert_eq!(
+        event.spans[0].start_timestamp,
+        received_at - Duration::milliseconds(7750)
+    );
+    assert_eq!(
+        event.spans[0].timestamp,
+        Some(received_at - Duration::seconds(5))
+    );
+}
+
+#[test]
+fn test_clock_drift_out_of_range() {
+    let received_at = config().received_at;
+    let sent_at = Utc.timestamp_opt(-8_300_000_000_000, 0).unwrap();
+    let timestamp = received_at - Duration::seconds(5);
+    let drift = ClockDrift::new(sent_at, received_at);
+    assert_eq!(drift.correct(timestamp), None);
+
+    let mut event = Event {
+        timestamp,
+        ..Default::default()
+    };
+    assert!(correct_clock_drift(
+        &mut event,
+        sent_at,
+        received_at,
+        Duration::hours(1)
+    ));
+    assert_eq!(event.timestamp, timestamp);
+    assert_eq!(event.meta.get("timestamp").unwrap().errors.len(), 1);
+}
+
+#[test]
+fn test_normalize_clock_drift() {
+    let config = NormalizeConfig {
+        sent_at: Some(config().received_at + Duration::days(1)),
+        ..config()
+    };
+
+    let mut event = Event {
+        timestamp: config.received_at + Duration::days(1) - Duration::seconds(1),
+        ..Default::default()
+    };
+    event.normalize(&config);
+    assert_eq!(event.timestamp, config.received_at - Duration::seconds(1));
+    assert_eq!(event.meta.get("timestamp").unwrap().errors.len(), 1);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "clock")]
+use chrono::{TimeZone, Utc};
+
+use sentry_types::protocol::outcome::{Outcome, OutcomeKind};
+use sentry_types::protocol::quota::DataCategory;
+use sentry_types::ProjectId;
+
+#[test]
+fn test_outcome_roundtrip() {
+    let outcome = Outcome {
+        timestamp: Utc.with_ymd_and_hms(2020, 1, 1, 12, 0, 0).unwrap(),
+        org_id: Some(1),
+        key_id: Some(17),
+        event_id: Some("d43e86c9-6e42-4a93-a4fb-da156dd17341".parse().unwrap()),
+        ..Outcome::with_reason(
+            OutcomeKind::RateLimited,
+            Some("key_quota"),
+            ProjectId::new(42),
+            DataCategory::Error,
+            1,
+        )
+    };
+
+    let json = serde_json::to_string(&outcome).unwrap();
+    assert_eq!(
+        json,
+        r#"{"timestamp":"2020-01-01T12:00:00Z","org_id":1,"project_id":42,"key_id":17,"outcome":2,"reason":"key_quota","event_id":"d43e86c9-6e42-4a93-a4fb-da156dd17341","category":1,"quantity":1}"#
+    );
+    assert_eq!(serde_json::from_str::<Outcome>(&json).unwrap(), outcome);
+}
+
+#[test]
+fn test_outcome_accepted_without_reason() {
+    let outcome = Outcome::with_reason(
+        OutcomeKind::Accepted,
+        Some("ignored"),
+        ProjectId::new(42),
+        DataCategory::Attachment,
+        1024,
+    );
+    assert_eq!(outcome.reason, None);
+    assert_eq!(outcome.quantity, Some(1024));
+}
+
+#[test]
+fn test_outcome_unknown_category() {
+    let json = r#"{"timestamp":"2020-01-01T12:00:00Z","project_id":42,"outcome":1,"reason":"localhost","category":99}"#;
+    let outcome: Outcome = serde_json::from_str(json).unwrap();
+    assert_eq!(outcome.outcome, OutcomeKind::Filtered);
+    assert_eq!(outcome.category, Some(DataCategory::Unknown("99".into())));
+    assert_eq!(serde_json::to_string(&outcome).unwrap(), json);
+
+    let mut outcome = outcome;
+    outcome.category = Some(DataCategory::Unknown("custom".into()));
+    assert!(serde_json::to_string(&outcome).is_err());
+
+    let json = r#"{"timestamp":"2020-01-01T12:00:00Z","project_id":42,"outcome":9}"#;
+    assert!(serde_json::from_str::<Outcome>(json).is_err());
+}
+
+#[test]
+fn test_outcome_kind_names() {
+    assert_eq!(OutcomeKind::RateLimited.to_string(), "rate_limited");
+    assert_eq!(
+        "client_discard".parse::<OutcomeKind>().unwrap(),
+        OutcomeKind::ClientDiscard
+    );
+    assert!("dropped".parse::<OutcomeKind>().is_err());
+}
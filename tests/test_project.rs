@@ -0,0 +1,57 @@
+use sentry_types::protocol::project::{ProjectConfig, ProjectKeyStatus, ProjectState};
+use sentry_types::protocol::quota::QuotaScope;
+use sentry_types::ProjectId;
+
+#[test]
+fn test_project_state() {
+    let json = r#"{
+        "projectId": 42,
+        "organizationId": 1,
+        "slug": "backend",
+        "disabled": false,
+        "publicKeys": [
+            {"publicKey": "a94ae32be2584e0bbd7a4cbb95971fee", "numericId": 17},
+            {"publicKey": "1f3c1bb2a7b84e2a8e08e8a3b3b3c1d0", "status": "disabled"}
+        ],
+        "lastChange": "2020-01-01T12:00:00Z",
+        "config": {
+            "allowedDomains": ["example.com"],
+            "trustedRelays": ["relay-key"],
+            "filterSettings": {"webCrawlers": {"isEnabled": true}},
+            "quotas": [{"scope": "project", "limit": 0}],
+            "features": ["organizations:profiling"],
+            "piiConfig": null
+        },
+        "rev": "abc"
+    }"#;
+
+    let state: ProjectState = serde_json::from_str(json).unwrap();
+    assert_eq!(state.project_id, Some(ProjectId::new(42)));
+    assert_eq!(state.slug.as_deref(), Some("backend"));
+    assert_eq!(state.public_keys[0].numeric_id, Some(17));
+    assert_eq!(state.public_keys[0].status, ProjectKeyStatus::Enabled);
+    assert_eq!(state.public_keys[1].status, ProjectKeyStatus::Disabled);
+    assert!(state.config.filter_settings.web_crawlers.is_enabled);
+    assert_eq!(state.config.quotas[0].scope, QuotaScope::Project);
+    assert!(state.config.has_feature("organizations:profiling"));
+    assert!(state.config.is_trusted_relay("relay-key"));
+    assert!(state.config.other.contains_key("piiConfig"));
+    assert!(state.other.contains_key("rev"));
+
+    assert!(state.accepts_key("a94ae32be2584e0bbd7a4cbb95971fee"));
+    assert!(!state.accepts_key("1f3c1bb2a7b84e2a8e08e8a3b3b3c1d0"));
+    assert!(!state.accepts_key("unknown"));
+
+    let reparsed: ProjectState =
+        serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+    assert_eq!(reparsed, state);
+}
+
+#[test]
+fn test_project_state_defaults() {
+    let state: ProjectState = serde_json::from_str("{}").unwrap();
+    assert_eq!(state, ProjectState::default());
+    assert_eq!(state.config, ProjectConfig::default());
+    assert_eq!(state.config.allowed_domains, vec!["*".to_string()]);
+    assert!(!state.accepts_key("a94ae32be2584e0bbd7a4cbb95971fee"));
+}
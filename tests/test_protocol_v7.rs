@@ -1,10 +1,7 @@
+#![cfg(feature = "clock")]
 #[macro_use]
 extern crate serde_json;
 
-use chrono;
-
-use uuid;
-
 use chrono::{DateTime, TimeZone, Utc};
 use std::borrow::Cow;
 use uuid::Uuid;
@@ -16,7 +13,7 @@ fn event_id() -> Uuid {
 }
 
 fn event_time() -> DateTime<Utc> {
-    Utc.ymd(2017, 12, 24).and_hms(8, 12, 0)
+    Utc.with_ymd_and_hms(2017, 12, 24, 8, 12, 0).unwrap()
 }
 
 fn reserialize(event: &v7::Event<'_>) -> v7::Event<'static> {
@@ -43,6 +40,68 @@ mod test_event {
         assert_eq!(event.sdk, None);
     }
 
+    #[test]
+    fn test_event_empty_object() {
+        let event: v7::Event = serde_json::from_str("{}").unwrap();
+        assert_eq!(
+            event,
+            v7::Event {
+                event_id: event.event_id,
+                timestamp: event.timestamp,
+                ..Default::default()
+            }
+        );
+
+        let json = serde_json::to_value(&event).unwrap();
+        let keys: Vec<_> = json.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["event_id", "timestamp"]);
+        assert_eq!(json["event_id"], event.event_id.to_simple().to_string());
+    }
+
+    fn empty_default<T>() -> String
+    where
+        T: Default + PartialEq + std::fmt::Debug + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let value: T = serde_json::from_str("{}").unwrap();
+        assert_eq!(value, T::default());
+        serde_json::to_string(&value).unwrap()
+    }
+
+    #[test]
+    fn test_nested_empty_objects() {
+        assert_eq!(empty_default::<v7::Stacktrace>(), "{}");
+        assert_eq!(empty_default::<v7::User>(), "{}");
+        assert_eq!(empty_default::<v7::Request>(), "{}");
+        assert_eq!(empty_default::<v7::DebugMeta>(), "{}");
+        assert_eq!(empty_default::<v7::LogEntry>(), r#"{"message":""}"#);
+        assert_eq!(empty_default::<v7::Exception>(), r#"{"type":""}"#);
+        assert_eq!(empty_default::<v7::Mechanism>(), r#"{"type":""}"#);
+        assert_eq!(
+            empty_default::<sentry_types::protocol::view_hierarchy::ViewHierarchy>(),
+            r#"{"rendering_system":""}"#
+        );
+
+        let replay: v7::ReplayEvent = serde_json::from_str("{}").unwrap();
+        assert_eq!(
+            replay,
+            v7::ReplayEvent {
+                replay_id: replay.replay_id,
+                timestamp: replay.timestamp,
+                ..Default::default()
+            }
+        );
+
+        let profile: v7::Profile = serde_json::from_str("{}").unwrap();
+        assert_eq!(
+            profile,
+            v7::Profile {
+                event_id: profile.event_id,
+                timestamp: profile.timestamp,
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn test_event_to_string_timestamp() {
         let event = v7::Event {
@@ -62,8 +121,8 @@ mod test_event {
             event_id: event_id(),
             timestamp: event_time(),
             dist: Some("42".into()),
-            release: Some("my.awesome.app-1.0".into()),
-            environment: Some("prod".into()),
+            release: Some("my.awesome.app-1.0".parse().unwrap()),
+            environment: Some("prod".parse().unwrap()),
             ..Default::default()
         };
 
@@ -92,6 +151,58 @@ mod test_event {
         );
     }
 
+    #[test]
+    fn test_transaction_info() {
+        let event = v7::Event {
+            event_id: event_id(),
+            timestamp: event_time(),
+            transaction: Some("/users/:id".to_string()),
+            transaction_info: Some(v7::TransactionInfo {
+                source: v7::TransactionSource::Route,
+            }),
+            ..Default::default()
+        };
+        assert_roundtrip(&event);
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            "{\"event_id\":\"d43e86c96e424a93a4fbda156dd17341\",\"transaction\":\"/users/:id\",\
+             \"transaction_info\":{\"source\":\"route\"},\"timestamp\":1514103120}"
+        );
+
+        let info: v7::TransactionInfo =
+            serde_json::from_value(json!({"source": "sanitized"})).unwrap();
+        assert_eq!(
+            info.source,
+            v7::TransactionSource::Unknown("sanitized".into())
+        );
+        assert_eq!(
+            serde_json::to_value(&info).unwrap(),
+            json!({"source": "sanitized"})
+        );
+    }
+
+    #[test]
+    fn test_platform() {
+        let event = v7::Event {
+            event_id: event_id(),
+            timestamp: event_time(),
+            platform: v7::Platform::Native,
+            ..Default::default()
+        };
+        assert_roundtrip(&event);
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            "{\"event_id\":\"d43e86c96e424a93a4fbda156dd17341\",\"platform\":\"native\",\
+             \"timestamp\":1514103120}"
+        );
+
+        let event: v7::Event = serde_json::from_str("{\"platform\":\"brainfuck\"}").unwrap();
+        assert_eq!(event.platform, v7::Platform::Unknown("brainfuck".into()));
+        assert!(!event.platform.is_known());
+        assert_eq!(v7::Platform::from("javascript"), v7::Platform::JavaScript);
+        assert_eq!(v7::Platform::CSharp.to_string(), "csharp");
+    }
+
     #[test]
     fn test_logger() {
         let event = v7::Event {
@@ -110,6 +221,183 @@ mod test_event {
         );
     }
 
+    #[test]
+    fn test_logger_validation() {
+        assert_eq!(v7::validate_logger(v7::loggers::PANIC), Ok(()));
+        assert_eq!(v7::validate_logger("my.app.module"), Ok(()));
+        assert_eq!(v7::validate_logger(""), Err(v7::InvalidLoggerError::Empty));
+        assert_eq!(
+            v7::validate_logger("foo bar"),
+            Err(v7::InvalidLoggerError::InvalidCharacter)
+        );
+        assert_eq!(
+            v7::validate_logger(&"x".repeat(65)),
+            Err(v7::InvalidLoggerError::TooLong)
+        );
+    }
+
+    #[test]
+    fn test_logger_lenient() {
+        let mut event = v7::Event {
+            event_id: event_id(),
+            timestamp: event_time(),
+            ..Default::default()
+        };
+
+        event.set_logger_lenient("root");
+        assert_eq!(event.logger.as_deref(), Some("root"));
+        assert!(event.meta.is_empty());
+
+        event.set_logger_lenient(" my logger\n");
+        assert_eq!(event.logger.as_deref(), Some("my_logger"));
+
+        let long = format!("{}.very.specific.module", "a".repeat(60));
+        event.set_logger_lenient(&long);
+        assert_eq!(event.logger.as_deref(), Some("*very.specific.module"));
+        assert_roundtrip(&event);
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            "{\"event_id\":\"d43e86c96e424a93a4fbda156dd17341\",\"logger\":\
+             \"*very.specific.module\",\"timestamp\":1514103120,\"_meta\":{\"logger\":{\"\":\
+             {\"rem\":[[\"@logger:replace\",\"s\"]],\"len\":81}}}}"
+        );
+
+        event.set_logger_lenient("   ");
+        assert_eq!(event.logger, None);
+        assert_eq!(
+            event.meta.get("logger").unwrap().remarks,
+            vec![v7::Remark::new(v7::RemarkType::Removed, "@logger:remove")]
+        );
+        assert_eq!(
+            event.errors,
+            vec![v7::EventProcessingError::new(
+                v7::EventProcessingError::INVALID_DATA,
+                "logger",
+                Some("   ".into()),
+            )]
+        );
+
+        event.set_logger_lenient("app");
+        assert!(event.errors.is_empty());
+    }
+
+    #[test]
+    fn test_bounded_setters() {
+        let mut event = v7::Event {
+            event_id: event_id(),
+            timestamp: event_time(),
+            ..Default::default()
+        };
+
+        assert_eq!(event.set_environment("production"), None);
+        assert_eq!(event.environment.as_deref(), Some("production"));
+        assert!(event.meta.is_empty());
+
+        let meta = event.set_dist("ä".repeat(70)).unwrap();
+        assert_eq!(
+            event.dist.as_deref(),
+            Some(&*format!("{}...", "ä".repeat(61)))
+        );
+        assert_eq!(meta.original_length, Some(70));
+        assert_eq!(meta.remarks[0].range, Some((0, 64)));
+        assert_eq!(event.meta.get("dist"), Some(&meta));
+        assert_roundtrip(&event);
+
+        event.set_dist("1.0");
+        assert!(event.meta.is_empty());
+
+        let meta = event.set_release("x".repeat(250)).unwrap();
+        assert_eq!(
+            event.release.as_deref().map(str::len),
+            Some(v7::MAX_RELEASE_LENGTH)
+        );
+        assert_eq!(meta.original_length, Some(250));
+
+        let meta = event.set_environment("prod east").unwrap();
+        assert_eq!(event.environment, None);
+        assert_eq!(meta.original_value, Some("prod east".into()));
+        assert_eq!(
+            meta.errors,
+            vec![json!(["invalid_data", {"reason": "invalid character in environment name"}])]
+        );
+        assert_eq!(event.meta.get("environment"), Some(&meta));
+
+        let mut user = v7::User::default();
+        assert!(user
+            .set_email(format!("{}@example.com", "a".repeat(80)))
+            .is_some());
+        assert_eq!(
+            user.email.as_ref().map(|email| email.len()),
+            Some(v7::MAX_EMAIL_LENGTH)
+        );
+        assert_eq!(user.set_id("42"), None);
+    }
+
+    #[test]
+    fn test_release_and_environment_validation() {
+        let release = v7::Release::new("  my-app@1.0+42\n").unwrap();
+        assert_eq!(release, "my-app@1.0+42");
+        assert_eq!(
+            v7::Release::new("a/b"),
+            Err(v7::InvalidReleaseError::InvalidCharacter)
+        );
+        assert_eq!(v7::Release::new(" "), Err(v7::InvalidReleaseError::Empty));
+        assert_eq!(
+            v7::Release::new(".."),
+            Err(v7::InvalidReleaseError::RestrictedName)
+        );
+        assert_eq!(
+            v7::Release::new("latest"),
+            Err(v7::InvalidReleaseError::RestrictedName)
+        );
+        assert_eq!(
+            v7::Release::new("x".repeat(201)),
+            Err(v7::InvalidReleaseError::TooLong)
+        );
+
+        let environment: v7::Environment = "production".parse().unwrap();
+        assert_eq!(environment.as_str(), "production");
+        assert_eq!(
+            "prod east".parse::<v7::Environment>(),
+            Err(v7::InvalidEnvironmentError::InvalidCharacter)
+        );
+        assert_eq!(
+            "None".parse::<v7::Environment>(),
+            Err(v7::InvalidEnvironmentError::RestrictedName)
+        );
+        assert_eq!(
+            "x".repeat(65).parse::<v7::Environment>(),
+            Err(v7::InvalidEnvironmentError::TooLong)
+        );
+
+        assert!(serde_json::from_str::<v7::Environment>("\"a/b\"").is_err());
+
+        let event = v7::parse_event_bytes(br#"{"environment":"a/b","release":42}"#).unwrap();
+        assert_eq!(event.environment, None);
+        assert_eq!(event.release.as_deref(), Some("42"));
+        let meta = event.meta.get("environment").unwrap();
+        assert_eq!(meta.original_value, Some("a/b".into()));
+        assert_eq!(
+            meta.errors,
+            vec![json!(["invalid_data", {"reason": "invalid character in environment name"}])]
+        );
+        assert_eq!(
+            event.errors,
+            vec![v7::EventProcessingError::new(
+                v7::EventProcessingError::INVALID_DATA,
+                "environment",
+                Some("a/b".into()),
+            )]
+        );
+
+        let event = v7::parse_event_bytes(br#"{"release":["1.0"]}"#).unwrap();
+        assert_eq!(event.release, None);
+        assert_eq!(
+            event.meta.get("release").unwrap().original_value,
+            Some(json!(["1.0"]))
+        );
+    }
+
     #[test]
     fn test_culprit() {
         let event = v7::Event {
@@ -129,6 +417,47 @@ mod test_event {
     }
 }
 
+mod test_meta {
+    use super::*;
+
+    #[test]
+    fn test_meta_tree() {
+        let event: v7::Event = serde_json::from_value(json!({
+            "_meta": {
+                "message": {"": {"rem": [["!limit", "s", 0, 10]], "len": 42}},
+                "breadcrumbs": {"values": {"0": {"message": {"": {"err": ["invalid_data"]}}}}}
+            }
+        }))
+        .unwrap();
+
+        let meta = event.meta.get("message").unwrap();
+        assert_eq!(meta.original_length, Some(42));
+        assert_eq!(
+            meta.remarks,
+            vec![v7::Remark {
+                rule_id: "!limit".into(),
+                ty: v7::RemarkType::Substituted,
+                range: Some((0, 10)),
+            }]
+        );
+        assert_eq!(
+            event
+                .meta
+                .get("breadcrumbs.values.0.message")
+                .unwrap()
+                .errors,
+            vec![json!("invalid_data")]
+        );
+        assert!(event.meta.get("logger").is_none());
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(
+            json["_meta"]["message"],
+            json!({"": {"rem": [["!limit", "s", 0, 10]], "len": 42}})
+        );
+    }
+}
+
 mod test_fingerprint {
     use super::*;
 
@@ -191,6 +520,7 @@ mod test_values {
     fn test_values_object() {
         let values = v7::Values {
             values: vec![1, 2, 3],
+            data: Default::default(),
         };
 
         assert_eq!(
@@ -204,6 +534,44 @@ mod test_values {
         );
     }
 
+    #[test]
+    fn test_values_array() {
+        let values: v7::Values<u32> = serde_json::from_str("[1,2,3]").unwrap();
+        assert_eq!(values, v7::Values::from(vec![1, 2, 3]));
+        assert_eq!(
+            serde_json::to_string(&values).unwrap(),
+            "{\"values\":[1,2,3]}".to_string()
+        );
+    }
+
+    #[test]
+    fn test_values_data() {
+        let json = "{\"values\":[1,2,3],\"other\":true}";
+        let values: v7::Values<u32> = serde_json::from_str(json).unwrap();
+        assert_eq!(values.values, vec![1, 2, 3]);
+        assert_eq!(values.data.get("other"), Some(&true.into()));
+        assert_eq!(serde_json::to_string(&values).unwrap(), json);
+
+        let values: v7::Values<u32> = serde_json::from_str("{\"other\":true}").unwrap();
+        assert!(values.values.is_empty());
+        assert!(!values.is_empty());
+
+        assert!(serde_json::from_str::<v7::Values<u32>>("{\"values\":[\"x\"]}").is_err());
+        assert!(serde_json::from_str::<v7::Values<u32>>("42").is_err());
+    }
+
+    #[test]
+    fn test_values_in_event() {
+        let event: v7::Event = serde_json::from_value(json!({
+            "event_id": "d43e86c96e424a93a4fbda156dd17341",
+            "timestamp": 1514103120,
+            "exception": [{"type": "ZeroDivisionError"}],
+        }))
+        .unwrap();
+        assert_eq!(event.exception.len(), 1);
+        assert_eq!(event.exception[0].ty, "ZeroDivisionError");
+    }
+
     #[test]
     fn test_values_option() {
         assert_eq!(
@@ -311,7 +679,7 @@ mod test_timestamp {
     fn test_timestamp_float() {
         let event = v7::Event {
             event_id: event_id(),
-            timestamp: Utc.ymd(2017, 12, 24).and_hms_milli(8, 12, 0, 500),
+            timestamp: Utc.timestamp_millis_opt(1_514_103_120_500).unwrap(),
             ..Default::default()
         };
 
@@ -321,6 +689,21 @@ mod test_timestamp {
             "{\"event_id\":\"d43e86c96e424a93a4fbda156dd17341\",\"timestamp\":1514103120.5}"
         );
     }
+
+    #[test]
+    fn test_timestamp_micros() {
+        for micros in &[1, 7, 999_999] {
+            let event = v7::Event {
+                event_id: event_id(),
+                timestamp: Utc.timestamp_opt(1_514_103_120, micros * 1000).unwrap(),
+                ..Default::default()
+            };
+            assert_roundtrip(&event);
+        }
+
+        let event: v7::Event<'_> = serde_json::from_str(r#"{"timestamp":-1.5}"#).unwrap();
+        assert_eq!(event.timestamp.timestamp_millis(), -1500);
+    }
 }
 
 mod test_user {
@@ -438,6 +821,105 @@ mod test_breadcrumbs {
              \"/api/0/organizations/foo\"}}]}}"
         );
     }
+
+    #[test]
+    fn test_breadcrumbs_ring() {
+        let breadcrumb = |message: &str| v7::Breadcrumb {
+            timestamp: event_time(),
+            message: Some(message.into()),
+            ..Default::default()
+        };
+
+        let mut breadcrumbs = v7::Breadcrumbs::new(2);
+        breadcrumbs.push(breadcrumb("a"));
+        breadcrumbs.push(breadcrumb("b"));
+        assert_eq!(breadcrumbs.dropped(), 0);
+        breadcrumbs.extend(vec![breadcrumb("c"), breadcrumb("d")]);
+        assert_eq!(breadcrumbs.len(), 2);
+        assert_eq!(breadcrumbs.dropped(), 2);
+        let messages: Vec<_> = breadcrumbs
+            .iter()
+            .map(|b| b.message.as_deref().unwrap())
+            .collect();
+        assert_eq!(messages, vec!["c", "d"]);
+
+        let mut event = v7::Event {
+            event_id: event_id(),
+            timestamp: event_time(),
+            ..Default::default()
+        };
+        event.set_breadcrumbs(breadcrumbs);
+        assert_eq!(event.breadcrumbs.len(), 2);
+        assert_eq!(
+            event
+                .meta
+                .get("breadcrumbs.values")
+                .unwrap()
+                .original_length,
+            Some(4)
+        );
+        assert_eq!(
+            serde_json::to_value(&event).unwrap()["_meta"],
+            json!({"breadcrumbs": {"values": {"": {"len": 4}}}})
+        );
+
+        let mut empty = v7::Breadcrumbs::new(0);
+        empty.push(breadcrumb("a"));
+        assert!(empty.is_empty());
+        assert_eq!(empty.dropped(), 1);
+        assert_eq!(v7::Breadcrumbs::default().capacity(), 100);
+    }
+
+    #[test]
+    fn test_breadcrumbs_sort_and_dedup() {
+        let breadcrumb = |secs: i64, message: &str, category: &str| v7::Breadcrumb {
+            timestamp: event_time() + chrono::Duration::seconds(secs),
+            message: Some(message.into()),
+            category: Some(category.into()),
+            ..Default::default()
+        };
+
+        let mut breadcrumbs: v7::Breadcrumbs = vec![
+            breadcrumb(2, "c", "ui"),
+            breadcrumb(0, "a", "ui"),
+            breadcrumb(1, "b", "http"),
+            breadcrumb(0, "a", "ui"),
+            breadcrumb(0, "a", "http"),
+            breadcrumb(1, "b", "http"),
+        ]
+        .into_iter()
+        .collect();
+
+        breadcrumbs.sort_by_timestamp();
+        let messages: Vec<_> = breadcrumbs
+            .iter()
+            .map(|b| {
+                format!(
+                    "{}:{}",
+                    b.message.as_deref().unwrap(),
+                    b.category.as_deref().unwrap()
+                )
+            })
+            .collect();
+        assert_eq!(
+            messages,
+            vec!["a:ui", "a:ui", "a:http", "b:http", "b:http", "c:ui"]
+        );
+
+        breadcrumbs.dedup();
+        let messages: Vec<_> = breadcrumbs
+            .iter()
+            .map(|b| {
+                format!(
+                    "{}:{}",
+                    b.message.as_deref().unwrap(),
+                    b.category.as_deref().unwrap()
+                )
+            })
+            .collect();
+        assert_eq!(messages, vec!["a:ui", "a:http", "b:http", "c:ui"]);
+        assert_eq!(breadcrumbs.dropped(), 0);
+    }
 }
 
 mod test_stacktrace {
@@ -468,6 +950,48 @@ mod test_stacktrace {
              \"lineno\":1}]}}"
         );
     }
+
+    #[test]
+    fn test_frame_source_context() {
+        let source = "line 1\nline 2\r\nline 3\nline 4\nline 5\n";
+        let mut frame = v7::Frame::default();
+
+        assert!(frame.set_source_context(source, 2, 2));
+        assert_eq!(frame.lineno, Some(2));
+        assert_eq!(frame.pre_context, vec!["line 1"]);
+        assert_eq!(frame.context_line.as_deref(), Some("line 2"));
+        assert_eq!(frame.post_context, vec!["line 3", "line 4"]);
+
+        assert!(frame.set_source_context(source, 5, v7::DEFAULT_CONTEXT_LINES));
+        assert_eq!(frame.pre_context.len(), 4);
+        assert!(frame.post_context.is_empty());
+
+        assert!(!frame.set_source_context(source, 0, 2));
+        assert!(!frame.set_source_context(source, 6, 2));
+        assert_eq!(frame.lineno, Some(5));
+    }
+
+    #[test]
+    fn test_frame_source_context_trimming() {
+        let long_line: String = (0..200).map(|i| (b'a' + (i % 26) as u8) as char).collect();
+        let source = format!("{}\n{}", long_line, long_line);
+        let mut frame = v7::Frame {
+            colno: Some(100),
+            ..Default::default()
+        };
+
+        assert!(frame.set_source_context(&source, 2, 1));
+        let context_line = frame.context_line.unwrap();
+        assert!(context_line.starts_with("{snip} "));
+        assert!(context_line.ends_with(" {snip}"));
+        assert_eq!(&context_line[7..147], &long_line[40..180]);
+
+        assert_eq!(frame.pre_context.len(), 1);
+        assert_eq!(
+            frame.pre_context[0],
+            format!("{} {{snip}}", &long_line[..140])
+        );
+    }
 }
 
 mod test_template_info {
@@ -502,6 +1026,31 @@ mod test_template_info {
 mod test_threads {
     use super::*;
 
+    #[test]
+    fn test_thread_registers() {
+        let thread: v7::Thread = serde_json::from_value(json!({
+            "id": 1,
+            "stacktrace": {
+                "frames": [],
+                "registers": {"rip": "0x2A", "rsp": 16, "rbp": "32"},
+            },
+        }))
+        .unwrap();
+
+        let registers = thread.registers().unwrap();
+        assert_eq!(registers.get("rip"), Some(&v7::RegVal(42)));
+        assert_eq!(registers.instruction_pointer(), Some(v7::RegVal(42)));
+        assert_eq!(
+            serde_json::to_value(registers).unwrap(),
+            json!({"rbp": "0x20", "rip": "0x2a", "rsp": "0x10"})
+        );
+
+        let registers: v7::Registers = vec![("pc", 1u64)].into_iter().collect();
+        assert_eq!(registers.instruction_pointer(), Some(v7::RegVal(1)));
+        assert!(v7::Thread::default().registers().is_none());
+        assert!(serde_json::from_value::<v7::Registers>(json!({"pc": "nope"})).is_err());
+    }
+
     #[test]
     fn test_threads_values() {
         let event = v7::Event {
@@ -593,6 +1142,90 @@ mod test_threads {
 mod test_request {
     use super::*;
 
+    #[test]
+    fn test_request_headers() {
+        let request: v7::Request = serde_json::from_value(json!({
+            "headers": {"content-type": "text/plain", "X_FORWARDED_FOR": "1.2.3.4"},
+        }))
+        .unwrap();
+        assert_eq!(request.headers["Content-Type"], "text/plain");
+        assert_eq!(request.headers["X-Forwarded-For"], "1.2.3.4");
+        assert_eq!(request.header("CONTENT-TYPE"), Some("text/plain"));
+        assert_eq!(request.header("x_forwarded_for"), Some("1.2.3.4"));
+        assert_eq!(request.header("accept"), None);
+
+        let request: v7::Request = serde_json::from_value(json!({
+            "headers": [["accept", "text/html"], ["Accept", "application/json"]],
+        }))
+        .unwrap();
+        assert_eq!(request.headers["Accept"], "text/html, application/json");
+
+        assert_eq!(v7::normalize_header_name(" user_agent "), "User-Agent");
+    }
+
+    #[test]
+    fn test_request_env_keys() {
+        let mut request = v7::Request::default();
+        request.env.insert("wsgi.version".into(), "1.0".into());
+        request.env.insert("PATH_INFO".into(), "/bar".into());
+        assert!(request.validate_env().is_ok());
+
+        request.env.insert("PATH INFO".into(), "/bar".into());
+        assert_eq!(
+            request.validate_env(),
+            Err(v7::InvalidEnvKeyError("PATH INFO".into()))
+        );
+    }
+
+    #[test]
+    fn test_request_url() {
+        let url: url::Url = "https://example.invalid/path?foo=bar&baz#top"
+            .parse()
+            .unwrap();
+        let mut request = v7::Request::default();
+        request.set_url(&url);
+        assert_eq!(
+            request.url.as_ref().map(|url| url.as_str()),
+            Some("https://example.invalid/path")
+        );
+        assert_eq!(request.query_string.as_deref(), Some("foo=bar&baz"));
+        assert_eq!(request.fragment.as_deref(), Some("top"));
+        assert_eq!(request.full_url(), Some(url));
+
+        let url: url::Url = "https://example.invalid/".parse().unwrap();
+        request.set_url(&url);
+        assert_eq!(request.query_string, None);
+        assert_eq!(request.fragment, None);
+        assert_eq!(request.full_url(), Some(url));
+
+        request.query_string = Some("?a=b".into());
+        assert_eq!(
+            request.full_url().unwrap().as_str(),
+            "https://example.invalid/?a=b"
+        );
+        assert_eq!(v7::Request::default().full_url(), None);
+    }
+
+    #[test]
+    fn test_request_remote_addr() {
+        let mut request = v7::Request::default();
+        assert_eq!(request.remote_addr(), None);
+
+        request.set_remote_addr(v7::IpAddress::Auto);
+        assert_eq!(request.env["REMOTE_ADDR"], "{{auto}}");
+        assert!(request.remote_addr().unwrap().is_auto());
+
+        request.set_remote_addr("127.0.0.1".parse().unwrap());
+        let addr = request.remote_addr().unwrap();
+        assert!(!addr.is_auto());
+        assert_eq!(addr.as_addr(), Some(&"127.0.0.1".parse().unwrap()));
+        assert_eq!(addr.into_addr(), Some("127.0.0.1".parse().unwrap()));
+        assert_eq!(v7::IpAddress::Auto.into_addr(), None);
+
+        request.env.insert("REMOTE_ADDR".into(), "invalid".into());
+        assert_eq!(request.remote_addr(), None);
+    }
+
     #[test]
     fn test_request_full() {
         let event = v7::Event {
@@ -603,6 +1236,7 @@ mod test_request {
                 method: Some("GET".into()),
                 data: Some("{}".into()),
                 query_string: Some("foo=bar&blub=blah".into()),
+                fragment: None,
                 cookies: Some("dummy=42".into()),
                 headers: {
                     let mut hm = v7::Map::new();
@@ -705,7 +1339,8 @@ fn test_extra() {
                 json!({
                     "dirty": true,
                     "revision": 17
-                }),
+                })
+                .into(),
             );
             m
         },
@@ -774,6 +1409,7 @@ mod test_debug_meta {
                         image_size: 4096,
                         image_vmaddr: 32768.into(),
                         id: "494f3aea-88fa-4296-9644-fa8ef5d139b6-1234".parse().unwrap(),
+                        code_id: None,
                     }
                     .into(),
                     v7::ProguardDebugImage {
@@ -799,6 +1435,43 @@ mod test_debug_meta {
              \"8c954262-f905-4992-8a61-f60825f4553b\"}]}}"
         );
     }
+
+    #[test]
+    fn test_debug_image_ids() {
+        let build_id: Vec<u8> = (0..20).collect();
+        let debug_id = v7::debug_id_from_build_id(&build_id).unwrap();
+        assert_eq!(debug_id.to_string(), "03020100-0504-0706-0809-0a0b0c0d0e0f");
+        assert!(v7::debug_id_from_build_id(&[]).is_none());
+
+        let code_id = v7::code_id_from_build_id(&build_id);
+        assert_eq!(code_id.as_str(), "000102030405060708090a0b0c0d0e0f10111213");
+        assert_eq!(
+            v7::code_id_from_pe(0x5ab3_80f3, 0x1_1000).as_str(),
+            "5ab380f311000"
+        );
+
+        let image: v7::DebugImage = serde_json::from_value(json!({
+            "type": "symbolic",
+            "name": "libc.so",
+            "image_addr": "0x1000",
+            "image_size": 4096,
+            "debug_id": "00000000-0000-0000-0000-000000000000",
+            "code_id": "000102030405060708090A0B0C0D0E0F10111213",
+        }))
+        .unwrap();
+        assert_eq!(image.debug_id(), debug_id);
+        assert_eq!(image.code_id(), Some(&code_id));
+
+        let image: v7::DebugImage = v7::ProguardDebugImage {
+            uuid: "8c954262-f905-4992-8a61-f60825f4553b".parse().unwrap(),
+        }
+        .into();
+        assert_eq!(
+            image.debug_id().to_string(),
+            "8c954262-f905-4992-8a61-f60825f4553b"
+        );
+        assert_eq!(image.code_id(), None);
+    }
 }
 
 mod test_exception {
@@ -978,7 +1651,7 @@ mod test_exception {
                         m.insert("x15".into(), v7::RegVal(0x881));
                         m.insert("x22".into(), v7::RegVal(0x1_b139_9bb0));
                         m.insert("x12".into(), v7::RegVal(0x1_b3b3_7b1d));
-                        m
+                        m.into()
                     },
                 }),
                 raw_stacktrace: Some(v7::Stacktrace {
@@ -1043,6 +1716,10 @@ mod test_exception {
                     ),
                     handled: Some(false),
                     synthetic: None,
+                    source: None,
+                    is_exception_group: None,
+                    exception_id: None,
+                    parent_id: None,
                     data: {
                         let mut map = v7::Map::new();
                         map.insert("relevant_address".into(), "0x1".into());
@@ -1088,21 +1765,252 @@ mod test_exception {
 }
 
 #[test]
-fn test_sdk_info() {
-    let event = v7::Event {
-        event_id: event_id(),
-        timestamp: event_time(),
-        sdk: Some(Cow::Owned(v7::ClientSdkInfo {
-            name: "sentry-rust".into(),
-            version: "1.0".into(),
-            integrations: vec!["rocket".into()],
-            packages: vec![v7::ClientSdkPackage {
-                name: "cargo:sentry".into(),
-                version: "1.0".into(),
-            }],
-        })),
-        ..Default::default()
-    };
+fn test_numbers_from_strings() {
+    let frame: v7::Frame = serde_json::from_value(json!({
+        "function": "main",
+        "lineno": "42",
+        "colno": " 7 ",
+    }))
+    .unwrap();
+    assert_eq!(frame.lineno, Some(42));
+    assert_eq!(frame.colno, Some(7));
+    assert_eq!(
+        serde_json::to_value(&frame).unwrap(),
+        json!({"function": "main", "lineno": 42, "colno": 7})
+    );
+
+    let device: v7::DeviceContext =
+        serde_json::from_value(json!({"memory_size": "1024", "processor_count": 8})).unwrap();
+    assert_eq!(device.memory_size, Some(1024));
+    assert_eq!(device.processor_count, Some(8));
+
+    let frame: v7::Frame = serde_json::from_value(json!({"lineno": "forty-two"})).unwrap();
+    assert_eq!(frame.lineno, None);
+}
+
+#[test]
+fn test_numbers_from_strings_recorded() {
+    let event = v7::parse_event_bytes(
+        br#"{"exception":{"values":[{"type":"E","stacktrace":{"frames":[
+            {"lineno":"abc","colno":"7"},{"lineno":{"a":1}}
+        ]}}]}}"#,
+    )
+    .unwrap();
+    let frames = &event.exception.values[0]
+        .stacktrace
+        .as_ref()
+        .unwrap()
+        .frames;
+    assert_eq!(frames[0].lineno, None);
+    assert_eq!(frames[0].colno, Some(7));
+    assert_eq!(frames[1].lineno, None);
+
+    let frames = "exception.values.0.stacktrace.frames";
+    let invalid_data = |path: String, value| {
+        v7::EventProcessingError::new(v7::EventProcessingError::INVALID_DATA, path, Some(value))
+    };
+    assert_eq!(
+        event.errors,
+        vec![
+            invalid_data(format!("{}.0.lineno", frames), json!("abc")),
+            invalid_data(format!("{}.1.lineno", frames), json!({"a": 1})),
+        ]
+    );
+
+    let meta = event.meta.get(&format!("{}.0.lineno", frames)).unwrap();
+    assert_eq!(meta.errors, vec![json!("invalid_data")]);
+    assert_eq!(meta.original_value, Some(json!("abc")));
+    let meta = event.meta.get(&format!("{}.0.colno", frames)).unwrap();
+    assert_eq!(
+        meta.remarks,
+        vec![v7::Remark::new(v7::RemarkType::Substituted, "!coerce")]
+    );
+    assert!(meta.errors.is_empty());
+    assert!(event.meta.meta.is_empty());
+
+    let event = v7::parse_event_bytes(br#"{"stacktrace":{"frames":[{"lineno":42}]}}"#).unwrap();
+    assert!(event.errors.is_empty());
+    assert!(event.meta.is_empty());
+}
+
+#[test]
+fn test_lenient_booleans() {
+    let frame: v7::Frame = serde_json::from_value(json!({"in_app": "yes"})).unwrap();
+    assert_eq!(frame.in_app, Some(true));
+    let frame: v7::Frame = serde_json::from_value(json!({"in_app": 0})).unwrap();
+    assert_eq!(frame.in_app, Some(false));
+    let frame: v7::Frame = serde_json::from_value(json!({"in_app": " False "})).unwrap();
+    assert_eq!(frame.in_app, Some(false));
+    assert_eq!(
+        serde_json::to_value(&frame).unwrap(),
+        json!({"in_app": false})
+    );
+
+    let thread: v7::Thread =
+        serde_json::from_value(json!({"crashed": "true", "current": 1})).unwrap();
+    assert!(thread.crashed);
+    assert!(thread.current);
+    let thread: v7::Thread = serde_json::from_value(json!({"crashed": null})).unwrap();
+    assert!(!thread.crashed);
+
+    let mechanism: v7::Mechanism =
+        serde_json::from_value(json!({"type": "generic", "handled": "no"})).unwrap();
+    assert_eq!(mechanism.handled, Some(false));
+
+    let frame: v7::Frame = serde_json::from_value(json!({"in_app": 2})).unwrap();
+    assert_eq!(frame.in_app, None);
+    let frame: v7::Frame = serde_json::from_value(json!({"in_app": "maybe"})).unwrap();
+    assert_eq!(frame.in_app, None);
+}
+
+#[test]
+fn test_lenient_booleans_recorded() {
+    let event = v7::parse_event_bytes(
+        br#"{"threads":[{"crashed":"yes","current":[true]}],"stacktrace":{"frames":[{"in_app":"maybe"}]},
+            "contexts":{"my.device":{"type":"device","simulator":"no","processor_count":"x"}}}"#,
+    )
+    .unwrap();
+    assert!(event.threads.values[0].crashed);
+    assert!(!event.threads.values[0].current);
+    assert_eq!(event.stacktrace.as_ref().unwrap().frames[0].in_app, None);
+
+    let errors: Vec<_> = event
+        .errors
+        .iter()
+        .map(|e| (e.name.as_deref().unwrap(), e.value.clone().unwrap()))
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            ("threads.values.0.current", json!([true])),
+            ("stacktrace.frames.0.in_app", json!("maybe")),
+            (r"contexts.my\.device.processor_count", json!("x")),
+        ]
+    );
+    assert!(event.errors.iter().all(|e| e.ty == "invalid_data"));
+
+    let meta = event.meta.get("stacktrace.frames.0.in_app").unwrap();
+    assert_eq!(meta.errors, vec![json!("invalid_data")]);
+    assert_eq!(meta.original_value, Some(json!("maybe")));
+
+    let coerced = vec![v7::Remark::new(v7::RemarkType::Substituted, "!coerce")];
+    let meta = event.meta.get("threads.values.0.crashed").unwrap();
+    assert_eq!(meta.remarks, coerced);
+    let meta = event.meta.get(r"contexts.my\.device.simulator").unwrap();
+    assert_eq!(meta.remarks, coerced);
+    assert!(event.meta.meta.is_empty());
+}
+
+#[test]
+fn test_meta_tree_paths() {
+    assert_eq!(
+        v7::MetaTree::split_path(r"tags.app\.version.a\\b"),
+        vec!["tags", "app.version", "a\\b"]
+    );
+    assert_eq!(
+        v7::MetaTree::join_path(["tags", "app.version", "a\\b"]),
+        r"tags.app\.version.a\\b"
+    );
+
+    let original = v7::Meta {
+        original_length: Some(300),
+        ..Default::default()
+    };
+    let mut paths = v7::Map::new();
+    paths.insert("breadcrumbs.values.0".to_string(), original.clone());
+    paths.insert(r"tags.app\.version".to_string(), original.clone());
+
+    let tree = v7::MetaTree::from_paths(paths.clone());
+    assert_eq!(
+        serde_json::to_value(&tree).unwrap(),
+        json!({
+            "breadcrumbs": {"values": {"0": {"": {"len": 300}}}},
+            "tags": {"app.version": {"": {"len": 300}}},
+        })
+    );
+    assert_eq!(tree.get(r"tags.app\.version"), Some(&original));
+    assert_eq!(tree.to_paths(), paths);
+
+    assert!(v7::MetaTree::split_path("").is_empty());
+    paths.insert(String::new(), original.clone());
+    let tree = v7::MetaTree::from_paths(paths.clone());
+    assert_eq!(tree.meta, original);
+    assert_eq!(tree.get(""), Some(&original));
+    assert_eq!(tree.to_paths(), paths);
+}
+
+#[test]
+fn test_meta_tree_errors_in_children() {
+    let tree: v7::MetaTree = serde_json::from_value(json!({
+        "breadcrumbs": {
+            "": {"err": ["invalid_data"]},
+            "values": {
+                "0": {"message": {"": {"err": ["invalid_data", "value_too_long"]}}},
+                "3": {"": {"err": ["invalid_data"]}, "data": {"": {"len": 12}}},
+            },
+        },
+    }))
+    .unwrap();
+
+    let values = tree.get_tree("breadcrumbs.values").unwrap();
+    assert_eq!(values.errors_in_children(), 3);
+    assert_eq!(values.error_count(), 3);
+    assert_eq!(tree.get_tree("breadcrumbs").unwrap().error_count(), 4);
+    assert_eq!(tree.errors_in_children(), 4);
+    assert!(tree.get_tree("threads").is_none());
+}
+
+#[test]
+fn test_exception_group() {
+    let json = "{\"exception\":{\"values\":[{\"type\":\"ValueError\",\"mechanism\":                {\"type\":\"chained\",\"source\":\"exceptions[0]\",\"exception_id\":1,                \"parent_id\":0}},{\"type\":\"ExceptionGroup\",\"mechanism\":{\"type\":                \"generic\",\"is_exception_group\":true,\"exception_id\":0}}]}}";
+
+    let event: v7::Event<'_> = serde_json::from_str(json).unwrap();
+    let group = event.exception[1].mechanism.as_ref().unwrap();
+    assert_eq!(group.is_exception_group, Some(true));
+    assert_eq!(group.exception_id, Some(0));
+    let child = event.exception[0].mechanism.as_ref().unwrap();
+    assert_eq!(child.source.as_deref(), Some("exceptions[0]"));
+    assert_eq!(child.parent_id, group.exception_id);
+
+    let value = serde_json::to_value(&event).unwrap();
+    assert_eq!(
+        value["exception"],
+        serde_json::from_str::<serde_json::Value>(json).unwrap()["exception"]
+    );
+}
+
+#[test]
+fn test_mechanism_meta_names() {
+    let errno = v7::CError::from(2);
+    assert_eq!(errno.name.as_deref(), Some("ENOENT"));
+    assert_eq!(v7::CError::from(11).name, None);
+
+    let signal = v7::PosixSignal::from((11, 2));
+    assert_eq!(signal.name.as_deref(), Some("SIGSEGV"));
+    assert_eq!(signal.code_name.as_deref(), Some("SEGV_ACCERR"));
+    assert_eq!(v7::PosixSignal::from(6).name.as_deref(), Some("SIGABRT"));
+    assert_eq!(v7::PosixSignal::from(7).name, None);
+
+    assert_eq!(v7::MachException::name_for(10), Some("EXC_CRASH"));
+    assert_eq!(v7::MachException::name_for(99), None);
+}
+
+#[test]
+fn test_sdk_info() {
+    let event = v7::Event {
+        event_id: event_id(),
+        timestamp: event_time(),
+        sdk: Some(Cow::Owned(v7::ClientSdkInfo {
+            name: "sentry-rust".into(),
+            version: "1.0".into(),
+            integrations: vec!["rocket".into()],
+            packages: vec![v7::ClientSdkPackage {
+                name: "cargo:sentry".into(),
+                version: "1.0".into(),
+            }],
+        })),
+        ..Default::default()
+    };
 
     assert_roundtrip(&event);
     assert_eq!(
@@ -1113,6 +2021,24 @@ fn test_sdk_info() {
     );
 }
 
+#[test]
+fn test_sdk_info_macro() {
+    let sdk: v7::ClientSdkInfo = sentry_types::client_sdk_info!();
+    assert_eq!(sdk.name, "sentry-types");
+    assert_eq!(sdk.version, env!("CARGO_PKG_VERSION"));
+    assert!(sdk.integrations.is_empty());
+    assert_eq!(
+        sdk.packages,
+        vec![v7::ClientSdkPackage {
+            name: "cargo:sentry-types".into(),
+            version: env!("CARGO_PKG_VERSION").into(),
+        }]
+    );
+
+    let sdk = sentry_types::client_sdk_info!["rocket", String::from("log")];
+    assert_eq!(sdk.integrations, vec!["rocket", "log"]);
+}
+
 mod test_contexts {
     use super::*;
 
@@ -1143,6 +2069,11 @@ mod test_contexts {
                         external_free_storage: Some(2_097_152),
                         boot_time: Some("2018-02-08T12:52:12Z".parse().unwrap()),
                         timezone: Some("Europe/Vienna".into()),
+                        processor_count: Some(8),
+                        processor_frequency: Some(2400),
+                        device_type: Some("Mobile".into()),
+                        battery_status: Some("Charging".into()),
+                        screen_dpi: Some(480),
                         other: Default::default(),
                     }
                     .into(),
@@ -1162,7 +2093,8 @@ mod test_contexts {
              \"memory_size\":3137978368,\"free_memory\":322781184,\"usable_memory\":2843525120,\
              \"storage_size\":63989469184,\"free_storage\":31994734592,\"external_storage_size\":\
              2097152,\"external_free_storage\":2097152,\"boot_time\":\"2018-02-08T12:52:12Z\",\
-             \"timezone\":\"Europe/Vienna\"}}}"
+             \"timezone\":\"Europe/Vienna\",\"processor_count\":8,\"processor_frequency\":2400,\
+             \"device_type\":\"Mobile\",\"battery_status\":\"Charging\",\"screen_dpi\":480}}}"
         );
     }
 
@@ -1216,6 +2148,8 @@ mod test_contexts {
                         app_name: Some("Baz App".into()),
                         app_version: Some("1.0".into()),
                         app_build: Some("100001".into()),
+                        app_memory: Some(22_883_948),
+                        in_foreground: Some(true),
                         other: Default::default(),
                     }
                     .into(),
@@ -1232,7 +2166,8 @@ mod test_contexts {
              \"contexts\":{\"app\":{\"type\":\"app\",\"app_start_time\":\"2018-02-08T22:21:57Z\",\
              \"device_app_hash\":\"4c793e3776474877ae30618378e9662a\",\"build_type\":\
              \"testflight\",\"app_identifier\":\"foo.bar.baz\",\"app_name\":\"Baz \
-             App\",\"app_version\":\"1.0\",\"app_build\":\"100001\"}}}"
+             App\",\"app_version\":\"1.0\",\"app_build\":\"100001\",\"app_memory\":22883948,\
+             \"in_foreground\":true}}}"
         );
     }
 
@@ -1398,6 +2333,19 @@ fn test_addr_format() {
         serde_json::from_str::<v7::Addr>("\"0X2A\"").unwrap(),
         v7::Addr(42)
     );
+    assert_eq!(
+        serde_json::from_str::<v7::Addr>("\"0xdeadbeef \"").unwrap(),
+        v7::Addr(0xdead_beef)
+    );
+    assert_eq!(
+        serde_json::from_str::<v7::Addr>("4096.0").unwrap(),
+        v7::Addr(4096)
+    );
+    assert_eq!(
+        serde_json::from_str::<v7::Addr>("18446744073709551615").unwrap(),
+        v7::Addr(u64::MAX)
+    );
+    assert!(serde_json::from_str::<v7::Addr>("18446744073709551616.0").is_err());
 }
 
 #[test]
@@ -1437,3 +2385,1107 @@ fn test_orientation() {
         "\"portrait\""
     );
 }
+
+#[test]
+fn test_parse_event_bytes() {
+    let event =
+        v7::parse_event_bytes(b"{\"event_id\":\"d43e86c96e424a93a4fbda156dd17341\"}").unwrap();
+    assert_eq!(event.event_id, event_id());
+
+    assert!(v7::parse_event_bytes(b"{\"timestamp\":9223372036854775807}").is_err());
+    assert!(v7::parse_event_bytes(b"{\"timestamp\":18446744073709551615}").is_err());
+    assert!(v7::parse_event_bytes(b"\xff").is_err());
+}
+
+#[test]
+fn test_span_status() {
+    use v7::SpanStatus;
+
+    assert_eq!(SpanStatus::from_http_status(200), SpanStatus::Ok);
+    assert_eq!(SpanStatus::from_http_status(302), SpanStatus::Ok);
+    assert_eq!(SpanStatus::from_http_status(404), SpanStatus::NotFound);
+    assert_eq!(
+        SpanStatus::from_http_status(418),
+        SpanStatus::InvalidArgument
+    );
+    assert_eq!(
+        SpanStatus::from_http_status(429),
+        SpanStatus::ResourceExhausted
+    );
+    assert_eq!(SpanStatus::from_http_status(502), SpanStatus::InternalError);
+    assert_eq!(
+        SpanStatus::from_http_status(504),
+        SpanStatus::DeadlineExceeded
+    );
+    assert_eq!(SpanStatus::from_http_status(42), SpanStatus::Unknown);
+
+    for code in 0..17 {
+        let status = SpanStatus::from_grpc_code(code).unwrap();
+        assert_eq!(status.grpc_code(), code);
+        assert_eq!(status.to_string().parse::<SpanStatus>().unwrap(), status);
+    }
+    assert_eq!(SpanStatus::from_grpc_code(17), None);
+
+    assert_eq!(
+        serde_json::to_string(&SpanStatus::DeadlineExceeded).unwrap(),
+        "\"deadline_exceeded\""
+    );
+    assert_eq!(
+        serde_json::from_str::<SpanStatus>("\"unknown_error\"").unwrap(),
+        SpanStatus::Unknown
+    );
+    assert!("bogus".parse::<SpanStatus>().is_err());
+}
+
+mod test_trace {
+    use super::*;
+
+    fn trace_id() -> v7::TraceId {
+        "4c79f60c11214eb38604f4ae0781bfb2".parse().unwrap()
+    }
+
+    #[test]
+    fn test_trace_id() {
+        let id = trace_id();
+        assert_eq!(id.to_string(), "4c79f60c11214eb38604f4ae0781bfb2");
+        assert_eq!(
+            serde_json::to_string(&id).unwrap(),
+            "\"4c79f60c11214eb38604f4ae0781bfb2\""
+        );
+        assert!("4c79f60c11214eb38604f4ae0781bfb"
+            .parse::<v7::TraceId>()
+            .is_err());
+        assert!("4c79f60c11214eb38604f4ae0781bfbx"
+            .parse::<v7::TraceId>()
+            .is_err());
+        assert_ne!(v7::TraceId::new(), v7::TraceId::new());
+    }
+
+    #[test]
+    fn test_span_id() {
+        let id: v7::SpanId = "b7ad6b7169203331".parse().unwrap();
+        assert_eq!(id.to_string(), "b7ad6b7169203331");
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"b7ad6b7169203331\"");
+        assert!("b7ad6b716920333".parse::<v7::SpanId>().is_err());
+        assert!("+7ad6b7169203331".parse::<v7::SpanId>().is_err());
+        assert_ne!(v7::SpanId::new(), v7::SpanId::new());
+    }
+
+    #[test]
+    fn test_sentry_trace() {
+        let trace: v7::SentryTrace = "4c79f60c11214eb38604f4ae0781bfb2-b7ad6b7169203331-1"
+            .parse()
+            .unwrap();
+        assert_eq!(trace.trace_id, trace_id());
+        assert_eq!(trace.sampled, Some(true));
+        assert_eq!(
+            trace.to_string(),
+            "4c79f60c11214eb38604f4ae0781bfb2-b7ad6b7169203331-1"
+        );
+
+        let deferred: v7::SentryTrace = "4c79f60c11214eb38604f4ae0781bfb2-b7ad6b7169203331"
+            .parse()
+            .unwrap();
+        assert_eq!(deferred.sampled, None);
+        assert!("4c79f60c11214eb38604f4ae0781bfb2-b7ad6b7169203331-x"
+            .parse::<v7::SentryTrace>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_traceparent() {
+        let trace = v7::SentryTrace::from_traceparent(
+            "00-4c79f60c11214eb38604f4ae0781bfb2-b7ad6b7169203331-01",
+        )
+        .unwrap();
+        assert_eq!(trace.trace_id, trace_id());
+        assert_eq!(trace.span_id.to_string(), "b7ad6b7169203331");
+        assert_eq!(trace.sampled, Some(true));
+        assert_eq!(
+            trace.to_traceparent(),
+            "00-4c79f60c11214eb38604f4ae0781bfb2-b7ad6b7169203331-01"
+        );
+
+        let trace = v7::SentryTrace::from_traceparent(
+            "01-4c79f60c11214eb38604f4ae0781bfb2-b7ad6b7169203331-00-future",
+        )
+        .unwrap();
+        assert_eq!(trace.sampled, None);
+        assert_eq!(
+            trace.to_traceparent(),
+            "00-4c79f60c11214eb38604f4ae0781bfb2-b7ad6b7169203331-00"
+        );
+
+        for invalid in &[
+            "00-4c79f60c11214eb38604f4ae0781bfb2-b7ad6b7169203331-01-extra",
+            "ff-4c79f60c11214eb38604f4ae0781bfb2-b7ad6b7169203331-01",
+            "00-00000000000000000000000000000000-b7ad6b7169203331-01",
+            "00-4c79f60c11214eb38604f4ae0781bfb2-0000000000000000-01",
+            "00-4C79F60C11214EB38604F4AE0781BFB2-b7ad6b7169203331-01",
+            "00-4c79f60c11214eb38604f4ae0781bfb2-b7ad6b7169203331",
+        ] {
+            assert!(v7::SentryTrace::from_traceparent(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn test_tracestate() {
+        let mut state: v7::TraceState = "congo=t61rcWkgMzE, sentry=old,rojo=00f067aa0ba902b7"
+            .parse()
+            .unwrap();
+        assert_eq!(state.sentry(), Some("old"));
+        assert_eq!(state.get("rojo"), Some("00f067aa0ba902b7"));
+
+        state.set_sentry("new");
+        assert_eq!(
+            state.to_string(),
+            "sentry=new,congo=t61rcWkgMzE,rojo=00f067aa0ba902b7"
+        );
+
+        let incoming: v7::TraceState = "other=1,sentry=incoming,acme@sentry=x".parse().unwrap();
+        state.merge_sentry(&incoming);
+        assert_eq!(
+            state.to_string(),
+            "sentry=incoming,acme@sentry=x,congo=t61rcWkgMzE,rojo=00f067aa0ba902b7"
+        );
+
+        assert!("a=1,a=2".parse::<v7::TraceState>().is_err());
+        assert!("novalue".parse::<v7::TraceState>().is_err());
+    }
+
+    #[test]
+    fn test_dynamic_sampling_context_json() {
+        let mut dsc = v7::DynamicSamplingContext::new(trace_id(), "public");
+        dsc.release = Some("1.0".into());
+        dsc.sample_rate = Some(0.5);
+        dsc.sampled = Some(true);
+
+        let json = serde_json::to_string(&dsc).unwrap();
+        assert_eq!(
+            json,
+            "{\"trace_id\":\"4c79f60c11214eb38604f4ae0781bfb2\",\"public_key\":\"public\",\
+             \"release\":\"1.0\",\"sample_rate\":\"0.5\",\"sampled\":\"true\"}"
+        );
+        assert_eq!(
+            serde_json::from_str::<v7::DynamicSamplingContext>(&json).unwrap(),
+            dsc
+        );
+
+        let lenient: v7::DynamicSamplingContext = serde_json::from_value(json!({
+            "trace_id": "4c79f60c11214eb38604f4ae0781bfb2",
+            "public_key": "public",
+            "sample_rate": 0.25,
+            "sampled": false,
+        }))
+        .unwrap();
+        assert_eq!(lenient.sample_rate, Some(0.25));
+        assert_eq!(lenient.sampled, Some(false));
+    }
+
+    #[test]
+    fn test_dynamic_sampling_context_baggage() {
+        let dsc = v7::DynamicSamplingContext::from_baggage(
+            "other-vendor=foo;prop, sentry-trace_id=4c79f60c11214eb38604f4ae0781bfb2,\
+             sentry-public_key=public,sentry-transaction=GET%20%2Fhome,\
+             sentry-sample_rate=0.01,sentry-sampled=false,sentry-new_thing=x",
+        )
+        .unwrap();
+
+        assert_eq!(dsc.trace_id, trace_id());
+        assert_eq!(dsc.public_key, "public");
+        assert_eq!(dsc.transaction.as_deref(), Some("GET /home"));
+        assert_eq!(dsc.sample_rate, Some(0.01));
+        assert_eq!(dsc.sampled, Some(false));
+        assert_eq!(dsc.other["new_thing"], "x");
+
+        assert_eq!(
+            dsc.to_baggage(),
+            "sentry-trace_id=4c79f60c11214eb38604f4ae0781bfb2,sentry-public_key=public,\
+             sentry-transaction=GET%20/home,sentry-sample_rate=0.01,sentry-sampled=false,\
+             sentry-new_thing=x"
+        );
+        assert_eq!(
+            v7::DynamicSamplingContext::from_baggage(&dsc.to_baggage()).unwrap(),
+            dsc
+        );
+
+        assert_eq!(
+            v7::DynamicSamplingContext::from_baggage("sentry-public_key=public"),
+            None
+        );
+    }
+}
+
+#[test]
+fn test_index_fields() {
+    let event = v7::Event {
+        level: v7::Level::Warning,
+        release: Some("1.0".parse().unwrap()),
+        environment: Some("production".parse().unwrap()),
+        user: Some(v7::User {
+            email: Some("jane@example.com".into()),
+            ip_address: Some("127.0.0.1".parse().unwrap()),
+            ..Default::default()
+        }),
+        sdk: Some(Cow::Owned(v7::ClientSdkInfo {
+            name: "sentry.rust".into(),
+            version: "0.15.0".into(),
+            integrations: vec![],
+            packages: vec![],
+        })),
+        tags: {
+            let mut m = v7::Map::new();
+            m.insert("level".into(), "shadowed".into());
+            m.insert("region".into(), "eu".into());
+            m
+        },
+        ..Default::default()
+    };
+
+    let fields = event.index_fields();
+    assert_eq!(
+        fields
+            .iter()
+            .map(|(k, v)| (*k, v.as_ref()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("level", "warning"),
+            ("environment", "production"),
+            ("sentry:release", "1.0"),
+            ("sentry:user", "email:jane@example.com"),
+            ("user.email", "jane@example.com"),
+            ("user.ip", "127.0.0.1"),
+            ("sdk.name", "sentry.rust"),
+            ("sdk.version", "0.15.0"),
+            ("region", "eu"),
+        ]
+    );
+}
+
+#[test]
+fn test_well_known_tags() {
+    use v7::tag_keys;
+
+    let mut event = v7::Event::new();
+    event.set_tag("release", "my-app@1.0").unwrap();
+    event.set_tag(tag_keys::DIST, "42").unwrap();
+    event.set_tag(tag_keys::ENVIRONMENT, "production").unwrap();
+    event.set_tag(tag_keys::SERVER_NAME, "web-1").unwrap();
+    event.set_tag(tag_keys::TRANSACTION, "/users/").unwrap();
+    event.set_tag(tag_keys::LEVEL, "warning").unwrap();
+    event.set_tag("region", "eu").unwrap();
+
+    assert_eq!(event.release(), Some("my-app@1.0"));
+    assert_eq!(event.dist(), Some("42"));
+    assert_eq!(event.environment(), Some("production"));
+    assert_eq!(event.server_name(), Some("web-1"));
+    assert_eq!(event.transaction_tag(), Some("/users/"));
+    assert_eq!(event.level, v7::Level::Warning);
+    assert_eq!(event.tags.keys().collect::<Vec<_>>(), vec!["region"]);
+
+    assert_eq!(event.tag("release").as_deref(), Some("my-app@1.0"));
+    assert_eq!(event.tag(tag_keys::RELEASE).as_deref(), Some("my-app@1.0"));
+    assert_eq!(event.tag(tag_keys::LEVEL).as_deref(), Some("warning"));
+    assert_eq!(event.tag("region").as_deref(), Some("eu"));
+    assert_eq!(event.tag(tag_keys::HANDLED), None);
+
+    assert_eq!(
+        event.set_tag(tag_keys::RELEASE, "latest"),
+        Err(v7::SetTagError::InvalidRelease(
+            v7::InvalidReleaseError::RestrictedName
+        ))
+    );
+    assert_eq!(
+        event.set_tag(tag_keys::LEVEL, "loud"),
+        Err(v7::SetTagError::InvalidLevel)
+    );
+    assert_eq!(
+        event.set_tag(tag_keys::HANDLED, "yes"),
+        Err(v7::SetTagError::Derived)
+    );
+    assert_eq!(event.release(), Some("my-app@1.0"));
+}
+
+#[test]
+fn test_handled() {
+    let exception = |handled| v7::Exception {
+        ty: "Error".into(),
+        mechanism: Some(v7::Mechanism {
+            ty: "generic".into(),
+            handled,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let mut event = v7::Event::new();
+    assert_eq!(event.handled(), None);
+
+    event.exception.values = vec![exception(None), exception(Some(true))];
+    assert_eq!(event.handled(), Some(true));
+    assert_eq!(event.tag(v7::tag_keys::HANDLED).as_deref(), Some("yes"));
+    assert_eq!(
+        event.tag(v7::tag_keys::MECHANISM).as_deref(),
+        Some("generic")
+    );
+
+    event.exception.values.push(exception(Some(false)));
+    assert_eq!(event.handled(), Some(false));
+    assert_eq!(event.tag(v7::tag_keys::HANDLED).as_deref(), Some("no"));
+}
+
+#[test]
+fn test_profile() {
+    let json = r#"{
+        "version": "1",
+        "event_id": "d43e86c96e424a93a4fbda156dd17341",
+        "timestamp": "2017-12-24T08:12:00Z",
+        "platform": "rust",
+        "release": "app@1.0",
+        "device": {"architecture": "x86_64", "is_emulator": false},
+        "os": {"name": "Linux", "version": "6.1"},
+        "transaction": {
+            "id": "9ec79c33ec9942ab8353589fcb2e04dc",
+            "name": "/api/users",
+            "trace_id": "4c79f60c11214eb38604f4ae0781bfb2",
+            "active_thread_id": 1
+        },
+        "profile": {
+            "samples": [
+                {"stack_id": 0, "thread_id": "1", "elapsed_since_start_ns": 0},
+                {"stack_id": 1, "thread_id": "1", "elapsed_since_start_ns": 10000000}
+            ],
+            "stacks": [[0, 1], [2]],
+            "frames": [
+                {"function": "handle", "instruction_addr": "0x1000"},
+                {"function": "main"},
+                {"function": "idle"}
+            ],
+            "thread_metadata": {"1": {"name": "main"}}
+        },
+        "measurements": {}
+    }"#;
+
+    let profile: v7::Profile = serde_json::from_str(json).unwrap();
+    assert_eq!(profile.event_id, event_id());
+    assert_eq!(profile.timestamp, event_time());
+    assert_eq!(profile.platform, "rust");
+    assert_eq!(profile.profile.samples[1].thread_id, "1".into());
+    assert_eq!(
+        profile.transaction.as_ref().unwrap().trace_id.to_string(),
+        "4c79f60c11214eb38604f4ae0781bfb2"
+    );
+    assert!(profile.other.contains_key("measurements"));
+
+    let functions: Vec<_> = profile
+        .profile
+        .sample_frames(&profile.profile.samples[0])
+        .unwrap()
+        .iter()
+        .map(|frame| frame.function.as_deref().unwrap())
+        .collect();
+    assert_eq!(functions, vec!["handle", "main"]);
+
+    let invalid = v7::ProfileSample {
+        stack_id: 5,
+        ..Default::default()
+    };
+    assert_eq!(profile.profile.sample_frames(&invalid), None);
+
+    let serialized = serde_json::to_string(&profile).unwrap();
+    assert_eq!(
+        serde_json::from_str::<v7::Profile>(&serialized).unwrap(),
+        profile
+    );
+}
+
+#[test]
+fn test_replay_event() {
+    let event = v7::ReplayEvent {
+        replay_id: event_id(),
+        segment_id: 1,
+        replay_type: Some("session".into()),
+        timestamp: event_time(),
+        replay_start_timestamp: Some(event_time()),
+        error_ids: vec![event_id()],
+        ..Default::default()
+    };
+
+    let json = serde_json::to_string(&event).unwrap();
+    assert_eq!(
+        json,
+        "{\"replay_id\":\"d43e86c96e424a93a4fbda156dd17341\",\"segment_id\":1,\
+         \"replay_type\":\"session\",\"timestamp\":1514103120,\
+         \"replay_start_timestamp\":1514103120,\
+         \"error_ids\":[\"d43e86c9-6e42-4a93-a4fb-da156dd17341\"]}"
+    );
+    assert_eq!(
+        serde_json::from_str::<v7::ReplayEvent>(&json).unwrap(),
+        event
+    );
+}
+
+#[test]
+fn test_event_diff() {
+    let mut left = v7::Event {
+        event_id: event_id(),
+        timestamp: event_time(),
+        message: Some("Hello".into()),
+        exception: vec![v7::Exception {
+            ty: "Error".into(),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    };
+    left.tags.insert("app.version".into(), "1.0".into());
+
+    assert_eq!(left.diff(&left.clone()), vec![]);
+
+    let mut right = left.clone();
+    right.message = None;
+    right.level = v7::Level::Warning;
+    right.exception.values[0].ty = "TypeError".into();
+    right.exception.values.push(Default::default());
+    right.tags.insert("app.version".into(), "2.0".into());
+
+    let diffs = left.diff(&right);
+    let paths: Vec<_> = diffs.iter().map(|diff| diff.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "exception.values.0.type",
+            "exception.values.1",
+            "level",
+            "message",
+            r"tags.app\.version",
+        ]
+    );
+    assert_eq!(diffs[3].left, Some(json!("Hello")));
+    assert_eq!(diffs[3].right, None);
+    assert_eq!(
+        diffs[0].to_string(),
+        "exception.values.0.type: \"Error\" != \"TypeError\""
+    );
+}
+
+#[test]
+fn test_protocol_version() {
+    use sentry_types::protocol::{self, ProtocolEvent, Version};
+
+    assert_eq!(Version::latest().as_u16(), protocol::LATEST);
+    assert_eq!(v7::Event::VERSION, Version::V7);
+    assert_eq!("7".parse::<Version>().unwrap(), Version::V7);
+    assert!("8".parse::<Version>().is_err());
+    assert_eq!(serde_json::to_string(&Version::V7).unwrap(), "\"7\"");
+
+    let event: protocol::latest::Event = Version::V7
+        .parse_event(b"{\"event_id\":\"d43e86c96e424a93a4fbda156dd17341\"}")
+        .unwrap();
+    assert_eq!(event.event_id, event_id());
+}
+
+#[test]
+fn test_iterator_pipelines() {
+    let words = ["alpha", "beta", "gamma"];
+
+    let breadcrumbs: v7::Breadcrumbs = words
+        .iter()
+        .map(|word| v7::Breadcrumb {
+            timestamp: event_time(),
+            message: Some(word.to_string()),
+            ..Default::default()
+        })
+        .collect();
+    assert_eq!(breadcrumbs.capacity(), v7::DEFAULT_MAX_BREADCRUMBS);
+    let messages: Vec<_> = (&breadcrumbs)
+        .into_iter()
+        .filter_map(|b| b.message.as_deref())
+        .collect();
+    assert_eq!(messages, words);
+
+    let exceptions: v7::Values<v7::Exception> = words
+        .iter()
+        .map(|word| v7::Exception {
+            ty: word.to_string(),
+            ..Default::default()
+        })
+        .collect();
+
+    let mut registers: v7::Registers = vec![("rip", 1u64)].into_iter().collect();
+    registers.extend(vec![("rsp", 2u64)]);
+    let names: Vec<_> = (&registers)
+        .into_iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(names, ["rip", "rsp"]);
+
+    let event = v7::Event {
+        event_id: event_id(),
+        timestamp: event_time(),
+        fingerprint: words.iter().map(|word| word.to_string().into()).collect(),
+        tags: words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| (word.to_string().into(), i.to_string()))
+            .collect(),
+        contexts: vec![v7::Context::from(v7::OsContext::default())]
+            .into_iter()
+            .map(|context| (context.type_name().to_string(), context))
+            .collect(),
+        breadcrumbs: breadcrumbs.into_iter().rev().collect(),
+        exception: exceptions,
+        ..Default::default()
+    };
+
+    assert_eq!(event.fingerprint.len(), 3);
+    assert_eq!(event.tags["gamma"], "2");
+    assert!(event.contexts.contains_key("os"));
+    assert_eq!(event.breadcrumbs[0].message.as_deref(), Some("gamma"));
+    assert_eq!((&event.exception).into_iter().count(), 3);
+}
+
+#[test]
+fn test_event_macro() {
+    let event = sentry_types::event! {
+        event_id: event_id(),
+        timestamp: event_time(),
+        level: Warning,
+        message: "Hello World!",
+        logger: String::from("app.db"),
+        release: "app@1.0.0",
+        environment: "production",
+        fingerprint: ["{{ default }}", "db"],
+        tags: { "shard" => "7", String::from("region") => "eu" },
+        extra: { "attempts" => 3 },
+        user: Some(v7::User {
+            id: Some("42".into()),
+            ..Default::default()
+        }),
+    };
+
+    let mut expected = v7::Event {
+        event_id: event_id(),
+        timestamp: event_time(),
+        level: v7::Level::Warning,
+        message: Some("Hello World!".into()),
+        logger: Some("app.db".into()),
+        release: Some("app@1.0.0".parse().unwrap()),
+        environment: Some("production".parse().unwrap()),
+        fingerprint: vec!["{{ default }}".into(), "db".into()].into(),
+        user: Some(v7::User {
+            id: Some("42".into()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    expected.tags.insert("shard".into(), "7".into());
+    expected.tags.insert("region".into(), "eu".into());
+    expected.extra.insert("attempts".into(), 3.into());
+    assert_eq!(event, expected);
+
+    let event = sentry_types::event! {};
+    assert_eq!(event.level, v7::Level::Error);
+    let event = sentry_types::event! { level: v7::Level::Info };
+    assert_eq!(event.level, v7::Level::Info);
+}
+
+#[test]
+fn test_breadcrumb_macro() {
+    let breadcrumb = sentry_types::breadcrumb! {
+        timestamp: event_time(),
+        ty: "http",
+        category: "request",
+        level: Info,
+        message: "GET /",
+        data: { "status_code" => 200 },
+    };
+
+    let mut expected = v7::Breadcrumb {
+        timestamp: event_time(),
+        ty: "http".into(),
+        category: Some("request".into()),
+        level: v7::Level::Info,
+        message: Some("GET /".into()),
+        ..Default::default()
+    };
+    expected.data.insert("status_code".into(), 200.into());
+    assert_eq!(breadcrumb, expected);
+}
+
+#[test]
+fn test_canonical_json() {
+    let mut event = v7::Event {
+        event_id: event_id(),
+        timestamp: event_time(),
+        message: Some("Hello \"World\"".into()),
+        ..Default::default()
+    };
+    event.extra.insert(
+        "zeta".into(),
+        json!({"b": [1, {"y": 2, "x": 1}], "a": null}).into(),
+    );
+    event.tags.insert("b".into(), "2".into());
+    event.tags.insert("a".into(), "1".into());
+
+    assert_eq!(
+        event.to_canonical_json().unwrap(),
+        r#"{"event_id":"d43e86c96e424a93a4fbda156dd17341","extra":{"zeta":{"a":null,"b":[1,{"x":1,"y":2}]}},"message":"Hello \"World\"","tags":{"a":"1","b":"2"},"timestamp":1514103120}"#
+    );
+
+    let reparsed: v7::Event = serde_json::from_str(&event.to_canonical_json().unwrap()).unwrap();
+    assert_eq!(
+        reparsed.to_canonical_json().unwrap(),
+        event.to_canonical_json().unwrap()
+    );
+}
+
+#[test]
+fn test_timestamp_from_millis() {
+    use sentry_types::Timestamp;
+
+    let timestamp = Timestamp::from_millis(1_514_103_120_123.456).unwrap();
+    assert_eq!(
+        timestamp.as_datetime(),
+        Utc.timestamp_opt(1_514_103_120, 123_456_000).unwrap()
+    );
+    assert_eq!(timestamp.as_millis(), 1_514_103_120_123.456);
+
+    let event = v7::Event {
+        timestamp: timestamp.into(),
+        ..Default::default()
+    };
+    assert_eq!(Timestamp::from(event.timestamp), timestamp);
+
+    assert_eq!(
+        Timestamp::from_millis(-1500.0).unwrap().as_datetime(),
+        Utc.timestamp_opt(-2, 500_000_000).unwrap()
+    );
+    assert!(Timestamp::from_millis(f64::NAN).is_none());
+    assert!(Timestamp::from_millis(f64::INFINITY).is_none());
+    assert!(Timestamp::from_millis(1e300).is_none());
+}
+
+#[test]
+fn test_mark_in_app() {
+    let frame = |module: &str, in_app: Option<bool>| v7::Frame {
+        module: Some(module.into()),
+        in_app,
+        ..Default::default()
+    };
+    let mut stacktrace = v7::Stacktrace {
+        frames: vec![
+            frame("std::rt", None),
+            frame("tokio::runtime", None),
+            frame("tokio_util::codec", None),
+            frame("myapp::main", None),
+            frame("myapp::vendor::lib", None),
+            frame("std::panicking", Some(true)),
+            v7::Frame {
+                abs_path: Some("/app/src/handler.rs".into()),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    stacktrace.mark_in_app(&["myapp::", "/app/"], &["std::", "tokio::", "myapp::"]);
+
+    let in_app: Vec<_> = stacktrace.frames.iter().map(|f| f.in_app).collect();
+    assert_eq!(
+        in_app,
+        vec![
+            Some(false),
+            Some(false),
+            None,
+            Some(true),
+            Some(true),
+            Some(true),
+            Some(true),
+        ]
+    );
+}
+
+#[test]
+fn test_apply_source_context() {
+    let mut sources = std::collections::HashMap::new();
+    sources.insert("/src/main.rs", "fn main() {\n    run();\n}\n");
+    sources.insert("lib.rs", "pub fn run() {\n    panic!();\n}\n");
+
+    let frame = |abs_path: Option<&str>, filename: &str, lineno: Option<u64>| v7::Frame {
+        abs_path: abs_path.map(Into::into),
+        filename: Some(filename.into()),
+        lineno,
+        ..Default::default()
+    };
+    let mut stacktrace = v7::Stacktrace {
+        frames: vec![
+            frame(Some("/src/main.rs"), "main.rs", Some(2)),
+            frame(Some("/other/lib.rs"), "lib.rs", Some(2)),
+            frame(None, "lib.rs", None),
+            frame(None, "missing.rs", Some(1)),
+            frame(None, "lib.rs", Some(10)),
+        ],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        stacktrace.apply_source_context(|path| sources.get(path).copied()),
+        2
+    );
+
+    let frames = &stacktrace.frames;
+    assert_eq!(frames[0].context_line.as_deref(), Some("    run();"));
+    assert_eq!(frames[0].pre_context, vec!["fn main() {"]);
+    assert_eq!(frames[0].post_context, vec!["}"]);
+    assert_eq!(frames[1].context_line.as_deref(), Some("    panic!();"));
+    assert!(frames[2..].iter().all(|f| f.context_line.is_none()));
+}
+
+#[test]
+fn test_stacktrace_from_backtrace_str() {
+    let backtrace = "\
+stack backtrace:
+   0: rust_begin_unwind
+             at /rustc/abc/library/std/src/panicking.rs:584:5
+   1: core::panicking::panic_fmt
+   2: myapp::main
+             at ./src/main.rs:2:5
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.";
+
+    let stacktrace = v7::Stacktrace::from_backtrace_str(backtrace).unwrap();
+    let frames = &stacktrace.frames;
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0].function.as_deref(), Some("myapp::main"));
+    assert_eq!(frames[0].abs_path.as_deref(), Some("./src/main.rs"));
+    assert_eq!(frames[0].filename.as_deref(), Some("main.rs"));
+    assert_eq!(frames[0].lineno, Some(2));
+    assert_eq!(frames[0].colno, Some(5));
+    assert_eq!(
+        frames[1].function.as_deref(),
+        Some("core::panicking::panic_fmt")
+    );
+    assert_eq!(frames[1].lineno, None);
+    assert_eq!(frames[2].function.as_deref(), Some("rust_begin_unwind"));
+
+    let full = "\
+   0:     0x55d9fe5e8c0d - std::rt::lang_start::h0123456789abcdef
+                               at C:\\rust\\src\\rt.rs:93
+   1:     0x55d9fe5e8c00 - <unknown>";
+    let stacktrace = v7::Stacktrace::from_backtrace_str(full).unwrap();
+    let frame = &stacktrace.frames[1];
+    assert_eq!(frame.function.as_deref(), Some("std::rt::lang_start"));
+    assert_eq!(
+        frame.symbol.as_deref(),
+        Some("std::rt::lang_start::h0123456789abcdef")
+    );
+    assert_eq!(frame.instruction_addr, Some(v7::Addr(0x55d9_fe5e_8c0d)));
+    assert_eq!(frame.abs_path.as_deref(), Some("C:\\rust\\src\\rt.rs"));
+    assert_eq!(frame.filename.as_deref(), Some("rt.rs"));
+    assert_eq!(frame.lineno, Some(93));
+    assert_eq!(frame.colno, None);
+
+    assert!(v7::Stacktrace::from_backtrace_str("disabled backtrace").is_none());
+    let captured = std::backtrace::Backtrace::disabled();
+    assert!(v7::Stacktrace::from(&captured).frames.is_empty());
+}
+
+#[cfg(feature = "demangle")]
+#[test]
+fn test_demangle_functions() {
+    let frame = |symbol: &str, function: Option<&str>| v7::Frame {
+        symbol: Some(symbol.into()),
+        function: function.map(Into::into),
+        ..Default::default()
+    };
+    let mut stacktrace = v7::Stacktrace {
+        frames: vec![
+            frame("_ZN4core9panicking9panic_fmt17h0123456789abcdefE", None),
+            frame("_RNvCs1234_7mycrate3foo", None),
+            frame("_ZN3foo3barEi", None),
+            frame("main", None),
+            frame("_ZN3foo3barEi", Some("custom")),
+        ],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        stacktrace.frames[0].demangled_function().as_deref(),
+        Some("core::panicking::panic_fmt")
+    );
+    assert_eq!(stacktrace.demangle_functions(), 3);
+
+    let functions: Vec<_> = stacktrace
+        .frames
+        .iter()
+        .map(|f| f.function.as_deref())
+        .collect();
+    assert_eq!(
+        functions,
+        vec![
+            Some("core::panicking::panic_fmt"),
+            Some("mycrate::foo"),
+            Some("foo::bar(int)"),
+            None,
+            Some("custom"),
+        ]
+    );
+}
+
+#[test]
+fn test_link_crashed_thread() {
+    let thread = |id: u64, current: bool| v7::Thread {
+        id: Some(v7::ThreadId::Int(id)),
+        current,
+        stacktrace: Some(Default::default()),
+        ..Default::default()
+    };
+    let exception = |thread_id: Option<u64>| v7::Exception {
+        ty: "SIGSEGV".into(),
+        thread_id: thread_id.map(v7::ThreadId::Int),
+        ..Default::default()
+    };
+
+    // the current thread is assumed to have crashed
+    let mut event = v7::Event {
+        threads: vec![thread(1, false), thread(2, true)].into(),
+        exception: vec![exception(None)].into(),
+        ..Default::default()
+    };
+    assert_eq!(event.link_crashed_thread(), Some(v7::ThreadId::Int(2)));
+    assert_eq!(
+        event.crashed_thread().unwrap().id,
+        Some(v7::ThreadId::Int(2))
+    );
+    assert_eq!(event.exception[0].thread_id, Some(v7::ThreadId::Int(2)));
+
+    // an exception reference takes precedence and becomes current
+    let mut event = v7::Event {
+        threads: vec![thread(1, false), thread(2, false)].into(),
+        exception: vec![exception(Some(1))].into(),
+        ..Default::default()
+    };
+    assert_eq!(event.link_crashed_thread(), Some(v7::ThreadId::Int(1)));
+    assert!(event.threads[0].crashed && event.threads[0].current);
+    assert!(!event.threads[1].crashed && !event.threads[1].current);
+
+    // an explicitly crashed thread is kept and other flags are cleared
+    let mut event = v7::Event {
+        threads: vec![thread(1, true), thread(2, false)].into(),
+        ..Default::default()
+    };
+    event.threads[0].crashed = true;
+    event.threads[1].crashed = true;
+    assert_eq!(event.link_crashed_thread(), Some(v7::ThreadId::Int(1)));
+    assert!(!event.threads[1].crashed);
+
+    let mut event = v7::Event {
+        threads: vec![thread(1, false), thread(2, false)].into(),
+        ..Default::default()
+    };
+    assert_eq!(event.link_crashed_thread(), None);
+    assert!(event.crashed_thread().is_none());
+}
+
+#[test]
+fn test_context_type_from_key() {
+    let event: v7::Event = serde_json::from_str(
+        r#"{"contexts":{
+            "OS":{"name":"Linux"},
+            "client_os":{"type":"os","name":"iOS"},
+            "custom":{"aha":"oho"}
+        }}"#,
+    )
+    .unwrap();
+
+    match event.contexts["OS"] {
+        v7::Context::Os(ref os) => assert_eq!(os.name.as_deref(), Some("Linux")),
+        ref other => panic!("unexpected context {:?}", other),
+    }
+    assert_eq!(event.contexts["client_os"].type_name(), "os");
+    assert_eq!(event.contexts["custom"].type_name(), "unknown");
+
+    let value = serde_json::to_value(&event).unwrap();
+    assert_eq!(value["contexts"]["OS"]["type"], "os");
+    assert_eq!(value["contexts"]["custom"]["type"], "unknown");
+
+    let json = r#"{"contexts":{"app":{"app_name":"demo","type":"browser"},"os":"Linux"}}"#;
+    assert!(serde_json::from_str::<v7::Event>(json).is_err());
+    let json = r#"{"contexts":{"app":{"name":"Firefox","type":"browser"}}}"#;
+    let event: v7::Event = serde_json::from_str(json).unwrap();
+    assert_eq!(event.contexts["app"].type_name(), "browser");
+    let event: v7::Event = serde_json::from_value(value).unwrap();
+    assert_eq!(event.contexts["OS"].type_name(), "os");
+    assert_eq!(event.contexts["custom"].type_name(), "unknown");
+}
+
+#[test]
+fn test_repeated_context_keys() {
+    let event: v7::Event =
+        serde_json::from_str(r#"{"contexts":{"os":{"name":"Linux"},"os":{"name":"Windows"}}}"#)
+            .unwrap();
+    match event.contexts["os"] {
+        v7::Context::Os(ref os) => assert_eq!(os.name.as_deref(), Some("Linux")),
+        ref other => panic!("unexpected context {:?}", other),
+    }
+
+    let original = json!({"type": "os", "name": "Windows"});
+    assert_eq!(event.errors.len(), 1);
+    assert_eq!(event.errors[0].name.as_deref(), Some("contexts.os"));
+    assert_eq!(event.errors[0].value, Some(original.clone()));
+    let meta = event.meta.get("contexts.os").unwrap();
+    assert_eq!(
+        meta.errors,
+        vec![json!(["invalid_data", {"reason": "duplicate context key"}])]
+    );
+    assert_eq!(meta.original_value, Some(original));
+}
+
+#[test]
+fn test_merge_defaults() {
+    let mut scope = v7::Event {
+        release: Some("app@1.0".parse().unwrap()),
+        environment: Some("production".parse().unwrap()),
+        user: Some(v7::User {
+            id: Some("scope-user".into()),
+            ..Default::default()
+        }),
+        fingerprint: Cow::Owned(vec!["scope".into()]),
+        breadcrumbs: vec![v7::Breadcrumb {
+            message: Some("scope".into()),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    };
+    scope.tags.insert("region".into(), "eu".into());
+    scope.tags.insert("transaction".into(), "scope".into());
+    scope.extra.insert("attempt".into(), 1.into());
+
+    let mut event = v7::Event {
+        environment: Some("staging".parse().unwrap()),
+        breadcrumbs: vec![v7::Breadcrumb {
+            message: Some("event".into()),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    };
+    event.tags.insert("transaction".into(), "event".into());
+    let event_id = event.event_id;
+
+    event.merge_defaults(&scope);
+    assert_eq!(event.event_id, event_id);
+    assert_eq!(event.release.as_deref(), Some("app@1.0"));
+    assert_eq!(event.environment.as_deref(), Some("staging"));
+    assert_eq!(event.user.unwrap().id.as_deref(), Some("scope-user"));
+    assert_eq!(&*event.fingerprint, &["scope"]);
+    assert_eq!(event.tags["region"], "eu");
+    assert_eq!(event.tags["transaction"], "event");
+    assert_eq!(event.extra["attempt"], 1);
+    let messages: Vec<_> = event
+        .breadcrumbs
+        .iter()
+        .map(|b| b.message.as_deref().unwrap())
+        .collect();
+    assert_eq!(messages, ["scope", "event"]);
+}
+
+#[test]
+fn test_server_ordered() {
+    let mut event = v7::Event {
+        event_id: event_id(),
+        timestamp: event_time(),
+        level: v7::Level::Warning,
+        message: Some("hello".into()),
+        ..Default::default()
+    };
+    event.tags.insert("z".into(), "1".into());
+    event.tags.insert("a".into(), "2".into());
+    event.extra.insert(
+        "custom".into(),
+        json!({"b": 1, "a": [{"d": 1, "c": 2}]}).into(),
+    );
+
+    assert_eq!(
+        serde_json::to_string(&v7::ServerOrdered(&event)).unwrap(),
+        "{\"event_id\":\"d43e86c96e424a93a4fbda156dd17341\",\"level\":\"warning\",\
+         \"timestamp\":1514103120,\"message\":\"hello\",\"tags\":{\"a\":\"2\",\"z\":\"1\"},\
+         \"extra\":{\"custom\":{\"a\":[{\"c\":2,\"d\":1}],\"b\":1}}}"
+    );
+}
+
+#[test]
+fn test_shared_event() {
+    let event = v7::Event {
+        event_id: event_id(),
+        timestamp: event_time(),
+        message: Some("hello".into()),
+        ..Default::default()
+    };
+    let shared = v7::SharedEvent::new(event.clone());
+    let mut copy = shared.clone();
+    assert!(copy.ptr_eq(&shared));
+    assert_eq!(copy.message.as_deref(), Some("hello"));
+
+    copy.make_mut().message = Some("changed".into());
+    assert!(!copy.ptr_eq(&shared));
+    assert_eq!(shared.message.as_deref(), Some("hello"));
+    assert_eq!(copy.message.as_deref(), Some("changed"));
+
+    assert_eq!(
+        serde_json::to_string(&shared).unwrap(),
+        serde_json::to_string(&event).unwrap()
+    );
+    assert_eq!(shared.into_inner(), event);
+}
+
+#[test]
+fn test_event_type() {
+    let event: v7::Event = serde_json::from_str(r#"{"type":"transaction"}"#).unwrap();
+    assert_eq!(event.ty, Some(v7::EventType::Transaction));
+    assert_eq!(serde_json::to_value(&event).unwrap()["type"], "transaction");
+
+    let event: v7::Event = serde_json::from_str(r#"{"type":"nel"}"#).unwrap();
+    assert_eq!(event.event_type(), v7::EventType::Unknown("nel".into()));
+    assert_eq!(event.event_type().to_string(), "nel");
+
+    let mut event = v7::Event::new();
+    assert_eq!(event.event_type(), v7::EventType::Default);
+    event.exception.values.push(Default::default());
+    assert_eq!(event.event_type(), v7::EventType::Error);
+    assert!(serde_json::to_value(&event).unwrap().get("type").is_none());
+
+    assert!(v7::EventType::ExpectStaple.is_security_report());
+    assert!(!v7::EventType::Error.is_security_report());
+}
+
+#[test]
+fn test_partial_eq() {
+    fn assert_partial_eq<T: PartialEq + ?Sized>() {}
+    assert_partial_eq::<v7::Event<'static>>();
+    assert_partial_eq::<v7::ServerOrdered<'static, 'static>>();
+    assert_partial_eq::<v7::ParseSpanStatusError>();
+    assert_partial_eq::<v7::ParseTraceParentError>();
+    assert_partial_eq::<sentry_types::protocol::session::ParseSessionStatusError>();
+    assert_partial_eq::<sentry_types::protocol::tunnel::Tunnel>();
+    assert_partial_eq::<sentry_types::protocol::view::EventHeaderView<'static, 'static>>();
+
+    let event = v7::Event {
+        event_id: event_id(),
+        timestamp: event_time(),
+        ..Default::default()
+    };
+    assert_eq!(v7::ServerOrdered(&event), v7::ServerOrdered(&event.clone()));
+    assert_eq!(
+        "bogus".parse::<v7::SpanStatus>(),
+        Err(v7::ParseSpanStatusError)
+    );
+
+    let dsc = |sample_rate| v7::DynamicSamplingContext {
+        sample_rate: Some(sample_rate),
+        ..v7::DynamicSamplingContext::new(Default::default(), "public")
+    };
+    assert_eq!(dsc(0.0), dsc(-0.0));
+    assert_ne!(dsc(f64::NAN), dsc(f64::NAN));
+}
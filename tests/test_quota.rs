@@ -0,0 +1,73 @@
+use sentry_types::protocol::quota::{DataCategory, Quota, QuotaScope, Scoping};
+use sentry_types::ProjectId;
+
+fn scoping() -> Scoping {
+    Scoping {
+        organization_id: 1,
+        project_id: ProjectId::new(42),
+        public_key: "a94ae32be2584e0bbd7a4cbb95971fee".into(),
+        key_id: Some(17),
+    }
+}
+
+#[test]
+fn test_quota_roundtrip() {
+    let json = r#"{"id":"o","categories":["error","transaction"],"scope":"organization","scopeId":"1","limit":100,"window":60,"reasonCode":"org_quota"}"#;
+    let quota: Quota = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        quota,
+        Quota {
+            id: Some("o".into()),
+            categories: vec![DataCategory::Error, DataCategory::Transaction],
+            scope: QuotaScope::Organization,
+            scope_id: Some("1".into()),
+            limit: Some(100),
+            window: Some(60),
+            reason_code: Some("org_quota".into()),
+            namespace: None,
+        }
+    );
+    assert_eq!(serde_json::to_string(&quota).unwrap(), json);
+}
+
+#[test]
+fn test_quota_matches() {
+    let quota: Quota = serde_json::from_str(
+        r#"{"id":"k","categories":["error"],"scope":"key","scopeId":"17","limit":10,"window":60}"#,
+    )
+    .unwrap();
+    assert!(quota.matches(&DataCategory::Error, &scoping()));
+    assert!(!quota.matches(&DataCategory::Transaction, &scoping()));
+
+    let other_key = Scoping {
+        key_id: Some(18),
+        ..scoping()
+    };
+    assert!(!quota.matches(&DataCategory::Error, &other_key));
+
+    let unknown_key = Scoping {
+        key_id: None,
+        ..scoping()
+    };
+    assert!(!quota.matches(&DataCategory::Error, &unknown_key));
+
+    // Reject-all quotas without categories or scope id match everything.
+    let quota: Quota = serde_json::from_str(r#"{"scope":"project","limit":0}"#).unwrap();
+    assert!(quota.matches(&DataCategory::Attachment, &scoping()));
+}
+
+#[test]
+fn test_quota_invalid() {
+    let quotas = [
+        r#"{"scope":"galaxy","limit":0}"#,
+        r#"{"categories":["teleport"],"scope":"project","limit":0}"#,
+        r#"{"scope":"project","limit":10,"window":60}"#,
+        r#"{"id":"p","scope":"project","limit":10}"#,
+    ];
+
+    for json in &quotas {
+        let quota: Quota = serde_json::from_str(json).unwrap();
+        assert!(!quota.is_valid(), "{}", json);
+        assert!(!quota.matches(&DataCategory::Error, &scoping()), "{}", json);
+    }
+}
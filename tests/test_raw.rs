@@ -0,0 +1,63 @@
+#![cfg(feature = "clock")]
+use std::convert::TryFrom;
+
+use chrono::{TimeZone, Utc};
+
+use sentry_types::protocol::raw::RawEvent;
+use sentry_types::protocol::v7::{Event, EventType};
+
+#[test]
+fn test_raw_event_header() {
+    let json = br#"{"event_id":"d43e86c96e424a93a4fbda156dd17341","type":"transaction","timestamp":1577880000.5,"extra":{"big":[1,2,3]}}"#;
+    let raw = RawEvent::from(json.to_vec());
+
+    assert_eq!(
+        raw.event_id(),
+        Some("d43e86c9-6e42-4a93-a4fb-da156dd17341".parse().unwrap())
+    );
+    assert_eq!(raw.ty(), Some(&EventType::Transaction));
+    assert_eq!(
+        raw.timestamp(),
+        Some(Utc.timestamp_opt(1_577_880_000, 500_000_000).unwrap())
+    );
+
+    let clone = raw.clone();
+    assert_eq!(clone.as_bytes(), &json[..]);
+    assert_eq!(clone, raw);
+}
+
+#[test]
+fn test_raw_event_invalid() {
+    let raw = RawEvent::new(b"not json".to_vec());
+    assert!(raw.header().is_none());
+    assert_eq!(raw.event_id(), None);
+    assert!(raw.parse().is_err());
+    assert_eq!(raw.as_bytes(), b"not json");
+}
+
+#[test]
+fn test_raw_event_parse() {
+    let event = Event {
+        message: Some("Hello World!".into()),
+        ..Default::default()
+    };
+    let raw = RawEvent::try_from(&event).unwrap();
+    assert_eq!(raw.event_id(), Some(event.event_id));
+    assert_eq!(raw.ty(), None);
+    assert_eq!(raw.parse().unwrap().message, event.message);
+}
+
+#[test]
+fn test_raw_event_serialize() {
+    let json =
+        r#"{ "extra": {"b": 1, "a": 2.50}, "event_id": "d43e86c96e424a93a4fbda156dd17341" }"#;
+    let raw = RawEvent::from(json.as_bytes().to_vec());
+    assert_eq!(serde_json::to_string(&raw).unwrap(), json);
+    assert_eq!(
+        serde_json::to_string(&vec![raw.clone(), raw]).unwrap(),
+        format!("[{},{}]", json, json)
+    );
+
+    let invalid = RawEvent::new(b"not json".to_vec());
+    assert!(serde_json::to_string(&invalid).is_err());
+}
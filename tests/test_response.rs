@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+
+use sentry_types::protocol::quota::{DataCategory, QuotaScope};
+use sentry_types::protocol::response::{
+    parse_retry_after, ApiErrorResponse, RateLimits, StoreResponse,
+};
+
+#[test]
+fn test_store_response() {
+    let json = r#"{"id":"d43e86c96e424a93a4fbda156dd17341"}"#;
+    let response: StoreResponse = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        response.id,
+        "d43e86c9-6e42-4a93-a4fb-da156dd17341".parse().unwrap()
+    );
+    assert_eq!(serde_json::to_string(&response).unwrap(), json);
+}
+
+#[test]
+fn test_api_error_response() {
+    let json = r#"{"detail":"invalid event","causes":["missing field `type`"]}"#;
+    let response: ApiErrorResponse = serde_json::from_str(json).unwrap();
+    assert_eq!(response.detail.as_deref(), Some("invalid event"));
+    assert_eq!(response.causes, vec!["missing field `type`".to_string()]);
+    assert_eq!(
+        response.to_string(),
+        "invalid event\n  caused by: missing field `type`"
+    );
+    assert_eq!(serde_json::to_string(&response).unwrap(), json);
+
+    let response: ApiErrorResponse = serde_json::from_str("{}").unwrap();
+    assert_eq!(response, ApiErrorResponse::default());
+    assert_eq!(response.to_string(), "unknown error");
+}
+
+#[test]
+fn test_rate_limits() {
+    let limits = RateLimits::parse(
+        "60:error;transaction:key:over_quota, 2700::organization, invalid, 10:metric_bucket:project::custom;sessions",
+    );
+    assert_eq!(limits.limits.len(), 3);
+
+    let limit = &limits.limits[0];
+    assert_eq!(limit.retry_after, Duration::from_secs(60));
+    assert_eq!(
+        limit.categories,
+        vec![DataCategory::Error, DataCategory::Transaction]
+    );
+    assert_eq!(limit.scope, Some(QuotaScope::Key));
+    assert_eq!(limit.reason_code.as_deref(), Some("over_quota"));
+
+    assert!(limits.limits[1].categories.is_empty());
+    assert_eq!(
+        limits.limits[2].namespaces,
+        vec!["custom".to_string(), "sessions".to_string()]
+    );
+
+    assert_eq!(
+        limits.retry_after(&DataCategory::Error),
+        Some(Duration::from_secs(2700))
+    );
+    assert_eq!(
+        limits.to_string(),
+        "60:error;transaction:key:over_quota, 2700::organization, 10:metric_bucket:project::custom;sessions"
+    );
+
+    let limits = RateLimits::parse("");
+    assert!(limits.is_empty());
+    assert_eq!(limits.retry_after(&DataCategory::Error), None);
+}
+
+#[test]
+fn test_retry_after() {
+    let now = Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap();
+    assert_eq!(
+        parse_retry_after("120", now),
+        Some(Duration::from_secs(120))
+    );
+    assert_eq!(
+        parse_retry_after("1.5", now),
+        Some(Duration::from_millis(1500))
+    );
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:30:00 GMT", now),
+        Some(Duration::from_secs(120))
+    );
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+        Some(Duration::from_secs(0))
+    );
+    assert_eq!(parse_retry_after("-1", now), None);
+    assert_eq!(parse_retry_after("soon", now), None);
+}
@@ -0,0 +1,348 @@
+#![cfg(feature = "clock")]
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+use sentry_types::protocol::envelope::{Envelope, ItemType};
+use sentry_types::protocol::quota::DataCategory;
+use sentry_types::protocol::session::{
+    AbnormalMechanism, Session, SessionAggregateError, SessionAggregateItem, SessionAggregates,
+    SessionAttributes, SessionStatus, SessionUpdate, SessionUpdateError,
+};
+use sentry_types::protocol::v7;
+use sentry_types::Duration;
+
+fn session_id() -> Uuid {
+    "d43e86c9-6e42-4a93-a4fb-da156dd17341".parse().unwrap()
+}
+
+fn anr_event(value: &str) -> v7::Event<'static> {
+    v7::Event {
+        exception: vec![v7::Exception {
+            ty: "ApplicationNotResponding".into(),
+            value: Some(value.into()),
+            mechanism: Some(v7::Mechanism {
+                ty: "ANR".into(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_session_roundtrip() {
+    let mut session = Session::new("my-app@1.0");
+    session.session_id = session_id();
+    session.started = Utc.with_ymd_and_hms(2020, 2, 7, 14, 16, 0).unwrap();
+    session.status = SessionStatus::Abnormal;
+    session.abnormal_mechanism = Some(AbnormalMechanism::AnrForeground);
+
+    let json = serde_json::to_string(&session).unwrap();
+    assert_eq!(
+        json,
+        "{\"sid\":\"d43e86c9-6e42-4a93-a4fb-da156dd17341\",\"started\":\"2020-02-07T14:16:00Z\",\
+         \"init\":true,\"status\":\"abnormal\",\"abnormal_mechanism\":\"anr_foreground\",\
+         \"attrs\":{\"release\":\"my-app@1.0\"}}"
+    );
+    assert_eq!(serde_json::from_str::<Session>(&json).unwrap(), session);
+}
+
+#[test]
+fn test_session_minimal() {
+    let session: Session = serde_json::from_str(
+        "{\"started\":\"2020-02-07T14:16:00Z\",\"attrs\":{\"release\":\"1.0\"}}",
+    )
+    .unwrap();
+    assert_eq!(session.status, SessionStatus::Ok);
+    assert_eq!(session.abnormal_mechanism, None);
+    assert_eq!(session.errors, 0);
+}
+
+#[test]
+fn test_abnormal_mechanism_from_anr_event() {
+    let mut session = Session::new("1.0");
+    assert!(!session.record_anr_event(&v7::Event::new()));
+    assert_eq!(session.abnormal_mechanism, None);
+
+    assert!(session.record_anr_event(&anr_event("Background ANR")));
+    assert_eq!(
+        session.abnormal_mechanism,
+        Some(AbnormalMechanism::AnrBackground)
+    );
+
+    assert!(session.record_anr_event(&anr_event(
+        "Application Not Responding for at least 5000 ms."
+    )));
+    assert_eq!(
+        session.abnormal_mechanism,
+        Some(AbnormalMechanism::AnrForeground)
+    );
+}
+
+#[test]
+fn test_session_duration() {
+    let session: Session = serde_json::from_str(
+        "{\"started\":\"2020-02-07T14:16:00Z\",\"duration\":1.25,\"attrs\":{\"release\":\"1.0\"}}",
+    )
+    .unwrap();
+    let duration = session.duration.unwrap();
+    assert_eq!(duration.as_millis_f64(), 1250.0);
+    assert_eq!(duration.as_std(), std::time::Duration::from_millis(1250));
+
+    let mut session = session;
+    session.duration = Some(std::time::Duration::from_secs(3).into());
+    let json = serde_json::to_value(&session).unwrap();
+    assert_eq!(json["duration"], 3);
+
+    assert!(serde_json::from_str::<Session>(
+        "{\"started\":\"2020-02-07T14:16:00Z\",\"duration\":-1,\"attrs\":{\"release\":\"1.0\"}}",
+    )
+    .is_err());
+}
+
+#[test]
+fn test_duration_between() {
+    let start = Utc.with_ymd_and_hms(2020, 2, 7, 14, 16, 0).unwrap();
+    let end = start + chrono::Duration::milliseconds(1500);
+    assert_eq!(Duration::between(start, end).as_secs_f64(), 1.5);
+    assert_eq!(Duration::between(end, start), Duration::default());
+    assert_eq!(Duration::from_secs_f64(1.5).unwrap().to_string(), "1.5s");
+    assert_eq!(Duration::from_secs_f64(f64::NAN), None);
+}
+
+#[test]
+fn test_session_status_transitions() {
+    use SessionStatus::*;
+
+    for &next in &[Ok, Exited, Crashed, Abnormal] {
+        assert!(Ok.can_transition_to(next));
+    }
+    for &status in &[Exited, Crashed, Abnormal] {
+        assert!(status.is_terminal());
+        assert!(status.can_transition_to(status));
+        assert!(!status.can_transition_to(Ok));
+    }
+    assert!(!Ok.is_terminal());
+    assert!(!Crashed.can_transition_to(Exited));
+    assert!(!Exited.can_transition_to(Crashed));
+}
+
+#[test]
+fn test_session_update() {
+    let mut session = Session::new("1.0");
+    session
+        .update(SessionUpdate {
+            sequence: Some(1),
+            errors: Some(2),
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(!session.init);
+    assert_eq!(session.sequence, Some(1));
+    assert_eq!(session.errors, 2);
+    assert_eq!(session.status, SessionStatus::Ok);
+
+    assert_eq!(
+        session.update(SessionUpdate {
+            sequence: Some(2),
+            errors: Some(1),
+            ..Default::default()
+        }),
+        Err(SessionUpdateError::ErrorsDecreased {
+            current: 2,
+            errors: 1
+        })
+    );
+    assert_eq!(session.sequence, Some(1));
+
+    session
+        .update(SessionUpdate {
+            status: Some(SessionStatus::Crashed),
+            errors: Some(3),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(session.status, SessionStatus::Crashed);
+    assert_eq!(session.errors, 3);
+
+    let err = session
+        .update(SessionUpdate {
+            status: Some(SessionStatus::Exited),
+            errors: Some(4),
+            ..Default::default()
+        })
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid session status transition from crashed to exited"
+    );
+    assert_eq!(session.status, SessionStatus::Crashed);
+    assert_eq!(session.errors, 3);
+
+    session
+        .update(SessionUpdate {
+            status: Some(SessionStatus::Crashed),
+            duration: Some(std::time::Duration::from_secs(10).into()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(
+        session.duration,
+        Some(std::time::Duration::from_secs(10).into())
+    );
+}
+
+#[test]
+fn test_session_aggregates_push() {
+    let mut aggregates = SessionAggregates::new(SessionAttributes {
+        release: "1.0".into(),
+        ..Default::default()
+    });
+
+    let session = |minute, did: Option<&str>, status, errors| {
+        let mut session = Session::new("1.0");
+        session.started = Utc.with_ymd_and_hms(2020, 2, 7, 14, minute, 30).unwrap();
+        session.distinct_id = did.map(Into::into);
+        session.status = status;
+        session.errors = errors;
+        session
+    };
+
+    let sessions = vec![
+        session(16, Some("foo"), SessionStatus::Exited, 0),
+        session(59, Some("foo"), SessionStatus::Exited, 2),
+        session(0, None, SessionStatus::Crashed, 1),
+        session(10, None, SessionStatus::Abnormal, 0),
+        session(20, None, SessionStatus::Ok, 0),
+    ];
+    for session in &sessions {
+        aggregates.push(session).unwrap();
+    }
+
+    let mut late = session(0, Some("bar"), SessionStatus::Exited, 0);
+    late.started = Utc.with_ymd_and_hms(2020, 2, 7, 15, 0, 0).unwrap();
+    aggregates.push(&late).unwrap();
+
+    let hour = Utc.with_ymd_and_hms(2020, 2, 7, 14, 0, 0).unwrap();
+    assert_eq!(
+        aggregates.aggregates,
+        vec![
+            SessionAggregateItem {
+                exited: 1,
+                abnormal: 1,
+                crashed: 1,
+                ..SessionAggregateItem::new(hour, None)
+            },
+            SessionAggregateItem {
+                exited: 1,
+                errored: 1,
+                ..SessionAggregateItem::new(hour, Some("foo".into()))
+            },
+            SessionAggregateItem {
+                exited: 1,
+                ..SessionAggregateItem::new(late.started, Some("bar".into()))
+            },
+        ]
+    );
+    assert_eq!(aggregates.aggregates[0].total(), 3);
+
+    let mut other = session(0, None, SessionStatus::Exited, 0);
+    other.attributes.environment = Some("production".into());
+    assert_eq!(aggregates.push(&other), Err(SessionAggregateError));
+}
+
+#[test]
+fn test_session_aggregates_push_unsorted() {
+    let hour = |h| Utc.with_ymd_and_hms(2020, 2, 7, h, 0, 0).unwrap();
+    let mut aggregates = SessionAggregates::new(SessionAttributes {
+        release: "1.0".into(),
+        ..Default::default()
+    });
+    aggregates.aggregates = vec![
+        SessionAggregateItem {
+            crashed: u64::MAX,
+            ..SessionAggregateItem::new(hour(16), None)
+        },
+        SessionAggregateItem::new(hour(15), None),
+        SessionAggregateItem::new(hour(14), None),
+    ];
+
+    let mut session = Session::new("1.0");
+    session.status = SessionStatus::Crashed;
+    for h in &[14, 15, 16] {
+        session.started = hour(*h);
+        aggregates.push(&session).unwrap();
+    }
+
+    assert_eq!(aggregates.aggregates.len(), 3);
+    assert_eq!(aggregates.aggregates[0].started, hour(14));
+    assert_eq!(aggregates.aggregates[0].crashed, 1);
+    assert_eq!(aggregates.aggregates[1].crashed, 1);
+    assert_eq!(aggregates.aggregates[2].crashed, u64::MAX);
+
+    aggregates.aggregates[2].exited = 1;
+    assert_eq!(aggregates.aggregates[2].total(), u64::MAX);
+}
+
+#[test]
+fn test_session_aggregates_roundtrip() {
+    let json = "{\"aggregates\":[\
+                {\"started\":\"2020-02-07T14:00:00Z\",\"did\":\"foo\",\"exited\":2,\"errored\":1},\
+                {\"started\":\"2020-02-07T15:00:00Z\",\"abnormal\":1,\"crashed\":3}],\
+                \"attrs\":{\"release\":\"my-app@1.0\",\"environment\":\"production\"}}";
+
+    let aggregates: SessionAggregates = serde_json::from_str(json).unwrap();
+    assert_eq!(aggregates.attributes.release, "my-app@1.0");
+    assert_eq!(
+        aggregates.attributes.environment.as_deref(),
+        Some("production")
+    );
+    assert_eq!(aggregates.aggregates.len(), 2);
+    assert_eq!(aggregates.aggregates[0].distinct_id.as_deref(), Some("foo"));
+    assert_eq!(aggregates.aggregates[0].total(), 3);
+    assert_eq!(aggregates.aggregates[1].distinct_id, None);
+    assert_eq!(aggregates.aggregates[1].exited, 0);
+    assert_eq!(aggregates.aggregates[1].total(), 4);
+    assert_eq!(serde_json::to_string(&aggregates).unwrap(), json);
+}
+
+#[test]
+fn test_session_aggregates_minimal() {
+    let aggregates: SessionAggregates =
+        serde_json::from_str("{\"aggregates\":[],\"attrs\":{\"release\":\"1.0\"}}").unwrap();
+    assert!(aggregates.aggregates.is_empty());
+
+    assert!(
+        serde_json::from_str::<SessionAggregates>("{\"attrs\":{\"release\":\"1.0\"}}").is_err()
+    );
+    assert!(
+        serde_json::from_str::<Session>("{\"aggregates\":[],\"attrs\":{\"release\":\"1.0\"}}")
+            .is_err()
+    );
+}
+
+#[test]
+fn test_session_envelope_items() {
+    let mut session = Session::new("1.0");
+    session.session_id = session_id();
+    session.started = Utc.with_ymd_and_hms(2020, 2, 7, 14, 16, 0).unwrap();
+
+    let mut aggregates = SessionAggregates::new(session.attributes.clone());
+    aggregates.push(&session).unwrap();
+
+    let mut envelope = Envelope::new();
+    envelope.add_item(session.to_envelope_item());
+    envelope.add_item(aggregates.to_envelope_item());
+    let envelope = Envelope::from_slice(&envelope.to_vec()).unwrap();
+
+    let items = envelope.items();
+    assert_eq!(items[0].ty(), &ItemType::Session);
+    assert_eq!(items[0].session().unwrap().unwrap(), session);
+    assert!(items[0].session_aggregates().is_none());
+    assert_eq!(items[1].ty(), &ItemType::Sessions);
+    assert_eq!(items[1].session_aggregates().unwrap().unwrap(), aggregates);
+    assert!(items[1].session().is_none());
+    assert_eq!(envelope.counts_by_category()[&DataCategory::Session], 2);
+}
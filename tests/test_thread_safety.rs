@@ -0,0 +1,88 @@
+#![cfg(feature = "clock")]
+use std::thread;
+
+use sentry_types::protocol::enhancements::Enhancements;
+use sentry_types::protocol::envelope::{
+    Envelope, EnvelopeHeaders, EnvelopeItem, ItemType, ReceivedEnvelope, ReplayRecording,
+};
+use sentry_types::protocol::extra;
+use sentry_types::protocol::filters::FiltersConfig;
+use sentry_types::protocol::fingerprinting::FingerprintingRules;
+use sentry_types::protocol::grouping::GroupingConfig;
+use sentry_types::protocol::limits::ParseLimits;
+use sentry_types::protocol::metrics::MetricBucket;
+use sentry_types::protocol::normalize::NormalizeConfig;
+use sentry_types::protocol::outcome::Outcome;
+use sentry_types::protocol::project::ProjectConfig;
+use sentry_types::protocol::quota::Quota;
+use sentry_types::protocol::raw::RawEvent;
+use sentry_types::protocol::response::RateLimits;
+use sentry_types::protocol::session::{Session, SessionAggregates};
+use sentry_types::protocol::tunnel::{Tunnel, TunnelRequest};
+use sentry_types::protocol::v7;
+use sentry_types::protocol::view_hierarchy::ViewHierarchy;
+use sentry_types::{Auth, Dsn, ProjectId, ThinString};
+
+fn assert_thread_safe<T: Clone + Send + Sync + 'static>() {}
+
+#[test]
+fn test_protocol_types_are_thread_safe() {
+    assert_thread_safe::<v7::Event<'static>>();
+    assert_thread_safe::<v7::SharedEvent>();
+    assert_thread_safe::<v7::Breadcrumbs>();
+    assert_thread_safe::<v7::MetaTree>();
+    assert_thread_safe::<v7::Profile>();
+    assert_thread_safe::<v7::ReplayEvent>();
+    assert_thread_safe::<extra::Value>();
+    assert_thread_safe::<Envelope>();
+    assert_thread_safe::<EnvelopeHeaders>();
+    assert_thread_safe::<EnvelopeItem>();
+    assert_thread_safe::<ReceivedEnvelope>();
+    assert_thread_safe::<ReplayRecording>();
+    assert_thread_safe::<Enhancements>();
+    assert_thread_safe::<FiltersConfig>();
+    assert_thread_safe::<FingerprintingRules>();
+    assert_thread_safe::<GroupingConfig>();
+    assert_thread_safe::<ParseLimits>();
+    assert_thread_safe::<MetricBucket>();
+    assert_thread_safe::<NormalizeConfig>();
+    assert_thread_safe::<Outcome>();
+    assert_thread_safe::<ProjectConfig>();
+    assert_thread_safe::<Quota>();
+    assert_thread_safe::<RateLimits>();
+    assert_thread_safe::<RawEvent>();
+    assert_thread_safe::<Session>();
+    assert_thread_safe::<SessionAggregates>();
+    assert_thread_safe::<Tunnel>();
+    assert_thread_safe::<TunnelRequest>();
+    assert_thread_safe::<ViewHierarchy>();
+    assert_thread_safe::<Auth>();
+    assert_thread_safe::<Dsn>();
+    assert_thread_safe::<ProjectId>();
+    assert_thread_safe::<ThinString>();
+}
+
+#[test]
+fn test_send_event_and_envelope() {
+    let event = v7::Event {
+        message: Some("hello".into()),
+        ..Default::default()
+    };
+    let mut envelope = Envelope::new();
+    envelope.add_item(EnvelopeItem::new(
+        ItemType::Event,
+        serde_json::to_vec(&event).unwrap(),
+    ));
+
+    let shared = v7::SharedEvent::new(event.clone());
+    let (received, bytes) = thread::spawn({
+        let shared = shared.clone();
+        move || (shared, envelope.to_vec())
+    })
+    .join()
+    .unwrap();
+
+    assert!(received.ptr_eq(&shared));
+    assert_eq!(*received, event);
+    assert_eq!(Envelope::from_slice(&bytes).unwrap().items().len(), 1);
+}
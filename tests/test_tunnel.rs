@@ -0,0 +1,86 @@
+use sentry_types::protocol::envelope::Envelope;
+use sentry_types::protocol::tunnel::{Tunnel, TunnelError, TunnelRequest};
+use sentry_types::Dsn;
+
+const BODY: &[u8] = b"{\"event_id\":\"9ec79c33-ec99-42ab-8353-589fcb2e04dc\",\"dsn\":\"https://other@sentry.io:443/42\"}\n\
+                      {\"type\":\"event\",\"length\":2}\n\
+                      {}\n";
+
+fn tunnel() -> Tunnel {
+    Tunnel::new(vec!["https://public@sentry.io/42".parse().unwrap()])
+}
+
+#[test]
+fn test_tunnel_accept() {
+    let request = tunnel().accept(BODY).unwrap();
+    assert_eq!(request.dsn().public_key(), "other");
+    assert_eq!(
+        request.upstream_url().as_str(),
+        "https://sentry.io/api/42/envelope/"
+    );
+    assert_eq!(request.envelope().items().len(), 1);
+    assert_eq!(
+        request.to_vec(),
+        Envelope::from_slice(BODY).unwrap().to_vec()
+    );
+}
+
+#[test]
+fn test_tunnel_rejects() {
+    let tunnel = tunnel();
+
+    let body = b"{\"dsn\":\"https://public@sentry.io/43\"}\n";
+    assert!(matches!(
+        tunnel.accept(body),
+        Err(TunnelError::DsnNotAllowed)
+    ));
+
+    let body = b"{\"dsn\":\"https://public@evil.example.com/42\"}\n";
+    assert!(matches!(
+        tunnel.accept(body),
+        Err(TunnelError::DsnNotAllowed)
+    ));
+
+    let body = b"{\"dsn\":\"http://public@sentry.io/42\"}\n";
+    assert!(matches!(
+        tunnel.accept(body),
+        Err(TunnelError::DsnNotAllowed)
+    ));
+
+    assert!(matches!(
+        tunnel.accept(b"{}\n"),
+        Err(TunnelError::MissingDsn)
+    ));
+    assert!(matches!(
+        tunnel.accept(b"{\"dsn\":\"not a dsn\"}\n"),
+        Err(TunnelError::InvalidDsn(_))
+    ));
+    assert!(matches!(
+        tunnel.accept(b"{\"dsn\":42}\n"),
+        Err(TunnelError::InvalidDsn(_))
+    ));
+    assert!(matches!(
+        tunnel.accept(b"not json\n"),
+        Err(TunnelError::InvalidEnvelope(_))
+    ));
+}
+
+#[test]
+fn test_tunnel_rewrite_dsn() {
+    let mut request = TunnelRequest::from_slice(BODY).unwrap();
+    let relay: Dsn = "http://public@relay.internal:3000/7".parse().unwrap();
+    request.rewrite_dsn(relay.clone());
+    assert_eq!(request.dsn(), &relay);
+    assert_eq!(
+        request.upstream_url().as_str(),
+        "http://relay.internal:3000/api/7/envelope/"
+    );
+
+    let forwarded = Envelope::from_slice(&request.to_vec()).unwrap();
+    assert_eq!(forwarded.headers().dsn().unwrap().unwrap(), relay);
+    assert_eq!(
+        forwarded.headers().event_id,
+        request.envelope().headers().event_id
+    );
+    assert_eq!(forwarded.items(), request.envelope().items());
+}
@@ -0,0 +1,222 @@
+#![cfg(feature = "clock")]
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use chrono::{TimeZone, Utc};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use sentry_types::protocol::v7::{
+    Breadcrumb, Event, EventProcessingError, Exception, Level, MetaTree, Request, User,
+};
+use sentry_types::protocol::view::EventHeaderView;
+
+fn event() -> Event<'static> {
+    let mut event = Event {
+        event_id: "d43e86c9-6e42-4a93-a4fb-da156dd17341".parse().unwrap(),
+        level: Level::Warning,
+        timestamp: Utc.with_ymd_and_hms(2020, 1, 1, 12, 0, 0).unwrap(),
+        message: Some("Hello World!".into()),
+        release: Some("app@1.0.0".parse().unwrap()),
+        user: Some(User {
+            email: Some("jane@example.com".into()),
+            ..Default::default()
+        }),
+        request: Some(Request::default()),
+        server_name: Some("db-1".into()),
+        breadcrumbs: vec![Breadcrumb::default()].into(),
+        exception: vec![Exception {
+            ty: "ValueError".into(),
+            ..Default::default()
+        }]
+        .into(),
+        ..Default::default()
+    };
+    event.tags.insert("shard".into(), "7".into());
+    event.extra.insert("secret".into(), "hunter2".into());
+    event
+}
+
+#[test]
+fn test_header_view() {
+    let event = event();
+    assert_eq!(
+        serde_json::to_string(&EventHeaderView::new(&event)).unwrap(),
+        r#"{"event_id":"d43e86c96e424a93a4fbda156dd17341","level":"warning","timestamp":1577880000,"message":"Hello World!","release":"app@1.0.0","tags":{"shard":"7"}}"#
+    );
+}
+
+#[test]
+fn test_scrubbed_view() {
+    let event = event();
+    let json = serde_json::to_string(&event.scrubbed_view()).unwrap();
+    assert!(!json.contains("jane@example.com"));
+    assert!(!json.contains("hunter2"));
+    assert!(!json.contains("db-1"));
+
+    let scrubbed: Event = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        scrubbed,
+        Event {
+            user: None,
+            request: None,
+            server_name: None,
+            breadcrumbs: Default::default(),
+            extra: Default::default(),
+            ..event
+        }
+    );
+}
+
+#[test]
+fn test_views_of_default_event() {
+    let event = Event {
+        timestamp: Utc.with_ymd_and_hms(2020, 1, 1, 12, 0, 0).unwrap(),
+        ..Default::default()
+    };
+    let header: Event =
+        serde_json::from_value(serde_json::to_value(event.header_view()).unwrap()).unwrap();
+    assert_eq!(header, event);
+    let scrubbed: Event =
+        serde_json::from_value(serde_json::to_value(event.scrubbed_view()).unwrap()).unwrap();
+    assert_eq!(scrubbed, event);
+}
+
+fn parse<T: DeserializeOwned>(value: serde_json::Value) -> T {
+    serde_json::from_value(value).unwrap()
+}
+
+fn keys(value: &serde_json::Value) -> BTreeSet<&str> {
+    value
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect()
+}
+
+/// Returns an event with every field set to a value that is serialized.
+///
+/// This lists all fields of `Event` on purpose.  When a field is added, this
+/// stops compiling and the views must be checked for the new field.
+fn full_event() -> Event<'static> {
+    let mut meta = MetaTree::default();
+    for field in &["release", "exception", "user", "extra"] {
+        meta.get_mut(field).original_length = Some(1);
+    }
+
+    Event {
+        event_id: "d43e86c9-6e42-4a93-a4fb-da156dd17341".parse().unwrap(),
+        level: Level::Warning,
+        ty: Some("transaction".parse().unwrap()),
+        fingerprint: Cow::Owned(vec!["custom".into()]),
+        culprit: Some("culprit".into()),
+        transaction: Some("/users/:id".into()),
+        transaction_info: Some(parse(json!({"source": "route"}))),
+        message: Some("Hello World!".into()),
+        logentry: Some(parse(json!({"message": "Hello %s!", "params": ["World"]}))),
+        logger: Some("app".into()),
+        modules: parse(json!({"serde": "1.0"})),
+        platform: "rust".parse().unwrap(),
+        timestamp: Utc.with_ymd_and_hms(2020, 1, 1, 12, 0, 0).unwrap(),
+        start_timestamp: Some(Utc.with_ymd_and_hms(2020, 1, 1, 11, 59, 0).unwrap()),
+        server_name: Some("db-1".into()),
+        release: Some("app@1.0.0".parse().unwrap()),
+        dist: Some("1".into()),
+        environment: Some("production".parse().unwrap()),
+        user: Some(parse(json!({"email": "jane@example.com"}))),
+        request: Some(Request::default()),
+        contexts: parse(json!({"os": {"type": "os", "name": "Linux"}})),
+        breadcrumbs: vec![Breadcrumb::default()].into(),
+        exception: vec![Exception::default()].into(),
+        stacktrace: Some(parse(json!({"frames": [{"function": "main"}]}))),
+        template: Some(parse(json!({"filename": "index.html"}))),
+        threads: parse(json!([{"id": 1}])),
+        spans: parse(json!([{
+            "span_id": "b7ad6b7169203331",
+            "trace_id": "0af7651916cd43dd8448eb211c80319c",
+            "start_timestamp": 1577879940,
+        }])),
+        tags: parse(json!({"shard": "7"})),
+        extra: parse(json!({"secret": "hunter2"})),
+        debug_meta: Cow::Owned(parse(
+            json!({"images": [{"type": "proguard", "uuid": "d43e86c9-6e42-4a93-a4fb-da156dd17341"}]}),
+        )),
+        sdk: Some(Cow::Owned(parse(
+            json!({"name": "sentry.rust", "version": "0.1"}),
+        ))),
+        errors: vec![EventProcessingError::new(
+            EventProcessingError::INVALID_DATA,
+            "culprit",
+            None,
+        )],
+        meta,
+    }
+}
+
+#[test]
+fn test_views_cover_all_fields() {
+    let event = full_event();
+    let full = serde_json::to_value(&event).unwrap();
+    let header = serde_json::to_value(event.header_view()).unwrap();
+    let scrubbed = serde_json::to_value(event.scrubbed_view()).unwrap();
+
+    let excluded = [
+        "user",
+        "request",
+        "server_name",
+        "breadcrumbs",
+        "extra",
+        "modules",
+        "debug_meta",
+        "spans",
+    ];
+    let mut expected = keys(&full);
+    expected.retain(|key| !excluded.contains(key));
+    assert_eq!(keys(&scrubbed), expected);
+    for key in keys(&scrubbed) {
+        if key != "_meta" {
+            assert_eq!(scrubbed[key], full[key], "{}", key);
+        }
+    }
+
+    assert_eq!(
+        keys(&header),
+        [
+            "event_id",
+            "level",
+            "type",
+            "timestamp",
+            "platform",
+            "logger",
+            "transaction",
+            "message",
+            "release",
+            "dist",
+            "environment",
+            "tags",
+        ]
+        .iter()
+        .copied()
+        .collect()
+    );
+}
+
+#[test]
+fn test_scrubbed_view_meta() {
+    let event = full_event();
+    let scrubbed = serde_json::to_value(event.scrubbed_view()).unwrap();
+    assert_eq!(
+        scrubbed["_meta"],
+        json!({
+            "release": {"": {"len": 1}},
+            "exception": {"": {"len": 1}},
+        })
+    );
+
+    let mut event = event;
+    event.meta = MetaTree::default();
+    event.meta.get_mut("user").original_length = Some(1);
+    let scrubbed = serde_json::to_value(event.scrubbed_view()).unwrap();
+    assert!(scrubbed.get("_meta").is_none());
+}
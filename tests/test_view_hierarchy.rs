@@ -0,0 +1,44 @@
+use sentry_types::protocol::envelope::{AttachmentType, Envelope};
+use sentry_types::protocol::view_hierarchy::{ViewHierarchy, ViewHierarchyWindow};
+
+#[test]
+fn test_view_hierarchy_roundtrip() {
+    let json = r#"{"rendering_system":"UIKIT","windows":[{"type":"UIWindow","identifier":"main","x":0.0,"y":0.0,"width":390.0,"height":844.0,"alpha":1.0,"visible":true,"children":[{"type":"UILabel","tag":7},{"type":"UIButton"}]}]}"#;
+
+    let hierarchy = ViewHierarchy::from_slice(json.as_bytes()).unwrap();
+    assert_eq!(hierarchy.rendering_system, "UIKIT");
+    let window = &hierarchy.windows[0];
+    assert_eq!(window.identifier.as_deref(), Some("main"));
+    assert_eq!(window.width, Some(390.0));
+    assert_eq!(window.count(), 3);
+    assert_eq!(window.children[0].other["tag"], 7);
+    assert_eq!(serde_json::to_string(&hierarchy).unwrap(), json);
+}
+
+#[test]
+fn test_view_hierarchy_attachment() {
+    let hierarchy = ViewHierarchy {
+        rendering_system: "compose".into(),
+        windows: vec![ViewHierarchyWindow {
+            ty: "Column".into(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let item = hierarchy.to_attachment();
+    assert_eq!(item.attachment_type(), Some(AttachmentType::ViewHierarchy));
+    assert_eq!(item.header("filename"), Some("view-hierarchy.json".into()));
+    assert_eq!(
+        AttachmentType::infer("upload", item.payload()),
+        AttachmentType::ViewHierarchy
+    );
+
+    let mut envelope = Envelope::new();
+    envelope.add_item(item);
+    let parsed = Envelope::from_slice(&envelope.to_vec()).unwrap();
+    assert_eq!(
+        ViewHierarchy::from_slice(parsed.items()[0].payload()).unwrap(),
+        hierarchy
+    );
+}